@@ -0,0 +1,277 @@
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use crate::codegen;
+use crate::line_index::{report_source, LineIndex, Position};
+use crate::mod_resolver::{self, ResolvedModules};
+use crate::parser::ast::{ByteOffset, Statement};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+    Advice,
+}
+
+/// Part of a source, as byte offsets and as the positions editors show.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Span {
+    pub offset: Range<usize>,
+    pub start: Position,
+    pub end: Position,
+}
+
+impl Span {
+    fn new(index: &LineIndex, offset: Range<usize>) -> Option<Self> {
+        Some(Self {
+            start: index.position(offset.start)?,
+            end: index.position(offset.end)?,
+            offset,
+        })
+    }
+}
+
+/// Another part of the source a diagnostic points at, along with what it says about it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelatedSpan {
+    pub message: Option<String>,
+    pub span: Span,
+}
+
+/// A problem found on a module, the same a report of the assembler renders.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub message: String,
+    pub help: Option<String>,
+    pub severity: Severity,
+    /// Module the problem was found on
+    pub file: PathBuf,
+    /// What the first label of the report points at, `None` for reports without labels
+    pub span: Option<Span>,
+    /// What every other label of the report points at
+    pub related: Vec<RelatedSpan>,
+}
+
+impl Diagnostic {
+    fn from_report(file: PathBuf, report: &miette::Report) -> Self {
+        let index = report_source(report).map(LineIndex::new);
+        let mut spans = report.labels().into_iter().flatten().filter_map(|label| {
+            let span = Span::new(index.as_ref()?, label.offset()..label.offset() + label.len())?;
+            Some(RelatedSpan {
+                message: label.label().map(String::from),
+                span,
+            })
+        });
+
+        Self {
+            message: report.to_string(),
+            help: report.help().map(|help| help.to_string()),
+            severity: match report.severity() {
+                Some(miette::Severity::Warning) => Severity::Warning,
+                Some(miette::Severity::Advice) => Severity::Advice,
+                Some(miette::Severity::Error) | None => Severity::Error,
+            },
+            file,
+            span: spans.next().map(|related| related.span),
+            related: spans.collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DefinitionKind {
+    Label,
+    Constant,
+    Data,
+    Import,
+}
+
+/// Where a name is defined, for jumping from a `!name` to its definition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Definition {
+    pub name: String,
+    pub kind: DefinitionKind,
+    pub exported: bool,
+    /// Module the name is defined on
+    pub file: PathBuf,
+    /// The name on its definition
+    pub span: Span,
+}
+
+/// Everything [`check`] found on a program.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CheckResult {
+    pub diagnostics: Vec<Diagnostic>,
+    /// Names defined on every module, in the order modules are laid out
+    pub definitions: Vec<Definition>,
+}
+
+impl CheckResult {
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.severity == Severity::Error)
+    }
+}
+
+/// Parses and resolves a program without assembling it, returning what's wrong with it and the
+/// names it defines as plain data, for editors and language servers. Nothing is printed and no
+/// report handler is installed.
+///
+/// Modules stop being checked at their first error. An error while parsing or resolving imports
+/// stops the whole program from being checked, as the modules after it can't be found.
+pub fn check<P: AsRef<Path>>(source: String, path: P) -> CheckResult {
+    let modules = match mod_resolver::resolve_located(source, path, 0) {
+        Ok(modules) => modules,
+        Err((file, report)) => {
+            return CheckResult {
+                diagnostics: vec![Diagnostic::from_report(file, &report)],
+                definitions: vec![],
+            }
+        }
+    };
+
+    let definitions = definitions(&modules);
+    let diagnostics = codegen::diagnostics(modules)
+        .into_iter()
+        .map(|(file, report)| Diagnostic::from_report(file, &report))
+        .collect();
+
+    CheckResult {
+        diagnostics,
+        definitions,
+    }
+}
+
+fn definitions(modules: &ResolvedModules) -> Vec<Definition> {
+    let mut definitions = vec![];
+
+    for (module, ast) in modules.modules.iter().zip(&modules.asts) {
+        let source = &modules.sources[&module.path];
+        let index = LineIndex::new(source);
+        let mut define = |name: &ByteOffset, kind, exported| {
+            let Some(span) = Span::new(&index, Range::from(*name)) else {
+                return;
+            };
+            definitions.push(Definition {
+                name: source[Range::from(*name)].to_string(),
+                kind,
+                exported,
+                file: module.path.clone(),
+                span,
+            });
+        };
+
+        for statement in &ast.statements {
+            match statement {
+                Statement::Label { name, exported } => define(name, DefinitionKind::Label, *exported),
+                Statement::Const { name, exported, .. } => define(name, DefinitionKind::Constant, *exported),
+                Statement::Data { name, exported, .. } => define(name, DefinitionKind::Data, *exported),
+                Statement::Import { name, .. } => define(name, DefinitionKind::Import, false),
+                _ => {}
+            }
+        }
+    }
+
+    definitions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_definitions() {
+        let code = String::from(
+            "const LIMIT = $0010\n+const EXPORTED = $0001\nstart:\n  mov r1, !LIMIT\n+done:\n  hlt\ndata8 bytes = { $01 }\n",
+        );
+        let result = check(code, "main.aya");
+        assert_eq!(result.diagnostics, vec![]);
+
+        let definitions = result
+            .definitions
+            .iter()
+            .map(|definition| (definition.name.as_str(), definition.kind, definition.exported))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            definitions,
+            vec![
+                ("LIMIT", DefinitionKind::Constant, false),
+                ("EXPORTED", DefinitionKind::Constant, true),
+                ("start", DefinitionKind::Label, false),
+                ("done", DefinitionKind::Label, true),
+                ("bytes", DefinitionKind::Data, false),
+            ]
+        );
+
+        let start = &result.definitions[2];
+        assert_eq!(start.file, PathBuf::from("main.aya"));
+        assert_eq!(start.span.offset, 44..49);
+        assert_eq!(start.span.start, Position { line: 3, column: 1 });
+        assert_eq!(start.span.end, Position { line: 3, column: 6 });
+    }
+
+    #[test]
+    fn test_check_syntax_error() {
+        let result = check(String::from("start:\r\n  mov r1, @\r\n"), "main.aya");
+        assert!(result.has_errors());
+        assert_eq!(result.definitions, vec![]);
+
+        let [diagnostic] = result.diagnostics.as_slice() else {
+            panic!("expected a single diagnostic, got {:?}", result.diagnostics);
+        };
+        assert_eq!(diagnostic.severity, Severity::Error);
+        assert_eq!(diagnostic.file, PathBuf::from("main.aya"));
+        let span = diagnostic.span.as_ref().unwrap();
+        assert_eq!(span.start, Position { line: 2, column: 11 });
+    }
+
+    #[test]
+    fn test_check_related_spans() {
+        let result = check(String::from("const BIG = $10000\n"), "main.aya");
+
+        let [diagnostic] = result.diagnostics.as_slice() else {
+            panic!("expected a single diagnostic, got {:?}", result.diagnostics);
+        };
+        assert!(diagnostic.message.contains("INVALID_CONSTANT"));
+        assert_eq!(
+            diagnostic.help.as_deref(),
+            Some("hex number is not within the u16 range")
+        );
+        assert_eq!(
+            diagnostic.span.as_ref().unwrap().start,
+            Position { line: 1, column: 14 }
+        );
+        assert_eq!(diagnostic.related.len(), 1);
+        assert_eq!(diagnostic.related[0].message.as_deref(), Some("this constant"));
+    }
+
+    #[test]
+    fn test_check_missing_module() {
+        let code = String::from("import \"missing.aya\" Missing &[$1000] {}\nhlt\n");
+        let result = check(code, "main.aya");
+
+        let [diagnostic] = result.diagnostics.as_slice() else {
+            panic!("expected a single diagnostic, got {:?}", result.diagnostics);
+        };
+        assert!(diagnostic.message.contains("MISSING_MODULE"));
+        assert_eq!(diagnostic.span.as_ref().unwrap().start.line, 1);
+    }
+
+    #[test]
+    fn test_check_imported_module_errors() {
+        let dir = std::env::temp_dir().join(format!("aya-check-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("broken.aya"), "hlt\nmov r1,\n").unwrap();
+
+        let code = String::from("import \"broken.aya\" Broken &[$1000] {}\nhlt\n");
+        let result = check(code, dir.join("main.aya"));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let [diagnostic] = result.diagnostics.as_slice() else {
+            panic!("expected a single diagnostic, got {:?}", result.diagnostics);
+        };
+        assert_eq!(diagnostic.file, dir.join("broken.aya"));
+        assert_eq!(diagnostic.span.as_ref().unwrap().start.line, 2);
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::ops::Range;
 use std::path::PathBuf;
 
@@ -6,8 +6,8 @@ use aya_cpu::register::Register;
 
 use crate::mod_resolver::{Either, ResolvedModule, ResolvedModules};
 use crate::parser::ast::{Ast, Instruction, Operator, Statement};
-use crate::parser::error::{REGISTER_HELP, REGISTER_MSG};
-use crate::utils::{bail, unexpected_statement};
+use crate::parser::error::REGISTER_MSG;
+use crate::utils::{bail, unexpected_statement, warning};
 
 macro_rules! formatted {
     ($prefix:ident, $lhs:ident, $rhs:ident) => {
@@ -103,7 +103,7 @@ pub struct CodegenModule {
     pub address: u16,
     pub imports: Vec<PathBuf>,
     pub symbols: HashMap<String, u16>,
-    pub variables: Option<HashMap<String, Either>>,
+    pub variables: Option<BTreeMap<String, Either>>,
     pub exports: HashMap<String, u16>,
 }
 
@@ -114,6 +114,13 @@ pub struct CodeGenerator<'codegen> {
     code: Vec<String>,
     temp_registers: Vec<Register>,
     used_registers: Vec<Register>,
+    /// Value every temp register holds on the instruction being expanded, keyed by the
+    /// expression it was computed from, so repeated expressions share a register
+    values: HashMap<String, Register>,
+    /// Temp registers whose value is still going to be read, once for every pending read
+    held: Vec<Register>,
+    /// Diagnostics about code that assembles but should be written differently
+    warnings: Vec<miette::Report>,
 }
 
 trait ToExportedPrefix {
@@ -138,6 +145,9 @@ impl<'codegen> CodeGenerator<'codegen> {
             code: vec![],
             temp_registers: vec![Register::Acc, Register::R5, Register::R6, Register::R7, Register::R8],
             used_registers: Vec::with_capacity(8),
+            values: HashMap::new(),
+            held: vec![],
+            warnings: vec![],
         }
     }
 
@@ -149,6 +159,9 @@ impl<'codegen> CodeGenerator<'codegen> {
             code: vec![file],
             temp_registers: self.temp_registers,
             used_registers: self.used_registers,
+            values: self.values,
+            held: self.held,
+            warnings: self.warnings,
         }
     }
 
@@ -166,11 +179,36 @@ impl<'codegen> CodeGenerator<'codegen> {
         Ok(())
     }
 
+    /// Computes `node` into `target`, or into a temp register when there is no target. Values
+    /// already held by a temp register are reused instead of computed again.
     fn generate_code(
         &mut self,
         prefix: InstructionPrefix,
         node: &Statement,
         target: Option<Register>,
+    ) -> miette::Result<Register> {
+        let key = self.value_key(node)?;
+        if target.is_none() {
+            if let Some(&reg) = self.values.get(&key) {
+                self.held.push(reg);
+                return Ok(reg);
+            }
+        }
+
+        let dest = self.compute(prefix, node, target)?;
+        if target.is_none() {
+            self.values.insert(key, dest);
+            self.held.push(dest);
+        }
+
+        Ok(dest)
+    }
+
+    fn compute(
+        &mut self,
+        prefix: InstructionPrefix,
+        node: &Statement,
+        target: Option<Register>,
     ) -> miette::Result<Register> {
         if let Some(value) = self.evaluate_constants(node)? {
             let dest = match target {
@@ -199,7 +237,7 @@ impl<'codegen> CodeGenerator<'codegen> {
                 let reg = &self.source[Range::from(*reg)];
                 let reg = match Register::try_from(reg) {
                     Ok(reg) => reg,
-                    Err(_) => return Err(bail(self.source, REGISTER_HELP, REGISTER_MSG, node.offset())),
+                    Err(err) => return Err(bail(self.source, &err.to_string(), REGISTER_MSG, node.offset())),
                 };
                 self.code.push(formatted!(prefix, dest, reg));
                 Ok(dest)
@@ -214,9 +252,22 @@ impl<'codegen> CodeGenerator<'codegen> {
                 Ok(dest)
             }
             Statement::BinaryOp { lhs, operator, rhs } => {
-                let lhs = self.generate_code(InstructionPrefix::Mov, lhs, None)?;
+                let mut lhs = self.generate_code(InstructionPrefix::Mov, lhs, None)?;
                 let rhs = self.generate_code(InstructionPrefix::Mov, rhs, None)?;
+                self.unhold(lhs);
+                self.unhold(rhs);
+
+                // the operation overwrites its left side, which is copied first when it is still
+                // going to be read
+                if self.held.contains(&lhs) {
+                    let copy = self.get_temp_register(node)?;
+                    let prefix = InstructionPrefix::Mov;
+                    self.code.push(formatted!(prefix, copy, lhs));
+                    lhs = copy;
+                }
                 self.code.push(formatted!(operator, lhs, rhs));
+                self.forget(lhs);
+                self.values.insert(self.value_key(node)?, lhs);
 
                 let dest = target.unwrap_or(lhs);
                 if dest != lhs {
@@ -233,6 +284,36 @@ impl<'codegen> CodeGenerator<'codegen> {
         }
     }
 
+    /// Text identifying the value of `node`, equal for every expression computing the same value.
+    fn value_key(&self, node: &Statement) -> miette::Result<String> {
+        if let Some(value) = self.evaluate_constants(node)? {
+            return Ok(value);
+        }
+
+        match node {
+            Statement::Register(_) => Ok(self.get_register(node)?.to_string()),
+            Statement::Var(_) => self.gen_var(node),
+            Statement::BinaryOp { lhs, operator, rhs } => Ok(format!(
+                "({} {operator} {})",
+                self.value_key(lhs)?,
+                self.value_key(rhs)?
+            )),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Marks one pending read of `reg` as done.
+    fn unhold(&mut self, reg: Register) {
+        if let Some(idx) = self.held.iter().position(|held| *held == reg) {
+            self.held.remove(idx);
+        }
+    }
+
+    /// Forgets the value held by `reg`, once it is overwritten.
+    fn forget(&mut self, reg: Register) {
+        self.values.retain(|_, held| *held != reg);
+    }
+
     fn get_register(&self, offset: &Statement) -> miette::Result<Register> {
         let Statement::Register(offset) = offset else {
             unreachable!();
@@ -240,10 +321,18 @@ impl<'codegen> CodeGenerator<'codegen> {
         let reg_name = &self.source[Range::from(*offset)];
         match Register::try_from(reg_name) {
             Ok(reg) => Ok(reg),
-            Err(_) => Err(bail(self.source, REGISTER_HELP, REGISTER_MSG, *offset)),
+            Err(err) => Err(bail(self.source, &err.to_string(), REGISTER_MSG, *offset)),
         }
     }
 
+    /// Register holding the address of a pointer operand such as `&[r1]`.
+    fn get_register_pointer(&self, node: &Statement) -> miette::Result<Register> {
+        let Statement::Address(inner) = node else {
+            unreachable!();
+        };
+        self.get_register(inner.as_ref())
+    }
+
     fn get_address(&self, node: &Statement) -> miette::Result<String> {
         let Statement::Address(inner) = node else {
             return unexpected_statement(
@@ -287,6 +376,8 @@ impl<'codegen> CodeGenerator<'codegen> {
             self.code.push(formatted!(prefix, reg));
             self.temp_registers.push(reg);
         }
+        self.values.clear();
+        self.held.clear();
     }
 
     fn release_temp_register(&mut self, reg: Register) {
@@ -294,6 +385,7 @@ impl<'codegen> CodeGenerator<'codegen> {
         self.code.push(formatted!(prefix, reg));
         self.used_registers.retain(|r| *r != reg);
         self.temp_registers.push(reg);
+        self.forget(reg);
     }
 
     fn evaluate_constants(&self, node: &Statement) -> miette::Result<Option<String>> {
@@ -414,14 +506,18 @@ impl<'codegen> CodeGenerator<'codegen> {
     }
 
     fn gen_instruction(&mut self, instruction: &Instruction) -> miette::Result<()> {
+        // values are only shared within an instruction, the program may change them in between
+        self.values.clear();
+        self.held.clear();
+
         match instruction {
-            Instruction::MovRegReg(lhs, rhs) => {
+            Instruction::MovRegReg(_, lhs, rhs) => {
                 let prefix = InstructionPrefix::Mov;
                 let lhs = self.get_register(lhs)?;
                 let rhs = self.get_register(rhs)?;
                 self.code.push(formatted!(prefix, lhs, rhs));
             }
-            Instruction::MovLitReg(lhs, rhs) => {
+            Instruction::MovLitReg(_, lhs, rhs) => {
                 let prefix = InstructionPrefix::Mov;
                 let lhs = self.get_register(lhs)?;
 
@@ -434,7 +530,7 @@ impl<'codegen> CodeGenerator<'codegen> {
                 self.generate_code(prefix, rhs, Some(lhs))?;
                 self.release_all_temp_registers();
             }
-            Instruction::MovRegMem(lhs, rhs) => {
+            Instruction::MovRegMem(_, lhs, rhs) => {
                 let prefix = InstructionPrefix::Mov;
 
                 let Statement::Address(inner) = lhs else {
@@ -457,7 +553,7 @@ impl<'codegen> CodeGenerator<'codegen> {
                 let rhs = self.get_register(rhs)?;
                 self.code.push(formatted!(prefix, "&[{lhs}]", rhs));
             }
-            Instruction::MovMemReg(lhs, rhs) => {
+            Instruction::MovMemReg(_, lhs, rhs) => {
                 let prefix = InstructionPrefix::Mov;
                 let lhs = self.get_register(lhs)?;
 
@@ -479,7 +575,7 @@ impl<'codegen> CodeGenerator<'codegen> {
                 let rhs = self.get_address(rhs)?;
                 self.code.push(formatted!(prefix, lhs, "&[{rhs}]"));
             }
-            Instruction::MovLitMem(lhs, rhs) => {
+            Instruction::MovLitMem(_, lhs, rhs) => {
                 let prefix = InstructionPrefix::Mov;
 
                 let Statement::Address(inner) = lhs else {
@@ -515,25 +611,64 @@ impl<'codegen> CodeGenerator<'codegen> {
                 self.code.push(formatted!(prefix, "&[{lhs}]", rhs));
                 self.release_all_temp_registers();
             }
-            Instruction::MovRegPtrReg(lhs, rhs) => {
+            Instruction::MovRegPtrReg(_, lhs, rhs) => {
                 let prefix = InstructionPrefix::Mov;
-                let lhs = self.get_address(lhs)?;
-                let rhs = self.get_address(rhs)?;
-                self.code.push(formatted!(prefix, "&[{lhs}]", "&[{rhs}]"));
+                let lhs = self.get_register_pointer(lhs)?;
+                let rhs = match rhs {
+                    Statement::Address(_) => {
+                        self.warnings.push(warning(
+                            self.source,
+                            "`mov &[r1], &[r2]` is deprecated",
+                            "this stores r2 on the address held by r1, write it as `mov &[r1], r2` instead",
+                            instruction.offset(),
+                        ));
+                        self.get_register_pointer(rhs)?
+                    }
+                    _ => self.get_register(rhs)?,
+                };
+                self.code.push(formatted!(prefix, "&[{lhs}]", rhs));
+            }
+            Instruction::LoadRegPtr(_, lhs, rhs) => {
+                let prefix = InstructionPrefix::Mov;
+                let lhs = self.get_register(lhs)?;
+                let rhs = self.get_register_pointer(rhs)?;
+                self.code.push(formatted!(prefix, lhs, "&[{rhs}]"));
             }
-            Instruction::MovLitRegPtr(lhs, rhs) => {
+            Instruction::LoadRegPtrOff(_, lhs, rhs) => {
+                let prefix = InstructionPrefix::Mov;
+                let lhs = self.get_register(lhs)?;
+
+                let Statement::Address(inner) = rhs else {
+                    unreachable!();
+                };
+                let Statement::BinaryOp {
+                    lhs: address,
+                    rhs: offset,
+                    ..
+                } = inner.as_ref()
+                else {
+                    unreachable!();
+                };
+                let address = self.get_register(address)?;
+                let offset = match offset.as_ref() {
+                    Statement::Var(_) => self.gen_var(offset)?,
+                    _ => self.gen_hex_lit(offset)?,
+                };
+                self.code.push(formatted!(prefix, lhs, "&[{address} + {offset}]"));
+            }
+            Instruction::MovLitRegPtr(_, lhs, rhs) => {
                 let prefix = InstructionPrefix::Mov;
                 let lhs = self.get_address(lhs)?;
                 let rhs = self.gen_hex_lit(rhs)?;
                 self.code.push(formatted!(prefix, "&[{lhs}]", rhs));
             }
-            Instruction::Mov8RegReg(lhs, rhs) => {
+            Instruction::Mov8RegReg(_, lhs, rhs) => {
                 let prefix = InstructionPrefix::Mov8;
                 let lhs = self.get_register(lhs)?;
                 let rhs = self.get_register(rhs)?;
                 self.code.push(formatted!(prefix, lhs, rhs));
             }
-            Instruction::Mov8LitReg(lhs, rhs) => {
+            Instruction::Mov8LitReg(_, lhs, rhs) => {
                 let prefix = InstructionPrefix::Mov8;
                 let lhs = self.get_register(lhs)?;
 
@@ -546,7 +681,7 @@ impl<'codegen> CodeGenerator<'codegen> {
                 self.generate_code(prefix, rhs, Some(lhs))?;
                 self.release_all_temp_registers();
             }
-            Instruction::Mov8RegMem(lhs, rhs) => {
+            Instruction::Mov8RegMem(_, lhs, rhs) => {
                 let prefix = InstructionPrefix::Mov8;
 
                 let Statement::Address(inner) = lhs else {
@@ -569,7 +704,7 @@ impl<'codegen> CodeGenerator<'codegen> {
                 let rhs = self.get_register(rhs)?;
                 self.code.push(formatted!(prefix, "&[{lhs}]", rhs));
             }
-            Instruction::Mov8MemReg(lhs, rhs) => {
+            Instruction::Mov8MemReg(_, lhs, rhs) => {
                 let prefix = InstructionPrefix::Mov8;
                 let lhs = self.get_register(lhs)?;
 
@@ -591,7 +726,7 @@ impl<'codegen> CodeGenerator<'codegen> {
                 let rhs = self.get_address(rhs)?;
                 self.code.push(formatted!(prefix, lhs, "&[{rhs}]"));
             }
-            Instruction::Mov8LitMem(lhs, rhs) => {
+            Instruction::Mov8LitMem(_, lhs, rhs) => {
                 let prefix = InstructionPrefix::Mov8;
 
                 let Statement::Address(inner) = lhs else {
@@ -620,23 +755,23 @@ impl<'codegen> CodeGenerator<'codegen> {
                 self.code.push(formatted!(prefix, "&[{lhs}]", hex));
                 self.release_all_temp_registers();
             }
-            Instruction::Inc(reg) => {
+            Instruction::Inc(_, reg) => {
                 let prefix = InstructionPrefix::Inc;
                 let reg = self.get_register(reg)?;
                 self.code.push(formatted!(prefix, reg));
             }
-            Instruction::Dec(reg) => {
+            Instruction::Dec(_, reg) => {
                 let prefix = InstructionPrefix::Dec;
                 let reg = self.get_register(reg)?;
                 self.code.push(formatted!(prefix, reg));
             }
-            Instruction::AddRegReg(lhs, rhs) => {
+            Instruction::AddRegReg(_, lhs, rhs) => {
                 let prefix = InstructionPrefix::Add;
                 let lhs = self.get_register(lhs)?;
                 let rhs = self.get_register(rhs)?;
                 self.code.push(formatted!(prefix, lhs, rhs));
             }
-            Instruction::AddLitReg(lhs, rhs) => {
+            Instruction::AddLitReg(_, lhs, rhs) => {
                 let prefix = InstructionPrefix::Add;
                 let lhs = self.get_register(lhs)?;
 
@@ -649,13 +784,13 @@ impl<'codegen> CodeGenerator<'codegen> {
                 self.generate_code(prefix, rhs, Some(lhs))?;
                 self.release_all_temp_registers();
             }
-            Instruction::SubRegReg(lhs, rhs) => {
+            Instruction::SubRegReg(_, lhs, rhs) => {
                 let prefix = InstructionPrefix::Sub;
                 let lhs = self.get_register(lhs)?;
                 let rhs = self.get_register(rhs)?;
                 self.code.push(formatted!(prefix, lhs, rhs));
             }
-            Instruction::SubLitReg(lhs, rhs) => {
+            Instruction::SubLitReg(_, lhs, rhs) => {
                 let prefix = InstructionPrefix::Sub;
                 let lhs = self.get_register(lhs)?;
 
@@ -668,13 +803,13 @@ impl<'codegen> CodeGenerator<'codegen> {
                 self.generate_code(prefix, rhs, Some(lhs))?;
                 self.release_all_temp_registers();
             }
-            Instruction::MulRegReg(lhs, rhs) => {
+            Instruction::MulRegReg(_, lhs, rhs) => {
                 let prefix = InstructionPrefix::Mul;
                 let lhs = self.get_register(lhs)?;
                 let rhs = self.get_register(rhs)?;
                 self.code.push(formatted!(prefix, lhs, rhs));
             }
-            Instruction::MulLitReg(lhs, rhs) => {
+            Instruction::MulLitReg(_, lhs, rhs) => {
                 let prefix = InstructionPrefix::Mul;
                 let lhs = self.get_register(lhs)?;
 
@@ -687,13 +822,13 @@ impl<'codegen> CodeGenerator<'codegen> {
                 self.generate_code(prefix, rhs, Some(lhs))?;
                 self.release_all_temp_registers();
             }
-            Instruction::LshRegReg(lhs, rhs) => {
+            Instruction::LshRegReg(_, lhs, rhs) => {
                 let prefix = InstructionPrefix::Lsh;
                 let lhs = self.get_register(lhs)?;
                 let rhs = self.get_register(rhs)?;
                 self.code.push(formatted!(prefix, lhs, rhs));
             }
-            Instruction::LshLitReg(lhs, rhs) => {
+            Instruction::LshLitReg(_, lhs, rhs) => {
                 let prefix = InstructionPrefix::Lsh;
                 let lhs = self.get_register(lhs)?;
 
@@ -706,13 +841,13 @@ impl<'codegen> CodeGenerator<'codegen> {
                 self.generate_code(prefix, rhs, Some(lhs))?;
                 self.release_all_temp_registers();
             }
-            Instruction::RshRegReg(lhs, rhs) => {
+            Instruction::RshRegReg(_, lhs, rhs) => {
                 let prefix = InstructionPrefix::Rsh;
                 let lhs = self.get_register(lhs)?;
                 let rhs = self.get_register(rhs)?;
                 self.code.push(formatted!(prefix, lhs, rhs));
             }
-            Instruction::RshLitReg(lhs, rhs) => {
+            Instruction::RshLitReg(_, lhs, rhs) => {
                 let prefix = InstructionPrefix::Rsh;
                 let lhs = self.get_register(lhs)?;
 
@@ -725,13 +860,13 @@ impl<'codegen> CodeGenerator<'codegen> {
                 self.generate_code(prefix, rhs, Some(lhs))?;
                 self.release_all_temp_registers();
             }
-            Instruction::AndRegReg(lhs, rhs) => {
+            Instruction::AndRegReg(_, lhs, rhs) => {
                 let prefix = InstructionPrefix::And;
                 let lhs = self.get_register(lhs)?;
                 let rhs = self.get_register(rhs)?;
                 self.code.push(formatted!(prefix, lhs, rhs));
             }
-            Instruction::AndLitReg(lhs, rhs) => {
+            Instruction::AndLitReg(_, lhs, rhs) => {
                 let prefix = InstructionPrefix::And;
                 let lhs = self.get_register(lhs)?;
 
@@ -744,13 +879,13 @@ impl<'codegen> CodeGenerator<'codegen> {
                 self.generate_code(prefix, rhs, Some(lhs))?;
                 self.release_all_temp_registers();
             }
-            Instruction::OrRegReg(lhs, rhs) => {
+            Instruction::OrRegReg(_, lhs, rhs) => {
                 let prefix = InstructionPrefix::Or;
                 let lhs = self.get_register(lhs)?;
                 let rhs = self.get_register(rhs)?;
                 self.code.push(formatted!(prefix, lhs, rhs));
             }
-            Instruction::OrLitReg(lhs, rhs) => {
+            Instruction::OrLitReg(_, lhs, rhs) => {
                 let prefix = InstructionPrefix::Or;
                 let lhs = self.get_register(lhs)?;
 
@@ -763,13 +898,13 @@ impl<'codegen> CodeGenerator<'codegen> {
                 self.generate_code(prefix, rhs, Some(lhs))?;
                 self.release_all_temp_registers();
             }
-            Instruction::XorRegReg(lhs, rhs) => {
+            Instruction::XorRegReg(_, lhs, rhs) => {
                 let prefix = InstructionPrefix::Xor;
                 let lhs = self.get_register(lhs)?;
                 let rhs = self.get_register(rhs)?;
                 self.code.push(formatted!(prefix, lhs, rhs));
             }
-            Instruction::XorLitReg(lhs, rhs) => {
+            Instruction::XorLitReg(_, lhs, rhs) => {
                 let prefix = InstructionPrefix::Xor;
                 let lhs = self.get_register(lhs)?;
 
@@ -782,17 +917,17 @@ impl<'codegen> CodeGenerator<'codegen> {
                 self.generate_code(prefix, rhs, Some(lhs))?;
                 self.release_all_temp_registers();
             }
-            Instruction::Not(reg) => {
+            Instruction::Not(_, reg) => {
                 let prefix = InstructionPrefix::Not;
                 let reg = self.get_register(reg)?;
                 self.code.push(formatted!(prefix, reg));
             }
-            Instruction::PshReg(reg) => {
+            Instruction::PshReg(_, reg) => {
                 let prefix = InstructionPrefix::Psh;
                 let reg = self.get_register(reg)?;
                 self.code.push(formatted!(prefix, reg));
             }
-            Instruction::PshLit(lit) => {
+            Instruction::PshLit(_, lit) => {
                 let prefix = InstructionPrefix::Psh;
 
                 if let Statement::Var(offset) = lit {
@@ -811,12 +946,12 @@ impl<'codegen> CodeGenerator<'codegen> {
                 self.code.push(formatted!(prefix, result));
                 self.release_all_temp_registers();
             }
-            Instruction::Pop(reg) => {
+            Instruction::Pop(_, reg) => {
                 let prefix = InstructionPrefix::Pop;
                 let reg = self.get_register(reg)?;
                 self.code.push(formatted!(prefix, reg));
             }
-            Instruction::Call(address) => {
+            Instruction::Call(_, address) => {
                 let prefix = InstructionPrefix::Call;
 
                 let Statement::Address(inner) = address else {
@@ -841,7 +976,7 @@ impl<'codegen> CodeGenerator<'codegen> {
                 let prefix = InstructionPrefix::Ret;
                 self.code.push(prefix.to_string());
             }
-            Instruction::JeqReg(lhs, rhs) => {
+            Instruction::JeqReg(_, lhs, rhs) => {
                 let prefix = InstructionPrefix::Jeq;
 
                 let Statement::Address(inner) = lhs else {
@@ -864,7 +999,7 @@ impl<'codegen> CodeGenerator<'codegen> {
                 let rhs = self.get_register(rhs)?;
                 self.code.push(formatted!(prefix, "&[{lhs}]", rhs));
             }
-            Instruction::JeqLit(lhs, rhs) => {
+            Instruction::JeqLit(_, lhs, rhs) => {
                 let prefix = InstructionPrefix::Jeq;
 
                 let Statement::Address(inner) = lhs else {
@@ -898,7 +1033,7 @@ impl<'codegen> CodeGenerator<'codegen> {
                 self.code.push(formatted!(prefix, "&[{lhs}]", rhs));
                 self.release_all_temp_registers();
             }
-            Instruction::JgtReg(lhs, rhs) => {
+            Instruction::JgtReg(_, lhs, rhs) => {
                 let prefix = InstructionPrefix::Jgt;
 
                 let Statement::Address(inner) = lhs else {
@@ -921,7 +1056,7 @@ impl<'codegen> CodeGenerator<'codegen> {
                 let rhs = self.get_register(rhs)?;
                 self.code.push(formatted!(prefix, "&[{lhs}]", rhs));
             }
-            Instruction::JgtLit(lhs, rhs) => {
+            Instruction::JgtLit(_, lhs, rhs) => {
                 let prefix = InstructionPrefix::Jgt;
 
                 let Statement::Address(inner) = lhs else {
@@ -955,7 +1090,7 @@ impl<'codegen> CodeGenerator<'codegen> {
                 self.code.push(formatted!(prefix, "&[{lhs}]", rhs));
                 self.release_all_temp_registers();
             }
-            Instruction::JneReg(lhs, rhs) => {
+            Instruction::JneReg(_, lhs, rhs) => {
                 let prefix = InstructionPrefix::Jne;
 
                 let Statement::Address(inner) = lhs else {
@@ -978,7 +1113,7 @@ impl<'codegen> CodeGenerator<'codegen> {
                 let rhs = self.get_register(rhs)?;
                 self.code.push(formatted!(prefix, "&[{lhs}]", rhs));
             }
-            Instruction::JneLit(lhs, rhs) => {
+            Instruction::JneLit(_, lhs, rhs) => {
                 let prefix = InstructionPrefix::Jne;
 
                 let Statement::Address(inner) = lhs else {
@@ -1012,7 +1147,7 @@ impl<'codegen> CodeGenerator<'codegen> {
                 self.code.push(formatted!(prefix, "&[{lhs}]", rhs));
                 self.release_all_temp_registers();
             }
-            Instruction::JgeReg(lhs, rhs) => {
+            Instruction::JgeReg(_, lhs, rhs) => {
                 let prefix = InstructionPrefix::Jge;
 
                 let Statement::Address(inner) = lhs else {
@@ -1035,7 +1170,7 @@ impl<'codegen> CodeGenerator<'codegen> {
                 let rhs = self.get_register(rhs)?;
                 self.code.push(formatted!(prefix, "&[{lhs}]", rhs));
             }
-            Instruction::JgeLit(lhs, rhs) => {
+            Instruction::JgeLit(_, lhs, rhs) => {
                 let prefix = InstructionPrefix::Jge;
 
                 let Statement::Address(inner) = lhs else {
@@ -1069,7 +1204,7 @@ impl<'codegen> CodeGenerator<'codegen> {
                 self.code.push(formatted!(prefix, "&[{lhs}]", rhs));
                 self.release_all_temp_registers();
             }
-            Instruction::JleReg(lhs, rhs) => {
+            Instruction::JleReg(_, lhs, rhs) => {
                 let prefix = InstructionPrefix::Jle;
 
                 let Statement::Address(inner) = lhs else {
@@ -1092,7 +1227,7 @@ impl<'codegen> CodeGenerator<'codegen> {
                 let rhs = self.get_register(rhs)?;
                 self.code.push(formatted!(prefix, "&[{lhs}]", rhs));
             }
-            Instruction::JltLit(lhs, rhs) => {
+            Instruction::JltLit(_, lhs, rhs) => {
                 let prefix = InstructionPrefix::Jlt;
 
                 let Statement::Address(inner) = lhs else {
@@ -1126,7 +1261,7 @@ impl<'codegen> CodeGenerator<'codegen> {
                 self.code.push(formatted!(prefix, "&[{lhs}]", rhs));
                 self.release_all_temp_registers();
             }
-            Instruction::JltReg(lhs, rhs) => {
+            Instruction::JltReg(_, lhs, rhs) => {
                 let prefix = InstructionPrefix::Jlt;
 
                 let Statement::Address(inner) = lhs else {
@@ -1149,7 +1284,7 @@ impl<'codegen> CodeGenerator<'codegen> {
                 let rhs = self.get_register(rhs)?;
                 self.code.push(formatted!(prefix, "&[{lhs}]", rhs));
             }
-            Instruction::JleLit(lhs, rhs) => {
+            Instruction::JleLit(_, lhs, rhs) => {
                 let prefix = InstructionPrefix::Jle;
 
                 let Statement::Address(inner) = lhs else {
@@ -1183,7 +1318,7 @@ impl<'codegen> CodeGenerator<'codegen> {
                 self.code.push(formatted!(prefix, "&[{lhs}]", rhs));
                 self.release_all_temp_registers();
             }
-            Instruction::Jmp(address) => {
+            Instruction::Jmp(_, address) => {
                 let prefix = InstructionPrefix::Jmp;
 
                 let Statement::Address(inner) = address else {
@@ -1205,11 +1340,17 @@ impl<'codegen> CodeGenerator<'codegen> {
                 self.code.push(formatted!(prefix, "&[{address}]"));
                 self.release_all_temp_registers();
             }
-            Instruction::Hlt(_) => {
+            Instruction::Hlt(_, code) => {
                 let prefix = InstructionPrefix::Hlt;
-                self.code.push(prefix.to_string());
+                match code {
+                    Some(code) => {
+                        let code = self.gen_hex_lit(code)?;
+                        self.code.push(formatted!(prefix, code));
+                    }
+                    None => self.code.push(prefix.to_string()),
+                }
             }
-            Instruction::Int(lit) => {
+            Instruction::Int(_, lit) => {
                 let prefix = InstructionPrefix::Int;
                 let lit = self.gen_hex_lit(lit)?;
                 self.code.push(formatted!(prefix, lit));
@@ -1253,6 +1394,34 @@ pub fn generate(modules: ResolvedModules) -> miette::Result<Vec<CodegenModule>>
     Ok(gen_modules)
 }
 
+/// Warnings about deprecated syntax on every module, in the order modules are generated.
+pub fn warnings(modules: ResolvedModules) -> miette::Result<Vec<miette::Report>> {
+    let mut warnings = vec![];
+    for (_, source, ast) in modules {
+        let mut codegen = CodeGenerator::new(&source, &ast);
+        codegen.generate()?;
+        warnings.append(&mut codegen.warnings);
+    }
+
+    Ok(warnings)
+}
+
+/// Warnings and the first error of every module along with the path of their module, modules
+/// after one that fails to generate are still checked.
+pub fn diagnostics(modules: ResolvedModules) -> Vec<(PathBuf, miette::Report)> {
+    let mut diagnostics = vec![];
+    for (module, source, ast) in modules {
+        let mut codegen = CodeGenerator::new(&source, &ast);
+        let result = codegen.generate();
+        diagnostics.extend(codegen.warnings.drain(..).map(|warning| (module.path.clone(), warning)));
+        if let Err(err) = result {
+            diagnostics.push((module.path, err));
+        }
+    }
+
+    diagnostics
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1441,6 +1610,32 @@ POP R8"#
         );
     }
 
+    #[test]
+    fn test_gen_register_pointers() {
+        let source = "mov r1, &[r2]\nmov r1, &[r2 + $04]\nmov r1, &[r2 + !field]\nmov &[r1], r2";
+        let ast = crate::parser::parse(source).unwrap();
+        let mut generator = CodeGenerator::new(source, &ast);
+
+        generator.generate().unwrap();
+        let result = generator.to_string();
+        assert_eq!(
+            result,
+            "MOV R1, &[R2]\nMOV R1, &[R2 + $04]\nMOV R1, &[R2 + !field]\nMOV &[R1], R2"
+        );
+        assert!(generator.warnings.is_empty());
+
+        // the ambiguous store still assembles, into its canonical form
+        let source = "mov &[r1], &[r2]";
+        let ast = crate::parser::parse(source).unwrap();
+        let mut generator = CodeGenerator::new(source, &ast);
+
+        generator.generate().unwrap();
+        let result = generator.to_string();
+        assert_eq!(result, "MOV &[R1], R2");
+        assert_eq!(generator.warnings.len(), 1);
+        assert_eq!(generator.warnings[0].severity(), Some(miette::Severity::Warning));
+    }
+
     #[test]
     fn test_gen_mov_lit_mem() {
         let source = "mov &[$c0d3], $c0d3";
@@ -1535,11 +1730,10 @@ POP R8"#
 
         generator.generate().unwrap();
         let result = generator.to_string();
-        assert_eq!(result, "MOV &[r2], &[r3]");
+        assert_eq!(result, "MOV &[R2], R3");
     }
 
     #[test]
-    #[should_panic]
     fn test_gen_too_large() {
         let source = "mov &[!var + $c0d3 + r2], [$c0d3 + r2 + !var]";
         let ast = crate::parser::parse(source).unwrap();
@@ -1559,19 +1753,75 @@ ADD R7, R6
 ADD R8, R7
 PSH R5
 MOV R5, $C0D3
-PSH R4
-MOV R4, R2
-PSH R3
-MOV R3, !var
-ADD R4, R3
-ADD R5, R4
-POP R3
-POP R4
+PSH ACC
+MOV ACC, !var
+ADD R6, ACC
+ADD R5, R6
+MOV &[R8], R5
+POP ACC
 POP R5
 POP R6
 POP R7
-POP R8
-MOV &[R8], R5"#
+POP R8"#
+        );
+    }
+
+    #[test]
+    fn test_gen_shares_repeated_values() {
+        let source = "mov r1, [r2 + r2]";
+        let ast = crate::parser::parse(source).unwrap();
+        let mut generator = CodeGenerator::new(source, &ast);
+
+        generator.generate().unwrap();
+        let result = generator.to_string();
+        assert_eq!(
+            result,
+            r#"PSH R8
+MOV R8, R2
+ADD R8, R8
+MOV R1, R8
+POP R8"#
+        );
+
+        // the value of the outer left side is still read after the inner operation, so the inner
+        // one works on a copy of it
+        let source = "mov r1, [!var + !var + r2]";
+        let ast = crate::parser::parse(source).unwrap();
+        let mut generator = CodeGenerator::new(source, &ast);
+
+        generator.generate().unwrap();
+        let result = generator.to_string();
+        assert_eq!(
+            result,
+            r#"PSH R8
+MOV R8, !var
+PSH R7
+MOV R7, R2
+PSH R6
+MOV R6, R8
+ADD R6, R7
+ADD R8, R6
+MOV R1, R8
+POP R6
+POP R7
+POP R8"#
+        );
+    }
+
+    #[test]
+    fn test_gen_register_names() {
+        let source = "mov Acc, r1";
+        let ast = crate::parser::parse(source).unwrap();
+        let mut generator = CodeGenerator::new(source, &ast);
+        generator.generate().unwrap();
+        assert_eq!(generator.to_string(), "MOV ACC, R1");
+
+        // names outside the canonical set are rejected while parsing, listing the valid ones
+        let err = crate::parser::parse("mov r9, r1").unwrap_err();
+        assert_eq!(err.to_string(), REGISTER_MSG);
+        assert_eq!(
+            err.help().unwrap().to_string(),
+            "'r9' is not a valid register name, expected one of ACC, IP, R1, R2, R3, R4, R5, R6, R7, R8, SP, FP, IM"
         );
     }
 
@@ -2205,11 +2455,8 @@ MOV R7, R2
 ADD R8, R7
 PSH R6
 MOV R6, $0303
-PSH R5
-MOV R5, R2
-ADD R6, R5
+ADD R6, R7
 JEQ &[R8], R6
-POP R5
 POP R6
 POP R7
 POP R8"#
@@ -1,7 +1,10 @@
+use aya_cpu::instruction::Instruction as CpuInstruction;
 use aya_cpu::register::Register;
+use aya_cpu::word::Word;
 
 use crate::codegen::CodegenModule;
-use crate::parser::ast::{Ast, Instruction, InstructionKind, Statement};
+use crate::parser::ast::{Ast, Instruction, Statement};
+use crate::symbols::Symbol;
 use crate::utils::bail_multi;
 
 fn encode_literal_or_address(module: &mut CodegenModule, node: &Statement, inst: &Instruction) -> miette::Result<u16> {
@@ -111,33 +114,66 @@ fn encode_literal_byte(module: &mut CodegenModule, node: &Statement, inst: &Inst
     }
 }
 
-fn encode_register(source: &str, value: &Statement) -> miette::Result<u8> {
+fn encode_register(source: &str, value: &Statement) -> miette::Result<Register> {
     let Statement::Register(name) = value else {
         unreachable!();
     };
     let name_str = &source[name.start..name.end];
-    match Register::try_from(name_str) {
-        Ok(register) => Ok(register.into()),
-        Err(_) => {
+    Register::try_from(name_str).map_err(|err| {
+        let labels = vec![
+            miette::LabeledSpan::at(*name, "this identifier"),
+            miette::LabeledSpan::at(value.offset(), "this statement"),
+        ];
+        bail_multi(
+            source,
+            labels,
+            "[INVALID_STATEMENT]: error while compiling statement",
+            &err.to_string(),
+        )
+    })
+}
+
+/// Register holding the address of a pointer operand such as `&[r1]`.
+fn encode_register_pointer(source: &str, value: &Statement) -> miette::Result<Register> {
+    let Statement::Address(inner) = value else {
+        unreachable!();
+    };
+    encode_register(source, inner.as_ref())
+}
+
+fn encode_address(module: &mut CodegenModule, node: &Statement, inst: &Instruction) -> miette::Result<Word> {
+    if let Statement::Address(inner) = node {
+        if let Statement::Register(name) = inner.as_ref() {
             let labels = vec![
-                miette::LabeledSpan::at(*name, "this identifier"),
-                miette::LabeledSpan::at(value.offset(), "this statement"),
+                miette::LabeledSpan::at(*name, "this register"),
+                miette::LabeledSpan::at(inst.offset(), "this statement"),
             ];
-            Err(bail_multi(
-                source,
+            return Err(bail_multi(
+                &module.code,
                 labels,
                 "[INVALID_STATEMENT]: error while compiling statement",
-                "hex number is not within the u8 range",
-            ))
+                "this instruction takes a literal address, it can't address memory through a register",
+            ));
         }
     }
+    encode_literal_or_address(module, node, inst).map(Word::from)
 }
 
-fn collect_symbols(module: &mut CodegenModule, ast: &Ast, address: &mut u16) {
+fn collect_symbols(module: &mut CodegenModule, ast: &Ast, address: &mut u16, labels: &mut Vec<Symbol>) {
+    let module_path = module.path.display().to_string();
+    let mut label = |name: &str, address: u16| {
+        labels.push(Symbol {
+            address,
+            name: name.into(),
+            module: module_path.clone(),
+        })
+    };
+
     for node in ast.statements.iter() {
         match node {
             Statement::Label { name, exported } => {
                 let name = &module.code[name.start..name.end];
+                label(name, *address);
                 module.symbols.insert(name.into(), *address);
                 if *exported {
                     module.exports.insert(name.into(), *address);
@@ -150,6 +186,7 @@ fn collect_symbols(module: &mut CodegenModule, ast: &Ast, address: &mut u16) {
                 exported,
             } => {
                 let name = &module.code[name.start..name.end];
+                label(name, *address);
                 module.symbols.insert(name.into(), *address);
                 let byte_size = if *size == 8 { 1 } else { 2 };
                 let total_size = values.len() * byte_size;
@@ -228,173 +265,196 @@ fn compile_data_block(
     Ok(())
 }
 
-fn compile_instruction(
-    module: &mut CodegenModule,
-    inst: &Instruction,
-    bytecode: &mut [u8; u16::MAX as usize],
-    address: &mut u16,
-) -> miette::Result<()> {
-    bytecode[*address as usize] = inst.opcode().into();
-    *address += 1;
-
-    match inst.kind() {
-        InstructionKind::LitReg | InstructionKind::MemReg | InstructionKind::MemReg8 => {
-            let lhs = inst.lhs();
-            let rhs = inst.rhs();
-            let register = encode_register(&module.code, lhs)?;
-            let value = encode_literal_or_address(module, rhs, inst)?;
-            let [lower, upper] = u16::to_le_bytes(value);
-            bytecode[*address as usize] = register;
-            *address += 1;
-            bytecode[*address as usize] = lower;
-            *address += 1;
-            bytecode[*address as usize] = upper;
-            *address += 1;
+/// Resolves the operands of `inst` into the instruction the cpu runs, whose encoding comes from
+/// aya-cpu so the assembler and the cpu can't disagree on it.
+fn lower_instruction(module: &mut CodegenModule, inst: &Instruction) -> miette::Result<CpuInstruction> {
+    let instruction = match inst {
+        Instruction::MovLitReg(_, lhs, rhs) => CpuInstruction::MovLitReg(
+            encode_register(&module.code, lhs)?,
+            encode_literal_or_address(module, rhs, inst)?,
+        ),
+        Instruction::MovRegReg(_, lhs, rhs) => {
+            CpuInstruction::MovRegReg(encode_register(&module.code, lhs)?, encode_register(&module.code, rhs)?)
         }
-        InstructionKind::LitReg8 => {
-            let lhs = inst.lhs();
-            let rhs = inst.rhs();
-            let register = encode_register(&module.code, lhs)?;
-            let value = encode_literal_byte(module, rhs, inst)?;
-            bytecode[*address as usize] = register;
-            *address += 1;
-            bytecode[*address as usize] = value;
-            *address += 1;
+        Instruction::MovRegMem(_, lhs, rhs) => {
+            CpuInstruction::MovRegMem(encode_register(&module.code, rhs)?, encode_address(module, lhs, inst)?)
         }
-        InstructionKind::LitMem8 => {
-            let lhs = inst.lhs();
-            let rhs = inst.rhs();
-            let value = encode_literal_or_address(module, lhs, inst)?;
-            let [lower, upper] = u16::to_le_bytes(value);
-            bytecode[*address as usize] = lower;
-            *address += 1;
-            bytecode[*address as usize] = upper;
-            *address += 1;
-            let value = encode_literal_byte(module, rhs, inst)?;
-            bytecode[*address as usize] = value;
-            *address += 1;
+        Instruction::MovMemReg(_, lhs, rhs) => {
+            CpuInstruction::MovMemReg(encode_address(module, rhs, inst)?, encode_register(&module.code, lhs)?)
         }
-        InstructionKind::RegMem8 => {
-            let lhs = inst.lhs();
-            let rhs = inst.rhs();
-            let Statement::Address(inner) = lhs else {
-                unreachable!();
+        Instruction::MovLitMem(_, lhs, rhs) => CpuInstruction::MovLitMem(
+            encode_address(module, lhs, inst)?,
+            encode_literal_or_address(module, rhs, inst)?,
+        ),
+        Instruction::MovRegPtrReg(_, lhs, rhs) => {
+            // the deprecated `mov &[r1], &[r2]` stores the same way as `mov &[r1], r2`
+            let rhs = match rhs {
+                Statement::Address(_) => encode_register_pointer(&module.code, rhs)?,
+                _ => encode_register(&module.code, rhs)?,
             };
-
-            if let Statement::Register(_) = inner.as_ref() {
-                let value = encode_register(&module.code, inner.as_ref())?;
-                let register = encode_register(&module.code, rhs)?;
-                bytecode[*address as usize] = value;
-                *address += 1;
-                bytecode[*address as usize] = register;
-                *address += 1;
-            } else {
-                let value = encode_literal_or_address(module, lhs, inst)?;
-                let [lower, upper] = u16::to_le_bytes(value);
-                let register = encode_register(&module.code, rhs)?;
-                bytecode[*address as usize] = lower;
-                *address += 1;
-                bytecode[*address as usize] = upper;
-                *address += 1;
-                bytecode[*address as usize] = register;
-                *address += 1;
-            }
+            CpuInstruction::MovRegPtrReg(encode_register_pointer(&module.code, lhs)?, rhs)
         }
-        InstructionKind::RegMem => {
-            let lhs = inst.lhs();
-            let rhs = inst.rhs();
-            let Statement::Address(inner) = lhs else {
+        Instruction::LoadRegPtr(_, lhs, rhs) => CpuInstruction::LoadRegPtr(
+            encode_register(&module.code, lhs)?,
+            encode_register_pointer(&module.code, rhs)?,
+        ),
+        Instruction::LoadRegPtrOff(_, lhs, rhs) => {
+            let Statement::Address(inner) = rhs else {
                 unreachable!();
             };
-
-            if let Statement::Register(_) = inner.as_ref() {
-                let value = encode_register(&module.code, inner.as_ref())?;
-                let register = encode_register(&module.code, rhs)?;
-                bytecode[*address as usize] = value;
-                *address += 1;
-                bytecode[*address as usize] = 0;
-                *address += 1;
-                bytecode[*address as usize] = register;
-                *address += 1;
-            } else {
-                let value = encode_literal_or_address(module, lhs, inst)?;
-                let [lower, upper] = u16::to_le_bytes(value);
-                let register = encode_register(&module.code, rhs)?;
-                bytecode[*address as usize] = lower;
-                *address += 1;
-                bytecode[*address as usize] = upper;
-                *address += 1;
-                bytecode[*address as usize] = register;
-                *address += 1;
-            }
-        }
-        InstructionKind::RegReg | InstructionKind::RegPtrReg | InstructionKind::RegReg8 => {
-            let lhs = inst.lhs();
-            let rhs = inst.rhs();
-            let dest = encode_register(&module.code, lhs)?;
-            let from = encode_register(&module.code, rhs)?;
-            bytecode[*address as usize] = dest;
-            *address += 1;
-            bytecode[*address as usize] = from;
-            *address += 1;
-        }
-        InstructionKind::LitRegPtr => {
-            let lhs = inst.lhs();
-            let rhs = inst.rhs();
-
-            let Statement::Address(inner) = lhs else {
+            let Statement::BinaryOp {
+                lhs: address,
+                rhs: offset,
+                ..
+            } = inner.as_ref()
+            else {
                 unreachable!();
             };
-
-            let reg = encode_register(&module.code, inner.as_ref())?;
-            let lit = encode_literal_or_address(module, rhs, inst)?;
-            let [lower, upper] = u16::to_le_bytes(lit);
-
-            bytecode[*address as usize] = reg;
-            *address += 1;
-            bytecode[*address as usize] = lower;
-            *address += 1;
-            bytecode[*address as usize] = upper;
-            *address += 1;
+            CpuInstruction::LoadRegPtrOff(
+                encode_register(&module.code, lhs)?,
+                encode_register(&module.code, address)?,
+                encode_literal_or_address(module, offset, inst)?,
+            )
+        }
+        Instruction::MovLitRegPtr(_, lhs, rhs) => CpuInstruction::MovLitRegPtr(
+            encode_register_pointer(&module.code, lhs)?,
+            encode_literal_or_address(module, rhs, inst)?,
+        ),
+        Instruction::Mov8LitReg(_, lhs, rhs) => CpuInstruction::Mov8LitReg(
+            encode_register(&module.code, lhs)?,
+            encode_literal_byte(module, rhs, inst)?,
+        ),
+        Instruction::Mov8RegReg(_, lhs, rhs) => {
+            CpuInstruction::Mov8RegReg(encode_register(&module.code, lhs)?, encode_register(&module.code, rhs)?)
+        }
+        Instruction::Mov8RegMem(_, lhs, rhs) => {
+            CpuInstruction::Mov8RegMem(encode_register(&module.code, rhs)?, encode_address(module, lhs, inst)?)
         }
-        InstructionKind::LitMem => {
-            let lhs = inst.lhs();
-            let rhs = inst.rhs();
-            let value = encode_literal_or_address(module, lhs, inst)?;
-            let [lower, upper] = u16::to_le_bytes(value);
-            bytecode[*address as usize] = lower;
-            *address += 1;
-            bytecode[*address as usize] = upper;
-            *address += 1;
-            let value = encode_literal_or_address(module, rhs, inst)?;
-            let [lower, upper] = u16::to_le_bytes(value);
-            bytecode[*address as usize] = lower;
-            *address += 1;
-            bytecode[*address as usize] = upper;
-            *address += 1;
+        Instruction::Mov8MemReg(_, lhs, rhs) => {
+            CpuInstruction::Mov8MemReg(encode_address(module, rhs, inst)?, encode_register(&module.code, lhs)?)
         }
-        InstructionKind::SingleReg => {
-            let lhs = inst.lhs();
-            let register = encode_register(&module.code, lhs)?;
-            bytecode[*address as usize] = register;
-            *address += 1;
+        Instruction::Mov8LitMem(_, lhs, rhs) => CpuInstruction::Mov8LitMem(
+            encode_address(module, lhs, inst)?,
+            encode_literal_byte(module, rhs, inst)?,
+        ),
+        Instruction::AddRegReg(_, lhs, rhs)
+        | Instruction::SubRegReg(_, lhs, rhs)
+        | Instruction::MulRegReg(_, lhs, rhs)
+        | Instruction::LshRegReg(_, lhs, rhs)
+        | Instruction::RshRegReg(_, lhs, rhs)
+        | Instruction::AndRegReg(_, lhs, rhs)
+        | Instruction::OrRegReg(_, lhs, rhs)
+        | Instruction::XorRegReg(_, lhs, rhs) => {
+            let (lhs, rhs) = (encode_register(&module.code, lhs)?, encode_register(&module.code, rhs)?);
+            match inst {
+                Instruction::AddRegReg(..) => CpuInstruction::AddRegReg(lhs, rhs),
+                Instruction::SubRegReg(..) => CpuInstruction::SubRegReg(lhs, rhs),
+                Instruction::MulRegReg(..) => CpuInstruction::MulRegReg(lhs, rhs),
+                Instruction::LshRegReg(..) => CpuInstruction::LshRegReg(lhs, rhs),
+                Instruction::RshRegReg(..) => CpuInstruction::RshRegReg(lhs, rhs),
+                Instruction::AndRegReg(..) => CpuInstruction::AndRegReg(lhs, rhs),
+                Instruction::OrRegReg(..) => CpuInstruction::OrRegReg(lhs, rhs),
+                _ => CpuInstruction::XorRegReg(lhs, rhs),
+            }
         }
-        InstructionKind::SingleLit => {
-            let lhs = inst.lhs();
-            let value = encode_literal_or_address(module, lhs, inst)?;
-            let [lower, upper] = u16::to_le_bytes(value);
-            bytecode[*address as usize] = lower;
-            *address += 1;
-            bytecode[*address as usize] = upper;
-            *address += 1;
+        Instruction::AddLitReg(_, lhs, rhs)
+        | Instruction::SubLitReg(_, lhs, rhs)
+        | Instruction::MulLitReg(_, lhs, rhs)
+        | Instruction::LshLitReg(_, lhs, rhs)
+        | Instruction::RshLitReg(_, lhs, rhs)
+        | Instruction::AndLitReg(_, lhs, rhs)
+        | Instruction::OrLitReg(_, lhs, rhs)
+        | Instruction::XorLitReg(_, lhs, rhs) => {
+            let (reg, lit) = (
+                encode_register(&module.code, lhs)?,
+                encode_literal_or_address(module, rhs, inst)?,
+            );
+            match inst {
+                Instruction::AddLitReg(..) => CpuInstruction::AddLitReg(reg, lit),
+                Instruction::SubLitReg(..) => CpuInstruction::SubLitReg(reg, lit),
+                Instruction::MulLitReg(..) => CpuInstruction::MulLitReg(reg, lit),
+                Instruction::LshLitReg(..) => CpuInstruction::LshLitReg(reg, lit),
+                Instruction::RshLitReg(..) => CpuInstruction::RshLitReg(reg, lit),
+                Instruction::AndLitReg(..) => CpuInstruction::AndLitReg(reg, lit),
+                Instruction::OrLitReg(..) => CpuInstruction::OrLitReg(reg, lit),
+                _ => CpuInstruction::XorLitReg(reg, lit),
+            }
         }
-        InstructionKind::NoArgs => {}
+        Instruction::Inc(_, lhs) => CpuInstruction::IncReg(encode_register(&module.code, lhs)?),
+        Instruction::Dec(_, lhs) => CpuInstruction::DecReg(encode_register(&module.code, lhs)?),
+        Instruction::Not(_, lhs) => CpuInstruction::Not(encode_register(&module.code, lhs)?),
+        Instruction::JeqLit(_, lhs, rhs)
+        | Instruction::JgtLit(_, lhs, rhs)
+        | Instruction::JneLit(_, lhs, rhs)
+        | Instruction::JgeLit(_, lhs, rhs)
+        | Instruction::JleLit(_, lhs, rhs)
+        | Instruction::JltLit(_, lhs, rhs) => {
+            let (address, lit) = (
+                encode_address(module, lhs, inst)?,
+                encode_literal_or_address(module, rhs, inst)?,
+            );
+            match inst {
+                Instruction::JeqLit(..) => CpuInstruction::JeqLit(address, lit),
+                Instruction::JgtLit(..) => CpuInstruction::JgtLit(address, lit),
+                Instruction::JneLit(..) => CpuInstruction::JneLit(address, lit),
+                Instruction::JgeLit(..) => CpuInstruction::JgeLit(address, lit),
+                Instruction::JleLit(..) => CpuInstruction::JleLit(address, lit),
+                _ => CpuInstruction::JltLit(address, lit),
+            }
+        }
+        Instruction::JeqReg(_, lhs, rhs)
+        | Instruction::JgtReg(_, lhs, rhs)
+        | Instruction::JneReg(_, lhs, rhs)
+        | Instruction::JgeReg(_, lhs, rhs)
+        | Instruction::JleReg(_, lhs, rhs)
+        | Instruction::JltReg(_, lhs, rhs) => {
+            let (address, reg) = (encode_address(module, lhs, inst)?, encode_register(&module.code, rhs)?);
+            match inst {
+                Instruction::JeqReg(..) => CpuInstruction::JeqReg(address, reg),
+                Instruction::JgtReg(..) => CpuInstruction::JgtReg(address, reg),
+                Instruction::JneReg(..) => CpuInstruction::JneReg(address, reg),
+                Instruction::JgeReg(..) => CpuInstruction::JgeReg(address, reg),
+                Instruction::JleReg(..) => CpuInstruction::JleReg(address, reg),
+                _ => CpuInstruction::JltReg(address, reg),
+            }
+        }
+        Instruction::Jmp(_, lhs) => CpuInstruction::Jmp(encode_address(module, lhs, inst)?),
+        Instruction::PshLit(_, lhs) => CpuInstruction::PushLit(encode_literal_or_address(module, lhs, inst)?),
+        Instruction::PshReg(_, lhs) => CpuInstruction::PushReg(encode_register(&module.code, lhs)?),
+        Instruction::Pop(_, lhs) => CpuInstruction::PopReg(encode_register(&module.code, lhs)?),
+        Instruction::Call(_, lhs) => CpuInstruction::Call(encode_address(module, lhs, inst)?),
+        Instruction::Ret(_) => CpuInstruction::Ret,
+        Instruction::Hlt(_, code) => CpuInstruction::Halt(match code {
+            Some(code) => encode_literal_byte(module, code, inst)?,
+            None => 0,
+        }),
+        Instruction::Int(_, lhs) => CpuInstruction::Int(encode_literal_or_address(module, lhs, inst)?),
+        Instruction::Rti(_) => CpuInstruction::Rti,
     };
 
+    Ok(instruction)
+}
+
+fn compile_instruction(
+    module: &mut CodegenModule,
+    inst: &Instruction,
+    bytecode: &mut [u8; u16::MAX as usize],
+    address: &mut u16,
+) -> miette::Result<()> {
+    for byte in lower_instruction(module, inst)?.encode() {
+        bytecode[*address as usize] = byte;
+        *address += 1;
+    }
+
     Ok(())
 }
 
-fn compile_module(module: &mut CodegenModule, ast: &Ast, bytecode: &mut [u8; u16::MAX as usize]) -> miette::Result<()> {
+/// Returns the address right past the last byte the module emitted.
+fn compile_module(
+    module: &mut CodegenModule,
+    ast: &Ast,
+    bytecode: &mut [u8; u16::MAX as usize],
+) -> miette::Result<u16> {
     let mut start_address = module.address;
     for node in ast.statements.iter() {
         match node {
@@ -403,24 +463,40 @@ fn compile_module(module: &mut CodegenModule, ast: &Ast, bytecode: &mut [u8; u16
             _ => {}
         }
     }
-    Ok(())
+    Ok(start_address)
+}
+
+pub fn compile(modules: Vec<CodegenModule>) -> miette::Result<Vec<u8>> {
+    compile_with_symbols(modules).map(|(bytecode, _)| bytecode)
 }
 
-pub fn compile(mut modules: Vec<CodegenModule>) -> miette::Result<Vec<u8>> {
+/// Compiles the modules, also returning every label and data block they define.
+pub fn compile_with_symbols(modules: Vec<CodegenModule>) -> miette::Result<(Vec<u8>, Vec<Symbol>)> {
+    compile_at(modules, 0)
+}
+
+/// Compiles modules laid out from `base`, the bytecode starts at `base` while symbols keep their
+/// addresses relative to the start of code memory.
+pub fn compile_at(mut modules: Vec<CodegenModule>, base: u16) -> miette::Result<(Vec<u8>, Vec<Symbol>)> {
     let mut bytecode = [0; u16::MAX as usize];
+    let mut labels = vec![];
+    // emitted bytes can be zero, such as the code of a trailing `hlt`, so the bytecode ends
+    // where the last module that emitted anything does rather than at its last nonzero byte
+    let mut last_address = base;
 
     for module in modules.iter_mut() {
         let ast = crate::parser::parse(&module.code)?;
         let mut module_address = module.address;
-        collect_symbols(module, &ast, &mut module_address);
-        compile_module(module, &ast, &mut bytecode)?;
+        collect_symbols(module, &ast, &mut module_address, &mut labels);
+        let end = compile_module(module, &ast, &mut bytecode)?;
+        if end > module.address {
+            last_address = last_address.max(end);
+        }
     }
 
-    let last_address = bytecode.iter().rev().position(|&b| b != 0).unwrap_or(0);
-    let last_address = u16::MAX as usize - last_address;
-    let bytecode = bytecode[..last_address].to_vec();
+    let bytecode = bytecode[base as usize..last_address as usize].to_vec();
 
-    Ok(bytecode)
+    Ok((bytecode, labels))
 }
 
 #[cfg(test)]
@@ -498,4 +574,100 @@ mod tests {
             ]
         );
     }
+
+    fn module(code: &str) -> CodegenModule {
+        CodegenModule {
+            name: "main".into(),
+            path: "main.aya".into(),
+            address: 0x0000,
+            imports: vec![],
+            symbols: HashMap::new(),
+            variables: None,
+            exports: HashMap::new(),
+            code: code.into(),
+        }
+    }
+
+    #[test]
+    fn test_sizes_match_encoding() {
+        let code = [
+            "mov r1, $10",
+            "mov r1, r2",
+            "mov &[$10], r1",
+            "mov r1, &[$10]",
+            "mov &[$10], $20",
+            "mov &[r1], &[r2]",
+            "mov &[r1], $20",
+            "mov &[r1], r2",
+            "mov r1, &[r2]",
+            "mov r1, &[r2 + $04]",
+            "mov8 r1, $10",
+            "mov8 r1, r2",
+            "mov8 &[$10], r1",
+            "mov8 r1, &[$10]",
+            "mov8 &[$10], $20",
+            "add r1, r2",
+            "add r1, $10",
+            "inc r1",
+            "not r1",
+            "jeq &[$10], $20",
+            "jeq &[$10], r1",
+            "jmp &[$10]",
+            "psh $10",
+            "psh r1",
+            "pop r1",
+            "call &[$10]",
+            "ret",
+            "hlt",
+            "int $03",
+            "rti",
+        ]
+        .join("\n");
+        let ast = crate::parser::parse(&code).unwrap();
+        let mut module = module(&code);
+
+        for statement in ast.statements.iter() {
+            let Statement::Instruction(inst) = statement else {
+                unreachable!();
+            };
+            let encoded = lower_instruction(&mut module, inst).unwrap().encode();
+            assert_eq!(encoded.len(), usize::from(inst.kind().byte_size()), "{inst:?}");
+            assert_eq!(encoded[0], u8::from(inst.opcode()), "{inst:?}");
+        }
+    }
+
+    #[test]
+    fn test_register_address() {
+        // stores write the right side through the address on the left side
+        let bytecode = compile(vec![module("mov &[r1], r2")]).unwrap();
+        assert_eq!(bytecode, [0x15, 0x02, 0x03]);
+        let bytecode = compile(vec![module("mov &[r1], &[r2]")]).unwrap();
+        assert_eq!(bytecode, [0x15, 0x02, 0x03]);
+
+        // loads read the address on the right side into the left side
+        let bytecode = compile(vec![module("mov r1, &[r2]")]).unwrap();
+        assert_eq!(bytecode, [0x1C, 0x02, 0x03]);
+        let bytecode = compile(vec![module("mov r1, &[r2 + $0104]")]).unwrap();
+        assert_eq!(bytecode, [0x1D, 0x02, 0x03, 0x04, 0x01]);
+    }
+
+    #[test]
+    fn test_compile_at() {
+        let modules = vec![CodegenModule {
+            name: "main".into(),
+            path: "bank.aya".into(),
+            address: 0x7D80,
+            imports: vec![],
+            symbols: HashMap::new(),
+            variables: None,
+            exports: HashMap::new(),
+            code: ["start:", "mov r1, $01", "back:", "mov r2, !back"].join("\n"),
+        }];
+
+        let (bytecode, symbols) = compile_at(modules, 0x7D80).unwrap();
+
+        assert_eq!(bytecode, [0x11, 0x02, 0x01, 0x00, 0x11, 0x03, 0x84, 0x7D]);
+        let addresses = symbols.iter().map(|symbol| symbol.address).collect::<Vec<_>>();
+        assert_eq!(addresses, [0x7D80, 0x7D84]);
+    }
 }
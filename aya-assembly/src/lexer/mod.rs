@@ -212,7 +212,7 @@ mov r1,         r2          ; mov register into register                    (Mov
 mov &[$c0d3],   r3          ; mov register into memory                      (MovRegMem)
 mov r1,         &[$3000]    ; mov memory into register                      (MovMemReg)
 mov &[$3000],   $abcd       ; mov literal into memory                       (MovLitMem)
-mov r1,         &[r2]       ; mov register pointer into register            (MovRegPtrReg)
+mov r1,         &[r2]       ; mov memory at register address into register    (LoadRegPtr)
 
 ; Math instructions
 add r1,         r2          ; add register into register                    (AddRegReg)
@@ -258,6 +258,7 @@ jle &[$0000],   $0000       ; jumps if literal is lesser or equal to ret    (Jle
 jlt &[$0000],   r2          ; jumps if register is lesser than ret          (JltReg)
 jlt &[$0000],   $0000       ; jumps if literal is lesser than ret           (JltLit)
 hlt                         ; halts the virtual machine                     (Halt)
+hlt $01                     ; halts with a code, 0 if left out              (Halt)
 
 ; Module system syntax
 import "./path.aya" ModuleName &[abcd] {
@@ -1,14 +1,23 @@
+mod check;
 mod codegen;
 mod compiler;
 mod file;
 mod lexer;
+mod line_index;
 mod mod_resolver;
 mod parser;
+pub mod symbols;
 mod utils;
 
+use std::collections::BTreeSet;
 use std::path::Path;
 
+pub use check::{check, CheckResult, Definition, DefinitionKind, Diagnostic, RelatedSpan, Severity, Span};
 pub use codegen::generate;
+use lexer::Kind;
+pub use line_index::{label_locations, LabelLocation, LineIndex, Position};
+pub use mod_resolver::Placement;
+use symbols::SymbolMap;
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
 pub enum AssembleBehavior {
@@ -32,6 +41,7 @@ pub fn assemble_code<P: AsRef<Path>>(
     behavior: AssembleBehavior,
     path: P,
 ) -> miette::Result<AssembleOutput> {
+    set_miette_hook();
     let modules = mod_resolver::resolve(code, &path)?;
     let modules = codegen::generate(modules)?;
 
@@ -49,3 +59,227 @@ pub fn assemble_code<P: AsRef<Path>>(
         AssembleBehavior::Bytecode => Ok(AssembleOutput::Bytecode(compiler::compile(modules)?)),
     }
 }
+
+/// Assembles `code` into bytecode along with the symbols it defines, their addresses are offsets
+/// from the start of the bytecode.
+pub fn assemble_with_symbols<P: AsRef<Path>>(code: String, path: P) -> miette::Result<(Vec<u8>, SymbolMap)> {
+    assemble_at(code, path, 0)
+}
+
+/// Assembles `code` to run from `base` bytes past the start of code memory, such as a code bank
+/// mapped elsewhere. Symbol addresses are offsets from the start of code memory, like jumps expect.
+pub fn assemble_at<P: AsRef<Path>>(code: String, path: P, base: u16) -> miette::Result<(Vec<u8>, SymbolMap)> {
+    set_miette_hook();
+    let modules = mod_resolver::resolve_at(code, &path, base)?;
+    let modules = codegen::generate(modules)?;
+    let (bytecode, symbols) = compiler::compile_at(modules, base)?;
+    Ok((bytecode, SymbolMap::new(symbols)))
+}
+
+/// Names referenced as `!name` anywhere on the program, including import lists, so tools can tell
+/// which constants and labels are never used.
+pub fn referenced_names<P: AsRef<Path>>(code: String, path: P) -> miette::Result<BTreeSet<String>> {
+    set_miette_hook();
+    let modules = mod_resolver::resolve(code, &path)?;
+    let mut names = BTreeSet::new();
+
+    for source in modules.sources.values() {
+        let mut lexer = lexer::Lexer::new(source);
+        while let Some(token) = lexer.next().transpose()? {
+            if token.kind != Kind::Bang {
+                continue;
+            }
+            if let Some(Ok(name)) = lexer.peek() {
+                if name.kind == Kind::Ident {
+                    names.insert(source[std::ops::Range::from(name.offset())].to_string());
+                }
+            }
+        }
+    }
+
+    Ok(names)
+}
+
+/// Problems on the program that don't stop it from assembling, such as deprecated syntax.
+pub fn warnings<P: AsRef<Path>>(code: String, path: P) -> miette::Result<Vec<miette::Report>> {
+    set_miette_hook();
+    let modules = mod_resolver::resolve(code, &path)?;
+    codegen::warnings(modules)
+}
+
+/// Where every import of the program places its module, so tools that know the memory map the
+/// program runs on can tell when a module lands somewhere it can't run from.
+pub fn import_placements<P: AsRef<Path>>(code: String, path: P) -> miette::Result<Vec<Placement>> {
+    set_miette_hook();
+    mod_resolver::import_placements(code, path)
+}
+
+/// Renders the reports of the assembler with more context than the default handler, it's only
+/// installed once, by whichever entry point runs first.
+fn set_miette_hook() {
+    miette::set_hook(Box::new(|_| {
+        Box::new(
+            miette::MietteHandlerOpts::new()
+                .terminal_links(true)
+                .context_lines(10)
+                .tab_width(2)
+                .color(true)
+                .break_words(true)
+                .build(),
+        )
+    }))
+    .ok();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_referenced_names() {
+        let code = String::from("const USED = $01\nconst UNUSED = $02\nstart:\nmov r1, !USED\njmp &[!start]\n");
+        let names = referenced_names(code, "main.aya").unwrap();
+        assert_eq!(names, BTreeSet::from([String::from("USED"), String::from("start")]));
+    }
+
+    #[test]
+    fn test_syntax_errors_are_reported() {
+        let code = String::from("start:\nmov r9, r1\n");
+        let err = assemble_code(code, AssembleBehavior::Bytecode, "main.aya").unwrap_err();
+        assert!(err.to_string().contains("invalid register name"));
+    }
+
+    #[test]
+    fn test_warnings() {
+        let code = String::from("mov &[r1], r2\nmov r1, &[r2 + $04]\n");
+        assert!(warnings(code, "main.aya").unwrap().is_empty());
+
+        let code = String::from("mov &[r1], &[r2]\n");
+        let warnings = warnings(code, "main.aya").unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].to_string(), "`mov &[r1], &[r2]` is deprecated");
+    }
+
+    #[test]
+    fn test_import_address_must_fit_16_bits() {
+        let code = String::from("import \"./other.aya\" Other &[$12345] {}\n");
+        let err = assemble_code(code, AssembleBehavior::Bytecode, "main.aya").unwrap_err();
+        assert_eq!(err.to_string(), "[INVALID_ADDRESS]: error while resolving import");
+        assert!(err.help().unwrap().to_string().contains("16 bits"));
+
+        let code = String::from("import \"./other.aya\" Other &[$] {}\n");
+        let err = assemble_code(code, AssembleBehavior::Bytecode, "main.aya").unwrap_err();
+        assert_eq!(err.to_string(), "[INVALID_ADDRESS]: error while resolving import");
+        assert!(err.help().unwrap().to_string().contains("hex numbers"));
+    }
+
+    #[test]
+    fn test_import_placements() {
+        let dir = std::env::temp_dir().join("aya-assembly-placements");
+        std::fs::create_dir_all(dir.join("lib")).unwrap();
+        std::fs::write(dir.join("lib/inner.aya"), "ret\n").unwrap();
+        std::fs::write(
+            dir.join("lib/outer.aya"),
+            "import \"./inner.aya\" Inner &[$0200] {}\nret\n",
+        )
+        .unwrap();
+
+        let code = String::from("import \"./lib/outer.aya\" Outer &[$0100] {}\nhlt\n");
+        let placements = import_placements(code, dir.join("main.aya")).unwrap();
+        let placements = placements
+            .iter()
+            .map(|placement| (placement.name.as_str(), placement.path.clone(), placement.address))
+            .collect::<Vec<_>>();
+
+        // modules come after the ones they import, and import paths are relative to the importer
+        assert_eq!(
+            placements,
+            [
+                ("Inner", dir.join("lib/inner.aya"), 0x0200),
+                ("Outer", dir.join("lib/outer.aya"), 0x0100),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_imported_constants() {
+        let dir = std::env::temp_dir().join("aya-assembly-imported-constants");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("consts.aya"), "+const PORT = $678D\nconst HIDDEN = $01\n").unwrap();
+
+        let code = String::from("import \"./consts.aya\" Consts &[$0000] {}\nmov8 &[!Consts.PORT], $0A\nhlt\n");
+        let AssembleOutput::Bytecode(bytecode) =
+            assemble_code(code, AssembleBehavior::Bytecode, dir.join("main.aya")).unwrap()
+        else {
+            unreachable!();
+        };
+        let expected = [
+            aya_cpu::instruction::Instruction::Mov8LitMem(0x678D.into(), 0x0A).encode(),
+            aya_cpu::instruction::Instruction::Halt(0).encode(),
+        ]
+        .concat();
+        assert_eq!(bytecode, expected);
+
+        // only exported constants are reachable from the importer
+        let code = String::from("import \"./consts.aya\" Consts &[$0000] {}\nmov r1, !Consts.HIDDEN\n");
+        let err = assemble_code(code, AssembleBehavior::Bytecode, dir.join("main.aya")).unwrap_err();
+        assert_eq!(err.to_string(), "[UNDEFINED_VARIABLE]: error while compiling statement");
+    }
+
+    #[test]
+    fn test_output_memory_hello() {
+        use aya_cpu::cpu::{Cpu, RunOutcome};
+        use aya_cpu::memory::{Addressable, OutputMemory, Result, OUTPUT_MEMORY_SIZE};
+        use aya_cpu::register::Register;
+        use aya_cpu::word::Word;
+
+        // the example maps the output device at the top of memory, over plain ram
+        const OUTPUT: u16 = 0xFF00;
+
+        struct Memory {
+            ram: Vec<u8>,
+            output: OutputMemory,
+        }
+
+        impl Addressable for Memory {
+            fn len(&self) -> usize {
+                self.ram.len() + OUTPUT_MEMORY_SIZE
+            }
+
+            fn read<W: Into<Word> + Copy>(&self, address: W) -> Result<u8> {
+                match u16::from(address.into()) {
+                    address if address >= OUTPUT => self.output.read(address - OUTPUT),
+                    address => Ok(self.ram[usize::from(address)]),
+                }
+            }
+
+            fn write<W: Into<Word> + Copy>(&mut self, address: W, byte: impl Into<u8>) -> Result<()> {
+                match u16::from(address.into()) {
+                    address if address >= OUTPUT => self.output.write(address - OUTPUT, byte),
+                    address => {
+                        self.ram[usize::from(address)] = byte.into();
+                        Ok(())
+                    }
+                }
+            }
+        }
+
+        let code = include_str!("../examples/hello.aya").to_string();
+        let AssembleOutput::Bytecode(bytecode) = assemble_code(code, AssembleBehavior::Bytecode, "hello.aya").unwrap()
+        else {
+            unreachable!();
+        };
+
+        let (output, captured) = OutputMemory::capture();
+        let memory = Memory {
+            ram: vec![0; usize::from(OUTPUT)],
+            output,
+        };
+        let mut cpu = Cpu::new(memory, 0x0000, 0x7FFE, 0x1000);
+        cpu.load_into_address(bytecode, 0x0000).unwrap();
+        assert!(matches!(cpu.run_bounded(10_000).unwrap(), RunOutcome::Halt { .. }));
+
+        assert_eq!(captured.contents(), "HELLO\n");
+        assert_eq!(cpu.registers.fetch(Register::R1), 6);
+    }
+}
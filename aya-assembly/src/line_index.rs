@@ -0,0 +1,154 @@
+/// Line and column of a byte offset of a source, both counted from one like editors show them.
+/// Columns count characters, so a tab or a multibyte character takes a single column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Where every line of a source starts, built once to turn the byte offsets spans are made of
+/// into positions. Lines end at `\n`, a `\r` right before it belongs to the line end rather than
+/// to the line, and a last line without a newline is still a line.
+#[derive(Debug, Clone)]
+pub struct LineIndex<'src> {
+    source: &'src str,
+    line_starts: Vec<usize>,
+}
+
+impl<'src> LineIndex<'src> {
+    pub fn new(source: &'src str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(offset, _)| offset + 1))
+            .collect();
+        Self { source, line_starts }
+    }
+
+    /// Amount of lines of the source, a newline at the very end starts an empty last line.
+    pub fn lines(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// Position of the character at `offset`, or `None` when the offset is past the end of the
+    /// source. The end of the source itself has a position, one column past its last character,
+    /// as spans pointing at the end of a file use it.
+    pub fn position(&self, offset: usize) -> Option<Position> {
+        if offset > self.source.len() {
+            return None;
+        }
+
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        let line_start = self.line_starts[line];
+        let text = self.source.get(line_start..offset)?;
+        let column = text.trim_end_matches('\r').chars().count() + 1;
+
+        Some(Position { line: line + 1, column })
+    }
+}
+
+/// A label of a diagnostic, with where the span it points at starts and ends. The end is the
+/// position right after the last character of the span.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LabelLocation {
+    pub label: Option<String>,
+    pub start: Position,
+    pub end: Position,
+}
+
+/// Where the labels of a diagnostic returned by the assembler point at on its source, for tools
+/// that place them on an editor rather than rendering the report. Labels of a report without
+/// source code, or pointing past its end, are left out.
+///
+/// Syntax errors point at the source as written, while errors found once the program is
+/// generated, such as undefined variables, point at the generated code of their module, see
+/// [`AssembleBehavior::Codegen`](crate::AssembleBehavior::Codegen).
+pub fn label_locations(report: &miette::Report) -> Vec<LabelLocation> {
+    let (Some(source), Some(labels)) = (report_source(report), report.labels()) else {
+        return vec![];
+    };
+
+    let index = LineIndex::new(source);
+    labels
+        .filter_map(|label| {
+            Some(LabelLocation {
+                label: label.label().map(String::from),
+                start: index.position(label.offset())?,
+                end: index.position(label.offset() + label.len())?,
+            })
+        })
+        .collect()
+}
+
+/// Source code a report of the assembler was made with.
+pub(crate) fn report_source(report: &miette::Report) -> Option<&str> {
+    // an empty span with every line after it as context reads back the whole source
+    let contents = report.source_code()?.read_span(&(0, 0).into(), 0, usize::MAX).ok()?;
+    std::str::from_utf8(contents.data()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{assemble_code, AssembleBehavior};
+
+    fn position(line: usize, column: usize) -> Option<Position> {
+        Some(Position { line, column })
+    }
+
+    #[test]
+    fn test_positions() {
+        let index = LineIndex::new("mov r1, r2\n\nhlt\n");
+        assert_eq!(index.lines(), 4);
+        assert_eq!(index.position(0), position(1, 1));
+        assert_eq!(index.position(4), position(1, 5));
+        assert_eq!(index.position(10), position(1, 11));
+        assert_eq!(index.position(11), position(2, 1));
+        assert_eq!(index.position(12), position(3, 1));
+        assert_eq!(index.position(16), position(4, 1));
+        assert_eq!(index.position(17), None);
+    }
+
+    #[test]
+    fn test_crlf_positions() {
+        let index = LineIndex::new("mov r1, r2\r\nhlt\r\n");
+        assert_eq!(index.lines(), 3);
+        assert_eq!(index.position(10), position(1, 11));
+        // the `\r` ends the line like the `\n` after it does
+        assert_eq!(index.position(11), position(1, 11));
+        assert_eq!(index.position(12), position(2, 1));
+        assert_eq!(index.position(14), position(2, 3));
+        assert_eq!(index.position(17), position(3, 1));
+    }
+
+    #[test]
+    fn test_last_line_without_newline() {
+        let index = LineIndex::new("start:\n  hlt");
+        assert_eq!(index.lines(), 2);
+        assert_eq!(index.position(9), position(2, 3));
+        assert_eq!(index.position(12), position(2, 6));
+        assert_eq!(index.position(13), None);
+    }
+
+    #[test]
+    fn test_columns_count_characters() {
+        let index = LineIndex::new("; olá\n\tmov");
+        assert_eq!(index.position(7), position(2, 1));
+        assert_eq!(index.position(6), position(1, 6));
+        assert_eq!(index.position(8), position(2, 2));
+    }
+
+    #[test]
+    fn test_label_locations() {
+        let code = String::from("start:\r\n  mov r1, $0001\r\n  mov r1, @\r\n  hlt");
+        let report = assemble_code(code, AssembleBehavior::Bytecode, "main.aya").unwrap_err();
+        let locations = label_locations(&report);
+        assert_eq!(locations.len(), 1);
+        assert_eq!(locations[0].start, Position { line: 3, column: 11 });
+
+        let code = String::from("start:\n  mov r1,");
+        let report = assemble_code(code, AssembleBehavior::Bytecode, "main.aya").unwrap_err();
+        let locations = label_locations(&report);
+        assert_eq!(locations.len(), 1);
+        assert_eq!(locations[0].start.line, 2);
+        assert_eq!(locations[0].end, Position { line: 2, column: 10 });
+    }
+}
@@ -1,8 +1,9 @@
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::num::IntErrorKind;
 use std::ops::Range;
 use std::path::{Path, PathBuf};
 
-use crate::parser::ast::{Ast, Statement};
+use crate::parser::ast::{Ast, ByteOffset, Statement};
 use crate::utils::{bail, bail_multi};
 
 #[derive(Debug, Clone)]
@@ -36,7 +37,36 @@ pub struct ResolvedModule {
     pub address: u16,
     pub imports: Vec<PathBuf>,
     pub symbols: HashMap<String, u16>,
-    pub variables: Option<HashMap<String, Either>>,
+    /// Constants declared with `+const`, which importing modules reach as `!Module.NAME`
+    pub exported: HashSet<String>,
+    pub variables: Option<BTreeMap<String, Either>>,
+}
+
+/// Where an import statement places its module.
+#[derive(Debug, Clone)]
+pub struct Placement {
+    /// Name the module was imported as
+    pub name: String,
+    /// Path of the imported module
+    pub path: PathBuf,
+    /// Offset from the start of the code the module is laid out at, like label addresses
+    pub address: u16,
+    /// Source of the importing module, so diagnostics can point at the import
+    source: String,
+    span: ByteOffset,
+}
+
+impl Placement {
+    /// A warning pointing at the placement address of the import.
+    pub fn warning<S: AsRef<str>>(&self, message: S, help: S) -> miette::Report {
+        miette::Report::from(
+            miette::MietteDiagnostic::new(message.as_ref())
+                .with_severity(miette::Severity::Warning)
+                .with_labels(vec![miette::LabeledSpan::at(self.span, "this address")])
+                .with_help(help.as_ref()),
+        )
+        .with_source_code(self.source.clone())
+    }
 }
 
 #[derive(Debug)]
@@ -79,15 +109,32 @@ impl Iterator for ResolvedModulesIntoIter {
 }
 
 pub fn resolve<P: AsRef<Path>>(code: String, path: P) -> miette::Result<ResolvedModules> {
+    resolve_at(code, path, 0)
+}
+
+/// Resolves the modules of a program whose entry module is laid out at `address` instead of the
+/// start of the bytecode.
+pub fn resolve_at<P: AsRef<Path>>(code: String, path: P, address: u16) -> miette::Result<ResolvedModules> {
+    resolve_located(code, path, address).map_err(|(_, err)| err)
+}
+
+/// Like [`resolve_at`], but errors come along with the path of the module they were found on.
+pub fn resolve_located<P: AsRef<Path>>(
+    code: String,
+    path: P,
+    address: u16,
+) -> Result<ResolvedModules, (PathBuf, miette::Report)> {
     let path = path.as_ref().to_path_buf();
     let mut context = Context {
         asts: vec![],
         modules: vec![],
         visited: HashSet::default(),
         sources: HashMap::default(),
+        failed: None,
     };
 
-    resolve_module("main", path.clone(), code, None, &mut context, 0)?;
+    resolve_module("main", path.clone(), code, None, &mut context, address)
+        .map_err(|err| (context.failed.take().unwrap_or(path), err))?;
 
     let mut sorted = topological_sort(&context.modules);
 
@@ -122,7 +169,24 @@ pub fn resolve<P: AsRef<Path>>(code: String, path: P) -> miette::Result<Resolved
         })
         .collect();
 
+    let exported: HashMap<PathBuf, Vec<(String, u16)>> = context
+        .modules
+        .iter()
+        .map(|module| {
+            let constants = module
+                .exported
+                .iter()
+                .map(|name| (format!("{}.{name}", module.name), module.symbols[name]))
+                .collect();
+            (module.path.clone(), constants)
+        })
+        .collect();
+
     for module in context.modules.iter_mut() {
+        for import in &module.imports {
+            module.symbols.extend(exported[import].iter().cloned());
+        }
+
         if let Some(variables) = &mut module.variables {
             for value in variables.values_mut() {
                 if let Either::ModuleField { module, field } = value {
@@ -216,13 +280,15 @@ struct Context {
     modules: Vec<ResolvedModule>,
     visited: HashSet<PathBuf>,
     sources: HashMap<PathBuf, String>,
+    /// Module the first error was found on, imports are resolved while resolving their importer
+    failed: Option<PathBuf>,
 }
 
 fn resolve_module(
     name: &str,
     path: PathBuf,
     code: String,
-    variables: Option<HashMap<String, Either>>,
+    variables: Option<BTreeMap<String, Either>>,
     context: &mut Context,
     address: u16,
 ) -> miette::Result<()> {
@@ -231,7 +297,9 @@ fn resolve_module(
     }
     context.visited.insert(path.clone());
 
-    let ast = crate::parser::parse(&code).expect("failed to parse");
+    let ast = crate::parser::parse(&code).inspect_err(|_| {
+        context.failed.get_or_insert_with(|| path.clone());
+    })?;
 
     let mut module = ResolvedModule {
         name: name.to_string(),
@@ -239,11 +307,16 @@ fn resolve_module(
         variables,
         address,
         symbols: Default::default(),
+        exported: Default::default(),
         imports: Default::default(),
     };
 
-    resolve_constants(&code, &mut module, &ast)?;
-    resolve_imports(&code, &mut module, &ast, context)?;
+    if let Err(err) =
+        resolve_constants(&code, &mut module, &ast).and_then(|_| resolve_imports(&code, &mut module, &ast, context))
+    {
+        context.failed.get_or_insert(path);
+        return Err(err);
+    }
 
     context.asts.push(ast);
     context.sources.insert(path, code);
@@ -275,6 +348,9 @@ fn resolve_constants(code: &str, module: &mut ResolvedModule, ast: &Ast) -> miet
 
         let name = &code[Range::from(*name)];
         module.symbols.insert(name.to_string(), value_hex);
+        if *exported {
+            module.exported.insert(name.to_string());
+        }
     }
 
     Ok(())
@@ -283,23 +359,67 @@ fn resolve_constants(code: &str, module: &mut ResolvedModule, ast: &Ast) -> miet
 fn resolve_imports(code: &str, module: &mut ResolvedModule, ast: &Ast, context: &mut Context) -> miette::Result<()> {
     for (name, path, variables, address) in ast.imports() {
         let variables = resolve_import_vars(code, module, variables)?;
+        let address = placement_address(code, name, address)?;
         let name = &code[name.start..name.end];
-        let path = &code[path.start..path.end];
-        let address = &code[Range::from(*address)];
-        let address = u16::from_str_radix(address, 16).unwrap();
-        let code = crate::file::load_module_from_path(path).unwrap();
-        resolve_module(name, path.into(), code, Some(variables), context, address)?;
-        module.imports.push(path.into());
+        let span = *path;
+        let path = import_path(&module.path, &code[Range::from(span)]);
+        let code = crate::file::load_module_from_path(&path).map_err(|err| {
+            let help = format!("failed to read {}: {err}", path.display());
+            bail(code, &help, "[MISSING_MODULE]: error while resolving import", span)
+        })?;
+        resolve_module(name, path.clone(), code, Some(variables), context, address)?;
+        module.imports.push(path);
     }
     Ok(())
 }
 
+/// Imports are relative to the importing module, so projects build from any directory.
+fn import_path(module: &Path, path: &str) -> PathBuf {
+    module.parent().unwrap_or(Path::new("")).join(path)
+}
+
+/// Address an import places its module at, which must be a hex number on the 16 bit address space.
+fn placement_address(code: &str, name: &ByteOffset, address: &ByteOffset) -> miette::Result<u16> {
+    u16::from_str_radix(&code[Range::from(*address)], 16).map_err(|err| {
+        let help = match err.kind() {
+            IntErrorKind::PosOverflow => "placement addresses must fit in 16 bits, up to &[$FFFF]",
+            _ => "placement addresses are hex numbers, such as &[$1000]",
+        };
+        let labels = vec![
+            miette::LabeledSpan::at(*name, "this import"),
+            miette::LabeledSpan::at(*address, "this address"),
+        ];
+        bail_multi(code, labels, "[INVALID_ADDRESS]: error while resolving import", help)
+    })
+}
+
+/// Where every import of the program places its module, imports of every module included.
+pub fn import_placements<P: AsRef<Path>>(code: String, path: P) -> miette::Result<Vec<Placement>> {
+    let modules = resolve(code, path)?;
+    let mut placements = vec![];
+
+    for (module, ast) in modules.modules.iter().zip(&modules.asts) {
+        let source = &modules.sources[&module.path];
+        for (name, path, _, address) in ast.imports() {
+            placements.push(Placement {
+                name: source[Range::from(*name)].to_string(),
+                path: import_path(&module.path, &source[Range::from(*path)]),
+                address: placement_address(source, name, address)?,
+                source: source.clone(),
+                span: *address,
+            });
+        }
+    }
+
+    Ok(placements)
+}
+
 fn resolve_import_vars(
     code: &str,
     module: &mut ResolvedModule,
     variables: &[Statement],
-) -> miette::Result<HashMap<String, Either>> {
-    let mut resolved_variables = HashMap::default();
+) -> miette::Result<BTreeMap<String, Either>> {
+    let mut resolved_variables = BTreeMap::default();
 
     for variable in variables {
         let Statement::ImportVar { name, value } = variable else {
@@ -190,10 +190,12 @@ pub enum InstructionKind {
     MemReg8,
     LitMem8,
     RegPtrReg,
+    RegPtrOffReg,
     LitRegPtr,
     NoArgs,
     SingleReg,
     SingleLit,
+    SingleLit8,
 }
 
 impl InstructionKind {
@@ -210,365 +212,375 @@ impl InstructionKind {
             InstructionKind::MemReg8 => 4,
             InstructionKind::LitMem8 => 4,
             InstructionKind::RegPtrReg => 3,
+            InstructionKind::RegPtrOffReg => 5,
             InstructionKind::LitRegPtr => 4,
             InstructionKind::NoArgs => 1,
             InstructionKind::SingleReg => 2,
             InstructionKind::SingleLit => 3,
+            InstructionKind::SingleLit8 => 2,
         }
     }
 }
 
+/// Every instruction holds the offset of its mnemonic first, followed by its operands.
 #[derive(Debug, PartialEq, Eq)]
 pub enum Instruction {
-    MovLitReg(Statement, Statement),
-    MovRegReg(Statement, Statement),
-    MovRegMem(Statement, Statement),
-    MovMemReg(Statement, Statement),
-    MovLitMem(Statement, Statement),
-    MovRegPtrReg(Statement, Statement),
-    MovLitRegPtr(Statement, Statement),
-    Mov8LitReg(Statement, Statement),
-    Mov8RegReg(Statement, Statement),
-    Mov8RegMem(Statement, Statement),
-    Mov8MemReg(Statement, Statement),
-    Mov8LitMem(Statement, Statement),
-    AddRegReg(Statement, Statement),
-    AddLitReg(Statement, Statement),
-    SubRegReg(Statement, Statement),
-    SubLitReg(Statement, Statement),
-    MulRegReg(Statement, Statement),
-    MulLitReg(Statement, Statement),
-    LshRegReg(Statement, Statement),
-    LshLitReg(Statement, Statement),
-    RshRegReg(Statement, Statement),
-    RshLitReg(Statement, Statement),
-    AndRegReg(Statement, Statement),
-    AndLitReg(Statement, Statement),
-    OrLitReg(Statement, Statement),
-    OrRegReg(Statement, Statement),
-    XorLitReg(Statement, Statement),
-    XorRegReg(Statement, Statement),
-    Inc(Statement),
-    Dec(Statement),
-    Not(Statement),
-    JeqLit(Statement, Statement),
-    JeqReg(Statement, Statement),
-    JgtLit(Statement, Statement),
-    JgtReg(Statement, Statement),
-    JneLit(Statement, Statement),
-    JneReg(Statement, Statement),
-    JgeLit(Statement, Statement),
-    JgeReg(Statement, Statement),
-    JleLit(Statement, Statement),
-    JleReg(Statement, Statement),
-    JltLit(Statement, Statement),
-    JltReg(Statement, Statement),
-    Jmp(Statement),
-    PshLit(Statement),
-    PshReg(Statement),
-    Pop(Statement),
-    Call(Statement),
+    MovLitReg(ByteOffset, Statement, Statement),
+    MovRegReg(ByteOffset, Statement, Statement),
+    MovRegMem(ByteOffset, Statement, Statement),
+    MovMemReg(ByteOffset, Statement, Statement),
+    MovLitMem(ByteOffset, Statement, Statement),
+    MovRegPtrReg(ByteOffset, Statement, Statement),
+    MovLitRegPtr(ByteOffset, Statement, Statement),
+    LoadRegPtr(ByteOffset, Statement, Statement),
+    LoadRegPtrOff(ByteOffset, Statement, Statement),
+    Mov8LitReg(ByteOffset, Statement, Statement),
+    Mov8RegReg(ByteOffset, Statement, Statement),
+    Mov8RegMem(ByteOffset, Statement, Statement),
+    Mov8MemReg(ByteOffset, Statement, Statement),
+    Mov8LitMem(ByteOffset, Statement, Statement),
+    AddRegReg(ByteOffset, Statement, Statement),
+    AddLitReg(ByteOffset, Statement, Statement),
+    SubRegReg(ByteOffset, Statement, Statement),
+    SubLitReg(ByteOffset, Statement, Statement),
+    MulRegReg(ByteOffset, Statement, Statement),
+    MulLitReg(ByteOffset, Statement, Statement),
+    LshRegReg(ByteOffset, Statement, Statement),
+    LshLitReg(ByteOffset, Statement, Statement),
+    RshRegReg(ByteOffset, Statement, Statement),
+    RshLitReg(ByteOffset, Statement, Statement),
+    AndRegReg(ByteOffset, Statement, Statement),
+    AndLitReg(ByteOffset, Statement, Statement),
+    OrLitReg(ByteOffset, Statement, Statement),
+    OrRegReg(ByteOffset, Statement, Statement),
+    XorLitReg(ByteOffset, Statement, Statement),
+    XorRegReg(ByteOffset, Statement, Statement),
+    Inc(ByteOffset, Statement),
+    Dec(ByteOffset, Statement),
+    Not(ByteOffset, Statement),
+    JeqLit(ByteOffset, Statement, Statement),
+    JeqReg(ByteOffset, Statement, Statement),
+    JgtLit(ByteOffset, Statement, Statement),
+    JgtReg(ByteOffset, Statement, Statement),
+    JneLit(ByteOffset, Statement, Statement),
+    JneReg(ByteOffset, Statement, Statement),
+    JgeLit(ByteOffset, Statement, Statement),
+    JgeReg(ByteOffset, Statement, Statement),
+    JleLit(ByteOffset, Statement, Statement),
+    JleReg(ByteOffset, Statement, Statement),
+    JltLit(ByteOffset, Statement, Statement),
+    JltReg(ByteOffset, Statement, Statement),
+    Jmp(ByteOffset, Statement),
+    PshLit(ByteOffset, Statement),
+    PshReg(ByteOffset, Statement),
+    Pop(ByteOffset, Statement),
+    Call(ByteOffset, Statement),
     Ret(ByteOffset),
-    Hlt(ByteOffset),
-    Int(Statement),
+    Hlt(ByteOffset, Option<Statement>),
+    Int(ByteOffset, Statement),
     Rti(ByteOffset),
 }
 
 impl Instruction {
     pub fn lhs(&self) -> &Statement {
         match self {
-            Instruction::MovLitReg(lhs, _)
-            | Instruction::MovRegReg(lhs, _)
-            | Instruction::MovRegMem(lhs, _)
-            | Instruction::MovMemReg(lhs, _)
-            | Instruction::MovLitMem(lhs, _)
-            | Instruction::MovRegPtrReg(lhs, _)
-            | Instruction::MovLitRegPtr(lhs, _)
-            | Instruction::Mov8LitReg(lhs, _)
-            | Instruction::Mov8RegReg(lhs, _)
-            | Instruction::Mov8RegMem(lhs, _)
-            | Instruction::Mov8MemReg(lhs, _)
-            | Instruction::Mov8LitMem(lhs, _)
-            | Instruction::AddRegReg(lhs, _)
-            | Instruction::AddLitReg(lhs, _)
-            | Instruction::SubRegReg(lhs, _)
-            | Instruction::SubLitReg(lhs, _)
-            | Instruction::MulRegReg(lhs, _)
-            | Instruction::MulLitReg(lhs, _)
-            | Instruction::LshRegReg(lhs, _)
-            | Instruction::LshLitReg(lhs, _)
-            | Instruction::RshRegReg(lhs, _)
-            | Instruction::RshLitReg(lhs, _)
-            | Instruction::AndRegReg(lhs, _)
-            | Instruction::AndLitReg(lhs, _)
-            | Instruction::OrLitReg(lhs, _)
-            | Instruction::OrRegReg(lhs, _)
-            | Instruction::XorLitReg(lhs, _)
-            | Instruction::XorRegReg(lhs, _)
-            | Instruction::JeqLit(lhs, _)
-            | Instruction::JeqReg(lhs, _)
-            | Instruction::JgtLit(lhs, _)
-            | Instruction::JgtReg(lhs, _)
-            | Instruction::JneLit(lhs, _)
-            | Instruction::JneReg(lhs, _)
-            | Instruction::JgeLit(lhs, _)
-            | Instruction::JgeReg(lhs, _)
-            | Instruction::JleLit(lhs, _)
-            | Instruction::JleReg(lhs, _)
-            | Instruction::JltLit(lhs, _)
-            | Instruction::JltReg(lhs, _)
-            | Instruction::PshLit(lhs)
-            | Instruction::PshReg(lhs)
-            | Instruction::Pop(lhs)
-            | Instruction::Call(lhs)
-            | Instruction::Inc(lhs)
-            | Instruction::Dec(lhs)
-            | Instruction::Jmp(lhs)
-            | Instruction::Int(lhs)
-            | Instruction::Not(lhs) => lhs,
-
-            Instruction::Ret(_) | Instruction::Hlt(_) | Instruction::Rti(_) => unreachable!(),
+            Instruction::MovLitReg(_, lhs, _)
+            | Instruction::MovRegReg(_, lhs, _)
+            | Instruction::MovRegMem(_, lhs, _)
+            | Instruction::MovMemReg(_, lhs, _)
+            | Instruction::MovLitMem(_, lhs, _)
+            | Instruction::MovRegPtrReg(_, lhs, _)
+            | Instruction::MovLitRegPtr(_, lhs, _)
+            | Instruction::LoadRegPtr(_, lhs, _)
+            | Instruction::LoadRegPtrOff(_, lhs, _)
+            | Instruction::Mov8LitReg(_, lhs, _)
+            | Instruction::Mov8RegReg(_, lhs, _)
+            | Instruction::Mov8RegMem(_, lhs, _)
+            | Instruction::Mov8MemReg(_, lhs, _)
+            | Instruction::Mov8LitMem(_, lhs, _)
+            | Instruction::AddRegReg(_, lhs, _)
+            | Instruction::AddLitReg(_, lhs, _)
+            | Instruction::SubRegReg(_, lhs, _)
+            | Instruction::SubLitReg(_, lhs, _)
+            | Instruction::MulRegReg(_, lhs, _)
+            | Instruction::MulLitReg(_, lhs, _)
+            | Instruction::LshRegReg(_, lhs, _)
+            | Instruction::LshLitReg(_, lhs, _)
+            | Instruction::RshRegReg(_, lhs, _)
+            | Instruction::RshLitReg(_, lhs, _)
+            | Instruction::AndRegReg(_, lhs, _)
+            | Instruction::AndLitReg(_, lhs, _)
+            | Instruction::OrLitReg(_, lhs, _)
+            | Instruction::OrRegReg(_, lhs, _)
+            | Instruction::XorLitReg(_, lhs, _)
+            | Instruction::XorRegReg(_, lhs, _)
+            | Instruction::JeqLit(_, lhs, _)
+            | Instruction::JeqReg(_, lhs, _)
+            | Instruction::JgtLit(_, lhs, _)
+            | Instruction::JgtReg(_, lhs, _)
+            | Instruction::JneLit(_, lhs, _)
+            | Instruction::JneReg(_, lhs, _)
+            | Instruction::JgeLit(_, lhs, _)
+            | Instruction::JgeReg(_, lhs, _)
+            | Instruction::JleLit(_, lhs, _)
+            | Instruction::JleReg(_, lhs, _)
+            | Instruction::JltLit(_, lhs, _)
+            | Instruction::JltReg(_, lhs, _)
+            | Instruction::PshLit(_, lhs)
+            | Instruction::PshReg(_, lhs)
+            | Instruction::Pop(_, lhs)
+            | Instruction::Call(_, lhs)
+            | Instruction::Inc(_, lhs)
+            | Instruction::Dec(_, lhs)
+            | Instruction::Jmp(_, lhs)
+            | Instruction::Int(_, lhs)
+            | Instruction::Not(_, lhs) => lhs,
+
+            Instruction::Ret(_) | Instruction::Hlt(_, _) | Instruction::Rti(_) => unreachable!(),
         }
     }
 
     pub fn rhs(&self) -> &Statement {
         match self {
-            Instruction::MovLitReg(_, rhs)
-            | Instruction::MovRegReg(_, rhs)
-            | Instruction::MovRegMem(_, rhs)
-            | Instruction::MovMemReg(_, rhs)
-            | Instruction::MovLitMem(_, rhs)
-            | Instruction::MovRegPtrReg(_, rhs)
-            | Instruction::MovLitRegPtr(_, rhs)
-            | Instruction::Mov8LitReg(_, rhs)
-            | Instruction::Mov8RegReg(_, rhs)
-            | Instruction::Mov8RegMem(_, rhs)
-            | Instruction::Mov8MemReg(_, rhs)
-            | Instruction::Mov8LitMem(_, rhs)
-            | Instruction::AddRegReg(_, rhs)
-            | Instruction::AddLitReg(_, rhs)
-            | Instruction::SubRegReg(_, rhs)
-            | Instruction::SubLitReg(_, rhs)
-            | Instruction::MulRegReg(_, rhs)
-            | Instruction::MulLitReg(_, rhs)
-            | Instruction::LshRegReg(_, rhs)
-            | Instruction::LshLitReg(_, rhs)
-            | Instruction::RshRegReg(_, rhs)
-            | Instruction::RshLitReg(_, rhs)
-            | Instruction::AndRegReg(_, rhs)
-            | Instruction::AndLitReg(_, rhs)
-            | Instruction::OrLitReg(_, rhs)
-            | Instruction::OrRegReg(_, rhs)
-            | Instruction::XorLitReg(_, rhs)
-            | Instruction::XorRegReg(_, rhs)
-            | Instruction::JeqLit(_, rhs)
-            | Instruction::JeqReg(_, rhs)
-            | Instruction::JgtLit(_, rhs)
-            | Instruction::JgtReg(_, rhs)
-            | Instruction::JneLit(_, rhs)
-            | Instruction::JneReg(_, rhs)
-            | Instruction::JgeLit(_, rhs)
-            | Instruction::JgeReg(_, rhs)
-            | Instruction::JleLit(_, rhs)
-            | Instruction::JleReg(_, rhs)
-            | Instruction::JltLit(_, rhs)
-            | Instruction::JltReg(_, rhs) => rhs,
-
-            Instruction::PshLit(_)
-            | Instruction::PshReg(_)
-            | Instruction::Pop(_)
-            | Instruction::Call(_)
-            | Instruction::Inc(_)
-            | Instruction::Dec(_)
-            | Instruction::Not(_)
-            | Instruction::Jmp(_)
+            Instruction::MovLitReg(_, _, rhs)
+            | Instruction::MovRegReg(_, _, rhs)
+            | Instruction::MovRegMem(_, _, rhs)
+            | Instruction::MovMemReg(_, _, rhs)
+            | Instruction::MovLitMem(_, _, rhs)
+            | Instruction::MovRegPtrReg(_, _, rhs)
+            | Instruction::MovLitRegPtr(_, _, rhs)
+            | Instruction::LoadRegPtr(_, _, rhs)
+            | Instruction::LoadRegPtrOff(_, _, rhs)
+            | Instruction::Mov8LitReg(_, _, rhs)
+            | Instruction::Mov8RegReg(_, _, rhs)
+            | Instruction::Mov8RegMem(_, _, rhs)
+            | Instruction::Mov8MemReg(_, _, rhs)
+            | Instruction::Mov8LitMem(_, _, rhs)
+            | Instruction::AddRegReg(_, _, rhs)
+            | Instruction::AddLitReg(_, _, rhs)
+            | Instruction::SubRegReg(_, _, rhs)
+            | Instruction::SubLitReg(_, _, rhs)
+            | Instruction::MulRegReg(_, _, rhs)
+            | Instruction::MulLitReg(_, _, rhs)
+            | Instruction::LshRegReg(_, _, rhs)
+            | Instruction::LshLitReg(_, _, rhs)
+            | Instruction::RshRegReg(_, _, rhs)
+            | Instruction::RshLitReg(_, _, rhs)
+            | Instruction::AndRegReg(_, _, rhs)
+            | Instruction::AndLitReg(_, _, rhs)
+            | Instruction::OrLitReg(_, _, rhs)
+            | Instruction::OrRegReg(_, _, rhs)
+            | Instruction::XorLitReg(_, _, rhs)
+            | Instruction::XorRegReg(_, _, rhs)
+            | Instruction::JeqLit(_, _, rhs)
+            | Instruction::JeqReg(_, _, rhs)
+            | Instruction::JgtLit(_, _, rhs)
+            | Instruction::JgtReg(_, _, rhs)
+            | Instruction::JneLit(_, _, rhs)
+            | Instruction::JneReg(_, _, rhs)
+            | Instruction::JgeLit(_, _, rhs)
+            | Instruction::JgeReg(_, _, rhs)
+            | Instruction::JleLit(_, _, rhs)
+            | Instruction::JleReg(_, _, rhs)
+            | Instruction::JltLit(_, _, rhs)
+            | Instruction::JltReg(_, _, rhs) => rhs,
+
+            Instruction::PshLit(_, _)
+            | Instruction::PshReg(_, _)
+            | Instruction::Pop(_, _)
+            | Instruction::Call(_, _)
+            | Instruction::Inc(_, _)
+            | Instruction::Dec(_, _)
+            | Instruction::Not(_, _)
+            | Instruction::Jmp(_, _)
             | Instruction::Ret(_)
-            | Instruction::Hlt(_)
+            | Instruction::Hlt(_, _)
             | Instruction::Rti(_)
-            | Instruction::Int(_) => unreachable!(),
+            | Instruction::Int(_, _) => unreachable!(),
         }
     }
 
     pub fn opcode(&self) -> OpCode {
         match self {
-            Instruction::MovLitReg(_, _) => OpCode::MovLitReg,
-            Instruction::MovRegReg(_, _) => OpCode::MovRegReg,
-            Instruction::MovRegMem(_, _) => OpCode::MovRegMem,
-            Instruction::MovMemReg(_, _) => OpCode::MovMemReg,
-            Instruction::MovLitMem(_, _) => OpCode::MovLitMem,
-            Instruction::MovRegPtrReg(_, _) => OpCode::MovRegPtrReg,
-            Instruction::MovLitRegPtr(_, _) => OpCode::MovLitRegPtr,
-
-            Instruction::Mov8LitReg(_, _) => OpCode::Mov8LitReg,
-            Instruction::Mov8RegReg(_, _) => OpCode::Mov8RegReg,
-            Instruction::Mov8RegMem(_, _) => OpCode::Mov8RegMem,
-            Instruction::Mov8MemReg(_, _) => OpCode::Mov8MemReg,
-            Instruction::Mov8LitMem(_, _) => OpCode::Mov8LitMem,
-
-            Instruction::AddRegReg(_, _) => OpCode::AddRegReg,
-            Instruction::AddLitReg(_, _) => OpCode::AddLitReg,
-            Instruction::SubRegReg(_, _) => OpCode::SubRegReg,
-            Instruction::SubLitReg(_, _) => OpCode::SubLitReg,
-            Instruction::Inc(_) => OpCode::IncReg,
-            Instruction::Dec(_) => OpCode::DecReg,
-            Instruction::MulLitReg(_, _) => OpCode::MulLitReg,
-            Instruction::MulRegReg(_, _) => OpCode::MulRegReg,
-
-            Instruction::LshLitReg(_, _) => OpCode::LshLitReg,
-            Instruction::LshRegReg(_, _) => OpCode::LshRegReg,
-            Instruction::RshLitReg(_, _) => OpCode::RshLitReg,
-            Instruction::RshRegReg(_, _) => OpCode::RshRegReg,
-            Instruction::AndLitReg(_, _) => OpCode::AndLitReg,
-            Instruction::AndRegReg(_, _) => OpCode::AndRegReg,
-            Instruction::OrLitReg(_, _) => OpCode::OrLitReg,
-            Instruction::OrRegReg(_, _) => OpCode::OrRegReg,
-            Instruction::XorLitReg(_, _) => OpCode::XorLitReg,
-            Instruction::XorRegReg(_, _) => OpCode::XorRegReg,
-            Instruction::Not(_) => OpCode::Not,
-
-            Instruction::PshLit(_) => OpCode::PushLit,
-            Instruction::PshReg(_) => OpCode::PushReg,
-            Instruction::Pop(_) => OpCode::Pop,
-            Instruction::Call(_) => OpCode::Call,
+            Instruction::MovLitReg(_, _, _) => OpCode::MovLitReg,
+            Instruction::MovRegReg(_, _, _) => OpCode::MovRegReg,
+            Instruction::MovRegMem(_, _, _) => OpCode::MovRegMem,
+            Instruction::MovMemReg(_, _, _) => OpCode::MovMemReg,
+            Instruction::MovLitMem(_, _, _) => OpCode::MovLitMem,
+            Instruction::MovRegPtrReg(_, _, _) => OpCode::MovRegPtrReg,
+            Instruction::MovLitRegPtr(_, _, _) => OpCode::MovLitRegPtr,
+            Instruction::LoadRegPtr(_, _, _) => OpCode::LoadRegPtr,
+            Instruction::LoadRegPtrOff(_, _, _) => OpCode::LoadRegPtrOff,
+
+            Instruction::Mov8LitReg(_, _, _) => OpCode::Mov8LitReg,
+            Instruction::Mov8RegReg(_, _, _) => OpCode::Mov8RegReg,
+            Instruction::Mov8RegMem(_, _, _) => OpCode::Mov8RegMem,
+            Instruction::Mov8MemReg(_, _, _) => OpCode::Mov8MemReg,
+            Instruction::Mov8LitMem(_, _, _) => OpCode::Mov8LitMem,
+
+            Instruction::AddRegReg(_, _, _) => OpCode::AddRegReg,
+            Instruction::AddLitReg(_, _, _) => OpCode::AddLitReg,
+            Instruction::SubRegReg(_, _, _) => OpCode::SubRegReg,
+            Instruction::SubLitReg(_, _, _) => OpCode::SubLitReg,
+            Instruction::Inc(_, _) => OpCode::IncReg,
+            Instruction::Dec(_, _) => OpCode::DecReg,
+            Instruction::MulLitReg(_, _, _) => OpCode::MulLitReg,
+            Instruction::MulRegReg(_, _, _) => OpCode::MulRegReg,
+
+            Instruction::LshLitReg(_, _, _) => OpCode::LshLitReg,
+            Instruction::LshRegReg(_, _, _) => OpCode::LshRegReg,
+            Instruction::RshLitReg(_, _, _) => OpCode::RshLitReg,
+            Instruction::RshRegReg(_, _, _) => OpCode::RshRegReg,
+            Instruction::AndLitReg(_, _, _) => OpCode::AndLitReg,
+            Instruction::AndRegReg(_, _, _) => OpCode::AndRegReg,
+            Instruction::OrLitReg(_, _, _) => OpCode::OrLitReg,
+            Instruction::OrRegReg(_, _, _) => OpCode::OrRegReg,
+            Instruction::XorLitReg(_, _, _) => OpCode::XorLitReg,
+            Instruction::XorRegReg(_, _, _) => OpCode::XorRegReg,
+            Instruction::Not(_, _) => OpCode::Not,
+
+            Instruction::PshLit(_, _) => OpCode::PushLit,
+            Instruction::PshReg(_, _) => OpCode::PushReg,
+            Instruction::Pop(_, _) => OpCode::Pop,
+            Instruction::Call(_, _) => OpCode::Call,
             Instruction::Ret(_) => OpCode::Ret,
-            Instruction::Hlt(_) => OpCode::Halt,
-
-            Instruction::JeqLit(_, _) => OpCode::JeqLit,
-            Instruction::JeqReg(_, _) => OpCode::JeqReg,
-            Instruction::JgtLit(_, _) => OpCode::JgtLit,
-            Instruction::JgtReg(_, _) => OpCode::JgtReg,
-            Instruction::JneLit(_, _) => OpCode::JneLit,
-            Instruction::JneReg(_, _) => OpCode::JneReg,
-            Instruction::JgeLit(_, _) => OpCode::JgeLit,
-            Instruction::JgeReg(_, _) => OpCode::JgeReg,
-            Instruction::JleLit(_, _) => OpCode::JleLit,
-            Instruction::JleReg(_, _) => OpCode::JleReg,
-            Instruction::JltLit(_, _) => OpCode::JltLit,
-            Instruction::JltReg(_, _) => OpCode::JltReg,
-            Instruction::Jmp(_) => OpCode::Jmp,
-            Instruction::Int(_) => OpCode::Int,
+            Instruction::Hlt(_, _) => OpCode::Halt,
+
+            Instruction::JeqLit(_, _, _) => OpCode::JeqLit,
+            Instruction::JeqReg(_, _, _) => OpCode::JeqReg,
+            Instruction::JgtLit(_, _, _) => OpCode::JgtLit,
+            Instruction::JgtReg(_, _, _) => OpCode::JgtReg,
+            Instruction::JneLit(_, _, _) => OpCode::JneLit,
+            Instruction::JneReg(_, _, _) => OpCode::JneReg,
+            Instruction::JgeLit(_, _, _) => OpCode::JgeLit,
+            Instruction::JgeReg(_, _, _) => OpCode::JgeReg,
+            Instruction::JleLit(_, _, _) => OpCode::JleLit,
+            Instruction::JleReg(_, _, _) => OpCode::JleReg,
+            Instruction::JltLit(_, _, _) => OpCode::JltLit,
+            Instruction::JltReg(_, _, _) => OpCode::JltReg,
+            Instruction::Jmp(_, _) => OpCode::Jmp,
+            Instruction::Int(_, _) => OpCode::Int,
             Instruction::Rti(_) => OpCode::Rti,
         }
     }
 
     pub fn kind(&self) -> InstructionKind {
         match self {
-            Instruction::MovLitReg(_, _)
-            | Instruction::AddLitReg(_, _)
-            | Instruction::SubLitReg(_, _)
-            | Instruction::MulLitReg(_, _)
-            | Instruction::AndLitReg(_, _)
-            | Instruction::OrLitReg(_, _)
-            | Instruction::LshLitReg(_, _)
-            | Instruction::RshLitReg(_, _)
-            | Instruction::XorLitReg(_, _) => InstructionKind::LitReg,
-
-            Instruction::Mov8LitReg(_, _) => InstructionKind::LitReg8,
-            Instruction::Mov8RegReg(_, _) => InstructionKind::RegReg8,
-            Instruction::Mov8RegMem(_, _) => InstructionKind::RegMem8,
-            Instruction::Mov8MemReg(_, _) => InstructionKind::MemReg8,
-            Instruction::Mov8LitMem(_, _) => InstructionKind::LitMem8,
-
-            Instruction::MovRegReg(_, _)
-            | Instruction::AddRegReg(_, _)
-            | Instruction::SubRegReg(_, _)
-            | Instruction::MulRegReg(_, _)
-            | Instruction::AndRegReg(_, _)
-            | Instruction::OrRegReg(_, _)
-            | Instruction::LshRegReg(_, _)
-            | Instruction::RshRegReg(_, _)
-            | Instruction::XorRegReg(_, _) => InstructionKind::RegReg,
-
-            Instruction::MovLitMem(_, _)
-            | Instruction::JneLit(_, _)
-            | Instruction::JeqLit(_, _)
-            | Instruction::JgtLit(_, _)
-            | Instruction::JgeLit(_, _)
-            | Instruction::JleLit(_, _)
-            | Instruction::JltLit(_, _) => InstructionKind::LitMem,
-
-            Instruction::Inc(_)
-            | Instruction::Dec(_)
-            | Instruction::Not(_)
-            | Instruction::PshReg(_)
-            | Instruction::Pop(_) => InstructionKind::SingleReg,
-
-            Instruction::MovRegMem(_, _)
-            | Instruction::JneReg(_, _)
-            | Instruction::JeqReg(_, _)
-            | Instruction::JgtReg(_, _)
-            | Instruction::JgeReg(_, _)
-            | Instruction::JleReg(_, _)
-            | Instruction::JltReg(_, _) => InstructionKind::RegMem,
-
-            Instruction::MovMemReg(_, _) => InstructionKind::MemReg,
-            Instruction::MovRegPtrReg(_, _) => InstructionKind::RegPtrReg,
-            Instruction::MovLitRegPtr(_, _) => InstructionKind::LitRegPtr,
-            Instruction::PshLit(_) | Instruction::Call(_) | Instruction::Jmp(_) | Instruction::Int(_) => {
+            Instruction::MovLitReg(_, _, _)
+            | Instruction::AddLitReg(_, _, _)
+            | Instruction::SubLitReg(_, _, _)
+            | Instruction::MulLitReg(_, _, _)
+            | Instruction::AndLitReg(_, _, _)
+            | Instruction::OrLitReg(_, _, _)
+            | Instruction::LshLitReg(_, _, _)
+            | Instruction::RshLitReg(_, _, _)
+            | Instruction::XorLitReg(_, _, _) => InstructionKind::LitReg,
+
+            Instruction::Mov8LitReg(_, _, _) => InstructionKind::LitReg8,
+            Instruction::Mov8RegReg(_, _, _) => InstructionKind::RegReg8,
+            Instruction::Mov8RegMem(_, _, _) => InstructionKind::RegMem8,
+            Instruction::Mov8MemReg(_, _, _) => InstructionKind::MemReg8,
+            Instruction::Mov8LitMem(_, _, _) => InstructionKind::LitMem8,
+
+            Instruction::MovRegReg(_, _, _)
+            | Instruction::AddRegReg(_, _, _)
+            | Instruction::SubRegReg(_, _, _)
+            | Instruction::MulRegReg(_, _, _)
+            | Instruction::AndRegReg(_, _, _)
+            | Instruction::OrRegReg(_, _, _)
+            | Instruction::LshRegReg(_, _, _)
+            | Instruction::RshRegReg(_, _, _)
+            | Instruction::XorRegReg(_, _, _) => InstructionKind::RegReg,
+
+            Instruction::MovLitMem(_, _, _)
+            | Instruction::JneLit(_, _, _)
+            | Instruction::JeqLit(_, _, _)
+            | Instruction::JgtLit(_, _, _)
+            | Instruction::JgeLit(_, _, _)
+            | Instruction::JleLit(_, _, _)
+            | Instruction::JltLit(_, _, _) => InstructionKind::LitMem,
+
+            Instruction::Inc(_, _)
+            | Instruction::Dec(_, _)
+            | Instruction::Not(_, _)
+            | Instruction::PshReg(_, _)
+            | Instruction::Pop(_, _) => InstructionKind::SingleReg,
+
+            Instruction::MovRegMem(_, _, _)
+            | Instruction::JneReg(_, _, _)
+            | Instruction::JeqReg(_, _, _)
+            | Instruction::JgtReg(_, _, _)
+            | Instruction::JgeReg(_, _, _)
+            | Instruction::JleReg(_, _, _)
+            | Instruction::JltReg(_, _, _) => InstructionKind::RegMem,
+
+            Instruction::MovMemReg(_, _, _) => InstructionKind::MemReg,
+            Instruction::MovRegPtrReg(_, _, _) | Instruction::LoadRegPtr(_, _, _) => InstructionKind::RegPtrReg,
+            Instruction::LoadRegPtrOff(_, _, _) => InstructionKind::RegPtrOffReg,
+            Instruction::MovLitRegPtr(_, _, _) => InstructionKind::LitRegPtr,
+            Instruction::PshLit(_, _) | Instruction::Call(_, _) | Instruction::Jmp(_, _) | Instruction::Int(_, _) => {
                 InstructionKind::SingleLit
             }
-            Instruction::Ret(_) | Instruction::Hlt(_) | Instruction::Rti(_) => InstructionKind::NoArgs,
+            Instruction::Hlt(_, _) => InstructionKind::SingleLit8,
+            Instruction::Ret(_) | Instruction::Rti(_) => InstructionKind::NoArgs,
         }
     }
 
     pub fn offset(&self) -> ByteOffset {
-        const NORMAL: usize = 4;
-        const SMALL: usize = 3;
-        const BIG: usize = 5;
-
         match self {
-            Instruction::MovLitReg(lhs, rhs) => (lhs.offset().start - NORMAL..rhs.offset().end).into(),
-            Instruction::MovRegReg(lhs, rhs) => (lhs.offset().start - NORMAL..rhs.offset().end).into(),
-            Instruction::MovRegMem(lhs, rhs) => (lhs.offset().start - NORMAL..rhs.offset().end).into(),
-            Instruction::MovMemReg(lhs, rhs) => (lhs.offset().start - NORMAL..rhs.offset().end).into(),
-            Instruction::MovLitMem(lhs, rhs) => (lhs.offset().start - NORMAL..rhs.offset().end).into(),
-            Instruction::MovRegPtrReg(lhs, rhs) => (lhs.offset().start - NORMAL..rhs.offset().end).into(),
-            Instruction::MovLitRegPtr(lhs, rhs) => (lhs.offset().start - NORMAL..rhs.offset().end).into(),
-            Instruction::Mov8LitReg(lhs, rhs) => (lhs.offset().start - BIG..rhs.offset().end).into(),
-            Instruction::Mov8RegReg(lhs, rhs) => (lhs.offset().start - BIG..rhs.offset().end).into(),
-            Instruction::Mov8RegMem(lhs, rhs) => (lhs.offset().start - BIG..rhs.offset().end).into(),
-            Instruction::Mov8MemReg(lhs, rhs) => (lhs.offset().start - BIG..rhs.offset().end).into(),
-            Instruction::Mov8LitMem(lhs, rhs) => (lhs.offset().start - BIG..rhs.offset().end).into(),
-            Instruction::AddRegReg(lhs, rhs) => (lhs.offset().start - NORMAL..rhs.offset().end).into(),
-            Instruction::AddLitReg(lhs, rhs) => (lhs.offset().start - NORMAL..rhs.offset().end).into(),
-            Instruction::SubRegReg(lhs, rhs) => (lhs.offset().start - NORMAL..rhs.offset().end).into(),
-            Instruction::SubLitReg(lhs, rhs) => (lhs.offset().start - NORMAL..rhs.offset().end).into(),
-            Instruction::MulRegReg(lhs, rhs) => (lhs.offset().start - NORMAL..rhs.offset().end).into(),
-            Instruction::MulLitReg(lhs, rhs) => (lhs.offset().start - NORMAL..rhs.offset().end).into(),
-            Instruction::LshRegReg(lhs, rhs) => (lhs.offset().start - NORMAL..rhs.offset().end).into(),
-            Instruction::LshLitReg(lhs, rhs) => (lhs.offset().start - NORMAL..rhs.offset().end).into(),
-            Instruction::RshRegReg(lhs, rhs) => (lhs.offset().start - NORMAL..rhs.offset().end).into(),
-            Instruction::RshLitReg(lhs, rhs) => (lhs.offset().start - NORMAL..rhs.offset().end).into(),
-            Instruction::AndRegReg(lhs, rhs) => (lhs.offset().start - NORMAL..rhs.offset().end).into(),
-            Instruction::AndLitReg(lhs, rhs) => (lhs.offset().start - NORMAL..rhs.offset().end).into(),
-            Instruction::OrLitReg(lhs, rhs) => (lhs.offset().start - SMALL..rhs.offset().end).into(),
-            Instruction::OrRegReg(lhs, rhs) => (lhs.offset().start - SMALL..rhs.offset().end).into(),
-            Instruction::XorLitReg(lhs, rhs) => (lhs.offset().start - NORMAL..rhs.offset().end).into(),
-            Instruction::XorRegReg(lhs, rhs) => (lhs.offset().start - NORMAL..rhs.offset().end).into(),
-            Instruction::Inc(stat) => (stat.offset().start - NORMAL..stat.offset().end).into(),
-            Instruction::Dec(stat) => (stat.offset().start - NORMAL..stat.offset().end).into(),
-            Instruction::Not(stat) => (stat.offset().start - NORMAL..stat.offset().end).into(),
-            Instruction::JeqLit(lhs, rhs) => (lhs.offset().start - NORMAL..rhs.offset().end).into(),
-            Instruction::JeqReg(lhs, rhs) => (lhs.offset().start - NORMAL..rhs.offset().end).into(),
-            Instruction::JgtLit(lhs, rhs) => (lhs.offset().start - NORMAL..rhs.offset().end).into(),
-            Instruction::JgtReg(lhs, rhs) => (lhs.offset().start - NORMAL..rhs.offset().end).into(),
-            Instruction::JneLit(lhs, rhs) => (lhs.offset().start - NORMAL..rhs.offset().end).into(),
-            Instruction::JneReg(lhs, rhs) => (lhs.offset().start - NORMAL..rhs.offset().end).into(),
-            Instruction::JgeLit(lhs, rhs) => (lhs.offset().start - NORMAL..rhs.offset().end).into(),
-            Instruction::JgeReg(lhs, rhs) => (lhs.offset().start - NORMAL..rhs.offset().end).into(),
-            Instruction::JleLit(lhs, rhs) => (lhs.offset().start - NORMAL..rhs.offset().end).into(),
-            Instruction::JleReg(lhs, rhs) => (lhs.offset().start - NORMAL..rhs.offset().end).into(),
-            Instruction::JltLit(lhs, rhs) => (lhs.offset().start - NORMAL..rhs.offset().end).into(),
-            Instruction::JltReg(lhs, rhs) => (lhs.offset().start - NORMAL..rhs.offset().end).into(),
-            Instruction::Jmp(stat) => (stat.offset().start - NORMAL..stat.offset().end).into(),
-            Instruction::PshLit(stat) => (stat.offset().start - NORMAL..stat.offset().end).into(),
-            Instruction::PshReg(stat) => (stat.offset().start - NORMAL..stat.offset().end).into(),
-            Instruction::Pop(stat) => (stat.offset().start - NORMAL..stat.offset().end).into(),
-            Instruction::Call(stat) => (stat.offset().start - BIG..stat.offset().end).into(),
-            Instruction::Ret(offset) => *offset,
-            Instruction::Hlt(offset) => *offset,
-            Instruction::Int(stat) => (stat.offset().start - NORMAL..stat.offset().end).into(),
-            Instruction::Rti(offset) => *offset,
+            Instruction::MovLitReg(offset, _, rhs)
+            | Instruction::MovRegReg(offset, _, rhs)
+            | Instruction::MovRegMem(offset, _, rhs)
+            | Instruction::MovMemReg(offset, _, rhs)
+            | Instruction::MovLitMem(offset, _, rhs)
+            | Instruction::MovRegPtrReg(offset, _, rhs)
+            | Instruction::MovLitRegPtr(offset, _, rhs)
+            | Instruction::LoadRegPtr(offset, _, rhs)
+            | Instruction::LoadRegPtrOff(offset, _, rhs)
+            | Instruction::Mov8LitReg(offset, _, rhs)
+            | Instruction::Mov8RegReg(offset, _, rhs)
+            | Instruction::Mov8RegMem(offset, _, rhs)
+            | Instruction::Mov8MemReg(offset, _, rhs)
+            | Instruction::Mov8LitMem(offset, _, rhs)
+            | Instruction::AddRegReg(offset, _, rhs)
+            | Instruction::AddLitReg(offset, _, rhs)
+            | Instruction::SubRegReg(offset, _, rhs)
+            | Instruction::SubLitReg(offset, _, rhs)
+            | Instruction::MulRegReg(offset, _, rhs)
+            | Instruction::MulLitReg(offset, _, rhs)
+            | Instruction::LshRegReg(offset, _, rhs)
+            | Instruction::LshLitReg(offset, _, rhs)
+            | Instruction::RshRegReg(offset, _, rhs)
+            | Instruction::RshLitReg(offset, _, rhs)
+            | Instruction::AndRegReg(offset, _, rhs)
+            | Instruction::AndLitReg(offset, _, rhs)
+            | Instruction::OrLitReg(offset, _, rhs)
+            | Instruction::OrRegReg(offset, _, rhs)
+            | Instruction::XorLitReg(offset, _, rhs)
+            | Instruction::XorRegReg(offset, _, rhs)
+            | Instruction::JeqLit(offset, _, rhs)
+            | Instruction::JeqReg(offset, _, rhs)
+            | Instruction::JgtLit(offset, _, rhs)
+            | Instruction::JgtReg(offset, _, rhs)
+            | Instruction::JneLit(offset, _, rhs)
+            | Instruction::JneReg(offset, _, rhs)
+            | Instruction::JgeLit(offset, _, rhs)
+            | Instruction::JgeReg(offset, _, rhs)
+            | Instruction::JleLit(offset, _, rhs)
+            | Instruction::JleReg(offset, _, rhs)
+            | Instruction::JltLit(offset, _, rhs)
+            | Instruction::JltReg(offset, _, rhs) => (offset.start..rhs.offset().end).into(),
+            Instruction::Inc(offset, stat)
+            | Instruction::Dec(offset, stat)
+            | Instruction::Not(offset, stat)
+            | Instruction::Jmp(offset, stat)
+            | Instruction::PshLit(offset, stat)
+            | Instruction::PshReg(offset, stat)
+            | Instruction::Pop(offset, stat)
+            | Instruction::Call(offset, stat)
+            | Instruction::Int(offset, stat) => (offset.start..stat.offset().end).into(),
+            Instruction::Hlt(offset, Some(code)) => (offset.start..code.offset().end).into(),
+            Instruction::Ret(offset) | Instruction::Hlt(offset, None) | Instruction::Rti(offset) => *offset,
         }
     }
 }
@@ -1,8 +1,11 @@
 use std::ops::Range;
 
+use aya_cpu::register::Register;
+
 use super::Result;
 use crate::lexer::{Kind, Lexer, Token, TransposeRef};
 use crate::parser::ast::ByteOffset;
+use crate::parser::error::REGISTER_MSG;
 use crate::utils::{bail, unexpected_eof, unexpected_token};
 
 pub fn peek<S: AsRef<str>>(source: S, lexer: &mut Lexer) -> Result<Token> {
@@ -57,9 +60,9 @@ pub fn parse_identifier<S: AsRef<str>>(source: S, lexer: &mut Lexer, help: S, me
 pub fn parse_register<S: AsRef<str>>(source: S, lexer: &mut Lexer) -> Result<ByteOffset> {
     let offset = parse_identifier(source.as_ref(), lexer, "", "")?;
     let name = &source.as_ref()[Range::<usize>::from(offset)];
-    match name.to_lowercase().as_str() {
-        "acc" | "ip" | "r1" | "r2" | "r3" | "r4" | "r5" | "r6" | "r7" | "r8" | "sp" | "fp" | "im" => Ok(offset),
-        _ => unexpected_token(source.as_ref(), &Token::from_ident(name, offset.start, offset.end)),
+    match Register::try_from(name) {
+        Ok(_) => Ok(offset),
+        Err(err) => Err(bail(source.as_ref(), &err.to_string(), REGISTER_MSG, offset)),
     }
 }
 
@@ -71,9 +74,19 @@ pub fn parse_string<S: AsRef<str>>(source: S, lexer: &mut Lexer, help: S, messag
     expect(Kind::String, lexer, source.as_ref(), help.as_ref(), message.as_ref())
 }
 
+/// Parses `!name`, or `!Module.NAME` for an exported constant of an imported module, whose offset
+/// spans the module and the constant names.
 pub fn parse_variable<S: AsRef<str>>(source: S, lexer: &mut Lexer, help: S, message: S) -> Result<ByteOffset> {
     expect(Kind::Bang, lexer, source.as_ref(), help.as_ref(), message.as_ref())?;
-    expect(Kind::Ident, lexer, source.as_ref(), help.as_ref(), message.as_ref())
+    let name = expect(Kind::Ident, lexer, source.as_ref(), help.as_ref(), message.as_ref())?;
+
+    match lexer.peek() {
+        Some(Ok(dot)) if dot.kind == Kind::Dot && dot.offset().start == name.end => {}
+        _ => return Ok(name),
+    }
+    lexer.next().transpose()?;
+    let field = expect(Kind::Ident, lexer, source.as_ref(), help.as_ref(), message.as_ref())?;
+    Ok((name.start..field.end).into())
 }
 
 pub fn parse_keyword<S: AsRef<str>>(source: S, lexer: &mut Lexer, expected: Kind) -> Result<ByteOffset> {
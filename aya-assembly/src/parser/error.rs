@@ -10,7 +10,6 @@ pub static VAR_MSG: &str = "[SYNTAX_ERROR]: variable name must be a valid identi
 pub static VAR_HELP: &str = "variables must start with a ! [BANG] followed by a valid identifier";
 
 pub static REGISTER_MSG: &str = "[SYNTAX_ERROR]: invalid register name";
-pub static REGISTER_HELP: &str = "register name must be in the set of valid registers";
 
 pub static BRACKETED_EXPR_HELP: &str = "invalid bracketed expression";
 pub static BRACKETED_EXPR_MSG: &str = "[SYNTAX_ERROR]: invalid bracketed expression";
@@ -94,7 +94,7 @@ fn parse_import_var_value<S: AsRef<str>>(source: S, lexer: &mut Lexer) -> Result
             HEX_LIT_HELP,
             HEX_LIT_MSG,
         )?)),
-        _ => return unexpected_token(source.as_ref(), token),
+        _ => unexpected_token(source.as_ref(), token),
     }
 }
 
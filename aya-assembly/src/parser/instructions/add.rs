@@ -7,7 +7,7 @@ use crate::parser::Result;
 use crate::utils::unexpected_token;
 
 pub fn parse_add<S: AsRef<str>>(source: S, lexer: &mut Lexer) -> Result<Statement> {
-    parse_keyword(source.as_ref(), lexer, Kind::Add)?;
+    let offset = parse_keyword(source.as_ref(), lexer, Kind::Add)?;
 
     let lhs = Statement::Register(parse_register(source.as_ref(), lexer)?);
 
@@ -29,10 +29,10 @@ pub fn parse_add<S: AsRef<str>>(source: S, lexer: &mut Lexer) -> Result<Statemen
     };
 
     match token.kind {
-        Kind::Ident => Ok(Instruction::AddRegReg(lhs, rhs).into()),
-        Kind::HexNumber => Ok(Instruction::AddLitReg(lhs, rhs).into()),
-        Kind::Bang => Ok(Instruction::AddLitReg(lhs, rhs).into()),
-        Kind::LBracket => Ok(Instruction::AddLitReg(lhs, rhs).into()),
+        Kind::Ident => Ok(Instruction::AddRegReg(offset, lhs, rhs).into()),
+        Kind::HexNumber => Ok(Instruction::AddLitReg(offset, lhs, rhs).into()),
+        Kind::Bang => Ok(Instruction::AddLitReg(offset, lhs, rhs).into()),
+        Kind::LBracket => Ok(Instruction::AddLitReg(offset, lhs, rhs).into()),
         _ => unreachable!(),
     }
 }
@@ -9,7 +9,7 @@ use crate::parser::Result;
 use crate::utils::{unexpected_eof, unexpected_token};
 
 pub fn parse_and<S: AsRef<str>>(source: S, lexer: &mut Lexer) -> Result<Statement> {
-    parse_keyword(source.as_ref(), lexer, Kind::And)?;
+    let offset = parse_keyword(source.as_ref(), lexer, Kind::And)?;
 
     let lhs = Statement::Register(parse_register(source.as_ref(), lexer)?);
 
@@ -38,10 +38,10 @@ pub fn parse_and<S: AsRef<str>>(source: S, lexer: &mut Lexer) -> Result<Statemen
     };
 
     match kind {
-        Kind::Ident => Ok(Instruction::AndRegReg(lhs, rhs).into()),
-        Kind::HexNumber => Ok(Instruction::AndLitReg(lhs, rhs).into()),
-        Kind::Bang => Ok(Instruction::AndLitReg(lhs, rhs).into()),
-        Kind::LBracket => Ok(Instruction::AndLitReg(lhs, rhs).into()),
+        Kind::Ident => Ok(Instruction::AndRegReg(offset, lhs, rhs).into()),
+        Kind::HexNumber => Ok(Instruction::AndLitReg(offset, lhs, rhs).into()),
+        Kind::Bang => Ok(Instruction::AndLitReg(offset, lhs, rhs).into()),
+        Kind::LBracket => Ok(Instruction::AndLitReg(offset, lhs, rhs).into()),
         _ => unreachable!(),
     }
 }
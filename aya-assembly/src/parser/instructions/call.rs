@@ -7,7 +7,7 @@ use crate::parser::Result;
 use crate::utils::{unexpected_eof, unexpected_token};
 
 pub fn parse_call<S: AsRef<str>>(source: S, lexer: &mut Lexer) -> Result<Statement> {
-    parse_keyword(source.as_ref(), lexer, Kind::Call)?;
+    let offset = parse_keyword(source.as_ref(), lexer, Kind::Call)?;
 
     let Ok(Some(token)) = lexer.peek().transpose() else {
         let Err(err) = lexer.next().transpose() else {
@@ -29,8 +29,8 @@ pub fn parse_call<S: AsRef<str>>(source: S, lexer: &mut Lexer) -> Result<Stateme
     };
 
     match kind {
-        Kind::HexNumber => Ok(Instruction::Call(value).into()),
-        Kind::Ampersand => Ok(Instruction::Call(value).into()),
+        Kind::HexNumber => Ok(Instruction::Call(offset, value).into()),
+        Kind::Ampersand => Ok(Instruction::Call(offset, value).into()),
         _ => unreachable!(),
     }
 }
@@ -4,9 +4,9 @@ use crate::parser::common::{parse_keyword, parse_register};
 use crate::parser::Result;
 
 pub fn parse_dec<S: AsRef<str>>(source: S, lexer: &mut Lexer) -> Result<Statement> {
-    parse_keyword(source.as_ref(), lexer, Kind::Dec)?;
+    let offset = parse_keyword(source.as_ref(), lexer, Kind::Dec)?;
     let value = Statement::Register(parse_register(source.as_ref(), lexer)?);
-    Ok(Instruction::Dec(value).into())
+    Ok(Instruction::Dec(offset, value).into())
 }
 
 #[cfg(test)]
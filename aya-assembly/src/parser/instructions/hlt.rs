@@ -1,11 +1,22 @@
 use crate::lexer::{Kind, Lexer};
 use crate::parser::ast::{Instruction, Statement};
-use crate::parser::common::parse_keyword;
+use crate::parser::common::{parse_hex_lit, parse_keyword};
+use crate::parser::error::{HEX_LIT_HELP, HEX_LIT_MSG};
 use crate::parser::Result;
 
+/// Parses `hlt`, optionally followed by the byte the program halts with.
 pub fn parse_hlt<S: AsRef<str>>(source: S, lexer: &mut Lexer) -> Result<Statement> {
     let offset = parse_keyword(source.as_ref(), lexer, Kind::Hlt)?;
-    Ok(Instruction::Hlt(offset).into())
+    let code = match lexer.peek() {
+        Some(Ok(token)) if token.kind == Kind::HexNumber => Some(Statement::HexLiteral(parse_hex_lit(
+            source.as_ref(),
+            lexer,
+            HEX_LIT_HELP,
+            HEX_LIT_MSG,
+        )?)),
+        _ => None,
+    };
+    Ok(Instruction::Hlt(offset, code).into())
 }
 
 #[cfg(test)]
@@ -23,4 +34,11 @@ mod tests {
         let result = run_instruction(input);
         insta::assert_debug_snapshot!(result);
     }
+
+    #[test]
+    fn test_hlt_code() {
+        let input = "hlt $03";
+        let result = run_instruction(input);
+        insta::assert_debug_snapshot!(result);
+    }
 }
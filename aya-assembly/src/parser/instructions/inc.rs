@@ -4,9 +4,9 @@ use crate::parser::common::{parse_keyword, parse_register};
 use crate::parser::Result;
 
 pub fn parse_inc<S: AsRef<str>>(source: S, lexer: &mut Lexer) -> Result<Statement> {
-    parse_keyword(source.as_ref(), lexer, Kind::Inc)?;
+    let offset = parse_keyword(source.as_ref(), lexer, Kind::Inc)?;
     let value = Statement::Register(parse_register(source.as_ref(), lexer)?);
-    Ok(Instruction::Inc(value).into())
+    Ok(Instruction::Inc(offset, value).into())
 }
 
 #[cfg(test)]
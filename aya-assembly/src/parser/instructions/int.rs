@@ -5,9 +5,9 @@ use crate::parser::error::{HEX_LIT_HELP, HEX_LIT_MSG};
 use crate::parser::Result;
 
 pub fn parse_int<S: AsRef<str>>(source: S, lexer: &mut Lexer) -> Result<Statement> {
-    parse_keyword(source.as_ref(), lexer, Kind::Int)?;
+    let offset = parse_keyword(source.as_ref(), lexer, Kind::Int)?;
     let value = Statement::HexLiteral(parse_hex_lit(source.as_ref(), lexer, HEX_LIT_HELP, HEX_LIT_MSG)?);
-    Ok(Instruction::Int(value).into())
+    Ok(Instruction::Int(offset, value).into())
 }
 
 #[cfg(test)]
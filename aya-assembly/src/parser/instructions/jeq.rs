@@ -10,7 +10,7 @@ use crate::parser::Result;
 use crate::utils::{unexpected_eof, unexpected_token};
 
 pub fn parse_jeq<S: AsRef<str>>(source: S, lexer: &mut Lexer) -> Result<Statement> {
-    parse_keyword(source.as_ref(), lexer, Kind::Jeq)?;
+    let offset = parse_keyword(source.as_ref(), lexer, Kind::Jeq)?;
 
     let lhs = parse_address_expr(source.as_ref(), lexer, ADDRESS_HELP, ADDRESS_MSG)?;
 
@@ -39,10 +39,10 @@ pub fn parse_jeq<S: AsRef<str>>(source: S, lexer: &mut Lexer) -> Result<Statemen
     };
 
     match kind {
-        Kind::Ident => Ok(Instruction::JeqReg(lhs, rhs).into()),
-        Kind::HexNumber => Ok(Instruction::JeqLit(lhs, rhs).into()),
-        Kind::Bang => Ok(Instruction::JeqLit(lhs, rhs).into()),
-        Kind::LBracket => Ok(Instruction::JeqLit(lhs, rhs).into()),
+        Kind::Ident => Ok(Instruction::JeqReg(offset, lhs, rhs).into()),
+        Kind::HexNumber => Ok(Instruction::JeqLit(offset, lhs, rhs).into()),
+        Kind::Bang => Ok(Instruction::JeqLit(offset, lhs, rhs).into()),
+        Kind::LBracket => Ok(Instruction::JeqLit(offset, lhs, rhs).into()),
         _ => unreachable!(),
     }
 }
@@ -10,7 +10,7 @@ use crate::parser::Result;
 use crate::utils::{unexpected_eof, unexpected_token};
 
 pub fn parse_jge<S: AsRef<str>>(source: S, lexer: &mut Lexer) -> Result<Statement> {
-    parse_keyword(source.as_ref(), lexer, Kind::Jge)?;
+    let offset = parse_keyword(source.as_ref(), lexer, Kind::Jge)?;
 
     let lhs = parse_address_expr(source.as_ref(), lexer, ADDRESS_HELP, ADDRESS_MSG)?;
 
@@ -39,10 +39,10 @@ pub fn parse_jge<S: AsRef<str>>(source: S, lexer: &mut Lexer) -> Result<Statemen
     };
 
     match kind {
-        Kind::Ident => Ok(Instruction::JgeReg(lhs, rhs).into()),
-        Kind::HexNumber => Ok(Instruction::JgeLit(lhs, rhs).into()),
-        Kind::Bang => Ok(Instruction::JgeLit(lhs, rhs).into()),
-        Kind::LBracket => Ok(Instruction::JgeLit(lhs, rhs).into()),
+        Kind::Ident => Ok(Instruction::JgeReg(offset, lhs, rhs).into()),
+        Kind::HexNumber => Ok(Instruction::JgeLit(offset, lhs, rhs).into()),
+        Kind::Bang => Ok(Instruction::JgeLit(offset, lhs, rhs).into()),
+        Kind::LBracket => Ok(Instruction::JgeLit(offset, lhs, rhs).into()),
         _ => unreachable!(),
     }
 }
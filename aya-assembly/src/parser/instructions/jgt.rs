@@ -10,7 +10,7 @@ use crate::parser::Result;
 use crate::utils::{unexpected_eof, unexpected_token};
 
 pub fn parse_jgt<S: AsRef<str>>(source: S, lexer: &mut Lexer) -> Result<Statement> {
-    parse_keyword(source.as_ref(), lexer, Kind::Jgt)?;
+    let offset = parse_keyword(source.as_ref(), lexer, Kind::Jgt)?;
 
     let lhs = parse_address_expr(source.as_ref(), lexer, ADDRESS_HELP, ADDRESS_MSG)?;
 
@@ -39,10 +39,10 @@ pub fn parse_jgt<S: AsRef<str>>(source: S, lexer: &mut Lexer) -> Result<Statemen
     };
 
     match kind {
-        Kind::Ident => Ok(Instruction::JgtReg(lhs, rhs).into()),
-        Kind::HexNumber => Ok(Instruction::JgtLit(lhs, rhs).into()),
-        Kind::Bang => Ok(Instruction::JgtLit(lhs, rhs).into()),
-        Kind::LBracket => Ok(Instruction::JgtLit(lhs, rhs).into()),
+        Kind::Ident => Ok(Instruction::JgtReg(offset, lhs, rhs).into()),
+        Kind::HexNumber => Ok(Instruction::JgtLit(offset, lhs, rhs).into()),
+        Kind::Bang => Ok(Instruction::JgtLit(offset, lhs, rhs).into()),
+        Kind::LBracket => Ok(Instruction::JgtLit(offset, lhs, rhs).into()),
         _ => unreachable!(),
     }
 }
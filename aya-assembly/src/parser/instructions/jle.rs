@@ -10,7 +10,7 @@ use crate::parser::Result;
 use crate::utils::{unexpected_eof, unexpected_token};
 
 pub fn parse_jle<S: AsRef<str>>(source: S, lexer: &mut Lexer) -> Result<Statement> {
-    parse_keyword(source.as_ref(), lexer, Kind::Jle)?;
+    let offset = parse_keyword(source.as_ref(), lexer, Kind::Jle)?;
 
     let lhs = parse_address_expr(source.as_ref(), lexer, ADDRESS_HELP, ADDRESS_MSG)?;
 
@@ -39,10 +39,10 @@ pub fn parse_jle<S: AsRef<str>>(source: S, lexer: &mut Lexer) -> Result<Statemen
     };
 
     match kind {
-        Kind::Ident => Ok(Instruction::JleReg(lhs, rhs).into()),
-        Kind::HexNumber => Ok(Instruction::JleLit(lhs, rhs).into()),
-        Kind::Bang => Ok(Instruction::JleLit(lhs, rhs).into()),
-        Kind::LBracket => Ok(Instruction::JleLit(lhs, rhs).into()),
+        Kind::Ident => Ok(Instruction::JleReg(offset, lhs, rhs).into()),
+        Kind::HexNumber => Ok(Instruction::JleLit(offset, lhs, rhs).into()),
+        Kind::Bang => Ok(Instruction::JleLit(offset, lhs, rhs).into()),
+        Kind::LBracket => Ok(Instruction::JleLit(offset, lhs, rhs).into()),
         _ => unreachable!(),
     }
 }
@@ -10,7 +10,7 @@ use crate::parser::Result;
 use crate::utils::{unexpected_eof, unexpected_token};
 
 pub fn parse_jlt<S: AsRef<str>>(source: S, lexer: &mut Lexer) -> Result<Statement> {
-    parse_keyword(source.as_ref(), lexer, Kind::Jlt)?;
+    let offset = parse_keyword(source.as_ref(), lexer, Kind::Jlt)?;
 
     let lhs = parse_address_expr(source.as_ref(), lexer, ADDRESS_HELP, ADDRESS_MSG)?;
 
@@ -39,10 +39,10 @@ pub fn parse_jlt<S: AsRef<str>>(source: S, lexer: &mut Lexer) -> Result<Statemen
     };
 
     match kind {
-        Kind::Ident => Ok(Instruction::JltReg(lhs, rhs).into()),
-        Kind::HexNumber => Ok(Instruction::JltLit(lhs, rhs).into()),
-        Kind::Bang => Ok(Instruction::JltLit(lhs, rhs).into()),
-        Kind::LBracket => Ok(Instruction::JltLit(lhs, rhs).into()),
+        Kind::Ident => Ok(Instruction::JltReg(offset, lhs, rhs).into()),
+        Kind::HexNumber => Ok(Instruction::JltLit(offset, lhs, rhs).into()),
+        Kind::Bang => Ok(Instruction::JltLit(offset, lhs, rhs).into()),
+        Kind::LBracket => Ok(Instruction::JltLit(offset, lhs, rhs).into()),
         _ => unreachable!(),
     }
 }
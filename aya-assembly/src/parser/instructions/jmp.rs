@@ -6,11 +6,11 @@ use crate::parser::expressions::parse_address_expr;
 use crate::parser::Result;
 
 pub fn parse_jmp<S: AsRef<str>>(source: S, lexer: &mut Lexer) -> Result<Statement> {
-    parse_keyword(source.as_ref(), lexer, Kind::Jmp)?;
+    let offset = parse_keyword(source.as_ref(), lexer, Kind::Jmp)?;
 
     let lhs = parse_address_expr(source.as_ref(), lexer, ADDRESS_HELP, ADDRESS_MSG)?;
 
-    Ok(Instruction::Jmp(lhs).into())
+    Ok(Instruction::Jmp(offset, lhs).into())
 }
 
 #[cfg(test)]
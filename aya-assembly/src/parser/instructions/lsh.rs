@@ -9,7 +9,7 @@ use crate::parser::Result;
 use crate::utils::unexpected_token;
 
 pub fn parse_lsh<S: AsRef<str>>(source: S, lexer: &mut Lexer) -> Result<Statement> {
-    parse_keyword(source.as_ref(), lexer, Kind::Lsh)?;
+    let offset = parse_keyword(source.as_ref(), lexer, Kind::Lsh)?;
 
     let lhs = Statement::Register(parse_register(source.as_ref(), lexer)?);
 
@@ -31,10 +31,10 @@ pub fn parse_lsh<S: AsRef<str>>(source: S, lexer: &mut Lexer) -> Result<Statemen
     };
 
     match token.kind {
-        Kind::Ident => Ok(Instruction::LshRegReg(lhs, rhs).into()),
-        Kind::HexNumber => Ok(Instruction::LshLitReg(lhs, rhs).into()),
-        Kind::Bang => Ok(Instruction::LshLitReg(lhs, rhs).into()),
-        Kind::LBracket => Ok(Instruction::LshLitReg(lhs, rhs).into()),
+        Kind::Ident => Ok(Instruction::LshRegReg(offset, lhs, rhs).into()),
+        Kind::HexNumber => Ok(Instruction::LshLitReg(offset, lhs, rhs).into()),
+        Kind::Bang => Ok(Instruction::LshLitReg(offset, lhs, rhs).into()),
+        Kind::LBracket => Ok(Instruction::LshLitReg(offset, lhs, rhs).into()),
         _ => unreachable!(),
     }
 }
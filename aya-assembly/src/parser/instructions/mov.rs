@@ -1,5 +1,5 @@
 use crate::lexer::{Kind, Lexer};
-use crate::parser::ast::{Instruction, Statement};
+use crate::parser::ast::{Instruction, Operator, Statement};
 use crate::parser::common::{expect, parse_hex_lit, parse_keyword, parse_register, parse_variable, peek};
 use crate::parser::error::{
     ADDRESS_HELP, ADDRESS_MSG, BRACKETED_EXPR_HELP, BRACKETED_EXPR_MSG, COMMA_MSG, HEX_LIT_HELP, HEX_LIT_MSG, VAR_HELP,
@@ -10,7 +10,7 @@ use crate::parser::Result;
 use crate::utils::unexpected_token;
 
 pub fn parse_mov<S: AsRef<str>>(source: S, lexer: &mut Lexer) -> Result<Statement> {
-    parse_keyword(source.as_ref(), lexer, Kind::Mov)?;
+    let offset = parse_keyword(source.as_ref(), lexer, Kind::Mov)?;
 
     let lhs_token = peek(source.as_ref(), lexer)?;
     let lhs = match lhs_token.kind {
@@ -39,29 +39,38 @@ pub fn parse_mov<S: AsRef<str>>(source: S, lexer: &mut Lexer) -> Result<Statemen
 
     match (lhs_token.kind, rhs_token.kind) {
         // MovRegReg
-        (Kind::Ident, Kind::Ident) => Ok(Instruction::MovRegReg(lhs, rhs).into()),
+        (Kind::Ident, Kind::Ident) => Ok(Instruction::MovRegReg(offset, lhs, rhs).into()),
         // MovLitReg
-        (Kind::Ident, Kind::Bang) => Ok(Instruction::MovLitReg(lhs, rhs).into()),
-        (Kind::Ident, Kind::HexNumber) => Ok(Instruction::MovLitReg(lhs, rhs).into()),
-        (Kind::Ident, Kind::LBracket) => Ok(Instruction::MovLitReg(lhs, rhs).into()),
+        (Kind::Ident, Kind::Bang) => Ok(Instruction::MovLitReg(offset, lhs, rhs).into()),
+        (Kind::Ident, Kind::HexNumber) => Ok(Instruction::MovLitReg(offset, lhs, rhs).into()),
+        (Kind::Ident, Kind::LBracket) => Ok(Instruction::MovLitReg(offset, lhs, rhs).into()),
+        // MovRegPtrReg
+        (Kind::Ampersand, Kind::Ident) if is_reg_address(&lhs) => {
+            Ok(Instruction::MovRegPtrReg(offset, lhs, rhs).into())
+        }
         // MovRegMem
-        (Kind::Ampersand, Kind::Ident) => Ok(Instruction::MovRegMem(lhs, rhs).into()),
+        (Kind::Ampersand, Kind::Ident) => Ok(Instruction::MovRegMem(offset, lhs, rhs).into()),
+        // LoadRegPtr
+        (Kind::Ident, Kind::Ampersand) if is_reg_address(&rhs) => Ok(Instruction::LoadRegPtr(offset, lhs, rhs).into()),
+        // LoadRegPtrOff
+        (Kind::Ident, Kind::Ampersand) if is_reg_offset_address(&rhs) => {
+            Ok(Instruction::LoadRegPtrOff(offset, lhs, rhs).into())
+        }
         // MovMemReg
-        (Kind::Ident, Kind::Ampersand) => Ok(Instruction::MovMemReg(lhs, rhs).into()),
+        (Kind::Ident, Kind::Ampersand) => Ok(Instruction::MovMemReg(offset, lhs, rhs).into()),
         // MovLitRegPtr
-        (Kind::Ampersand, Kind::HexNumber) if is_reg_address(&lhs) => Ok(Instruction::MovLitRegPtr(lhs, rhs).into()),
-        (Kind::Ampersand, Kind::Ampersand) if is_reg_address(&rhs) && is_reg_address(&lhs) => {
-            Ok(Instruction::MovRegPtrReg(lhs, rhs).into())
+        (Kind::Ampersand, Kind::HexNumber) if is_reg_address(&lhs) => {
+            Ok(Instruction::MovLitRegPtr(offset, lhs, rhs).into())
         }
         // MovLitMem
-        (Kind::Ampersand, Kind::LBracket) => Ok(Instruction::MovLitMem(lhs, rhs).into()),
-        (Kind::Ampersand, Kind::Bang) => Ok(Instruction::MovLitMem(lhs, rhs).into()),
-        (Kind::Ampersand, Kind::HexNumber) => Ok(Instruction::MovLitMem(lhs, rhs).into()),
-        // MovRegPtrReg
+        (Kind::Ampersand, Kind::LBracket) => Ok(Instruction::MovLitMem(offset, lhs, rhs).into()),
+        (Kind::Ampersand, Kind::Bang) => Ok(Instruction::MovLitMem(offset, lhs, rhs).into()),
+        (Kind::Ampersand, Kind::HexNumber) => Ok(Instruction::MovLitMem(offset, lhs, rhs).into()),
+        // MovRegPtrReg, deprecated form of `mov &[r1], r2`
         (Kind::Ampersand, Kind::Ampersand) if is_reg_address(&rhs) && is_reg_address(&lhs) => {
-            Ok(Instruction::MovRegPtrReg(lhs, rhs).into())
+            Ok(Instruction::MovRegPtrReg(offset, lhs, rhs).into())
         }
-        _ => return unexpected_token(source.as_ref(), &rhs_token),
+        _ => unexpected_token(source.as_ref(), &rhs_token),
     }
 }
 
@@ -72,6 +81,24 @@ fn is_reg_address(result: &Statement) -> bool {
     matches!(inner.as_ref(), Statement::Register(_))
 }
 
+/// Whether `result` addresses memory by a register plus a literal, as in `&[r2 + $04]`.
+fn is_reg_offset_address(result: &Statement) -> bool {
+    let Statement::Address(inner) = result else {
+        return false;
+    };
+    let Statement::BinaryOp { lhs, operator, rhs } = inner.as_ref() else {
+        return false;
+    };
+    matches!(
+        (lhs.as_ref(), operator, rhs.as_ref()),
+        (
+            Statement::Register(_),
+            Operator::Add,
+            Statement::HexLiteral(_) | Statement::Var(_)
+        )
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -90,7 +117,7 @@ mod tests {
         let Statement::Instruction(inner) = result else {
             unreachable!();
         };
-        assert!(matches!(inner.as_ref(), Instruction::MovRegReg(_, _)));
+        assert!(matches!(inner.as_ref(), Instruction::MovRegReg(_, _, _)));
     }
 
     #[test]
@@ -102,7 +129,7 @@ mod tests {
         let Statement::Instruction(inner) = result else {
             unreachable!();
         };
-        assert!(matches!(inner.as_ref(), Instruction::MovLitReg(_, _)));
+        assert!(matches!(inner.as_ref(), Instruction::MovLitReg(_, _, _)));
     }
 
     #[test]
@@ -114,7 +141,7 @@ mod tests {
         let Statement::Instruction(inner) = result else {
             unreachable!();
         };
-        assert!(matches!(inner.as_ref(), Instruction::MovLitReg(_, _)));
+        assert!(matches!(inner.as_ref(), Instruction::MovLitReg(_, _, _)));
     }
 
     #[test]
@@ -126,7 +153,7 @@ mod tests {
         let Statement::Instruction(inner) = result else {
             unreachable!();
         };
-        assert!(matches!(inner.as_ref(), Instruction::MovLitReg(_, _)));
+        assert!(matches!(inner.as_ref(), Instruction::MovLitReg(_, _, _)));
     }
 
     #[test]
@@ -138,7 +165,7 @@ mod tests {
         let Statement::Instruction(inner) = result else {
             unreachable!();
         };
-        assert!(matches!(inner.as_ref(), Instruction::MovRegMem(_, _)));
+        assert!(matches!(inner.as_ref(), Instruction::MovRegMem(_, _, _)));
     }
 
     #[test]
@@ -150,7 +177,7 @@ mod tests {
         let Statement::Instruction(inner) = result else {
             unreachable!();
         };
-        assert!(matches!(inner.as_ref(), Instruction::MovRegMem(_, _)));
+        assert!(matches!(inner.as_ref(), Instruction::MovRegMem(_, _, _)));
     }
 
     #[test]
@@ -162,7 +189,7 @@ mod tests {
         let Statement::Instruction(inner) = result else {
             unreachable!();
         };
-        assert!(matches!(inner.as_ref(), Instruction::MovRegMem(_, _)));
+        assert!(matches!(inner.as_ref(), Instruction::MovRegMem(_, _, _)));
     }
 
     #[test]
@@ -174,7 +201,7 @@ mod tests {
         let Statement::Instruction(inner) = result else {
             unreachable!();
         };
-        assert!(matches!(inner.as_ref(), Instruction::MovMemReg(_, _)));
+        assert!(matches!(inner.as_ref(), Instruction::MovMemReg(_, _, _)));
     }
 
     #[test]
@@ -186,7 +213,7 @@ mod tests {
         let Statement::Instruction(inner) = result else {
             unreachable!();
         };
-        assert!(matches!(inner.as_ref(), Instruction::MovMemReg(_, _)));
+        assert!(matches!(inner.as_ref(), Instruction::MovMemReg(_, _, _)));
     }
 
     #[test]
@@ -198,7 +225,7 @@ mod tests {
         let Statement::Instruction(inner) = result else {
             unreachable!();
         };
-        assert!(matches!(inner.as_ref(), Instruction::MovMemReg(_, _)));
+        assert!(matches!(inner.as_ref(), Instruction::MovMemReg(_, _, _)));
     }
 
     #[test]
@@ -210,7 +237,7 @@ mod tests {
         let Statement::Instruction(inner) = result else {
             unreachable!();
         };
-        assert!(matches!(inner.as_ref(), Instruction::MovLitMem(_, _)));
+        assert!(matches!(inner.as_ref(), Instruction::MovLitMem(_, _, _)));
     }
 
     #[test]
@@ -222,7 +249,7 @@ mod tests {
         let Statement::Instruction(inner) = result else {
             unreachable!();
         };
-        assert!(matches!(inner.as_ref(), Instruction::MovLitMem(_, _)));
+        assert!(matches!(inner.as_ref(), Instruction::MovLitMem(_, _, _)));
     }
 
     #[test]
@@ -234,7 +261,7 @@ mod tests {
         let Statement::Instruction(inner) = result else {
             unreachable!();
         };
-        assert!(matches!(inner.as_ref(), Instruction::MovLitMem(_, _)));
+        assert!(matches!(inner.as_ref(), Instruction::MovLitMem(_, _, _)));
     }
 
     #[test]
@@ -246,7 +273,7 @@ mod tests {
         let Statement::Instruction(inner) = result else {
             unreachable!();
         };
-        assert!(matches!(inner.as_ref(), Instruction::MovLitMem(_, _)));
+        assert!(matches!(inner.as_ref(), Instruction::MovLitMem(_, _, _)));
     }
 
     #[test]
@@ -258,7 +285,7 @@ mod tests {
         let Statement::Instruction(inner) = result else {
             unreachable!();
         };
-        assert!(matches!(inner.as_ref(), Instruction::MovLitMem(_, _)));
+        assert!(matches!(inner.as_ref(), Instruction::MovLitMem(_, _, _)));
     }
 
     #[test]
@@ -270,7 +297,7 @@ mod tests {
         let Statement::Instruction(inner) = result else {
             unreachable!();
         };
-        assert!(matches!(inner.as_ref(), Instruction::MovLitMem(_, _)));
+        assert!(matches!(inner.as_ref(), Instruction::MovLitMem(_, _, _)));
     }
 
     #[test]
@@ -282,7 +309,7 @@ mod tests {
         let Statement::Instruction(inner) = result else {
             unreachable!();
         };
-        assert!(matches!(inner.as_ref(), Instruction::MovLitMem(_, _)));
+        assert!(matches!(inner.as_ref(), Instruction::MovLitMem(_, _, _)));
     }
 
     #[test]
@@ -294,6 +321,54 @@ mod tests {
         let Statement::Instruction(inner) = result else {
             unreachable!();
         };
-        assert!(matches!(inner.as_ref(), Instruction::MovRegPtrReg(_, _)));
+        assert!(matches!(inner.as_ref(), Instruction::MovRegPtrReg(_, _, _)));
+    }
+
+    #[test]
+    fn test_mov_reg_ptr_reg_store() {
+        let input = "mov &[r2], r3";
+        let result = run_instruction(input);
+        insta::assert_debug_snapshot!(result);
+
+        let Statement::Instruction(inner) = result else {
+            unreachable!();
+        };
+        assert!(matches!(inner.as_ref(), Instruction::MovRegPtrReg(_, _, _)));
+    }
+
+    #[test]
+    fn test_load_reg_ptr() {
+        let input = "mov r1, &[r2]";
+        let result = run_instruction(input);
+        insta::assert_debug_snapshot!(result);
+
+        let Statement::Instruction(inner) = result else {
+            unreachable!();
+        };
+        assert!(matches!(inner.as_ref(), Instruction::LoadRegPtr(_, _, _)));
+    }
+
+    #[test]
+    fn test_load_reg_ptr_off() {
+        let result = run_instruction("mov r1, &[r2 + $04]");
+        insta::assert_debug_snapshot!(result);
+
+        let Statement::Instruction(inner) = result else {
+            unreachable!();
+        };
+        assert!(matches!(inner.as_ref(), Instruction::LoadRegPtrOff(_, _, _)));
+
+        let Statement::Instruction(inner) = run_instruction("mov r1, &[r2 + !offset]") else {
+            unreachable!();
+        };
+        assert!(matches!(inner.as_ref(), Instruction::LoadRegPtrOff(_, _, _)));
+
+        // anything other than a register plus a literal is expanded into a temp register
+        for input in ["mov r1, &[$04 + r2]", "mov r1, &[r2 - $04]", "mov r1, &[r2 + r3]"] {
+            let Statement::Instruction(inner) = run_instruction(input) else {
+                unreachable!();
+            };
+            assert!(matches!(inner.as_ref(), Instruction::MovMemReg(_, _, _)), "{input}");
+        }
     }
 }
@@ -7,7 +7,7 @@ use crate::parser::{parse_address_var, Result};
 use crate::utils::unexpected_token;
 
 pub fn parse_mov8<S: AsRef<str>>(source: S, lexer: &mut Lexer) -> Result<Statement> {
-    parse_keyword(source.as_ref(), lexer, Kind::Mov8)?;
+    let offset = parse_keyword(source.as_ref(), lexer, Kind::Mov8)?;
 
     let lhs_token = peek(source.as_ref(), lexer)?;
     let lhs = match lhs_token.kind {
@@ -35,19 +35,19 @@ pub fn parse_mov8<S: AsRef<str>>(source: S, lexer: &mut Lexer) -> Result<Stateme
 
     match (lhs_token.kind, rhs_token.kind) {
         // MovRegReg
-        (Kind::Ident, Kind::Ident) => Ok(Instruction::Mov8RegReg(lhs, rhs).into()),
+        (Kind::Ident, Kind::Ident) => Ok(Instruction::Mov8RegReg(offset, lhs, rhs).into()),
         // MovLitReg
-        (Kind::Ident, Kind::Bang) => Ok(Instruction::Mov8LitReg(lhs, rhs).into()),
-        (Kind::Ident, Kind::HexNumber) => Ok(Instruction::Mov8LitReg(lhs, rhs).into()),
-        (Kind::Ident, Kind::LBracket) => Ok(Instruction::Mov8LitReg(lhs, rhs).into()),
+        (Kind::Ident, Kind::Bang) => Ok(Instruction::Mov8LitReg(offset, lhs, rhs).into()),
+        (Kind::Ident, Kind::HexNumber) => Ok(Instruction::Mov8LitReg(offset, lhs, rhs).into()),
+        (Kind::Ident, Kind::LBracket) => Ok(Instruction::Mov8LitReg(offset, lhs, rhs).into()),
         // MovRegMem
-        (Kind::Ampersand, Kind::Ident) => Ok(Instruction::Mov8RegMem(lhs, rhs).into()),
+        (Kind::Ampersand, Kind::Ident) => Ok(Instruction::Mov8RegMem(offset, lhs, rhs).into()),
         // MovMemReg
-        (Kind::Ident, Kind::Ampersand) => Ok(Instruction::Mov8MemReg(lhs, rhs).into()),
+        (Kind::Ident, Kind::Ampersand) => Ok(Instruction::Mov8MemReg(offset, lhs, rhs).into()),
         // MovLitMem
-        (Kind::Ampersand, Kind::Bang) => Ok(Instruction::Mov8LitMem(lhs, rhs).into()),
-        (Kind::Ampersand, Kind::HexNumber) => Ok(Instruction::Mov8LitMem(lhs, rhs).into()),
-        _ => return unexpected_token(source.as_ref(), &rhs_token),
+        (Kind::Ampersand, Kind::Bang) => Ok(Instruction::Mov8LitMem(offset, lhs, rhs).into()),
+        (Kind::Ampersand, Kind::HexNumber) => Ok(Instruction::Mov8LitMem(offset, lhs, rhs).into()),
+        _ => unexpected_token(source.as_ref(), &rhs_token),
     }
 }
 
@@ -69,7 +69,7 @@ mod tests {
         let Statement::Instruction(inner) = result else {
             unreachable!();
         };
-        assert!(matches!(inner.as_ref(), Instruction::Mov8RegReg(_, _)));
+        assert!(matches!(inner.as_ref(), Instruction::Mov8RegReg(_, _, _)));
     }
 
     #[test]
@@ -81,7 +81,7 @@ mod tests {
         let Statement::Instruction(inner) = result else {
             unreachable!();
         };
-        assert!(matches!(inner.as_ref(), Instruction::Mov8LitReg(_, _)));
+        assert!(matches!(inner.as_ref(), Instruction::Mov8LitReg(_, _, _)));
     }
 
     #[test]
@@ -93,7 +93,7 @@ mod tests {
         let Statement::Instruction(inner) = result else {
             unreachable!();
         };
-        assert!(matches!(inner.as_ref(), Instruction::Mov8LitReg(_, _)));
+        assert!(matches!(inner.as_ref(), Instruction::Mov8LitReg(_, _, _)));
     }
 
     #[test]
@@ -105,7 +105,7 @@ mod tests {
         let Statement::Instruction(inner) = result else {
             unreachable!();
         };
-        assert!(matches!(inner.as_ref(), Instruction::Mov8RegMem(_, _)));
+        assert!(matches!(inner.as_ref(), Instruction::Mov8RegMem(_, _, _)));
     }
 
     #[test]
@@ -117,7 +117,7 @@ mod tests {
         let Statement::Instruction(inner) = result else {
             unreachable!();
         };
-        assert!(matches!(inner.as_ref(), Instruction::Mov8RegMem(_, _)));
+        assert!(matches!(inner.as_ref(), Instruction::Mov8RegMem(_, _, _)));
     }
 
     #[test]
@@ -129,7 +129,7 @@ mod tests {
         let Statement::Instruction(inner) = result else {
             unreachable!();
         };
-        assert!(matches!(inner.as_ref(), Instruction::Mov8RegMem(_, _)));
+        assert!(matches!(inner.as_ref(), Instruction::Mov8RegMem(_, _, _)));
     }
 
     #[test]
@@ -141,7 +141,7 @@ mod tests {
         let Statement::Instruction(inner) = result else {
             unreachable!();
         };
-        assert!(matches!(inner.as_ref(), Instruction::Mov8MemReg(_, _)));
+        assert!(matches!(inner.as_ref(), Instruction::Mov8MemReg(_, _, _)));
     }
 
     #[test]
@@ -153,7 +153,7 @@ mod tests {
         let Statement::Instruction(inner) = result else {
             unreachable!();
         };
-        assert!(matches!(inner.as_ref(), Instruction::Mov8MemReg(_, _)));
+        assert!(matches!(inner.as_ref(), Instruction::Mov8MemReg(_, _, _)));
     }
 
     #[test]
@@ -165,7 +165,7 @@ mod tests {
         let Statement::Instruction(inner) = result else {
             unreachable!();
         };
-        assert!(matches!(inner.as_ref(), Instruction::Mov8LitMem(_, _)));
+        assert!(matches!(inner.as_ref(), Instruction::Mov8LitMem(_, _, _)));
     }
 
     #[test]
@@ -177,7 +177,7 @@ mod tests {
         let Statement::Instruction(inner) = result else {
             unreachable!();
         };
-        assert!(matches!(inner.as_ref(), Instruction::Mov8LitMem(_, _)));
+        assert!(matches!(inner.as_ref(), Instruction::Mov8LitMem(_, _, _)));
     }
 
     #[test]
@@ -189,7 +189,7 @@ mod tests {
         let Statement::Instruction(inner) = result else {
             unreachable!();
         };
-        assert!(matches!(inner.as_ref(), Instruction::Mov8LitMem(_, _)));
+        assert!(matches!(inner.as_ref(), Instruction::Mov8LitMem(_, _, _)));
     }
 
     #[test]
@@ -201,7 +201,7 @@ mod tests {
         let Statement::Instruction(inner) = result else {
             unreachable!();
         };
-        assert!(matches!(inner.as_ref(), Instruction::Mov8LitMem(_, _)));
+        assert!(matches!(inner.as_ref(), Instruction::Mov8LitMem(_, _, _)));
     }
 
     #[test]
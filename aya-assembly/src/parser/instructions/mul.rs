@@ -9,7 +9,7 @@ use crate::parser::Result;
 use crate::utils::{unexpected_eof, unexpected_token};
 
 pub fn parse_mul<S: AsRef<str>>(source: S, lexer: &mut Lexer) -> Result<Statement> {
-    parse_keyword(source.as_ref(), lexer, Kind::Mul)?;
+    let offset = parse_keyword(source.as_ref(), lexer, Kind::Mul)?;
 
     let lhs = Statement::Register(parse_register(source.as_ref(), lexer)?);
 
@@ -38,10 +38,10 @@ pub fn parse_mul<S: AsRef<str>>(source: S, lexer: &mut Lexer) -> Result<Statemen
     };
 
     match kind {
-        Kind::Ident => Ok(Instruction::MulRegReg(lhs, rhs).into()),
-        Kind::HexNumber => Ok(Instruction::MulLitReg(lhs, rhs).into()),
-        Kind::Bang => Ok(Instruction::MulLitReg(lhs, rhs).into()),
-        Kind::LBracket => Ok(Instruction::MulLitReg(lhs, rhs).into()),
+        Kind::Ident => Ok(Instruction::MulRegReg(offset, lhs, rhs).into()),
+        Kind::HexNumber => Ok(Instruction::MulLitReg(offset, lhs, rhs).into()),
+        Kind::Bang => Ok(Instruction::MulLitReg(offset, lhs, rhs).into()),
+        Kind::LBracket => Ok(Instruction::MulLitReg(offset, lhs, rhs).into()),
         _ => unreachable!(),
     }
 }
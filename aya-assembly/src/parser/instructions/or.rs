@@ -9,7 +9,7 @@ use crate::parser::Result;
 use crate::utils::{unexpected_eof, unexpected_token};
 
 pub fn parse_or<S: AsRef<str>>(source: S, lexer: &mut Lexer) -> Result<Statement> {
-    parse_keyword(source.as_ref(), lexer, Kind::Or)?;
+    let offset = parse_keyword(source.as_ref(), lexer, Kind::Or)?;
 
     let lhs = Statement::Register(parse_register(source.as_ref(), lexer)?);
 
@@ -38,10 +38,10 @@ pub fn parse_or<S: AsRef<str>>(source: S, lexer: &mut Lexer) -> Result<Statement
     };
 
     match kind {
-        Kind::Ident => Ok(Instruction::OrRegReg(lhs, rhs).into()),
-        Kind::HexNumber => Ok(Instruction::OrLitReg(lhs, rhs).into()),
-        Kind::Bang => Ok(Instruction::OrLitReg(lhs, rhs).into()),
-        Kind::LBracket => Ok(Instruction::OrLitReg(lhs, rhs).into()),
+        Kind::Ident => Ok(Instruction::OrRegReg(offset, lhs, rhs).into()),
+        Kind::HexNumber => Ok(Instruction::OrLitReg(offset, lhs, rhs).into()),
+        Kind::Bang => Ok(Instruction::OrLitReg(offset, lhs, rhs).into()),
+        Kind::LBracket => Ok(Instruction::OrLitReg(offset, lhs, rhs).into()),
         _ => unreachable!(),
     }
 }
@@ -4,9 +4,9 @@ use crate::parser::common::{parse_keyword, parse_register};
 use crate::parser::Result;
 
 pub fn parse_pop<S: AsRef<str>>(source: S, lexer: &mut Lexer) -> Result<Statement> {
-    parse_keyword(source.as_ref(), lexer, Kind::Pop)?;
+    let offset = parse_keyword(source.as_ref(), lexer, Kind::Pop)?;
     let value = Statement::Register(parse_register(source.as_ref(), lexer)?);
-    Ok(Instruction::Pop(value).into())
+    Ok(Instruction::Pop(offset, value).into())
 }
 
 #[cfg(test)]
@@ -7,7 +7,7 @@ use crate::parser::Result;
 use crate::utils::{unexpected_eof, unexpected_token};
 
 pub fn parse_psh<S: AsRef<str>>(source: S, lexer: &mut Lexer) -> Result<Statement> {
-    parse_keyword(source.as_ref(), lexer, Kind::Psh)?;
+    let offset = parse_keyword(source.as_ref(), lexer, Kind::Psh)?;
 
     let Ok(Some(token)) = lexer.peek().transpose() else {
         let Err(err) = lexer.next().transpose() else {
@@ -26,10 +26,10 @@ pub fn parse_psh<S: AsRef<str>>(source: S, lexer: &mut Lexer) -> Result<Statemen
     };
 
     match kind {
-        Kind::Ident => Ok(Instruction::PshReg(value).into()),
-        Kind::HexNumber => Ok(Instruction::PshLit(value).into()),
-        Kind::Bang => Ok(Instruction::PshLit(value).into()),
-        Kind::LBracket => Ok(Instruction::PshLit(value).into()),
+        Kind::Ident => Ok(Instruction::PshReg(offset, value).into()),
+        Kind::HexNumber => Ok(Instruction::PshLit(offset, value).into()),
+        Kind::Bang => Ok(Instruction::PshLit(offset, value).into()),
+        Kind::LBracket => Ok(Instruction::PshLit(offset, value).into()),
         _ => unreachable!(),
     }
 }
@@ -9,7 +9,7 @@ use crate::parser::Result;
 use crate::utils::{unexpected_eof, unexpected_token};
 
 pub fn parse_xor<S: AsRef<str>>(source: S, lexer: &mut Lexer) -> Result<Statement> {
-    parse_keyword(source.as_ref(), lexer, Kind::Xor)?;
+    let offset = parse_keyword(source.as_ref(), lexer, Kind::Xor)?;
 
     let lhs = Statement::Register(parse_register(source.as_ref(), lexer)?);
 
@@ -38,10 +38,10 @@ pub fn parse_xor<S: AsRef<str>>(source: S, lexer: &mut Lexer) -> Result<Statemen
     };
 
     match kind {
-        Kind::Ident => Ok(Instruction::XorRegReg(lhs, rhs).into()),
-        Kind::HexNumber => Ok(Instruction::XorLitReg(lhs, rhs).into()),
-        Kind::Bang => Ok(Instruction::XorLitReg(lhs, rhs).into()),
-        Kind::LBracket => Ok(Instruction::XorLitReg(lhs, rhs).into()),
+        Kind::Ident => Ok(Instruction::XorRegReg(offset, lhs, rhs).into()),
+        Kind::HexNumber => Ok(Instruction::XorLitReg(offset, lhs, rhs).into()),
+        Kind::Bang => Ok(Instruction::XorLitReg(offset, lhs, rhs).into()),
+        Kind::LBracket => Ok(Instruction::XorLitReg(offset, lhs, rhs).into()),
         _ => unreachable!(),
     }
 }
@@ -90,8 +90,6 @@ fn parse_statement<S: AsRef<str>>(source: S, lexer: &mut Lexer) -> Result<Statem
 }
 
 pub fn parse<S: AsRef<str>>(source: S) -> Result<Ast> {
-    set_miette_hook();
-
     let source = source.as_ref();
     let mut lexer = Lexer::new(source);
     let mut statements = vec![];
@@ -104,21 +102,6 @@ pub fn parse<S: AsRef<str>>(source: S) -> Result<Ast> {
     Ok(Ast { statements })
 }
 
-fn set_miette_hook() {
-    miette::set_hook(Box::new(|_| {
-        Box::new(
-            miette::MietteHandlerOpts::new()
-                .terminal_links(true)
-                .context_lines(10)
-                .tab_width(2)
-                .color(true)
-                .break_words(true)
-                .build(),
-        )
-    }))
-    .ok();
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -178,4 +161,70 @@ mod tests {
         let result = parse(input).unwrap();
         insta::assert_debug_snapshot!(result);
     }
+
+    #[test]
+    fn test_instruction_offset_at_start_of_source() {
+        let sources = [
+            "mov r1, r2",
+            "mov r1, $0102",
+            "mov &[$0102], r1",
+            "mov r1, &[$0102]",
+            "mov &[$0102], $0304",
+            "mov &[r1], r2",
+            "mov &[r1], $0102",
+            "mov8 r1, r2",
+            "mov8 r1, $01",
+            "mov8 &[$0102], r1",
+            "mov8 r1, &[$0102]",
+            "mov8 &[$0102], $03",
+            "add r1, r2",
+            "add r1, $0102",
+            "sub r1, r2",
+            "sub r1, $0102",
+            "mul r1, r2",
+            "mul r1, $0102",
+            "lsh r1, r2",
+            "lsh r1, $0102",
+            "rsh r1, r2",
+            "rsh r1, $0102",
+            "and r1, r2",
+            "and r1, $0102",
+            "or r1, r2",
+            "or r1, $0102",
+            "xor r1, r2",
+            "xor r1, $0102",
+            "inc r1",
+            "dec r1",
+            "not r1",
+            "jeq &[$0102], r1",
+            "jeq &[$0102], $0304",
+            "jgt &[$0102], r1",
+            "jgt &[$0102], $0304",
+            "jne &[$0102], r1",
+            "jne &[$0102], $0304",
+            "jge &[$0102], r1",
+            "jge &[$0102], $0304",
+            "jle &[$0102], r1",
+            "jle &[$0102], $0304",
+            "jlt &[$0102], r1",
+            "jlt &[$0102], $0304",
+            "jmp &[$0102]",
+            "psh r1",
+            "psh $0102",
+            "pop r1",
+            "call &[$0102]",
+            "ret",
+            "hlt",
+            "int $01",
+            "rti",
+        ];
+
+        for source in sources {
+            let ast = parse(source).unwrap();
+            let [Statement::Instruction(instruction)] = ast.statements.as_slice() else {
+                panic!("`{source}` didn't parse into a single instruction");
+            };
+            assert_eq!(instruction.offset().start, 0, "`{source}`");
+        }
+    }
 }
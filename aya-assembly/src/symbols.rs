@@ -0,0 +1,189 @@
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Symbol {
+    pub address: u16,
+    pub name: String,
+    /// Path of the module defining the symbol
+    pub module: String,
+}
+
+/// Address of every label and data block of a program sorted by address, so tools can tell where
+/// an address belongs. The text format has one symbol per line, as its address in hex, name and
+/// the module defining it, separated by spaces:
+///
+/// ```text
+/// 2280 start main.aya
+/// 2290 draw_player sprites/player.aya
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SymbolMap {
+    symbols: Vec<Symbol>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// Line of the offending symbol, starting at 1
+    pub line: usize,
+    pub reason: &'static str,
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.reason)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl SymbolMap {
+    pub fn new(mut symbols: Vec<Symbol>) -> Self {
+        symbols.sort();
+        Self { symbols }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Symbol> {
+        self.symbols.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    /// The symbol `address` belongs to, the closest one at or before it.
+    pub fn lookup(&self, address: u16) -> Option<&Symbol> {
+        let idx = self.symbols.partition_point(|symbol| symbol.address <= address);
+        idx.checked_sub(1).map(|idx| &self.symbols[idx])
+    }
+
+    pub fn to_text(&self) -> String {
+        self.symbols
+            .iter()
+            .map(|symbol| format!("{:04X} {} {}\n", symbol.address, symbol.name, symbol.module))
+            .collect()
+    }
+
+    /// The symbols as a JSON array of `{ "address", "name", "module" }` objects.
+    pub fn to_json(&self) -> String {
+        let entries = self
+            .symbols
+            .iter()
+            .map(|symbol| {
+                format!(
+                    "  {{ \"address\": {}, \"name\": {}, \"module\": {} }}",
+                    symbol.address,
+                    json_string(&symbol.name),
+                    json_string(&symbol.module)
+                )
+            })
+            .collect::<Vec<_>>();
+
+        match entries.is_empty() {
+            true => String::from("[]\n"),
+            false => format!("[\n{}\n]\n", entries.join(",\n")),
+        }
+    }
+
+    /// Parses the text format, blank lines are ignored.
+    pub fn parse(source: &str) -> Result<Self, ParseError> {
+        let mut symbols = vec![];
+
+        for (idx, line) in source.lines().enumerate() {
+            let error = |reason| ParseError { line: idx + 1, reason };
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let (address, rest) = line
+                .split_once(' ')
+                .ok_or(error("expected an address, name and module"))?;
+            let address = u16::from_str_radix(address, 16).map_err(|_| error("address must be a 16 bit hex number"))?;
+            let (name, module) = rest
+                .split_once(' ')
+                .ok_or(error("expected an address, name and module"))?;
+
+            symbols.push(Symbol {
+                address,
+                name: name.to_string(),
+                module: module.to_string(),
+            });
+        }
+
+        Ok(Self::new(symbols))
+    }
+}
+
+fn json_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for ch in value.chars() {
+        match ch {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            ch if ch.is_control() => escaped.push_str(&format!("\\u{:04x}", ch as u32)),
+            ch => escaped.push(ch),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbol(address: u16, name: &str, module: &str) -> Symbol {
+        Symbol {
+            address,
+            name: name.into(),
+            module: module.into(),
+        }
+    }
+
+    #[test]
+    fn test_text_round_trip() {
+        let map = SymbolMap::new(vec![
+            symbol(0x2290, "loop", "main.aya"),
+            symbol(0x2280, "start", "main.aya"),
+            symbol(0x3280, "draw", "my game/draw.aya"),
+        ]);
+
+        let text = map.to_text();
+        assert_eq!(
+            text,
+            "2280 start main.aya\n2290 loop main.aya\n3280 draw my game/draw.aya\n"
+        );
+        assert_eq!(SymbolMap::parse(&text).unwrap(), map);
+    }
+
+    #[test]
+    fn test_lookup() {
+        let map = SymbolMap::new(vec![
+            symbol(0x2280, "start", "main.aya"),
+            symbol(0x2290, "loop", "main.aya"),
+        ]);
+
+        assert_eq!(map.lookup(0x2000), None);
+        assert_eq!(map.lookup(0x2280).unwrap().name, "start");
+        assert_eq!(map.lookup(0x228F).unwrap().name, "start");
+        assert_eq!(map.lookup(0xFFFF).unwrap().name, "loop");
+    }
+
+    #[test]
+    fn test_json() {
+        let map = SymbolMap::new(vec![symbol(0x2280, "start", "C:\\game \"1\".aya")]);
+        assert_eq!(
+            map.to_json(),
+            "[\n  { \"address\": 8832, \"name\": \"start\", \"module\": \"C:\\\\game \\\"1\\\".aya\" }\n]\n"
+        );
+        assert_eq!(SymbolMap::default().to_json(), "[]\n");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = SymbolMap::parse("2280 start main.aya\n\nstart main.aya").unwrap_err();
+        assert_eq!(error.to_string(), "line 3: address must be a 16 bit hex number");
+
+        let error = SymbolMap::parse("2280 start").unwrap_err();
+        assert_eq!(error.to_string(), "line 1: expected an address, name and module");
+    }
+}
@@ -10,6 +10,16 @@ pub fn bail<S: AsRef<str>>(source: S, help: S, message: S, offset: impl Into<mie
     .with_source_code(source.as_ref().to_string())
 }
 
+pub fn warning<S: AsRef<str>>(source: S, message: S, help: S, offset: impl Into<miette::SourceSpan>) -> miette::Report {
+    miette::Report::from(
+        miette::MietteDiagnostic::new(message.as_ref())
+            .with_severity(miette::Severity::Warning)
+            .with_labels(vec![miette::LabeledSpan::at(offset, "this bit")])
+            .with_help(help.as_ref()),
+    )
+    .with_source_code(source.as_ref().to_string())
+}
+
 pub fn bail_multi<S: AsRef<str>>(
     source: &str,
     labels: impl IntoIterator<Item = miette::LabeledSpan>,
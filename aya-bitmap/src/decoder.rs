@@ -3,35 +3,68 @@ use std::path::Path;
 use super::color::Color;
 use super::consts::{HEADER_SIZE, INFO_HEADER_SIZE};
 use super::error::{Error, Result};
-use super::{BitDepth, Bitmap, BitmapHeader, BitmapInfoHeader};
+use super::{BitDepth, Bitmap, BitmapHeader, BitmapInfoHeader, Compression};
 
-pub fn from_reader<R: std::io::Read>(reader: &mut R, file_name: String) -> Result<Bitmap> {
-    let mut buffer = vec![];
-    reader.read_to_end(&mut buffer)?;
+/// Bytes every PNG file starts with
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
 
-    if buffer.len() < HEADER_SIZE + INFO_HEADER_SIZE {
-        return Err(Error::NonBitmap);
+/// Decodes a bitmap held in memory, the decoded bitmap has no file name. PNG images are decoded
+/// too when the `png` feature is enabled.
+pub fn decode_bytes(buffer: &[u8]) -> Result<Bitmap> {
+    if buffer.starts_with(PNG_SIGNATURE) {
+        #[cfg(feature = "png")]
+        return crate::png::decode_png(buffer);
+        #[cfg(not(feature = "png"))]
+        return Err(Error::PngUnsupported);
     }
 
-    if &buffer[..2] != b"BM" {
-        return Err(Error::NonBitmap);
+    if read_bytes::<2>(buffer, 0)? != *b"BM" {
+        return Err(Error::BadMagic);
     }
+    ensure_len(buffer, 0, HEADER_SIZE + INFO_HEADER_SIZE)?;
 
-    let header = decode_header(&buffer)?;
-    let info_header = decode_info_header(&buffer)?;
+    let header = decode_header(buffer)?;
+    let info_header = decode_info_header(buffer)?;
+    let palette = decode_palette(&info_header, buffer)?;
 
-    // Up to here, everything was within bounds, but from now on, we could have a
-    // corrupted bitmap, which then requires us to bound check everything.
-
-    let palette = decode_palette(&info_header, &buffer)?;
+    let data_offset = header.data_offset as usize;
+    let indices = match (info_header.compression, info_header.bit_depth) {
+        (Compression::Rgb, depth @ (BitDepth::MonoChrome | BitDepth::Bit4 | BitDepth::Bit8)) => {
+            let bits = depth.bits();
+            Some(decode_rows(data_offset, &info_header, bits, buffer, |row, col| {
+                Ok(packed_index(row, col, bits))
+            })?)
+        }
+        (Compression::Rle8, BitDepth::Bit8) | (Compression::Rle4, BitDepth::Bit4) => {
+            Some(decode_rle_indices(data_offset, &info_header, buffer)?)
+        }
+        _ => None,
+    };
 
-    // TODO: implement the rest of formats
-    let data = match info_header.bit_depth {
-        BitDepth::MonoChrome => todo!(),
-        BitDepth::Bit4 => decode_4_bit_colors(header.data_offset, &info_header, &palette, &buffer),
-        BitDepth::Bit8 => todo!(),
-        BitDepth::Bit16 => todo!(),
-        BitDepth::Bit24 => todo!(),
+    let data = match &indices {
+        Some(indices) => indices
+            .iter()
+            .map(|index| palette_color(&palette, *index))
+            .collect::<Result<_>>()?,
+        None => match (info_header.compression, info_header.bit_depth) {
+            (Compression::Rgb | Compression::Bitfields, BitDepth::Bit16) => {
+                let packing = Rgb16::from_header(&info_header, buffer)?;
+                decode_rows(data_offset, &info_header, 16, buffer, |row, col| {
+                    Ok(packing.unpack(u16::from_le_bytes([row[col * 2], row[col * 2 + 1]])))
+                })?
+            }
+            // pixels are stored in BGR order, just like palette entries
+            (Compression::Rgb, BitDepth::Bit24) => decode_rows(data_offset, &info_header, 24, buffer, |row, col| {
+                Ok(Color::from([row[col * 3], row[col * 3 + 1], row[col * 3 + 2]]))
+            })?,
+            // pixels are stored in BGRA order, the alpha channel is dropped as colors are always opaque
+            (Compression::Rgb, BitDepth::Bit32) => decode_rows(data_offset, &info_header, 32, buffer, |row, col| {
+                Ok(Color::from([row[col * 4], row[col * 4 + 1], row[col * 4 + 2]]))
+            })?,
+            // run length encoding is only defined for the bit depth matching it, and bitfields are only
+            // supported on 16-bit bitmaps
+            (compression, _) => return Err(Error::UnsupportedCompression(compression.into())),
+        },
     };
 
     Ok(Bitmap {
@@ -39,39 +72,82 @@ pub fn from_reader<R: std::io::Read>(reader: &mut R, file_name: String) -> Resul
         info_header,
         palette,
         data,
-        file_name,
+        indices,
+        file_name: None,
     })
 }
 
-pub fn decode<P: AsRef<Path>>(path: P) -> Result<Bitmap> {
-    let mut file = std::fs::OpenOptions::new().read(true).open(&path)?;
-    from_reader(&mut file, path.as_ref().to_string_lossy().to_string())
+/// Fails with [`Error::Truncated`] unless `buffer` holds `len` bytes starting at `offset`.
+fn ensure_len(buffer: &[u8], offset: usize, len: usize) -> Result<()> {
+    let got = buffer.len().saturating_sub(offset);
+    match got >= len {
+        true => Ok(()),
+        false => Err(Error::Truncated {
+            expected: len,
+            got,
+            at_offset: offset,
+        }),
+    }
 }
 
-fn decode_info_header(buffer: &[u8]) -> Result<BitmapInfoHeader> {
-    let width: [u8; 4] = buffer[0x12..0x16].try_into().unwrap();
-    let width = u32::from_le_bytes(width);
+fn read_bytes<const N: usize>(buffer: &[u8], offset: usize) -> Result<[u8; N]> {
+    ensure_len(buffer, offset, N)?;
+    Ok(buffer[offset..offset + N].try_into().unwrap())
+}
 
-    let height: [u8; 4] = buffer[0x16..0x1A].try_into().unwrap();
-    let height = u32::from_le_bytes(height);
+fn read_u16(buffer: &[u8], offset: usize) -> Result<u16> {
+    read_bytes(buffer, offset).map(u16::from_le_bytes)
+}
 
-    let bit_depth: [u8; 2] = buffer[0x1C..0x1E].try_into().unwrap();
-    let bit_depth = u16::from_le_bytes(bit_depth);
-    let bit_depth = BitDepth::try_from(bit_depth)?;
+fn read_u32(buffer: &[u8], offset: usize) -> Result<u32> {
+    read_bytes(buffer, offset).map(u32::from_le_bytes)
+}
 
-    let image_size: [u8; 4] = buffer[0x22..0x26].try_into().unwrap();
-    let image_size = u32::from_le_bytes(image_size);
+fn palette_color(palette: &[Color], index: u8) -> Result<Color> {
+    palette.get(index as usize).copied().ok_or(Error::OutOfBounds)
+}
 
-    let num_colors: [u8; 4] = buffer[0x2E..0x32].try_into().unwrap();
-    let num_colors = u32::from_le_bytes(num_colors);
+/// Reads the whole reader and decodes its contents, the decoded bitmap has no file name.
+pub fn decode_reader(mut reader: impl std::io::Read) -> Result<Bitmap> {
+    let mut buffer = vec![];
+    reader.read_to_end(&mut buffer)?;
+    decode_bytes(&buffer)
+}
+
+/// Decodes the bitmap file at `path`, errors are tagged with the path so they can be told apart
+/// when decoding many files.
+pub fn decode<P: AsRef<Path>>(path: P) -> Result<Bitmap> {
+    let file_name = path.as_ref().to_string_lossy().to_string();
+    let bitmap = std::fs::File::open(&path)
+        .map_err(Error::from)
+        .and_then(decode_reader)
+        .map_err(|err| Error::File {
+            name: file_name.clone(),
+            source: Box::new(err),
+        })?;
 
-    let important_colors: [u8; 4] = buffer[0x32..0x36].try_into().unwrap();
-    let important_colors = u32::from_le_bytes(important_colors);
+    Ok(bitmap.with_file_name(file_name))
+}
+
+fn decode_info_header(buffer: &[u8]) -> Result<BitmapInfoHeader> {
+    let width = read_u32(buffer, 0x12)?;
+
+    let height = read_bytes(buffer, 0x16).map(i32::from_le_bytes)?;
+    let top_down = height < 0;
+    let height = height.unsigned_abs();
+
+    let bit_depth = BitDepth::try_from(read_u16(buffer, 0x1C)?)?;
+    let compression = Compression::try_from(read_u32(buffer, 0x1E)?)?;
+    let image_size = read_u32(buffer, 0x22)?;
+    let num_colors = read_u32(buffer, 0x2E)?;
+    let important_colors = read_u32(buffer, 0x32)?;
 
     Ok(BitmapInfoHeader {
         width,
         height,
+        top_down,
         bit_depth,
+        compression,
         image_size,
         num_colors,
         important_colors,
@@ -79,11 +155,8 @@ fn decode_info_header(buffer: &[u8]) -> Result<BitmapInfoHeader> {
 }
 
 fn decode_header(buffer: &[u8]) -> Result<BitmapHeader> {
-    let file_size: [u8; 4] = buffer[0x02..0x06].try_into().unwrap();
-    let file_size = u32::from_le_bytes(file_size);
-
-    let data_offset: [u8; 4] = buffer[0x0A..0x0E].try_into().unwrap();
-    let data_offset = u32::from_le_bytes(data_offset);
+    let file_size = read_u32(buffer, 0x02)?;
+    let data_offset = read_u32(buffer, 0x0A)?;
 
     Ok(BitmapHeader { file_size, data_offset })
 }
@@ -93,10 +166,13 @@ fn decode_palette(info_header: &BitmapInfoHeader, buffer: &[u8]) -> Result<Vec<C
 
     // TODO: Handle 16Bit encoding... why microsoft?
     if info_header.bit_depth.has_palette() {
-        for i in 0..info_header.num_colors as usize {
-            let colors_offset = 0x36 + i * 4;
-            let end = colors_offset + 3;
-            let color: [u8; 3] = buffer[colors_offset..end].try_into().map_err(|_| Error::OutOfBounds)?;
+        // a palette without a color count holds every color the bit depth can address
+        let num_colors = match info_header.num_colors {
+            0 => 1 << info_header.bit_depth.bits(),
+            num_colors => num_colors as usize,
+        };
+        for i in 0..num_colors {
+            let color: [u8; 3] = read_bytes(buffer, HEADER_SIZE + INFO_HEADER_SIZE + i * 4)?;
             colors.push(Color::from(color));
         }
     }
@@ -104,31 +180,434 @@ fn decode_palette(info_header: &BitmapInfoHeader, buffer: &[u8]) -> Result<Vec<C
     Ok(colors)
 }
 
-fn decode_4_bit_colors(
-    data_offset: u32,
+/// Palette index of the `col`th pixel of a row packing `bits` per pixel, pixels that share a byte
+/// are stored from the most significant bits down.
+pub(crate) fn packed_index(row: &[u8], col: usize, bits: usize) -> u8 {
+    let bit = col * bits;
+    let shift = 8 - bits - bit % 8;
+    (row[bit / 8] >> shift) & (u8::MAX >> (8 - bits))
+}
+
+/// Row of the pixel data holding the `row`th row of the image counting from the top, rows are
+/// stored bottom-up unless the bitmap is top-down.
+fn source_row(info_header: &BitmapInfoHeader, row: u32) -> u32 {
+    match info_header.top_down {
+        true => row,
+        false => info_header.height - 1 - row,
+    }
+}
+
+/// Packings of 16-bit pixels, each channel is stored from the most significant bits down as red,
+/// green and blue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Rgb16 {
+    Rgb555,
+    Rgb565,
+}
+
+impl Rgb16 {
+    const RGB555_MASKS: [u32; 3] = [0x7C00, 0x03E0, 0x001F];
+    const RGB565_MASKS: [u32; 3] = [0xF800, 0x07E0, 0x001F];
+
+    /// Uncompressed 16-bit bitmaps are always 555, while bitfield ones describe their packing with
+    /// red, green and blue masks right after the info header.
+    fn from_header(info_header: &BitmapInfoHeader, buffer: &[u8]) -> Result<Self> {
+        if info_header.compression == Compression::Rgb {
+            return Ok(Self::Rgb555);
+        }
+
+        let masks_offset = HEADER_SIZE + INFO_HEADER_SIZE;
+        let masks = [
+            read_u32(buffer, masks_offset)?,
+            read_u32(buffer, masks_offset + 4)?,
+            read_u32(buffer, masks_offset + 8)?,
+        ];
+
+        match masks {
+            Self::RGB555_MASKS => Ok(Self::Rgb555),
+            Self::RGB565_MASKS => Ok(Self::Rgb565),
+            [red, green, blue] => Err(Error::UnsupportedBitfields { red, green, blue }),
+        }
+    }
+
+    /// Expands a pixel to 8 bits per channel, repeating the high bits of every channel on the low
+    /// ones so full intensity stays full intensity.
+    fn unpack(self, pixel: u16) -> Color {
+        let expand5 = |value: u16| ((value << 3) | (value >> 2)) as u8;
+        let expand6 = |value: u16| ((value << 2) | (value >> 4)) as u8;
+
+        match self {
+            Self::Rgb555 => Color::new(
+                expand5((pixel >> 10) & 0x1F),
+                expand5((pixel >> 5) & 0x1F),
+                expand5(pixel & 0x1F),
+            ),
+            Self::Rgb565 => Color::new(
+                expand5(pixel >> 11),
+                expand6((pixel >> 5) & 0x3F),
+                expand5(pixel & 0x1F),
+            ),
+        }
+    }
+}
+
+/// Decodes uncompressed pixel data into colors or palette indices, calling `pixel` with the bytes of a row, padding excluded,
+/// and the column of every pixel on it. The whole pixel data is checked to be present up front.
+fn decode_rows<T>(
+    data_offset: usize,
     info_header: &BitmapInfoHeader,
-    palette: &[Color],
+    bits_per_pixel: usize,
     buffer: &[u8],
-) -> Vec<Color> {
-    let height = info_header.height;
-    let width = info_header.width;
-    let mut colors = vec![Color::new(0, 0, 0); (width * height) as usize];
+    pixel: impl Fn(&[u8], usize) -> Result<T>,
+) -> Result<Vec<T>> {
+    let width = info_header.width as usize;
+    let height = info_header.height as usize;
 
-    let stride = ((width + 1) / 2 + 3) & !3;
+    let row_len = (width * bits_per_pixel).div_ceil(8);
+    // every row is padded to a multiple of 4 bytes
+    let stride = row_len.div_ceil(4) * 4;
+    ensure_len(buffer, data_offset, stride.saturating_mul(height))?;
 
-    for row in 0..height {
-        let src_row = height - 1 - row;
-        let row_start = data_offset + src_row * stride;
+    let mut pixels = Vec::with_capacity(width * height);
+    for row in 0..info_header.height {
+        let row_start = data_offset + source_row(info_header, row) as usize * stride;
+        let row = &buffer[row_start..row_start + row_len];
 
         for col in 0..width {
-            let byte_idx = row_start + (col / 2);
-            let byte = buffer[byte_idx as usize];
+            pixels.push(pixel(row, col)?);
+        }
+    }
+
+    Ok(pixels)
+}
+
+/// Decodes run length encoded palette indices. The data is a sequence of two byte records, a
+/// non zero first byte repeats the second one that many times, while a zero one starts an escape:
+/// 0 ends the line, 1 ends the bitmap, 2 moves the cursor by the next two bytes, and anything else
+/// is followed by that many literal indices, padded to an even number of bytes. On RLE4 bitmaps
+/// every byte holds two indices, which runs alternate between.
+fn decode_rle_indices(data_offset: usize, info_header: &BitmapInfoHeader, buffer: &[u8]) -> Result<Vec<u8>> {
+    let height = info_header.height as usize;
+    let width = info_header.width as usize;
+    let nibbles = info_header.compression == Compression::Rle4;
+    // pixels skipped with a delta or left out by an early end of line keep the first color
+    let mut indices = vec![0u8; width.checked_mul(height).ok_or(Error::OutOfBounds)?];
+
+    let mut offset = data_offset;
+    let mut next = || {
+        let [byte] = read_bytes(buffer, offset)?;
+        offset += 1;
+        Ok::<_, Error>(byte)
+    };
+    // the cursor starts on the bottom left corner, as the image is stored bottom-up
+    let (mut x, mut y) = (0, 0);
+    let mut put = |x: usize, y: usize, index: u8| {
+        if x < width && y < height {
+            indices[(height - 1 - y) * width + x] = index;
+        }
+    };
+
+    loop {
+        match (next()?, next()?) {
+            (0, 0) => (x, y) = (0, y + 1),
+            (0, 1) => break,
+            (0, 2) => {
+                x += next()? as usize;
+                y += next()? as usize;
+            }
+            (0, count) => {
+                let count = count as usize;
+                let bytes_used = if nibbles { count.div_ceil(2) } else { count };
+                let mut byte = 0;
+                for pixel in 0..count {
+                    let index = match nibbles {
+                        true if pixel % 2 == 0 => {
+                            byte = next()?;
+                            byte >> 4
+                        }
+                        true => byte & 0xf,
+                        false => next()?,
+                    };
+                    put(x, y, index);
+                    x += 1;
+                }
+                // literal runs are padded to a 16 bit boundary
+                if bytes_used % 2 == 1 {
+                    next()?;
+                }
+            }
+            (count, value) => {
+                for pixel in 0..count as usize {
+                    let index = match nibbles {
+                        true if pixel % 2 == 0 => value >> 4,
+                        true => value & 0xf,
+                        false => value,
+                    };
+                    put(x, y, index);
+                    x += 1;
+                }
+            }
+        }
+    }
+
+    Ok(indices)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode_fixture(bytes: &[u8]) -> Bitmap {
+        decode_bytes(bytes).unwrap()
+    }
+
+    /// Every fixture holds the same 3x2 image, listed from the top left corner
+    fn fixture_pixels() -> Vec<Color> {
+        vec![
+            Color::new(0xFF, 0, 0),
+            Color::new(0, 0xFF, 0),
+            Color::new(0, 0, 0xFF),
+            Color::new(0xFF, 0xFF, 0xFF),
+            Color::new(0, 0, 0),
+            Color::new(0x12, 0x34, 0x56),
+        ]
+    }
+
+    #[test]
+    fn test_decode_32_bit() {
+        let bitmap = decode_fixture(include_bytes!("../fixtures/bit32.bmp"));
+        assert_eq!(bitmap.info_header().bit_depth, BitDepth::Bit32);
+        assert!(!bitmap.info_header().is_top_down());
+        assert_eq!(bitmap.data(), fixture_pixels());
+    }
+
+    #[test]
+    fn test_decode_32_bit_top_down() {
+        let bitmap = decode_fixture(include_bytes!("../fixtures/bit32_top_down.bmp"));
+        assert!(bitmap.info_header().is_top_down());
+        assert_eq!(bitmap.info_header().height(), 2);
+        assert_eq!(bitmap.data(), fixture_pixels());
+    }
+
+    #[test]
+    fn test_decode_24_bit_top_down() {
+        let bitmap = decode_fixture(include_bytes!("../fixtures/bit24_top_down.bmp"));
+        assert_eq!(bitmap.info_header().bit_depth, BitDepth::Bit24);
+        assert!(bitmap.info_header().is_top_down());
+        assert_eq!(bitmap.info_header().height(), 2);
+        assert_eq!(bitmap.data(), fixture_pixels());
+    }
+
+    /// Palette indices of the image held by the RLE fixtures, the RLE4 one starts with an
+    /// alternating run instead
+    #[rustfmt::skip]
+    const RLE_INDICES: [u8; 24] = [
+        0, 0, 0, 1, 0, 0,
+        2, 2, 0, 0, 0, 0,
+        0, 0, 3, 3, 3, 3,
+        1, 1, 1, 2, 3, 2,
+    ];
+
+    fn rle_colors(indices: &[u8], palette: &[Color]) -> Vec<Color> {
+        indices.iter().map(|index| palette[*index as usize]).collect()
+    }
+
+    #[test]
+    fn test_decode_rle8() {
+        let bitmap = decode_fixture(include_bytes!("../fixtures/rle8.bmp"));
+        assert_eq!(bitmap.info_header().compression(), Compression::Rle8);
+        assert_eq!(bitmap.data(), rle_colors(&RLE_INDICES, bitmap.palette()));
+    }
+
+    #[test]
+    fn test_decode_rle4() {
+        let bitmap = decode_fixture(include_bytes!("../fixtures/rle4.bmp"));
+        assert_eq!(bitmap.info_header().compression(), Compression::Rle4);
+
+        let mut indices = RLE_INDICES;
+        indices[18..21].copy_from_slice(&[1, 2, 1]);
+        assert_eq!(bitmap.data(), rle_colors(&indices, bitmap.palette()));
+    }
+
+    #[test]
+    fn test_unsupported_compression() {
+        let mut bytes = include_bytes!("../fixtures/rle8.bmp").to_vec();
+        // bitfields
+        bytes[0x1E] = 3;
+        let err = decode_bytes(&bytes).unwrap_err();
+        assert!(matches!(err, Error::UnsupportedCompression(3)));
+        assert_eq!(err.to_string(), "unsupported bitmap compression method 3");
+
+        // RLE4 on an 8-bit bitmap
+        bytes[0x1E] = 2;
+        let err = decode_bytes(&bytes).unwrap_err();
+        assert!(matches!(err, Error::UnsupportedCompression(2)));
+    }
+
+    #[test]
+    fn test_decode_sources() {
+        let bytes = include_bytes!("../fixtures/bit32.bmp");
+        let from_bytes = decode_bytes(bytes).unwrap();
+        assert_eq!(from_bytes.file_name(), None);
+
+        let from_reader = decode_reader(std::io::Cursor::new(bytes)).unwrap();
+        assert_eq!(from_reader.data(), from_bytes.data());
 
-            let color_idx = if col % 2 == 0 { byte >> 4 } else { byte & 0xf };
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/bit32.bmp");
+        let from_path = decode(path).unwrap();
+        assert_eq!(from_path.file_name(), Some(path));
+        assert_eq!(from_path.data(), from_bytes.data());
 
-            colors[(row * width + col) as usize] = palette[color_idx as usize];
+        assert_eq!(from_bytes.with_file_name("sheet.bmp").file_name(), Some("sheet.bmp"));
+    }
+
+    #[test]
+    fn test_path_errors_name_the_file() {
+        let err = decode("missing.bmp").unwrap_err();
+        assert!(
+            matches!(&err, Error::File { name, source } if name == "missing.bmp" && matches!(&**source, Error::Io(err) if err.kind() == std::io::ErrorKind::NotFound))
+        );
+        assert!(err.to_string().starts_with("missing.bmp: "), "{err}");
+    }
+
+    #[test]
+    fn test_decode_packed_indices() {
+        // 10 pixels wide, so rows end mid byte and need padding
+        let bitmap = decode_fixture(include_bytes!("../fixtures/mono.bmp"));
+        // the fixture doesn't declare its color count
+        assert_eq!(bitmap.palette(), [Color::new(0, 0, 0), Color::new(0xFF, 0xFF, 0xFF)]);
+        let palette = bitmap.palette();
+        let expected = (0..3)
+            .flat_map(|y| (0..10).map(move |x| palette[((x + y) % 3 == 0) as usize]))
+            .collect::<Vec<_>>();
+        assert_eq!(bitmap.data(), expected);
+
+        let bitmap = decode_fixture(include_bytes!("../fixtures/bit4.bmp"));
+        assert_eq!(bitmap.palette().len(), 16);
+        let expected = (0..3)
+            .flat_map(|y| (0..10).map(move |x| (x * 3 + y) % 16))
+            .map(|idx| Color::new(idx as u8 * 16, 0xFF - idx as u8 * 16, idx as u8))
+            .collect::<Vec<_>>();
+        assert_eq!(bitmap.data(), expected);
+    }
+
+    #[test]
+    fn test_paletted_indices() {
+        let bitmap = decode_fixture(include_bytes!("../fixtures/rle8.bmp"));
+        assert_eq!(bitmap.indices(), Some(&RLE_INDICES[..]));
+
+        let bitmap = decode_fixture(include_bytes!("../fixtures/bit4.bmp"));
+        let expected = (0..3)
+            .flat_map(|y| (0..10).map(move |x| (x * 3 + y) % 16))
+            .collect::<Vec<_>>();
+        assert_eq!(bitmap.indices(), Some(&expected[..]));
+
+        let bitmap = decode_fixture(include_bytes!("../fixtures/bit32.bmp"));
+        assert_eq!(bitmap.indices(), None);
+    }
+
+    /// Pixels of the fixtures after going through 16-bit packing, only the last one loses precision
+    fn fixture_pixels_16_bit(last: Color) -> Vec<Color> {
+        let mut pixels = fixture_pixels();
+        pixels[5] = last;
+        pixels
+    }
+
+    #[test]
+    fn test_decode_16_bit() {
+        let bitmap = decode_fixture(include_bytes!("../fixtures/bit16_555.bmp"));
+        assert_eq!(bitmap.data(), fixture_pixels_16_bit(Color::new(0x10, 0x31, 0x52)));
+
+        let bitmap = decode_fixture(include_bytes!("../fixtures/bit16_565.bmp"));
+        assert_eq!(bitmap.info_header().compression(), Compression::Bitfields);
+        assert_eq!(bitmap.data(), fixture_pixels_16_bit(Color::new(0x10, 0x34, 0x52)));
+    }
+
+    #[test]
+    fn test_unsupported_bitfields() {
+        let mut bytes = include_bytes!("../fixtures/bit16_565.bmp").to_vec();
+        // 4 bits per channel
+        let masks_offset = HEADER_SIZE + INFO_HEADER_SIZE;
+        for (idx, mask) in [0x0F00u32, 0x00F0, 0x000F].into_iter().enumerate() {
+            bytes[masks_offset + idx * 4..][..4].copy_from_slice(&mask.to_le_bytes());
+        }
+
+        let err = decode_bytes(&bytes).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::UnsupportedBitfields {
+                red: 0x0F00,
+                green: 0x00F0,
+                blue: 0x000F
+            }
+        ));
+    }
+
+    #[test]
+    fn test_truncated_files_never_panic() {
+        let encoded = crate::encode_indexed(3, 2, &fixture_pixels(), &[0, 1, 2, 3, 4, 5]).unwrap();
+        let files: [&[u8]; 10] = [
+            include_bytes!("../fixtures/mono.bmp"),
+            include_bytes!("../fixtures/bit4.bmp"),
+            include_bytes!("../fixtures/bit16_555.bmp"),
+            include_bytes!("../fixtures/bit16_565.bmp"),
+            include_bytes!("../fixtures/bit32.bmp"),
+            include_bytes!("../fixtures/bit32_top_down.bmp"),
+            include_bytes!("../fixtures/bit24_top_down.bmp"),
+            include_bytes!("../fixtures/rle8.bmp"),
+            include_bytes!("../fixtures/rle4.bmp"),
+            &encoded,
+        ];
+
+        for file in files {
+            assert!(decode_bytes(file).is_ok());
+            for len in 0..file.len() {
+                let result = decode_bytes(&file[..len]);
+                assert!(
+                    matches!(result, Err(Error::Truncated { .. })),
+                    "cut at {len} of {} decoded to {result:?}",
+                    file.len()
+                );
+            }
         }
     }
 
-    colors
+    #[test]
+    fn test_truncation_errors() {
+        let file = include_bytes!("../fixtures/bit32.bmp");
+
+        let err = decode_bytes(&file[..20]).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Truncated {
+                expected: 54,
+                got: 20,
+                at_offset: 0
+            }
+        ));
+
+        let data_offset = u32::from_le_bytes(file[0x0A..0x0E].try_into().unwrap()) as usize;
+        let err = decode_bytes(&file[..file.len() - 1]).unwrap_err();
+        assert!(matches!(err, Error::Truncated { expected, got, at_offset }
+            if at_offset == data_offset && expected == 24 && got == 23));
+        assert_eq!(
+            err.to_string(),
+            format!("truncated bitmap, expected 24 bytes at offset {data_offset} but only 23 are left")
+        );
+
+        let mut bytes = file.to_vec();
+        bytes[..2].copy_from_slice(b"XX");
+        assert!(matches!(decode_bytes(&bytes), Err(Error::BadMagic)));
+    }
+
+    #[cfg(not(feature = "png"))]
+    #[test]
+    fn test_png_needs_feature() {
+        let err = decode_bytes(include_bytes!("../fixtures/indexed.png")).unwrap_err();
+        assert!(matches!(err, Error::PngUnsupported));
+        assert_eq!(
+            err.to_string(),
+            "PNG images can only be decoded with the `png` feature of aya-bitmap enabled"
+        );
+    }
 }
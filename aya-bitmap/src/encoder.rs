@@ -0,0 +1,165 @@
+use super::color::Color;
+use super::consts::{HEADER_SIZE, INFO_HEADER_SIZE};
+use super::error::{Error, Result};
+
+/// Pixels per meter stored on encoded bitmaps, equivalent to 72 DPI
+const PIXELS_PER_METER: u32 = 2835;
+/// Largest palette an 8-bit bitmap can index
+const MAX_PALETTE_SIZE: usize = 256;
+
+/// Encodes `pixels` as a 24-bit bitmap. Pixels are expected row by row, starting from the top
+/// left corner, and there must be exactly `width * height` of them.
+pub fn encode_rgb(width: u32, height: u32, pixels: &[Color]) -> Result<Vec<u8>> {
+    if pixels.len() != (width * height) as usize {
+        return Err(Error::OutOfBounds);
+    }
+
+    // every row is padded to a multiple of 4 bytes
+    let stride = (width * 3 + 3) & !3;
+    let image_size = stride * height;
+    let data_offset = (HEADER_SIZE + INFO_HEADER_SIZE) as u32;
+
+    let mut buffer = Vec::with_capacity((data_offset + image_size) as usize);
+    encode_header(&mut buffer, data_offset + image_size, data_offset);
+    encode_info_header(&mut buffer, width, height, 24, image_size, 0);
+
+    // bitmaps are stored bottom-up, with colors in BGR order
+    for row in pixels.chunks_exact(width as usize).rev() {
+        let row_start = buffer.len();
+        for color in row {
+            let [r, g, b] = <[u8; 3]>::from(*color);
+            buffer.extend_from_slice(&[b, g, r]);
+        }
+        buffer.resize(row_start + stride as usize, 0);
+    }
+
+    Ok(buffer)
+}
+
+/// Encodes `pixels` as an 8-bit paletted bitmap, every pixel being an index into `palette`. Pixels
+/// are expected row by row, starting from the top left corner, and there must be exactly
+/// `width * height` of them.
+pub fn encode_indexed(width: u32, height: u32, palette: &[Color], pixels: &[u8]) -> Result<Vec<u8>> {
+    if pixels.len() != (width * height) as usize || palette.len() > MAX_PALETTE_SIZE {
+        return Err(Error::OutOfBounds);
+    }
+    if pixels.iter().any(|index| *index as usize >= palette.len()) {
+        return Err(Error::OutOfBounds);
+    }
+
+    // every row is padded to a multiple of 4 bytes
+    let stride = (width + 3) & !3;
+    let image_size = stride * height;
+    let data_offset = (HEADER_SIZE + INFO_HEADER_SIZE + palette.len() * 4) as u32;
+
+    let mut buffer = Vec::with_capacity((data_offset + image_size) as usize);
+    encode_header(&mut buffer, data_offset + image_size, data_offset);
+    encode_info_header(&mut buffer, width, height, 8, image_size, palette.len() as u32);
+
+    // palette entries are stored in BGR order followed by a reserved byte
+    for color in palette {
+        let [r, g, b] = <[u8; 3]>::from(*color);
+        buffer.extend_from_slice(&[b, g, r, 0]);
+    }
+
+    // bitmaps are stored bottom-up
+    for row in pixels.chunks_exact(width.max(1) as usize).rev() {
+        let row_start = buffer.len();
+        buffer.extend_from_slice(row);
+        buffer.resize(row_start + stride as usize, 0);
+    }
+
+    Ok(buffer)
+}
+
+fn encode_header(buffer: &mut Vec<u8>, file_size: u32, data_offset: u32) {
+    buffer.extend_from_slice(b"BM");
+    buffer.extend_from_slice(&file_size.to_le_bytes());
+    buffer.extend_from_slice(&0u32.to_le_bytes());
+    buffer.extend_from_slice(&data_offset.to_le_bytes());
+}
+
+/// `colors_used` is the length of the palette table, 0 meaning every color the bit depth allows.
+fn encode_info_header(
+    buffer: &mut Vec<u8>,
+    width: u32,
+    height: u32,
+    bit_depth: u16,
+    image_size: u32,
+    colors_used: u32,
+) {
+    buffer.extend_from_slice(&(INFO_HEADER_SIZE as u32).to_le_bytes());
+    buffer.extend_from_slice(&width.to_le_bytes());
+    buffer.extend_from_slice(&height.to_le_bytes());
+    // planes
+    buffer.extend_from_slice(&1u16.to_le_bytes());
+    buffer.extend_from_slice(&bit_depth.to_le_bytes());
+    // compression
+    buffer.extend_from_slice(&0u32.to_le_bytes());
+    buffer.extend_from_slice(&image_size.to_le_bytes());
+    buffer.extend_from_slice(&PIXELS_PER_METER.to_le_bytes());
+    buffer.extend_from_slice(&PIXELS_PER_METER.to_le_bytes());
+    buffer.extend_from_slice(&colors_used.to_le_bytes());
+    // important colors, 0 means every color
+    buffer.extend_from_slice(&0u32.to_le_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoder::decode_bytes;
+
+    fn palette() -> Vec<Color> {
+        vec![
+            Color::new(0, 0, 0),
+            Color::new(0xFF, 0, 0),
+            Color::new(0, 0xFF, 0),
+            Color::new(0x12, 0x34, 0x56),
+        ]
+    }
+
+    #[test]
+    fn test_indexed_round_trip() {
+        // 5 bytes per row need 3 bytes of padding
+        let (width, height) = (5, 3);
+        let pixels = (0..width * height).map(|idx| (idx % 4) as u8).collect::<Vec<_>>();
+
+        let encoded = encode_indexed(width, height, &palette(), &pixels).unwrap();
+        assert_eq!(
+            encoded.len(),
+            HEADER_SIZE + INFO_HEADER_SIZE + 4 * 4 + 8 * height as usize
+        );
+
+        let bitmap = decode_bytes(&encoded).unwrap();
+        assert_eq!(bitmap.palette(), palette());
+        let expected = pixels.iter().map(|idx| palette()[*idx as usize]).collect::<Vec<_>>();
+        assert_eq!(bitmap.data(), expected);
+
+        // paletted bitmaps are encoded back as 8-bit ones
+        assert_eq!(bitmap.encode(), encoded);
+    }
+
+    #[test]
+    fn test_rgb_round_trip() {
+        // 9 bytes per row need 3 bytes of padding
+        let (width, height) = (3, 2);
+        let pixels = (0..width * height)
+            .map(|idx| Color::new(idx as u8 * 40, 0xFF - idx as u8, 0x80))
+            .collect::<Vec<_>>();
+
+        let encoded = encode_rgb(width, height, &pixels).unwrap();
+        assert_eq!(encoded.len(), HEADER_SIZE + INFO_HEADER_SIZE + 12 * height as usize);
+
+        let bitmap = decode_bytes(&encoded).unwrap();
+        assert!(bitmap.palette().is_empty());
+        assert_eq!(bitmap.data(), pixels);
+        assert_eq!(bitmap.encode(), encoded);
+    }
+
+    #[test]
+    fn test_indexed_rejects_invalid_pixels() {
+        assert!(encode_indexed(2, 2, &palette(), &[0, 1, 2]).is_err());
+        assert!(encode_indexed(2, 2, &palette(), &[0, 1, 2, 4]).is_err());
+        assert!(encode_indexed(1, 1, &vec![Color::new(0, 0, 0); MAX_PALETTE_SIZE + 1], &[0]).is_err());
+    }
+}
@@ -1,22 +1,103 @@
+use crate::Color;
+
 pub type Result<T> = std::result::Result<T, Error>;
 
 #[derive(Debug)]
 pub enum Error {
-    NotFound,
+    /// The image couldn't be read or written
+    Io(std::io::Error),
+    /// The bit depth on the info header is not one bitmaps are made with
     NonBitmap,
+    /// The file doesn't start with the `BM` signature
+    BadMagic,
+    /// The file ended before `expected` bytes could be read at `at_offset`, only `got` were left
+    Truncated {
+        expected: usize,
+        got: usize,
+        at_offset: usize,
+    },
+    /// A region or palette index doesn't fit within the image or its palette
     OutOfBounds,
+    /// The compression method found on the info header, or the bit depth it was used with, is not
+    /// supported
+    UnsupportedCompression(u32),
+    /// A pixel at (`x`, `y`) is too far from every palette color to be quantized
+    ColorOutOfTolerance { color: Color, x: u32, y: u32 },
+    /// The image can't be split into `width` by `height` tiles
+    TileSize {
+        image_width: u32,
+        image_height: u32,
+        width: u32,
+        height: u32,
+    },
+    /// The channel masks of a bitfields bitmap describe a packing other than RGB555 or RGB565
+    UnsupportedBitfields { red: u32, green: u32, blue: u32 },
+    /// A line of a sprite sheet description is malformed or declares an invalid region
+    InvalidSheet { line: usize, reason: String },
+    /// The file is a PNG image, but the `png` feature is disabled
+    PngUnsupported,
+    /// The file is a malformed PNG image
+    InvalidPng(String),
+    /// Error found while decoding the named file
+    File { name: String, source: Box<Error> },
 }
 
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{self:?}")
+        match self {
+            Error::Io(err) => write!(f, "{err}"),
+            Error::NonBitmap => write!(f, "not a bitmap, unsupported bit depth"),
+            Error::OutOfBounds => write!(f, "region or palette index out of bounds"),
+            Error::UnsupportedCompression(method) => write!(f, "unsupported bitmap compression method {method}"),
+            Error::File { name, source } => write!(f, "{name}: {source}"),
+            Error::ColorOutOfTolerance { color, x, y } => write!(
+                f,
+                "color {color} at ({x}, {y}) is farther than the tolerance from every palette color"
+            ),
+            Error::TileSize {
+                image_width,
+                image_height,
+                width,
+                height,
+            } => write!(
+                f,
+                "a {image_width}x{image_height} image can't be split into {width}x{height} tiles"
+            ),
+            Error::UnsupportedBitfields { red, green, blue } => write!(
+                f,
+                "unsupported bitfield masks red {red:#x}, green {green:#x}, blue {blue:#x}"
+            ),
+            Error::InvalidSheet { line, reason } => write!(f, "line {line}: {reason}"),
+            Error::PngUnsupported => write!(
+                f,
+                "PNG images can only be decoded with the `png` feature of aya-bitmap enabled"
+            ),
+            Error::InvalidPng(reason) => write!(f, "invalid PNG image: {reason}"),
+            Error::BadMagic => write!(f, "not a bitmap, the file doesn't start with 'BM'"),
+            Error::Truncated {
+                expected,
+                got,
+                at_offset,
+            } => write!(
+                f,
+                "truncated bitmap, expected {expected} bytes at offset {at_offset} but only {got} are left"
+            ),
+        }
     }
 }
 
-impl std::error::Error for Error {}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(err) => Some(err),
+            Error::File { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
 
 impl From<std::io::Error> for Error {
-    fn from(_: std::io::Error) -> Self {
-        Self::NotFound
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
     }
 }
@@ -1,24 +1,44 @@
 mod color;
 mod consts;
 pub mod decoder;
+pub mod encoder;
 mod error;
+#[cfg(feature = "png")]
+mod png;
+mod quantize;
+mod sheet;
+mod tile;
 
 pub use color::Color;
-pub use decoder::decode;
-use error::{Error, Result};
+pub use decoder::{decode, decode_bytes, decode_reader};
+pub use encoder::{encode_indexed, encode_rgb};
+pub use error::Error;
+use error::Result;
+pub use quantize::{quantize, Quantized};
+pub use sheet::{parse_sheet, Rect, Region};
+pub use tile::Tile;
 
 #[derive(Debug)]
 pub struct Bitmap {
-    file_name: String,
+    /// Set for bitmaps decoded from a file, or named through [`Bitmap::with_file_name`]
+    file_name: Option<String>,
     header: BitmapHeader,
     info_header: BitmapInfoHeader,
     palette: Vec<Color>,
     data: Vec<Color>,
+    /// Palette index of every pixel, kept for paletted bit depths
+    indices: Option<Vec<u8>>,
 }
 
 impl Bitmap {
-    pub fn file_name(&self) -> &str {
-        &self.file_name
+    pub fn file_name(&self) -> Option<&str> {
+        self.file_name.as_deref()
+    }
+
+    /// Names a bitmap, so bitmaps decoded from memory can still be identified on messages.
+    pub fn with_file_name(mut self, file_name: impl Into<String>) -> Self {
+        self.file_name = Some(file_name.into());
+        self
     }
 
     pub fn data(&self) -> &[Color] {
@@ -36,9 +56,71 @@ impl Bitmap {
     pub fn palette(&self) -> &[Color] {
         &self.palette
     }
+
+    /// Palette index of every pixel as stored on the file, in the same order as [`Bitmap::data`].
+    /// Only paletted bitmaps, with 1, 4 or 8 bits per pixel, have them.
+    pub fn indices(&self) -> Option<&[u8]> {
+        self.indices.as_deref()
+    }
+
+    /// Splits the image into `width` by `height` tiles, row-major from the top left corner. Fails
+    /// with [`Error::TileSize`] unless the image dimensions are multiples of the tile size.
+    pub fn tiles(&self, width: u32, height: u32) -> Result<impl Iterator<Item = Tile<'_>>> {
+        tile::tiles(self, width, height)
+    }
+
+    /// Copies the pixels within `rect` into a new bitmap sharing the palette and name of this one.
+    /// Headers keep describing the original file, except for the image dimensions. Fails with
+    /// [`Error::OutOfBounds`] if `rect` doesn't fit within the image.
+    pub fn crop(&self, rect: Rect) -> Result<Bitmap> {
+        let width = self.info_header.width;
+        if !rect.fits(width, self.info_header.height) {
+            return Err(Error::OutOfBounds);
+        }
+
+        let pixels = (rect.y..rect.y + rect.height).flat_map(|y| {
+            let start = (y * width + rect.x) as usize;
+            start..start + rect.width as usize
+        });
+        let data = pixels.clone().map(|idx| self.data[idx]).collect();
+        let indices = self
+            .indices
+            .as_ref()
+            .map(|indices| pixels.map(|idx| indices[idx]).collect());
+
+        Ok(Bitmap {
+            file_name: self.file_name.clone(),
+            header: self.header.clone(),
+            info_header: BitmapInfoHeader {
+                width: rect.width,
+                height: rect.height,
+                ..self.info_header.clone()
+            },
+            palette: self.palette.clone(),
+            data,
+            indices,
+        })
+    }
+
+    /// Encodes the bitmap into the contents of a bitmap file. Paletted bitmaps are written as 8-bit
+    /// ones keeping their palette indices, and everything else as 24-bit.
+    pub fn encode(&self) -> Vec<u8> {
+        let BitmapInfoHeader { width, height, .. } = self.info_header;
+
+        // palettes past 256 colors, which only malformed files declare, can't be encoded as 8-bit
+        let indexed = self
+            .indices
+            .as_ref()
+            .map(|indices| encoder::encode_indexed(width, height, &self.palette, indices));
+        if let Some(Ok(bitmap)) = indexed {
+            return bitmap;
+        }
+
+        encoder::encode_rgb(width, height, &self.data).expect("bitmap data always matches its dimensions")
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct BitmapHeader {
     file_size: u32,
     data_offset: u32,
@@ -54,11 +136,14 @@ impl BitmapHeader {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct BitmapInfoHeader {
     width: u32,
     height: u32,
+    /// Rows are stored from the top of the image down, which bitmaps signal with a negative height
+    top_down: bool,
     bit_depth: BitDepth,
+    compression: Compression,
     num_colors: u32,
     image_size: u32,
     important_colors: u32,
@@ -69,10 +154,19 @@ impl BitmapInfoHeader {
         self.width
     }
 
+    /// Absolute height of the image, even for top-down bitmaps.
     pub fn height(&self) -> u32 {
         self.height
     }
 
+    pub fn is_top_down(&self) -> bool {
+        self.top_down
+    }
+
+    pub fn compression(&self) -> Compression {
+        self.compression
+    }
+
     pub fn image_size(&self) -> u32 {
         self.image_size
     }
@@ -90,12 +184,62 @@ enum BitDepth {
     Bit8,
     Bit16,
     Bit24,
+    Bit32,
 }
 
 impl BitDepth {
     pub(crate) fn has_palette(&self) -> bool {
         matches!(self, BitDepth::MonoChrome | BitDepth::Bit4 | BitDepth::Bit8)
     }
+
+    pub(crate) fn bits(&self) -> usize {
+        match self {
+            BitDepth::MonoChrome => 1,
+            BitDepth::Bit4 => 4,
+            BitDepth::Bit8 => 8,
+            BitDepth::Bit16 => 16,
+            BitDepth::Bit24 => 24,
+            BitDepth::Bit32 => 32,
+        }
+    }
+}
+
+/// How the pixel data of a bitmap is stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    /// Uncompressed rows
+    Rgb,
+    /// Run length encoded 8-bit palette indices
+    Rle8,
+    /// Run length encoded 4-bit palette indices
+    Rle4,
+    /// Uncompressed pixels whose channels are described by masks following the info header
+    Bitfields,
+}
+
+impl TryFrom<u32> for Compression {
+    type Error = Error;
+
+    fn try_from(method: u32) -> Result<Self> {
+        match method {
+            0 => Ok(Self::Rgb),
+            1 => Ok(Self::Rle8),
+            2 => Ok(Self::Rle4),
+            3 => Ok(Self::Bitfields),
+            _ => Err(Error::UnsupportedCompression(method)),
+        }
+    }
+}
+
+impl From<Compression> for u32 {
+    fn from(compression: Compression) -> Self {
+        match compression {
+            Compression::Rgb => 0,
+            Compression::Rle8 => 1,
+            Compression::Rle4 => 2,
+            Compression::Bitfields => 3,
+        }
+    }
 }
 
 impl TryFrom<u16> for BitDepth {
@@ -108,6 +252,7 @@ impl TryFrom<u16> for BitDepth {
             8 => Ok(Self::Bit8),
             16 => Ok(Self::Bit16),
             24 => Ok(Self::Bit24),
+            32 => Ok(Self::Bit32),
             _ => Err(Error::NonBitmap),
         }
     }
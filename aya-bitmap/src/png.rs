@@ -0,0 +1,139 @@
+use crate::decoder::packed_index;
+use crate::error::{Error, Result};
+use crate::{BitDepth, Bitmap, BitmapHeader, BitmapInfoHeader, Color, Compression};
+
+/// Decodes a PNG image into a bitmap. Indexed images keep their palette and indices, every other
+/// color type is expanded into colors, and transparency is dropped as colors are always opaque.
+pub(crate) fn decode_png(buffer: &[u8]) -> Result<Bitmap> {
+    let mut decoder = ::png::Decoder::new(buffer);
+    decoder.set_transformations(::png::Transformations::STRIP_16);
+    let mut reader = decoder.read_info().map_err(invalid)?;
+
+    let palette = reader
+        .info()
+        .palette
+        .as_ref()
+        .map(|palette| {
+            let (entries, _) = palette.as_chunks::<3>();
+            entries.iter().map(|&[r, g, b]| Color::new(r, g, b)).collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    let frame = reader.next_frame(&mut pixels).map_err(invalid)?;
+    let bits = frame.bit_depth as usize;
+    let rows = pixels.chunks(frame.line_size).take(frame.height as usize);
+    let cols = 0..frame.width as usize;
+
+    let (bit_depth, indices, data) = match frame.color_type {
+        ::png::ColorType::Indexed => {
+            let indices = rows
+                .flat_map(|row| cols.clone().map(move |col| packed_index(row, col, bits)))
+                .collect::<Vec<_>>();
+            let data = indices
+                .iter()
+                .map(|index| palette.get(*index as usize).copied().ok_or(Error::OutOfBounds))
+                .collect::<Result<Vec<_>>>()?;
+            // two bit images have no bitmap equivalent, but their palette fits on a 4-bit one
+            let bit_depth = match bits {
+                1 => BitDepth::MonoChrome,
+                2 | 4 => BitDepth::Bit4,
+                _ => BitDepth::Bit8,
+            };
+            (bit_depth, Some(indices), data)
+        }
+        ::png::ColorType::Grayscale | ::png::ColorType::GrayscaleAlpha => {
+            let channels = frame.color_type.samples();
+            let max = u8::MAX >> (8 - bits);
+            let data = rows
+                .flat_map(|row| {
+                    cols.clone().map(move |col| {
+                        let gray = (packed_index(row, col * channels, bits) as u16 * 255 / max as u16) as u8;
+                        Color::new(gray, gray, gray)
+                    })
+                })
+                .collect();
+            (BitDepth::Bit24, None, data)
+        }
+        ::png::ColorType::Rgb | ::png::ColorType::Rgba => {
+            let channels = frame.color_type.samples();
+            let data = rows
+                .flat_map(|row| {
+                    cols.clone().map(move |col| {
+                        let pixel = &row[col * channels..];
+                        Color::new(pixel[0], pixel[1], pixel[2])
+                    })
+                })
+                .collect();
+            (BitDepth::Bit24, None, data)
+        }
+    };
+
+    Ok(Bitmap {
+        file_name: None,
+        header: BitmapHeader {
+            file_size: buffer.len() as u32,
+            data_offset: 0,
+        },
+        info_header: BitmapInfoHeader {
+            width: frame.width,
+            height: frame.height,
+            top_down: true,
+            bit_depth,
+            compression: Compression::Rgb,
+            num_colors: palette.len() as u32,
+            image_size: 0,
+            important_colors: 0,
+        },
+        palette,
+        data,
+        indices,
+    })
+}
+
+fn invalid(err: ::png::DecodingError) -> Error {
+    Error::InvalidPng(err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Both fixtures hold the same 3x2 image as the bitmap ones
+    fn fixture_pixels() -> Vec<Color> {
+        vec![
+            Color::new(0xFF, 0, 0),
+            Color::new(0, 0xFF, 0),
+            Color::new(0, 0, 0xFF),
+            Color::new(0xFF, 0xFF, 0xFF),
+            Color::new(0, 0, 0),
+            Color::new(0x12, 0x34, 0x56),
+        ]
+    }
+
+    #[test]
+    fn test_decode_indexed_png() {
+        let bitmap = crate::decode_bytes(include_bytes!("../fixtures/indexed.png")).unwrap();
+        assert_eq!(bitmap.palette(), fixture_pixels());
+        assert_eq!(bitmap.indices(), Some(&[0, 1, 2, 3, 4, 5][..]));
+        assert_eq!(bitmap.data(), fixture_pixels());
+    }
+
+    #[test]
+    fn test_decode_rgba_png() {
+        let bitmap = crate::decode_bytes(include_bytes!("../fixtures/rgba.png")).unwrap();
+        assert_eq!((bitmap.info_header().width(), bitmap.info_header().height()), (3, 2));
+        assert!(bitmap.palette().is_empty());
+        assert_eq!(bitmap.indices(), None);
+        assert_eq!(bitmap.data(), fixture_pixels());
+    }
+
+    #[test]
+    fn test_truncated_png() {
+        let file = include_bytes!("../fixtures/rgba.png");
+        // the trailing end chunk, 12 bytes long, isn't needed to read the image
+        for len in 8..file.len() - 12 {
+            assert!(matches!(crate::decode_bytes(&file[..len]), Err(Error::InvalidPng(_))));
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use crate::error::{Error, Result};
+use crate::{Bitmap, Color};
+
+/// Palette indices of a quantized bitmap, one per pixel, in the same order as [`Bitmap::data`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Quantized {
+    pub indices: Vec<u8>,
+    /// Pixels whose color wasn't on the palette and were replaced by the nearest palette entry
+    pub approximated: usize,
+}
+
+/// Maps every pixel of `bitmap` to the index of the nearest color on `palette`, measured as the
+/// euclidean distance between their RGB components. Pixels farther than `tolerance` from every
+/// palette entry fail with [`Error::ColorOutOfTolerance`], so a tolerance of 0 only accepts exact
+/// palette colors.
+pub fn quantize(bitmap: &Bitmap, palette: &[Color], tolerance: u32) -> Result<Quantized> {
+    if palette.is_empty() || palette.len() > u8::MAX as usize + 1 {
+        return Err(Error::OutOfBounds);
+    }
+
+    let width = bitmap.info_header().width();
+    let tolerance = tolerance.saturating_mul(tolerance);
+    let mut indices = Vec::with_capacity(bitmap.data().len());
+    let mut approximated = 0;
+
+    for (idx, color) in bitmap.data().iter().enumerate() {
+        let (index, distance) = palette
+            .iter()
+            .map(|entry| distance_squared(*color, *entry))
+            .enumerate()
+            .min_by_key(|&(_, distance)| distance)
+            .expect("palette is never empty");
+
+        if distance > tolerance {
+            return Err(Error::ColorOutOfTolerance {
+                color: *color,
+                x: idx as u32 % width,
+                y: idx as u32 / width,
+            });
+        }
+
+        if distance != 0 {
+            approximated += 1;
+        }
+        indices.push(index as u8);
+    }
+
+    Ok(Quantized { indices, approximated })
+}
+
+fn distance_squared(a: Color, b: Color) -> u32 {
+    let a: [u8; 3] = a.into();
+    let b: [u8; 3] = b.into();
+    a.into_iter().zip(b).map(|(a, b)| (a.abs_diff(b) as u32).pow(2)).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn palette() -> Vec<Color> {
+        vec![Color::new(0, 0, 0), Color::new(0xFF, 0, 0), Color::new(0, 0, 0xFF)]
+    }
+
+    #[test]
+    fn test_quantize() {
+        let pixels = [
+            Color::new(0xFF, 0, 0),
+            Color::new(0xF0, 0x08, 0x08),
+            Color::new(0, 0, 0),
+            Color::new(0x04, 0x02, 0xFA),
+        ];
+        let bitmap = crate::decode_bytes(&crate::encode_rgb(2, 2, &pixels).unwrap()).unwrap();
+
+        let quantized = quantize(&bitmap, &palette(), 24).unwrap();
+        assert_eq!(quantized.indices, [1, 1, 0, 2]);
+        assert_eq!(quantized.approximated, 2);
+
+        let err = quantize(&bitmap, &palette(), 0).unwrap_err();
+        assert!(matches!(err, Error::ColorOutOfTolerance { x: 1, y: 0, .. }));
+        assert_eq!(
+            err.to_string(),
+            "color rgb(240, 8, 8) at (1, 0) is farther than the tolerance from every palette color"
+        );
+    }
+}
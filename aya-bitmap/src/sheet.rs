@@ -0,0 +1,179 @@
+use crate::error::{Error, Result};
+use crate::Bitmap;
+
+/// A rectangle of pixels on a bitmap, `x` and `y` are its top left corner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Rect {
+    pub fn new(x: u32, y: u32, width: u32, height: u32) -> Self {
+        Self { x, y, width, height }
+    }
+
+    /// Whether the rectangle lies entirely within a `width` by `height` image.
+    pub fn fits(&self, width: u32, height: u32) -> bool {
+        self.x.checked_add(self.width).is_some_and(|right| right <= width)
+            && self.y.checked_add(self.height).is_some_and(|bottom| bottom <= height)
+    }
+
+    pub fn overlaps(&self, other: &Rect) -> bool {
+        let spans = |start: u32, len: u32, other_start: u32, other_len: u32| {
+            (start as u64) < other_start as u64 + other_len as u64 && (other_start as u64) < start as u64 + len as u64
+        };
+        spans(self.x, self.width, other.x, other.width) && spans(self.y, self.height, other.y, other.height)
+    }
+}
+
+/// A named region of a sprite sheet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Region {
+    pub name: String,
+    pub rect: Rect,
+    /// Line of the sheet description declaring the region, starting at 1
+    pub line: usize,
+}
+
+/// Parses the description of a sprite sheet, a text file listing one region per line as
+/// `name x y width height`. Blank lines and lines starting with `#` are ignored. Every region must
+/// have a unique name, fit within `bitmap` and not overlap any other region, failing with
+/// [`Error::InvalidSheet`] pointing at the offending line otherwise.
+pub fn parse_sheet(source: &str, bitmap: &Bitmap) -> Result<Vec<Region>> {
+    let width = bitmap.info_header().width();
+    let height = bitmap.info_header().height();
+    let mut regions: Vec<Region> = vec![];
+
+    for (idx, text) in source.lines().enumerate() {
+        let line = idx + 1;
+        let text = text.trim();
+        if text.is_empty() || text.starts_with('#') {
+            continue;
+        }
+
+        let invalid = |reason: String| Error::InvalidSheet { line, reason };
+
+        let fields = text.split_whitespace().collect::<Vec<_>>();
+        let [name, x, y, w, h] = fields[..] else {
+            return Err(invalid(format!(
+                "expected `name x y width height`, found {} fields",
+                fields.len()
+            )));
+        };
+
+        let number = |field: &str| {
+            field
+                .parse::<u32>()
+                .map_err(|_| invalid(format!("'{field}' is not a valid number")))
+        };
+        let rect = Rect::new(number(x)?, number(y)?, number(w)?, number(h)?);
+
+        if rect.width == 0 || rect.height == 0 {
+            return Err(invalid(format!("region '{name}' is empty")));
+        }
+        if !rect.fits(width, height) {
+            return Err(invalid(format!(
+                "region '{name}' doesn't fit within the {width}x{height} image"
+            )));
+        }
+        if let Some(other) = regions.iter().find(|region| region.name == name) {
+            return Err(invalid(format!(
+                "region '{name}' was already declared on line {}",
+                other.line
+            )));
+        }
+        if let Some(other) = regions.iter().find(|region| region.rect.overlaps(&rect)) {
+            return Err(invalid(format!(
+                "region '{name}' overlaps region '{}' from line {}",
+                other.name, other.line
+            )));
+        }
+
+        regions.push(Region {
+            name: name.to_string(),
+            rect,
+            line,
+        });
+    }
+
+    Ok(regions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sheet() -> Bitmap {
+        crate::decode_bytes(include_bytes!("../fixtures/tiles16.bmp")).unwrap()
+    }
+
+    #[test]
+    fn test_parse_sheet() {
+        let source = "# player frames\nplayer_idle 0 0 8 16\n\nplayer_run 8 0 8 8\n  coin 8 8 8 8  \n";
+        let regions = parse_sheet(source, &sheet()).unwrap();
+
+        assert_eq!(
+            regions,
+            [
+                Region {
+                    name: "player_idle".into(),
+                    rect: Rect::new(0, 0, 8, 16),
+                    line: 2
+                },
+                Region {
+                    name: "player_run".into(),
+                    rect: Rect::new(8, 0, 8, 8),
+                    line: 4
+                },
+                Region {
+                    name: "coin".into(),
+                    rect: Rect::new(8, 8, 8, 8),
+                    line: 5
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_sheets() {
+        let error = |source: &str| parse_sheet(source, &sheet()).unwrap_err().to_string();
+
+        assert_eq!(
+            error("a 0 0 8 8\nb 4 4 8 8"),
+            "line 2: region 'b' overlaps region 'a' from line 1"
+        );
+        assert_eq!(
+            error("a 0 0 8 8\n\nb 12 0 8 8"),
+            "line 3: region 'b' doesn't fit within the 16x16 image"
+        );
+        assert_eq!(
+            error("a 0 0 8 8\na 8 0 8 8"),
+            "line 2: region 'a' was already declared on line 1"
+        );
+        assert_eq!(
+            error("a 0 0 8"),
+            "line 1: expected `name x y width height`, found 4 fields"
+        );
+        assert_eq!(error("a 0 x 8 8"), "line 1: 'x' is not a valid number");
+        assert_eq!(error("a 0 0 0 8"), "line 1: region 'a' is empty");
+    }
+
+    #[test]
+    fn test_crop() {
+        let sheet = sheet().with_file_name("sheet.bmp");
+        let cropped = sheet.crop(Rect::new(8, 8, 8, 4)).unwrap();
+
+        assert_eq!((cropped.info_header().width(), cropped.info_header().height()), (8, 4));
+        assert_eq!(cropped.file_name(), Some("sheet.bmp"));
+        // the bottom right tile of the fixture holds 0xC0 on red
+        let expected = (0..4)
+            .flat_map(|y| (0..8).map(move |x| crate::Color::new(0xC0, x * 0x20, y * 0x20)))
+            .collect::<Vec<_>>();
+        assert_eq!(cropped.data(), expected);
+
+        assert!(matches!(sheet.crop(Rect::new(12, 0, 8, 8)), Err(Error::OutOfBounds)));
+    }
+}
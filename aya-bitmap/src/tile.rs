@@ -0,0 +1,128 @@
+use crate::error::{Error, Result};
+use crate::{Bitmap, Color};
+
+/// A `width` by `height` block of a bitmap, borrowing its pixels.
+#[derive(Debug, Clone, Copy)]
+pub struct Tile<'a> {
+    data: &'a [Color],
+    image_width: u32,
+    col: u32,
+    row: u32,
+    width: u32,
+    height: u32,
+}
+
+impl<'a> Tile<'a> {
+    /// Column of the tile, counted in tiles from the left of the image.
+    pub fn col(&self) -> u32 {
+        self.col
+    }
+
+    /// Row of the tile, counted in tiles from the top of the image.
+    pub fn row(&self) -> u32 {
+        self.row
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Positions on [`Bitmap::data`] of every pixel of the tile, row-major, useful to index buffers
+    /// laid out like the bitmap data, such as quantized palette indices.
+    pub fn indices(&self) -> impl Iterator<Item = usize> + 'a {
+        let Tile {
+            image_width,
+            col,
+            row,
+            width,
+            height,
+            ..
+        } = *self;
+
+        (0..height).flat_map(move |y| {
+            let start = ((row * height + y) * image_width + col * width) as usize;
+            start..start + width as usize
+        })
+    }
+
+    /// Pixels of the tile, row-major.
+    pub fn pixels(&self) -> impl Iterator<Item = &'a Color> + 'a {
+        let data = self.data;
+        self.indices().map(move |idx| &data[idx])
+    }
+}
+
+pub(crate) fn tiles(bitmap: &Bitmap, width: u32, height: u32) -> Result<impl Iterator<Item = Tile<'_>>> {
+    let image_width = bitmap.info_header().width();
+    let image_height = bitmap.info_header().height();
+
+    if width == 0 || height == 0 || !image_width.is_multiple_of(width) || !image_height.is_multiple_of(height) {
+        return Err(Error::TileSize {
+            image_width,
+            image_height,
+            width,
+            height,
+        });
+    }
+
+    let cols = image_width / width;
+    let rows = image_height / height;
+    Ok((0..rows).flat_map(move |row| {
+        (0..cols).map(move |col| Tile {
+            data: bitmap.data(),
+            image_width,
+            col,
+            row,
+            width,
+            height,
+        })
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tiles() {
+        let bitmap = crate::decode_bytes(include_bytes!("../fixtures/tiles16.bmp")).unwrap();
+
+        let tiles = bitmap.tiles(8, 8).unwrap().collect::<Vec<_>>();
+        let positions = tiles.iter().map(|tile| (tile.col(), tile.row())).collect::<Vec<_>>();
+        assert_eq!(positions, [(0, 0), (1, 0), (0, 1), (1, 1)]);
+
+        // every pixel of the fixture holds the tile number on red and its position in the tile on
+        // green and blue
+        for (number, tile) in tiles.iter().enumerate() {
+            let pixels = tile.pixels().copied().collect::<Vec<_>>();
+            let expected = (0..8)
+                .flat_map(|y| (0..8).map(move |x| Color::new(number as u8 * 0x40, x * 0x20, y * 0x20)))
+                .collect::<Vec<_>>();
+            assert_eq!(pixels, expected);
+        }
+
+        assert_eq!(tiles[1].indices().take(3).collect::<Vec<_>>(), [8, 9, 10]);
+        assert_eq!(tiles[2].indices().next(), Some(128));
+    }
+
+    #[test]
+    fn test_tiles_must_divide_the_image() {
+        let bitmap = crate::decode_bytes(include_bytes!("../fixtures/tiles16.bmp")).unwrap();
+
+        assert!(bitmap.tiles(16, 4).is_ok());
+        assert!(matches!(
+            bitmap.tiles(6, 8),
+            Err(Error::TileSize {
+                image_width: 16,
+                image_height: 16,
+                width: 6,
+                height: 8
+            })
+        ));
+        assert!(bitmap.tiles(0, 8).is_err());
+    }
+}
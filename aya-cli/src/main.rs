@@ -1,18 +1,57 @@
-mod config;
-mod rom;
-
-use std::path::PathBuf;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
 use std::process::ExitCode;
 
+use aya_assembly::symbols::SymbolMap;
 use aya_assembly::{AssembleBehavior, AssembleOutput};
-use clap::Parser;
-use config::Config;
+use aya_console::debugger::{Debugger, Flow};
+use aya_console::Backend;
+use aya_packer::Config;
+use clap::{Parser, Subcommand};
 
 static CONFIG_FILE: &str = "aya.cfg";
 
 #[derive(Parser)]
-#[command(version, about, long_about = None)]
-pub struct Args {
+#[command(name = "aya", version, about, long_about = None)]
+pub struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// packs the rom described by a config, or by the flags given instead
+    Build(Box<BuildArgs>),
+    /// runs a packed rom
+    Run {
+        rom: PathBuf,
+
+        #[command(flatten)]
+        console: ConsoleArgs,
+    },
+    /// assembles a single program into bytecode
+    Asm {
+        file: PathBuf,
+
+        /// where the bytecode is written, defaults to the program path with a `.bin` extension
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// prints the code of the rom described by a config with every import and expression expanded
+    Expand {
+        #[arg(long, default_value = CONFIG_FILE)]
+        config: String,
+
+        /// writes the expanded code to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// debugs a packed rom from the terminal, or runs the debugger commands of a file
+    Dbg(DbgArgs),
+}
+
+#[derive(clap::Args)]
+pub struct BuildArgs {
     #[arg(short, required = false, long, requires = "sprites", requires = "name")]
     code: Option<String>,
 
@@ -22,72 +61,419 @@ pub struct Args {
     #[arg(short, required = false, long, requires = "code", requires = "sprites")]
     name: Option<String>,
 
+    /// code banks, each assembled on its own to run from the bank window
+    #[arg(long, required = false, requires = "code")]
+    banks: Option<Vec<String>>,
+
+    /// raw files loaded at the end of code memory, such as level maps
+    #[arg(long, required = false, requires = "code")]
+    data: Option<Vec<String>>,
+
+    /// author written on the rom header
+    #[arg(long, required = false)]
+    author: Option<String>,
+
+    /// description written on the rom header
+    #[arg(long, required = false)]
+    description: Option<String>,
+
     #[arg(short, required = false, long)]
     output: Option<String>,
 
+    /// also writes the expanded code of the rom, to `<output>.expanded.aya` unless `--expand-output` is given
     #[arg(short, required = false, long)]
     expand: Option<bool>,
 
+    /// where the expanded code is written
+    #[arg(long, required = false)]
+    expand_output: Option<String>,
+
     #[arg(long, required = false)]
     config: Option<String>,
 
+    /// runs the rom once it is packed
     #[arg(long, short, action = clap::ArgAction::SetTrue)]
     run: bool,
+
+    #[command(flatten)]
+    console: ConsoleArgs,
+
+    /// packs identical sprite tiles only once
+    #[arg(long, action = clap::ArgAction::SetTrue)]
+    dedup: bool,
+
+    /// writes the symbols of the rom next to it, as `<output>.map` and `<output>.map.json`
+    #[arg(long, action = clap::ArgAction::SetTrue)]
+    map: bool,
+
+    /// compresses the sprites of the rom, which the console expands when loading it
+    #[arg(long, action = clap::ArgAction::SetTrue)]
+    compress: bool,
+
+    /// prints how full every rom section is and the named sprites no code refers to
+    #[arg(long, action = clap::ArgAction::SetTrue)]
+    report: bool,
+
+    /// fails on sprite colors that are not exactly on the palette instead of using the nearest one
+    #[arg(long, action = clap::ArgAction::SetTrue)]
+    strict: bool,
 }
 
-fn main() -> std::result::Result<ExitCode, Box<dyn std::error::Error>> {
-    let args = Args::parse();
-    let run = args.run;
+#[derive(clap::Args)]
+pub struct DbgArgs {
+    rom: PathBuf,
 
-    let config = match args.code.is_some() {
-        true => Config::from_args(args),
-        false => config::read_from_file(args.config.unwrap_or(CONFIG_FILE.into()))
-            .expect("unable to read config file. Please certify that a aya.cfg file exists in the current directory"),
-    };
+    /// symbols of the rom, defaults to the `<rom>.map` file `aya build --map` writes
+    #[arg(long, required = false)]
+    symbols: Option<PathBuf>,
+
+    /// runs the commands of this file and exits with the code the program halted with, or 1 when
+    /// a command fails
+    #[arg(long, required = false)]
+    exec: Option<PathBuf>,
+
+    /// loads the rom even if its contents don't match the checksum on its header
+    #[arg(long, action = clap::ArgAction::SetTrue)]
+    skip_checksum: bool,
+
+    /// keeps code memory writable while the rom runs, for self modifying programs
+    #[arg(long, action = clap::ArgAction::SetTrue)]
+    writable_code: bool,
+}
+
+/// Flags of every command that runs a rom.
+#[derive(clap::Args)]
+pub struct ConsoleArgs {
+    /// renderer used when running the rom, either `raylib` or `soft` depending on enabled features
+    #[arg(long, required = false, default_value_t)]
+    renderer: Backend,
+
+    /// initial window size as a multiple of the console screen size
+    #[arg(long, required = false, default_value_t = aya_console::DEFAULT_SCALE, value_parser = clap::value_parser!(u16).range(1..))]
+    scale: u16,
+
+    /// runs the rom even if its contents don't match the checksum on its header
+    #[arg(long, action = clap::ArgAction::SetTrue)]
+    skip_checksum: bool,
+
+    /// keeps code memory writable while the rom runs, for self modifying programs
+    #[arg(long, action = clap::ArgAction::SetTrue)]
+    writable_code: bool,
+
+    /// writes the output of the serial debug port to this file instead of stdout
+    #[arg(long, required = false)]
+    serial_log: Option<PathBuf>,
+
+    /// only draws the first sprites touching every line, like older consoles did
+    #[arg(long, action = clap::ArgAction::SetTrue)]
+    sprite_limit: bool,
+
+    /// writes a line for every instruction the cpu runs to this file, to diff against other runs
+    #[arg(long, required = false)]
+    trace: Option<PathBuf>,
 
-    let path = PathBuf::from(&config.code);
+    /// loads the rom again whenever its file changes, without restarting the console
+    #[arg(long, action = clap::ArgAction::SetTrue)]
+    hot_reload: bool,
 
-    let behavior = if config.expand { AssembleBehavior::Codegen } else { AssembleBehavior::Bytecode };
+    /// carries on from where the program was when hot reloading instead of starting it over
+    #[arg(long, action = clap::ArgAction::SetTrue, requires = "hot_reload")]
+    keep_ip: bool,
 
-    let output = aya_assembly::assemble(&path, behavior)?;
+    /// keeps the backgrounds, sprites and other memory the program drew on when hot reloading
+    #[arg(long, action = clap::ArgAction::SetTrue, requires = "hot_reload")]
+    keep_ram: bool,
+
+    /// runs this many frames without a window, printing a hash of every frame for golden tests
+    #[arg(long, required = false, value_name = "FRAMES")]
+    frame_hash: Option<usize>,
+}
+
+impl ConsoleArgs {
+    fn options(&self) -> aya_console::Options {
+        aya_console::Options {
+            backend: self.renderer,
+            scale: self.scale,
+            skip_checksum: self.skip_checksum,
+            writable_code: self.writable_code,
+            serial_log: self.serial_log.clone(),
+            sprite_line_limit: self.sprite_limit,
+            trace: self.trace.clone(),
+            hot_reload: self.hot_reload,
+            keep_ip: self.keep_ip,
+            keep_ram: self.keep_ram,
+            frame_hash: self.frame_hash,
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    match Cli::parse().command {
+        Command::Build(args) => build(*args),
+        Command::Run { rom, console } => run(rom, &console.options()),
+        Command::Asm { file, output } => asm(&file, output.unwrap_or_else(|| file.with_extension("bin"))),
+        Command::Expand { config, output } => expand(config, output),
+        Command::Dbg(args) => dbg(args),
+    }
+}
+
+fn build(args: BuildArgs) -> ExitCode {
+    let (run_rom, report, options) = (args.run, args.report, args.console.options());
+    let (strict, dedup, map, compress) = (args.strict, args.dedup, args.map, args.compress);
+    let (expand, expand_output) = (args.expand.unwrap_or(false), args.expand_output.clone());
+    let mut config = match args.code.is_some() {
+        true => config_from_args(args),
+        false => match read_config(args.config.unwrap_or(CONFIG_FILE.into())) {
+            Ok(config) => config,
+            Err(code) => return code,
+        },
+    };
+    config.strict |= strict;
+    config.dedup |= dedup;
+    config.map |= map;
+    config.compress |= compress;
+    config.expand |= expand;
+    if let Some(expand_output) = expand_output {
+        config.expand_output = Some(expand_output);
+    }
 
     if config.expand {
-        let AssembleOutput::Codegen(code) = output else {
-            unreachable!();
+        let code = match aya_packer::expand(&config) {
+            Ok(code) => code,
+            Err(err) => return report_error(err),
         };
-        std::fs::write(config.output, code).expect("failed to write expanded code into specified output");
-        return Ok(ExitCode::FAILURE);
+        if let Err(code) = write_file(config.expand_path(), code) {
+            return code;
+        }
+    }
+
+    let packed = match aya_packer::pack(&config) {
+        Ok(packed) => packed,
+        Err(err) => return report_error(err),
+    };
+    for warning in &packed.warnings {
+        eprintln!("{warning:?}");
+    }
+    if packed.approximated > 0 {
+        eprintln!(
+            "warning: {} sprite pixels were approximated to the nearest palette color",
+            packed.approximated
+        );
+    }
+    if packed.saved_tiles > 0 {
+        eprintln!("note: {} duplicated tiles were packed only once", packed.saved_tiles);
+    }
+    if report {
+        println!("{}", packed.report);
+    }
+
+    if let Err(code) = write_file(config.output_path(), packed.rom) {
+        return code;
+    }
+    if config.map {
+        let output = config.output_path().into_os_string();
+        let path = |extension: &str| {
+            let mut path = output.clone();
+            path.push(extension);
+            path
+        };
+        let written = write_file(path(".map"), packed.symbols.to_text())
+            .and_then(|_| write_file(path(".map.json"), packed.symbols.to_json()));
+        if let Err(code) = written {
+            return code;
+        }
+    }
+
+    match run_rom {
+        true => run(config.output_path(), &options),
+        false => ExitCode::SUCCESS,
+    }
+}
+
+fn run(rom: impl AsRef<Path>, options: &aya_console::Options) -> ExitCode {
+    match aya_console::run_with_options(rom, options) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn asm(file: &Path, output: PathBuf) -> ExitCode {
+    let code = match std::fs::read_to_string(file) {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("error: failed to read {}: {err}", file.display());
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if let Ok(warnings) = aya_assembly::warnings(code.clone(), file) {
+        for warning in warnings {
+            eprintln!("{warning:?}");
+        }
     }
 
-    let AssembleOutput::Bytecode(code) = output else {
-        unreachable!();
+    match aya_assembly::assemble_code(code, AssembleBehavior::Bytecode, file) {
+        Ok(AssembleOutput::Bytecode(bytecode)) => match write_file(output, bytecode) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(code) => code,
+        },
+        Ok(AssembleOutput::Codegen(_)) => unreachable!(),
+        Err(report) => {
+            // the debug representation renders the diagnostic along with the offending source
+            eprintln!("{report:?}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn expand(config: String, output: Option<PathBuf>) -> ExitCode {
+    let config = match read_config(config) {
+        Ok(config) => config,
+        Err(code) => return code,
+    };
+    let code = match aya_packer::expand(&config) {
+        Ok(code) => code,
+        Err(err) => return report_error(err),
     };
 
-    let mut sprites = vec![];
-    let sprite_paths = config.sprites.iter().map(PathBuf::from).collect::<Vec<_>>();
-    for path in sprite_paths {
-        sprites.push(aya_bitmap::decode(path)?);
+    match output {
+        Some(output) => match write_file(output, code) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(code) => code,
+        },
+        None => {
+            print!("{code}");
+            ExitCode::SUCCESS
+        }
     }
+}
 
-    let sprites = match rom::compile_sprites(sprites) {
-        Ok(sprites) => sprites,
-        Err(rom::Error::SpriteTooBig(msg)) => {
-            eprintln!("{msg}");
-            return Ok(ExitCode::FAILURE);
+fn dbg(args: DbgArgs) -> ExitCode {
+    let options = aya_console::EmulatorOptions {
+        skip_checksum: args.skip_checksum,
+        writable_code: args.writable_code,
+        ..Default::default()
+    };
+    let mut debugger = match Debugger::open(&args.rom, &options) {
+        Ok(debugger) => debugger,
+        Err(err) => {
+            eprintln!("error: {err}");
+            return ExitCode::FAILURE;
         }
-        Err(rom::Error::UnknownColor(msg)) => {
-            eprintln!("{msg}");
-            return Ok(ExitCode::FAILURE);
+    };
+    if let Some(path) = &args.symbols {
+        let symbols = std::fs::read_to_string(path)
+            .map_err(|err| err.to_string())
+            .and_then(|symbols| SymbolMap::parse(&symbols).map_err(|err| err.to_string()));
+        match symbols {
+            Ok(symbols) => debugger.set_symbols(symbols),
+            Err(err) => {
+                eprintln!("error: failed to read symbols {}: {err}", path.display());
+                return ExitCode::FAILURE;
+            }
         }
+    }
+
+    let interactive = args.exec.is_none();
+    let commands: Box<dyn BufRead> = match &args.exec {
+        Some(path) => match std::fs::File::open(path) {
+            Ok(file) => Box::new(BufReader::new(file)),
+            Err(err) => {
+                eprintln!("error: failed to read {}: {err}", path.display());
+                return ExitCode::FAILURE;
+            }
+        },
+        None => Box::new(std::io::stdin().lock()),
     };
-    let header = rom::make_header(&config, code.len() as u16, sprites.len() as u16);
-    let rom = rom::compile(&header, &code, &sprites);
 
-    std::fs::write(&config.output, rom).expect("failed to write rom into specified output");
+    let mut stdout = std::io::stdout().lock();
+    let mut lines = commands.lines().enumerate();
+    loop {
+        if interactive {
+            // the prompt is only cosmetic, a terminal that can't show it can still take commands
+            let _ = write!(stdout, "(aya) ").and_then(|_| stdout.flush());
+        }
+        let Some((idx, line)) = lines.next() else {
+            break;
+        };
+        let line = match line {
+            Ok(line) => line,
+            Err(err) => {
+                eprintln!("error: failed to read commands: {err}");
+                return ExitCode::FAILURE;
+            }
+        };
+        match debugger.execute(&line, &mut stdout) {
+            Ok(Flow::Continue) => {}
+            Ok(Flow::Quit) => break,
+            Err(err) if interactive => eprintln!("error: {err}"),
+            Err(err) => {
+                eprintln!("error: line {}: {err}", idx + 1);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    ExitCode::from(debugger.halted().unwrap_or(0))
+}
+
+fn read_config(path: String) -> Result<Config, ExitCode> {
+    aya_packer::config::read_from_file(path).map_err(|report| {
+        eprintln!("{report:?}");
+        ExitCode::FAILURE
+    })
+}
+
+fn write_file(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> Result<(), ExitCode> {
+    std::fs::write(path.as_ref(), contents).map_err(|err| {
+        eprintln!("error: failed to write {}: {err}", path.as_ref().display());
+        ExitCode::FAILURE
+    })
+}
 
-    if run {
-        aya_console::run(config.output)?;
+fn config_from_args(args: BuildArgs) -> Config {
+    Config {
+        name: args.name.unwrap(),
+        author: args.author,
+        description: args.description,
+        code: args.code.unwrap(),
+        sprites: args.sprites.unwrap(),
+        output: args.output.unwrap_or(aya_packer::config::DEFAULT_OUTPUT.into()),
+        expand: args.expand.unwrap_or(false),
+        expand_output: args.expand_output,
+        strict: args.strict,
+        dedup: args.dedup,
+        background: None,
+        background_map: None,
+        interface_map: None,
+        map: args.map,
+        compress: args.compress,
+        banks: args.banks.unwrap_or_default(),
+        data: args.data.unwrap_or_default(),
+        palette: None,
+        base_dir: PathBuf::new(),
+        unmatched_sprites: vec![],
     }
+}
 
-    Ok(ExitCode::SUCCESS)
+fn report_error(err: aya_packer::Error) -> ExitCode {
+    match err {
+        aya_packer::Error::Assembly(report) => eprintln!("{report:?}"),
+        err => {
+            eprintln!("error: {err}");
+            if let aya_packer::Error::Bitmap(err) = &err {
+                let source = match err {
+                    aya_bitmap::Error::File { source, .. } => source.as_ref(),
+                    err => err,
+                };
+                if matches!(source, aya_bitmap::Error::PngUnsupported) {
+                    eprintln!("hint: build aya-cli with `--features png` to use PNG sprites");
+                }
+            }
+        }
+    }
+    ExitCode::FAILURE
 }
@@ -0,0 +1,254 @@
+#[cfg(feature = "renderer-raylib")]
+mod raylib;
+
+use std::collections::VecDeque;
+
+use aya_cpu::memory::{Addressable, Result};
+#[cfg(feature = "renderer-raylib")]
+pub use raylib::RaylibAudio;
+
+use crate::memory::AUDIO_MEM_LOC;
+use crate::{CLOCK_CYCLE, FPS};
+
+pub const SAMPLE_RATE: u32 = 44_100;
+
+/// How many cycles the cpu runs in one second, the apu uses this to convert the cycle budget it
+/// is ticked with into a number of samples, so pitch stays stable regardless of frame timing
+const CYCLES_PER_SECOND: f64 = CLOCK_CYCLE as f64 * FPS as f64;
+/// Samples that were never consumed by the audio device are dropped past this point to avoid
+/// building up latency
+const MAX_QUEUED_SAMPLES: usize = SAMPLE_RATE as usize / 4;
+
+const CHANNELS: u16 = 3;
+const NOISE_CHANNEL: u16 = 2;
+const CHANNEL_STRIDE: u16 = 4;
+const FREQUENCY_OFFSET: u16 = 0;
+const VOLUME_OFFSET: u16 = 2;
+const CONTROL_OFFSET: u16 = 3;
+
+const ENABLE_MASK: u8 = 0b00000001;
+const MAX_VOLUME: u8 = 0xF;
+/// Fraction of the full i16 range used by the mix, leaves some headroom so the device doesn't clip
+const MASTER_VOLUME: f32 = 0.5;
+
+pub trait Audio {
+    fn start() -> Self;
+    fn play(&mut self, apu: &mut Apu);
+}
+
+/// Audio backend for renderers without sound support, samples are consumed and discarded.
+#[cfg(any(feature = "renderer-soft", feature = "renderer-web"))]
+#[derive(Debug, Default)]
+pub struct SilentAudio;
+
+#[cfg(any(feature = "renderer-soft", feature = "renderer-web"))]
+impl Audio for SilentAudio {
+    fn start() -> Self {
+        Self
+    }
+
+    fn play(&mut self, apu: &mut Apu) {
+        apu.samples.clear();
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct ChannelRegisters {
+    frequency: u16,
+    volume: u8,
+    enabled: bool,
+}
+
+impl ChannelRegisters {
+    fn read(memory: &impl Addressable, channel: u16) -> Result<Self> {
+        let base = AUDIO_MEM_LOC.0 + channel * CHANNEL_STRIDE;
+        Ok(Self {
+            frequency: memory.read_word(base + FREQUENCY_OFFSET)?,
+            volume: memory.read(base + VOLUME_OFFSET)?.min(MAX_VOLUME),
+            enabled: memory.read(base + CONTROL_OFFSET)? & ENABLE_MASK == ENABLE_MASK,
+        })
+    }
+
+    fn amplitude(&self) -> f32 {
+        match self.enabled && self.frequency != 0 {
+            true => self.volume as f32 / MAX_VOLUME as f32,
+            false => 0.0,
+        }
+    }
+}
+
+/// Audio processing unit with two square wave channels and one noise channel. Every channel is
+/// controlled by four bytes in audio memory:
+///
+/// | BYTE  | DESCRIPTION                        |
+/// |-------|------------------------------------|
+/// | 00-01 | Frequency in hertz (little endian) |
+/// | 02    | Volume, from 0 to 15               |
+/// | 03    | Control, bit 0 enables the channel |
+#[derive(Debug)]
+pub struct Apu {
+    registers: [ChannelRegisters; CHANNELS as usize],
+    phases: [f32; CHANNELS as usize],
+    lfsr: u16,
+    pending_samples: f64,
+    samples: VecDeque<i16>,
+}
+
+impl Default for Apu {
+    fn default() -> Self {
+        Self {
+            registers: Default::default(),
+            phases: Default::default(),
+            lfsr: 1,
+            pending_samples: 0.0,
+            samples: VecDeque::with_capacity(MAX_QUEUED_SAMPLES),
+        }
+    }
+}
+
+impl Apu {
+    /// Latches the audio registers and produces as many samples as `cycles` cpu cycles last.
+    pub fn tick(&mut self, memory: &impl Addressable, cycles: usize) -> Result<()> {
+        for channel in 0..CHANNELS {
+            self.registers[channel as usize] = ChannelRegisters::read(memory, channel)?;
+        }
+
+        self.pending_samples += cycles as f64 * SAMPLE_RATE as f64 / CYCLES_PER_SECOND;
+        let samples = self.pending_samples as usize;
+        self.pending_samples -= samples as f64;
+
+        for _ in 0..samples {
+            let sample = self.mix();
+            self.samples.push_back(sample);
+        }
+
+        while self.samples.len() > MAX_QUEUED_SAMPLES {
+            self.samples.pop_front();
+        }
+
+        Ok(())
+    }
+
+    /// Fills `buffer` with the oldest queued samples, padding with silence when the apu fell
+    /// behind the audio device.
+    #[cfg(any(test, feature = "renderer-raylib"))]
+    pub fn fill(&mut self, buffer: &mut [i16]) {
+        for sample in buffer.iter_mut() {
+            *sample = self.samples.pop_front().unwrap_or_default();
+        }
+    }
+
+    #[cfg(any(test, feature = "renderer-raylib"))]
+    pub fn queued(&self) -> usize {
+        self.samples.len()
+    }
+
+    fn mix(&mut self) -> i16 {
+        let mut output = 0.0;
+
+        for channel in 0..CHANNELS {
+            let registers = self.registers[channel as usize];
+            let phase = &mut self.phases[channel as usize];
+            *phase += registers.frequency as f32 / SAMPLE_RATE as f32;
+
+            let clocks = phase.floor();
+            *phase -= clocks;
+
+            let level = match channel {
+                NOISE_CHANNEL => {
+                    // the noise channel clocks a 15 bit linear feedback shift register at its
+                    // frequency, and outputs its lowest bit
+                    for _ in 0..clocks as usize {
+                        let feedback = (self.lfsr ^ (self.lfsr >> 1)) & 1;
+                        self.lfsr = (self.lfsr >> 1) | (feedback << 14);
+                    }
+                    if self.lfsr & 1 == 1 {
+                        1.0
+                    } else {
+                        -1.0
+                    }
+                }
+                _ if *phase < 0.5 => 1.0,
+                _ => -1.0,
+            };
+
+            output += level * registers.amplitude();
+        }
+
+        (output / CHANNELS as f32 * MASTER_VOLUME * i16::MAX as f32) as i16
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use aya_cpu::MEMORY_SIZE;
+
+    use super::*;
+    use crate::memory::LinearMemory;
+
+    type AudioMemory = LinearMemory<MEMORY_SIZE>;
+
+    fn setup_channel(memory: &mut AudioMemory, channel: u16, frequency: u16, volume: u8) {
+        let base = AUDIO_MEM_LOC.0 + channel * CHANNEL_STRIDE;
+        memory.write_word(base + FREQUENCY_OFFSET, frequency).unwrap();
+        memory.write(base + VOLUME_OFFSET, volume).unwrap();
+        memory.write(base + CONTROL_OFFSET, ENABLE_MASK).unwrap();
+    }
+
+    #[test]
+    fn test_samples_follow_cycle_budget() {
+        let memory = AudioMemory::default();
+        let mut apu = Apu::default();
+
+        apu.tick(&memory, CLOCK_CYCLE).unwrap();
+        assert_eq!(apu.queued(), SAMPLE_RATE as usize / FPS as usize);
+
+        // cycle budgets smaller than a sample accumulate until a whole sample is due
+        let mut apu = Apu::default();
+        apu.tick(&memory, 2).unwrap();
+        assert_eq!(apu.queued(), 0);
+        apu.tick(&memory, 1).unwrap();
+        assert_eq!(apu.queued(), 1);
+    }
+
+    #[test]
+    fn test_disabled_channels_are_silent() {
+        let memory = AudioMemory::default();
+        let mut apu = Apu::default();
+        apu.tick(&memory, CLOCK_CYCLE).unwrap();
+
+        let mut buffer = [1; 64];
+        apu.fill(&mut buffer);
+
+        assert!(buffer.iter().all(|sample| *sample == 0));
+    }
+
+    #[test]
+    fn test_square_wave_period() {
+        let mut memory = AudioMemory::default();
+        // 441hz makes a period of exactly 100 samples
+        setup_channel(&mut memory, 0, 441, MAX_VOLUME);
+        let mut apu = Apu::default();
+        apu.tick(&memory, CLOCK_CYCLE).unwrap();
+
+        let mut buffer = [0; 100];
+        apu.fill(&mut buffer);
+
+        let high = buffer.iter().filter(|sample| **sample > 0).count();
+        let low = buffer.iter().filter(|sample| **sample < 0).count();
+        assert_eq!(high, 50);
+        assert_eq!(low, 50);
+    }
+
+    #[test]
+    fn test_fill_pads_with_silence() {
+        let mut memory = AudioMemory::default();
+        setup_channel(&mut memory, 1, 441, MAX_VOLUME);
+        let mut apu = Apu::default();
+
+        let mut buffer = [1; 16];
+        apu.fill(&mut buffer);
+
+        assert!(buffer.iter().all(|sample| *sample == 0));
+    }
+}
@@ -0,0 +1,41 @@
+use std::sync::OnceLock;
+
+use raylib::core::audio::{AudioStream, RaylibAudio as AudioDevice};
+
+use super::{Apu, Audio, SAMPLE_RATE};
+
+/// Amount of samples handed to the audio device on every update
+const SAMPLES_PER_BUFFER: usize = 1024;
+
+// audio streams borrow the device they were created from, keeping the device in a static lets the
+// stream live as long as the console does
+static DEVICE: OnceLock<AudioDevice> = OnceLock::new();
+
+#[derive(Debug)]
+pub struct RaylibAudio {
+    stream: AudioStream<'static>,
+    buffer: Vec<i16>,
+}
+
+impl Audio for RaylibAudio {
+    fn start() -> Self {
+        let device =
+            DEVICE.get_or_init(|| AudioDevice::init_audio_device().expect("failed to initialize audio device"));
+        device.set_audio_stream_buffer_size_default(SAMPLES_PER_BUFFER as i32);
+
+        let stream = device.new_audio_stream(SAMPLE_RATE, i16::BITS, 1);
+        stream.play();
+
+        Self {
+            stream,
+            buffer: vec![0; SAMPLES_PER_BUFFER],
+        }
+    }
+
+    fn play(&mut self, apu: &mut Apu) {
+        while self.stream.is_processed() && apu.queued() >= SAMPLES_PER_BUFFER {
+            apu.fill(&mut self.buffer);
+            self.stream.update(&self.buffer);
+        }
+    }
+}
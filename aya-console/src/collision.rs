@@ -0,0 +1,194 @@
+use aya_cpu::memory::Addressable;
+
+use crate::error::Result;
+use crate::memory::{BG_MEM_LOC, COLLISION_MEM_LOC};
+use crate::renderer::sprite::{sprite_table, Sprite};
+use crate::renderer::{SCREEN_HEIGHT, SCREEN_WIDTH, SPRITE_HEIGHT, SPRITE_WIDTH, TILES_WIDTH};
+use crate::scanline::BackgroundState;
+
+/// Set on the flags register when two sprites overlap
+pub const SPRITE_COLLISION_MASK: u8 = 0b00000001;
+/// Set on the flags register when a sprite overlaps a background cell holding a tile other than 0
+pub const BACKGROUND_COLLISION_MASK: u8 = 0b00000010;
+/// Written on the slot registers when nothing collided
+pub const NO_SPRITE: u8 = 0xFF;
+
+const FLAGS_OFFSET: u16 = 0;
+const FIRST_SPRITE_OFFSET: u16 = 1;
+const SECOND_SPRITE_OFFSET: u16 = 2;
+const BACKGROUND_SPRITE_OFFSET: u16 = 3;
+
+/// Collisions found on a frame, sprites are identified by their slot.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Collisions {
+    /// First pair of overlapping sprites, in slot order
+    pub sprites: Option<(u16, u16)>,
+    /// First sprite overlapping a background cell that holds a tile other than 0
+    pub background: Option<u16>,
+}
+
+impl Collisions {
+    /// Checks the enabled sprites against each other by their bounding boxes, and against the
+    /// background cells under their visible pixels, scrolled as latched on every line.
+    pub fn detect(memory: &impl Addressable, background: &[BackgroundState]) -> Result<Self> {
+        let sprites = sprite_table(memory, false)?;
+
+        let pair = sprites.iter().enumerate().find_map(|(idx, sprite)| {
+            sprites[idx + 1..]
+                .iter()
+                .find(|other| overlap(sprite, other))
+                .map(|other| (sprite.slot, other.slot))
+        });
+
+        let mut background_hit = None;
+        for sprite in sprites.iter() {
+            if touches_background(memory, background, sprite)? {
+                background_hit = Some(sprite.slot);
+                break;
+            }
+        }
+
+        Ok(Self {
+            sprites: pair,
+            background: background_hit,
+        })
+    }
+
+    /// Writes the collisions on the collision registers, replacing the ones of the previous frame.
+    pub fn publish(&self, memory: &mut impl Addressable) -> Result<()> {
+        let mut flags = 0;
+        if self.sprites.is_some() {
+            flags |= SPRITE_COLLISION_MASK;
+        }
+        if self.background.is_some() {
+            flags |= BACKGROUND_COLLISION_MASK;
+        }
+
+        let (first, second) = self
+            .sprites
+            .map_or((NO_SPRITE, NO_SPRITE), |(first, second)| (first as u8, second as u8));
+        let background = self.background.map_or(NO_SPRITE, |slot| slot as u8);
+
+        memory.write(COLLISION_MEM_LOC.0 + FLAGS_OFFSET, flags)?;
+        memory.write(COLLISION_MEM_LOC.0 + FIRST_SPRITE_OFFSET, first)?;
+        memory.write(COLLISION_MEM_LOC.0 + SECOND_SPRITE_OFFSET, second)?;
+        memory.write(COLLISION_MEM_LOC.0 + BACKGROUND_SPRITE_OFFSET, background)?;
+        Ok(())
+    }
+}
+
+fn overlap(sprite: &Sprite, other: &Sprite) -> bool {
+    sprite.x.abs_diff(other.x) < SPRITE_WIDTH && sprite.y.abs_diff(other.y) < SPRITE_HEIGHT
+}
+
+/// Whether any pixel of the sprite inside the playfield is drawn over a background cell holding a
+/// tile other than 0.
+fn touches_background(memory: &impl Addressable, background: &[BackgroundState], sprite: &Sprite) -> Result<bool> {
+    for row in 0..SPRITE_HEIGHT as usize {
+        // rows above the playfield are skipped, and rows below it end the sprite
+        let Ok(line) = usize::try_from(sprite.y + row as i16) else {
+            continue;
+        };
+        let Some(state) = background.get(line) else {
+            break;
+        };
+        let y = (line + state.scroll_y as usize) % SCREEN_HEIGHT;
+
+        for column in 0..SPRITE_WIDTH as usize {
+            let Ok(pixel_x) = usize::try_from(sprite.x + column as i16) else {
+                continue;
+            };
+            if pixel_x >= SCREEN_WIDTH {
+                break;
+            }
+            let x = (pixel_x + state.scroll_x as usize) % SCREEN_WIDTH;
+
+            let cell = (y / SPRITE_HEIGHT as usize) * TILES_WIDTH as usize + x / SPRITE_WIDTH as usize;
+            if memory.peek(BG_MEM_LOC.0 + cell as u16)? != 0 {
+                return Ok(true);
+            }
+        }
+    }
+
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use aya_cpu::MEMORY_SIZE;
+
+    use super::*;
+    use crate::memory::{LinearMemory, SPRITE_MEM_LOC};
+    use crate::renderer::sprite::SPRITE_STRIDE;
+    use crate::renderer::SPRITE_ENABLE_MASK;
+
+    fn write_sprite(memory: &mut LinearMemory<MEMORY_SIZE>, slot: u16, x: u8, y: u8) {
+        let address = SPRITE_MEM_LOC.0 + slot * SPRITE_STRIDE as u16;
+        memory.write(address + 1, x).unwrap();
+        memory.write(address + 2, y).unwrap();
+        memory.write(address + 3, SPRITE_ENABLE_MASK).unwrap();
+    }
+
+    fn detect(memory: &LinearMemory<MEMORY_SIZE>) -> Collisions {
+        Collisions::detect(memory, &[BackgroundState::default(); SCREEN_HEIGHT]).unwrap()
+    }
+
+    #[test]
+    fn test_sprite_collisions() {
+        let mut memory = LinearMemory::default();
+        assert_eq!(detect(&memory), Collisions::default());
+
+        // touching edges don't overlap
+        write_sprite(&mut memory, 3, 16, 16);
+        write_sprite(&mut memory, 5, 24, 16);
+        assert_eq!(detect(&memory).sprites, None);
+
+        write_sprite(&mut memory, 9, 23, 23);
+        write_sprite(&mut memory, 7, 10, 10);
+        // slot 3 overlaps slots 7 and 9, slot 5 overlaps slot 9 too, the first pair is reported
+        assert_eq!(detect(&memory).sprites, Some((3, 7)));
+
+        // disabled sprites never collide
+        memory
+            .write(SPRITE_MEM_LOC.0 + 3 * SPRITE_STRIDE as u16 + 3, 0)
+            .unwrap();
+        assert_eq!(detect(&memory).sprites, Some((5, 9)));
+    }
+
+    #[test]
+    fn test_background_collisions() {
+        let mut memory = LinearMemory::default();
+        // the cell on the second column of the second row
+        memory.write(BG_MEM_LOC.0 + TILES_WIDTH + 1, 4).unwrap();
+
+        write_sprite(&mut memory, 0, 0, 0);
+        write_sprite(&mut memory, 2, 100, 60);
+        assert_eq!(detect(&memory).background, None);
+
+        write_sprite(&mut memory, 1, 15, 15);
+        assert_eq!(detect(&memory).background, Some(1));
+
+        // scrolling moves the background under the sprites, the cell is now under slot 0
+        let scrolled = [BackgroundState {
+            scroll_x: 8,
+            scroll_y: 8,
+        }; SCREEN_HEIGHT];
+        let collisions = Collisions::detect(&memory, &scrolled).unwrap();
+        assert_eq!(collisions.background, Some(0));
+    }
+
+    #[test]
+    fn test_publish() {
+        let mut memory = LinearMemory::<MEMORY_SIZE>::default();
+        let collisions = Collisions {
+            sprites: Some((2, 11)),
+            background: None,
+        };
+        collisions.publish(&mut memory).unwrap();
+
+        let registers = (COLLISION_MEM_LOC.0..=COLLISION_MEM_LOC.1)
+            .map(|address| memory.read(address).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(registers, [SPRITE_COLLISION_MASK, 2, 11, NO_SPRITE]);
+    }
+}
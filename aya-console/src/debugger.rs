@@ -0,0 +1,468 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Write;
+use std::path::Path;
+
+use aya_assembly::symbols::SymbolMap;
+use aya_cpu::cpu::{Cpu, RunOutcome};
+use aya_cpu::instruction::Instruction;
+use aya_cpu::memory::Addressable;
+use aya_cpu::register::Register;
+use aya_rom::Rom;
+
+use crate::memory::memory_mapper::MemoryMapper;
+use crate::memory::serial::SerialMem;
+use crate::memory::MemoryMap;
+use crate::{setup_cpu, setup_memory, EmulatorOptions, Error};
+
+/// Instructions `run` gives up after when nothing stops the program, so a command file can't hang
+/// whoever runs it.
+pub const RUN_LIMIT: u64 = 10_000_000;
+
+/// Bytes `x` dumps and instructions `disas` shows when not told how many.
+const DEFAULT_COUNT: u16 = 16;
+
+/// Bytes read to decode an instruction, enough for the longest one.
+const MAX_INSTRUCTION_SIZE: u16 = 8;
+
+/// What the reader of the commands should do after one of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flow {
+    Continue,
+    Quit,
+}
+
+/// Everything that can make a debugger command fail.
+#[derive(Debug)]
+pub enum CommandError {
+    /// The command isn't one the debugger knows
+    Unknown(String),
+    /// An argument of the command is missing or malformed
+    Argument(String),
+    /// No symbol of the rom goes by this name
+    Symbol(String),
+    /// The program halted with this code, it has to be loaded again to run
+    Halted(u8),
+    /// The symbols next to the rom couldn't be parsed
+    Symbols(aya_assembly::symbols::ParseError),
+    /// The rom couldn't be loaded or the program did something the cpu can't execute
+    Console(Error),
+    /// The output of the command couldn't be written
+    Io(std::io::Error),
+}
+
+impl std::fmt::Display for CommandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CommandError::Unknown(command) => write!(f, "unknown command `{command}`"),
+            CommandError::Argument(reason) => write!(f, "{reason}"),
+            CommandError::Symbol(name) => write!(f, "no symbol named `{name}`"),
+            CommandError::Halted(code) => write!(
+                f,
+                "the program halted with code ${code:02X}, load it again to start over"
+            ),
+            CommandError::Symbols(err) => write!(f, "symbols couldn't be parsed, {err}"),
+            CommandError::Console(err) => write!(f, "{err}"),
+            CommandError::Io(err) => write!(f, "output couldn't be written, {err}"),
+        }
+    }
+}
+
+impl std::error::Error for CommandError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CommandError::Symbols(err) => Some(err),
+            CommandError::Console(err) => Some(err),
+            CommandError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<Error> for CommandError {
+    fn from(err: Error) -> Self {
+        Self::Console(err)
+    }
+}
+
+impl From<std::io::Error> for CommandError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+/// Why the program stopped running.
+enum Stop {
+    Halted(u8),
+    Breakpoint,
+    Watch { address: u16, before: u8, after: u8 },
+    Limit,
+}
+
+/// Drives the cpu of a rom one command at a time, as typed on a terminal or read from a command
+/// file. Only the cpu runs, no frames are composed and no interrupts are raised. Commands are
+///
+/// - `load <rom>` loads a rom in place of the running one, along with the symbols next to it
+/// - `b <location>` stops the program before it runs the instruction at the location
+/// - `watch <location>` stops the program once the byte at the location changes
+/// - `run` runs the program until something stops it, at most [`RUN_LIMIT`] instructions
+/// - `step [count]` runs one or `count` instructions
+/// - `info regs` prints every register
+/// - `x/<count> <location>` dumps `count` bytes of memory
+/// - `disas [location] [count]` disassembles from the location, the instruction pointer by default
+/// - `quit`
+///
+/// Locations are console addresses such as `$2290`, or symbol names such as `start`.
+#[derive(Debug)]
+pub struct Debugger {
+    cpu: Cpu<MemoryMapper>,
+    symbols: SymbolMap,
+    options: EmulatorOptions,
+    breakpoints: BTreeSet<u16>,
+    /// Watched addresses along with the byte they held when last checked
+    watches: BTreeMap<u16, u8>,
+    /// Code the program halted with, once it did
+    halted: Option<u8>,
+}
+
+impl Debugger {
+    /// Loads a rom from its bytes, the serial port output goes to stdout.
+    pub fn new(rom: &[u8], symbols: SymbolMap, options: &EmulatorOptions) -> Result<Self, CommandError> {
+        Ok(Self {
+            cpu: load_cpu(rom, options)?,
+            symbols,
+            options: options.clone(),
+            breakpoints: BTreeSet::new(),
+            watches: BTreeMap::new(),
+            halted: None,
+        })
+    }
+
+    /// Loads the rom file at `path`, along with the symbols `aya build --map` writes next to it
+    /// when there are any.
+    pub fn open(path: impl AsRef<Path>, options: &EmulatorOptions) -> Result<Self, CommandError> {
+        let (rom, symbols) = read_rom(path.as_ref())?;
+        Self::new(&rom, symbols, options)
+    }
+
+    pub fn set_symbols(&mut self, symbols: SymbolMap) {
+        self.symbols = symbols;
+    }
+
+    pub fn cpu(&self) -> &Cpu<MemoryMapper> {
+        &self.cpu
+    }
+
+    /// Code the program halted with, `None` while it can still run.
+    pub fn halted(&self) -> Option<u8> {
+        self.halted
+    }
+
+    /// Runs a single command, writing what it prints to `out`. Blank lines and lines starting
+    /// with `#` do nothing.
+    pub fn execute(&mut self, command: &str, out: &mut impl Write) -> Result<Flow, CommandError> {
+        let mut args = command.split_whitespace();
+        let Some(name) = args.next().filter(|name| !name.starts_with('#')) else {
+            return Ok(Flow::Continue);
+        };
+        let args = args.collect::<Vec<_>>();
+
+        match (name, args.as_slice()) {
+            ("load", [path]) => {
+                let (rom, symbols) = read_rom(Path::new(path))?;
+                self.cpu = load_cpu(&rom, &self.options)?;
+                self.symbols = symbols;
+                self.halted = None;
+                for (&address, value) in self.watches.iter_mut() {
+                    *value = self.cpu.memory.peek(address).map_err(Error::Memory)?;
+                }
+                writeln!(out, "loaded {path}")?;
+            }
+            ("b" | "break", [location]) => {
+                let address = self.location(location)?;
+                self.breakpoints.insert(address);
+                writeln!(out, "breakpoint at {}", self.describe(address))?;
+            }
+            ("watch", [location]) => {
+                let address = self.location(location)?;
+                let value = self.cpu.memory.peek(address).map_err(Error::Memory)?;
+                self.watches.insert(address, value);
+                writeln!(out, "watching {}", self.describe(address))?;
+            }
+            ("r" | "run" | "c" | "continue", []) => {
+                let stop = self.advance(RUN_LIMIT)?;
+                self.report(stop, out)?;
+            }
+            ("s" | "step", [] | [_]) => {
+                let count = args.first().map(|count| parse_count(count)).transpose()?.unwrap_or(1);
+                let stop = self.advance(u64::from(count))?;
+                self.report(stop, out)?;
+            }
+            ("info", ["regs" | "registers"]) | ("regs", []) => {
+                for register in Register::iter() {
+                    writeln!(out, "{register:<3} ${:04X}", self.cpu.registers.fetch(register))?;
+                }
+            }
+            (name, [location]) if name == "x" || name.starts_with("x/") => {
+                let count = match name.strip_prefix("x/") {
+                    Some(count) => parse_count(count)?,
+                    None => DEFAULT_COUNT,
+                };
+                let address = self.location(location)?;
+                self.dump(address, count, out)?;
+            }
+            ("disas", [] | [_] | [_, _]) => {
+                let address = match args.first() {
+                    Some(location) => self.location(location)?,
+                    None => self.cpu.registers.fetch(Register::IP),
+                };
+                let count = args.get(1).map(|count| parse_count(count)).transpose()?;
+                self.disassemble(address, count.unwrap_or(DEFAULT_COUNT), out)?;
+            }
+            ("q" | "quit", []) => return Ok(Flow::Quit),
+            _ => return Err(CommandError::Unknown(command.trim().to_string())),
+        }
+
+        Ok(Flow::Continue)
+    }
+
+    /// Runs at most `limit` instructions, stopping early on halts, breakpoints and watches.
+    fn advance(&mut self, limit: u64) -> Result<Stop, CommandError> {
+        if let Some(code) = self.halted {
+            return Err(CommandError::Halted(code));
+        }
+
+        for _ in 0..limit {
+            if let RunOutcome::Halt { code, .. } = self.cpu.run_bounded(1).map_err(Error::Cpu)? {
+                self.halted = Some(code);
+                return Ok(Stop::Halted(code));
+            }
+            for (&address, value) in self.watches.iter_mut() {
+                let after = self.cpu.memory.peek(address).map_err(Error::Memory)?;
+                if after != *value {
+                    let before = std::mem::replace(value, after);
+                    return Ok(Stop::Watch { address, before, after });
+                }
+            }
+            if self.breakpoints.contains(&self.cpu.registers.fetch(Register::IP)) {
+                return Ok(Stop::Breakpoint);
+            }
+        }
+
+        Ok(Stop::Limit)
+    }
+
+    /// Prints why the program stopped, followed by the instruction it would run next.
+    fn report(&self, stop: Stop, out: &mut impl Write) -> Result<(), CommandError> {
+        let ip = self.cpu.registers.fetch(Register::IP);
+        match stop {
+            Stop::Halted(code) => return Ok(writeln!(out, "program halted with code ${code:02X}")?),
+            Stop::Breakpoint => writeln!(out, "breakpoint at {}", self.describe(ip))?,
+            Stop::Watch { address, before, after } => writeln!(
+                out,
+                "{} changed from ${before:02X} to ${after:02X}",
+                self.describe(address)
+            )?,
+            Stop::Limit => {}
+        }
+        self.disassemble(ip, 1, out)
+    }
+
+    fn dump(&self, address: u16, count: u16, out: &mut impl Write) -> Result<(), CommandError> {
+        for row in (0..count).step_by(16) {
+            let start = address.wrapping_add(row);
+            write!(out, "${start:04X}:")?;
+            for offset in row..count.min(row + 16) {
+                match self.cpu.memory.peek(address.wrapping_add(offset)) {
+                    Ok(byte) => write!(out, " {byte:02X}")?,
+                    Err(_) => write!(out, " ??")?,
+                }
+            }
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+
+    /// Prints `count` instructions from `address`, bytes that aren't an instruction are printed
+    /// on their own.
+    fn disassemble(&self, mut address: u16, count: u16, out: &mut impl Write) -> Result<(), CommandError> {
+        let ip = self.cpu.registers.fetch(Register::IP);
+        for _ in 0..count {
+            let bytes = (0..MAX_INSTRUCTION_SIZE)
+                .map_while(|offset| self.cpu.memory.peek(address.wrapping_add(offset)).ok())
+                .collect::<Vec<_>>();
+            let marker = if address == ip { "=>" } else { "  " };
+            let size = match (Instruction::decode(&bytes), bytes.first()) {
+                (Ok((instruction, size)), _) => {
+                    writeln!(out, "{marker} {} {instruction}", self.describe(address))?;
+                    size as u16
+                }
+                (Err(_), Some(byte)) => {
+                    writeln!(out, "{marker} {} .byte ${byte:02X}", self.describe(address))?;
+                    1
+                }
+                (Err(_), None) => break,
+            };
+            address = address.wrapping_add(size);
+        }
+        Ok(())
+    }
+
+    /// Address of a location, written as a hex address such as `$2290` or as a symbol name.
+    fn location(&self, location: &str) -> Result<u16, CommandError> {
+        if let Some(address) = location.strip_prefix('$') {
+            return u16::from_str_radix(address, 16)
+                .map_err(|_| CommandError::Argument(format!("`{location}` is not a 16 bit hex address")));
+        }
+        self.symbols
+            .iter()
+            .find(|symbol| symbol.name == location)
+            .map(|symbol| symbol.address)
+            .ok_or_else(|| CommandError::Symbol(location.to_string()))
+    }
+
+    /// `address` along with the symbol it belongs to, such as `$2294 <draw_player+4>`. Symbols
+    /// only cover the rest of the memory region they are on.
+    fn describe(&self, address: u16) -> String {
+        let map = MemoryMap::default();
+        let region = |address| map.region_for(address).map(|region| region.start);
+        match self.symbols.lookup(address) {
+            Some(symbol) if region(symbol.address) != region(address) => format!("${address:04X}"),
+            Some(symbol) if symbol.address == address => format!("${address:04X} <{}>", symbol.name),
+            Some(symbol) => format!("${address:04X} <{}+{}>", symbol.name, address - symbol.address),
+            None => format!("${address:04X}"),
+        }
+    }
+}
+
+fn load_cpu(rom: &[u8], options: &EmulatorOptions) -> Result<Cpu<MemoryMapper>, Error> {
+    let rom = Rom::from_bytes(rom, !options.skip_checksum)?;
+    let mut cpu = setup_cpu(&rom, setup_memory(&rom, SerialMem::default())?)?;
+    cpu.memory.set_program_write_protected(!options.writable_code);
+    Ok(cpu)
+}
+
+/// Reads the rom at `path` along with the symbols at `<path>.map`, which are empty when there's
+/// no such file.
+fn read_rom(path: &Path) -> Result<(Vec<u8>, SymbolMap), CommandError> {
+    let read = |path: &Path| {
+        std::fs::read(path).map_err(|source| Error::Io {
+            path: path.to_path_buf(),
+            source,
+        })
+    };
+    let rom = read(path)?;
+
+    let mut map = path.as_os_str().to_owned();
+    map.push(".map");
+    let map = Path::new(&map);
+    let symbols = match map.exists() {
+        true => SymbolMap::parse(&String::from_utf8_lossy(&read(map)?)).map_err(CommandError::Symbols)?,
+        false => SymbolMap::default(),
+    };
+    Ok((rom, symbols))
+}
+
+fn parse_count(count: &str) -> Result<u16, CommandError> {
+    count
+        .parse()
+        .map_err(|_| CommandError::Argument(format!("`{count}` is not a count")))
+}
+
+#[cfg(test)]
+mod tests {
+    use aya_assembly::symbols::Symbol;
+
+    use super::*;
+    use crate::memory::{BG_MEM_LOC, CODE_MEM_LOC};
+    use crate::tests::test_rom;
+
+    /// Stores 5 on the first background byte then halts with code 3, with a `store` label on the
+    /// store.
+    fn debugger() -> Debugger {
+        let set = Instruction::MovLitReg(Register::R1, 5).encode();
+        let code = [
+            set.clone(),
+            Instruction::MovRegMem(Register::R1, BG_MEM_LOC.0.into()).encode(),
+            Instruction::Halt(3).encode(),
+        ]
+        .concat();
+        let symbols = SymbolMap::new(vec![Symbol {
+            address: CODE_MEM_LOC.0 + set.len() as u16,
+            name: String::from("store"),
+            module: String::from("main.aya"),
+        }]);
+        let rom = test_rom(&code).to_bytes().unwrap();
+        Debugger::new(&rom, symbols, &EmulatorOptions::default()).unwrap()
+    }
+
+    fn execute(debugger: &mut Debugger, command: &str) -> String {
+        let mut out = vec![];
+        assert_eq!(debugger.execute(command, &mut out).unwrap(), Flow::Continue);
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_breakpoints_and_registers() {
+        let mut debugger = debugger();
+        assert_eq!(execute(&mut debugger, "b store"), "breakpoint at $2284 <store>\n");
+        assert_eq!(
+            execute(&mut debugger, "run"),
+            "breakpoint at $2284 <store>\n=> $2284 <store> MovRegMem R1, &[$6280]\n"
+        );
+        assert!(execute(&mut debugger, "info regs").contains("R1  $0005\n"));
+
+        assert_eq!(execute(&mut debugger, "run"), "program halted with code $03\n");
+        assert_eq!(debugger.halted(), Some(3));
+        let mut out = vec![];
+        assert!(matches!(
+            debugger.execute("step", &mut out),
+            Err(CommandError::Halted(3))
+        ));
+    }
+
+    #[test]
+    fn test_watches_and_memory() {
+        let mut debugger = debugger();
+        execute(&mut debugger, "watch $6280");
+        assert_eq!(
+            execute(&mut debugger, "continue"),
+            "$6280 changed from $00 to $05\n=> $2288 <store+4> Halt $03\n"
+        );
+        assert_eq!(execute(&mut debugger, "x/3 $6280"), "$6280: 05 00 00\n");
+        assert_eq!(execute(&mut debugger, "x/2 $6796"), "$6796: ?? ??\n");
+    }
+
+    #[test]
+    fn test_step_and_disassembly() {
+        let mut debugger = debugger();
+        assert_eq!(
+            execute(&mut debugger, "disas $2280 2"),
+            "=> $2280 MovLitReg R1, $0005\n   $2284 <store> MovRegMem R1, &[$6280]\n"
+        );
+        assert_eq!(execute(&mut debugger, "step 2"), "=> $2288 <store+4> Halt $03\n");
+        assert_eq!(execute(&mut debugger, "# comments do nothing"), "");
+    }
+
+    #[test]
+    fn test_invalid_commands() {
+        let mut debugger = debugger();
+        let mut out = vec![];
+        assert!(matches!(
+            debugger.execute("jump", &mut out),
+            Err(CommandError::Unknown(_))
+        ));
+        assert!(matches!(
+            debugger.execute("b main", &mut out),
+            Err(CommandError::Symbol(_))
+        ));
+        assert!(matches!(
+            debugger.execute("b $XYZ", &mut out),
+            Err(CommandError::Argument(_))
+        ));
+        assert!(matches!(
+            debugger.execute("step many", &mut out),
+            Err(CommandError::Argument(_))
+        ));
+        assert_eq!(debugger.execute("quit", &mut out).unwrap(), Flow::Quit);
+    }
+}
@@ -0,0 +1,587 @@
+use aya_cpu::cpu::{Cpu, RunOutcome};
+use aya_cpu::memory::Addressable;
+use aya_cpu::trace::TraceWriter;
+use aya_rom::Rom;
+
+use crate::audio::Apu;
+use crate::collision::Collisions;
+use crate::error::Result;
+use crate::input::KeyStatus;
+use crate::memory::memory_mapper::{Devices, MemoryMapper};
+use crate::memory::serial::SerialMem;
+use crate::memory::{DirtyRegions, Interrupt, INPUT_MEM_LOC};
+use crate::renderer::{Rasterizer, Renderer, SCREEN_HEIGHT};
+use crate::scanline::{self, BackgroundState, LINES_PER_FRAME};
+use crate::{setup_cpu, setup_memory, CLOCK_CYCLE, PALETTE};
+
+/// Settings for loading a rom into an [`Emulator`], the defaults are what a player would want.
+#[derive(Debug, Clone, Default)]
+pub struct EmulatorOptions {
+    /// Runs roms even when their contents don't match the header checksum, useful while
+    /// developing tools that write roms
+    pub skip_checksum: bool,
+    /// Leaves code memory writable after the program is loaded, for self modifying programs
+    pub writable_code: bool,
+    /// Only draws the first sprites touching every line, like the sprite hardware of older
+    /// consoles did
+    pub sprite_line_limit: bool,
+}
+
+/// Stable hash of a composed frame, the same on every platform and build so it can be kept on
+/// golden tests. It's the 64 bit FNV-1a hash of the RGBA pixels of the frame.
+pub fn frame_hash(frame: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+    frame
+        .iter()
+        .fold(OFFSET_BASIS, |hash, &byte| (hash ^ u64::from(byte)).wrapping_mul(PRIME))
+}
+
+/// What [`Emulator::reload`] leaves alone when loading a new version of the rom, by default the
+/// program starts over as if the console was just turned on.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ReloadOptions {
+    /// Keeps the registers and the stack, so the program carries on from where it was instead of
+    /// starting over from its entry point, which only works when the code it was running didn't
+    /// move
+    pub keep_ip: bool,
+    /// Keeps every memory besides code, tiles and banks, such as the background and sprites the
+    /// program drew
+    pub keep_ram: bool,
+}
+
+/// The console without any window, input or audio device attached. Every frame is run with the
+/// keys given by the caller and composed into a `SCREEN_WIDTH * SCREEN_HEIGHT` RGBA buffer, so
+/// it can be embedded anywhere frames can be shown. The desktop and web frontends run their
+/// frames through it as well.
+#[derive(Debug)]
+pub struct Emulator {
+    cpu: Cpu<MemoryMapper>,
+    apu: Apu,
+    rasterizer: Rasterizer,
+    /// Background state latched at the start of every visible line of the last frame
+    background: [BackgroundState; SCREEN_HEIGHT],
+    previous_keys: KeyStatus,
+    /// RGBA pixels of the last composed frame
+    frame: Vec<u8>,
+    name: String,
+    title: String,
+    palette: Vec<(u8, u8, u8, u8)>,
+    checksum: u32,
+    /// Cycles every frame runs, the ones the rom was made for
+    cycles_per_frame: usize,
+    halted: bool,
+    options: EmulatorOptions,
+}
+
+impl Emulator {
+    /// Loads a rom from its bytes, the serial port output goes to stdout.
+    pub fn new(rom: &[u8], options: &EmulatorOptions) -> Result<Self> {
+        Self::with_serial(rom, options, SerialMem::default())
+    }
+
+    /// Loads a rom from its bytes, with the serial port writing to `serial`.
+    pub fn with_serial(rom: &[u8], options: &EmulatorOptions, serial: SerialMem) -> Result<Self> {
+        let rom = Rom::from_bytes(rom, !options.skip_checksum)?;
+
+        let mut cpu = setup_cpu(&rom, setup_memory(&rom, serial)?)?;
+        cpu.memory.set_program_write_protected(!options.writable_code);
+
+        let palette = rom.palette_colors().unwrap_or_else(|| PALETTE.to_vec());
+        let mut rasterizer = Rasterizer::default();
+        rasterizer.set_sprite_line_limit(options.sprite_line_limit);
+        rasterizer.set_palette(&palette);
+
+        let mut frame = Vec::new();
+        rasterizer.copy_rgba(&mut frame);
+
+        Ok(Self {
+            cpu,
+            apu: Apu::default(),
+            rasterizer,
+            background: [BackgroundState::default(); SCREEN_HEIGHT],
+            previous_keys: KeyStatus::reset(),
+            frame,
+            name: rom.name.to_string(),
+            title: rom.title(),
+            palette,
+            checksum: rom.checksum(),
+            cycles_per_frame: cycles_per_frame(&rom),
+            halted: false,
+            options: options.clone(),
+        })
+    }
+
+    /// Loads a new version of the rom in place of the one running, with the serial port and
+    /// whatever `options` keeps left alone. A rom that fails to load, such as one caught half
+    /// written, leaves the console untouched.
+    pub fn reload(&mut self, rom: &[u8], options: &ReloadOptions) -> Result<()> {
+        let rom = Rom::from_bytes(rom, !self.options.skip_checksum)?;
+        let fresh = setup_cpu(&rom, setup_memory(&rom, SerialMem::default())?)?;
+        let state = fresh.snapshot();
+
+        self.cpu.memory.replace_devices(fresh.memory, |device| match device {
+            Devices::Serial(_) => false,
+            Devices::Program(_) | Devices::Tile(_) | Devices::Bank(_) => true,
+            Devices::Stack(_) => !options.keep_ip && !options.keep_ram,
+            _ => !options.keep_ram,
+        });
+        self.cpu.memory.set_program_write_protected(!self.options.writable_code);
+        if !options.keep_ip {
+            self.cpu.restore(&state);
+        }
+        if !options.keep_ram {
+            self.apu = Apu::default();
+        }
+
+        self.palette = rom.palette_colors().unwrap_or_else(|| PALETTE.to_vec());
+        self.rasterizer.set_palette(&self.palette);
+        self.rasterizer.invalidate();
+        self.name = rom.name.to_string();
+        self.title = rom.title();
+        self.checksum = rom.checksum();
+        self.cycles_per_frame = cycles_per_frame(&rom);
+        self.halted = false;
+        Ok(())
+    }
+
+    /// Runs a frame with `input` held and returns it composed as RGBA pixels, row by row from the
+    /// top left corner. Once the program halts no more frames are run, and the last one is
+    /// returned again.
+    pub fn step_frame(&mut self, input: KeyStatus) -> Result<&[u8]> {
+        if self.run_frame(input)?.is_some() {
+            self.compose()?;
+        }
+
+        Ok(&self.frame)
+    }
+
+    /// Runs the cpu and the apu for a frame with `input` on the input latch, raising the input
+    /// interrupt when the keys changed since the previous frame, without composing it. Returns
+    /// how many instructions ran, only those before the halt on the frame the program halts on,
+    /// or `None` when it had already halted and nothing ran.
+    pub(crate) fn run_frame(&mut self, input: KeyStatus) -> Result<Option<usize>> {
+        if self.halted {
+            return Ok(None);
+        }
+
+        let cpu = &mut self.cpu;
+        // written every frame, so after loading a state the input latch reflects the keys held
+        // right now rather than the ones held when the state was saved
+        cpu.memory.write(INPUT_MEM_LOC.0, input)?;
+        // a change is held while a handler runs, and raised on the first frame after it returns
+        if input != self.previous_keys && raise(cpu, Interrupt::Input)? {
+            self.previous_keys = input;
+        }
+
+        let mut instructions = 0;
+        for line in 0..LINES_PER_FRAME {
+            if scanline::begin_line(&mut cpu.memory, line)? {
+                raise(cpu, Interrupt::Scanline)?;
+            }
+
+            // lines past the visible ones are part of the vertical blank and have nothing to latch
+            if let Some(state) = self.background.get_mut(line) {
+                *state = BackgroundState::latch(&cpu.memory)?;
+            }
+
+            let cycles = scanline::line_cycles(self.cycles_per_frame, line);
+            let ran = match cpu.run_bounded(cycles as u64)? {
+                // the frame ends with the program, the interrupts and the apu never see it
+                RunOutcome::Halt { instructions: ran, .. } => {
+                    self.halted = true;
+                    return Ok(Some(instructions + ran as usize));
+                }
+                // the limit is only reached once every instruction of the line ran
+                RunOutcome::InstructionLimitReached(_) => cycles,
+            };
+            instructions += ran;
+        }
+
+        // published before the after frame interrupt, so its handler can react to them
+        Collisions::detect(&cpu.memory, &self.background)?.publish(&mut cpu.memory)?;
+
+        cpu.memory.write(INPUT_MEM_LOC.0, KeyStatus::reset())?;
+        raise(cpu, Interrupt::AfterFrame)?;
+        self.apu.tick(&cpu.memory, CLOCK_CYCLE)?;
+
+        Ok(Some(instructions))
+    }
+
+    /// Rasterizes what memory holds right now into the frame buffer.
+    fn compose(&mut self) -> Result<()> {
+        let dirty = self.take_dirty();
+        self.rasterizer
+            .rasterize(&mut self.cpu.memory, &self.background, &dirty)?;
+        self.rasterizer.copy_rgba(&mut self.frame);
+        Ok(())
+    }
+
+    /// What was written to memory since the last time it was drawn.
+    pub(crate) fn take_dirty(&mut self) -> DirtyRegions {
+        self.cpu.memory.take_dirty()
+    }
+
+    /// Draws what memory holds right now on a frontend renderer instead of the frame buffer,
+    /// `dirty` being what was written since the renderer last drew.
+    pub(crate) fn draw_on(&mut self, renderer: &mut impl Renderer, dirty: &DirtyRegions) -> Result<()> {
+        renderer.draw_frame(&mut self.cpu.memory, &self.background, dirty)?;
+        Ok(())
+    }
+
+    /// Last composed frame, black until the first frame is run.
+    pub fn frame(&self) -> &[u8] {
+        &self.frame
+    }
+
+    /// Reads a byte of console memory without the side effects a read from the cpu could have.
+    pub fn peek(&self, address: u16) -> aya_cpu::memory::Result<u8> {
+        self.cpu.memory.peek(address)
+    }
+
+    /// Reads `size` bytes of console memory starting at `address`, without side effects.
+    pub fn peek_range(&self, address: u16, size: u16) -> aya_cpu::memory::Result<Vec<u8>> {
+        (0..size)
+            .map(|offset| self.peek(address.wrapping_add(offset)))
+            .collect()
+    }
+
+    /// The console cpu, for inspecting its registers and memory.
+    pub fn cpu(&self) -> &Cpu<MemoryMapper> {
+        &self.cpu
+    }
+
+    pub(crate) fn cpu_mut(&mut self) -> &mut Cpu<MemoryMapper> {
+        &mut self.cpu
+    }
+
+    pub(crate) fn apu_mut(&mut self) -> &mut Apu {
+        &mut self.apu
+    }
+
+    /// Writes every instruction the cpu runs from now on to `trace`, or stops tracing when `None`.
+    pub fn set_trace(&mut self, trace: Option<TraceWriter>) {
+        self.cpu.set_trace(trace);
+    }
+
+    /// Whether the program halted, after which frames are no longer run.
+    pub fn is_halted(&self) -> bool {
+        self.halted
+    }
+
+    /// Name of the rom as written on its header.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Name of the rom followed by its author, when the rom has one.
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Colors the rom is drawn with, its own palette or the console one.
+    pub fn palette(&self) -> &[(u8, u8, u8, u8)] {
+        &self.palette
+    }
+
+    /// Checksum of the rom contents, identifying which rom a save state belongs to.
+    pub fn checksum(&self) -> u32 {
+        self.checksum
+    }
+}
+
+/// Cycles a frame of `rom` runs, roms that don't say, such as version 1 ones, run at the console
+/// clock.
+fn cycles_per_frame(rom: &Rom) -> usize {
+    match rom.cycles_per_frame {
+        0 => CLOCK_CYCLE,
+        cycles => cycles as usize,
+    }
+}
+
+/// Raises `interrupt` unless a handler is still running, which entering another one would
+/// abandon half way. Returns whether it was raised.
+fn raise(cpu: &mut Cpu<MemoryMapper>, interrupt: Interrupt) -> Result<bool> {
+    if cpu.in_interrupt() {
+        return Ok(false);
+    }
+
+    cpu.handle_interrupt(interrupt)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use aya_cpu::instruction::Instruction;
+    use aya_cpu::register::Register;
+
+    use super::*;
+    use crate::memory::serial::SerialMem;
+    use crate::memory::{SizeError, CODE_MEM_LOC, INTERRUPT_MEM_LOC, SPRITE_MEMORY, SPRITE_MEM_LOC, TILE_MEMORY};
+    use crate::renderer::SCREEN_WIDTH;
+    use crate::scanline::CYCLES_PER_LINE;
+    use crate::tests::test_rom;
+    use crate::Error;
+
+    #[test]
+    fn test_step_frame_returns_rgba() {
+        let rom = include_bytes!("../../samples/build/game");
+        let mut emulator = Emulator::new(rom, &EmulatorOptions::default()).unwrap();
+        assert!(emulator.frame().iter().all(|&byte| byte == 0 || byte == 0xff));
+
+        let frame = emulator.step_frame(KeyStatus::reset()).unwrap();
+        assert_eq!(frame.len(), SCREEN_WIDTH * SCREEN_HEIGHT * 4);
+        assert!(frame.chunks(4).all(|pixel| pixel[3] == 0xff));
+        assert!(!emulator.is_halted());
+    }
+
+    #[test]
+    fn test_run_frame_counts_instructions() {
+        let rom = include_bytes!("../../samples/build/game");
+        let mut emulator = Emulator::new(rom, &EmulatorOptions::default()).unwrap();
+        let instructions = emulator.run_frame(KeyStatus::reset()).unwrap();
+        assert_eq!(instructions, Some(LINES_PER_FRAME * CYCLES_PER_LINE));
+
+        // the frame the program halts on counts what ran before the halt, later ones run nothing
+        let (serial, _) = SerialMem::capture();
+        let rom = include_bytes!("../../samples/build/serial");
+        let mut emulator = Emulator::with_serial(rom, &EmulatorOptions::default(), serial).unwrap();
+        let instructions = emulator.run_frame(KeyStatus::reset()).unwrap();
+        assert!(matches!(instructions, Some(ran) if ran > 0 && ran < LINES_PER_FRAME * CYCLES_PER_LINE));
+        assert!(emulator.is_halted());
+        assert_eq!(emulator.run_frame(KeyStatus::reset()).unwrap(), None);
+    }
+
+    #[test]
+    fn test_frames_run_the_cycles_of_the_rom() {
+        let code = Instruction::Jmp(0x0000.into()).encode();
+        let rom = Rom {
+            cycles_per_frame: 130,
+            ..test_rom(&code)
+        };
+        let mut emulator = Emulator::new(&rom.to_bytes().unwrap(), &EmulatorOptions::default()).unwrap();
+        assert_eq!(emulator.run_frame(KeyStatus::reset()).unwrap(), Some(130));
+
+        // roms that don't say run at the console clock
+        let rom = test_rom(&code).to_bytes().unwrap();
+        let mut emulator = Emulator::new(&rom, &EmulatorOptions::default()).unwrap();
+        assert_eq!(emulator.run_frame(KeyStatus::reset()).unwrap(), Some(CLOCK_CYCLE));
+    }
+
+    #[test]
+    fn test_frames_are_deterministic() {
+        let rom = include_bytes!("../../samples/build/game");
+        let mut first = Emulator::new(rom, &EmulatorOptions::default()).unwrap();
+        let mut second = Emulator::new(rom, &EmulatorOptions::default()).unwrap();
+
+        for _ in 0..10 {
+            let expected = first.step_frame(KeyStatus::reset()).unwrap().to_vec();
+            assert_eq!(second.step_frame(KeyStatus::reset()).unwrap(), expected);
+        }
+        assert_eq!(
+            first.peek_range(0, 0x100).unwrap(),
+            second.peek_range(0, 0x100).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_after_frame_handler_runs() {
+        const SQUARE1_VOLUME: u16 = 0x677F;
+        let rom = include_bytes!("../../samples/build/jingle");
+        let mut emulator = Emulator::new(rom, &EmulatorOptions::default()).unwrap();
+        assert_eq!(emulator.peek(SQUARE1_VOLUME).unwrap(), 0);
+
+        // interrupts are enabled from the start, the handler entered at the end of the first frame
+        // plays the first note
+        emulator.step_frame(KeyStatus::reset()).unwrap();
+        emulator.step_frame(KeyStatus::reset()).unwrap();
+        assert_eq!(emulator.peek(SQUARE1_VOLUME).unwrap(), 0x0F);
+    }
+
+    /// An emulator running a program that idles, with a handler for `interrupt` that takes about
+    /// two frames to run. The handler counts its entries on r5 and its exits on r6, which handlers
+    /// don't restore.
+    fn busy_handler(interrupt: Interrupt) -> Emulator {
+        let main = Instruction::Jmp(0x0000.into()).encode();
+        let entry = [
+            Instruction::IncReg(Register::R5),
+            Instruction::MovLitReg(Register::Acc, 0x0800),
+        ];
+        let entry = entry.iter().flat_map(Instruction::encode).collect::<Vec<_>>();
+        let (handler_offset, countdown) = (main.len() as u16, (main.len() + entry.len()) as u16);
+        let handler = [
+            Instruction::DecReg(Register::Acc),
+            Instruction::JneLit(countdown.into(), 0),
+            Instruction::IncReg(Register::R6),
+            Instruction::Rti,
+        ];
+        let code = [main, entry, handler.iter().flat_map(Instruction::encode).collect()].concat();
+
+        let rom = test_rom(&code).to_bytes().unwrap();
+        let mut emulator = Emulator::new(&rom, &EmulatorOptions::default()).unwrap();
+        let table_entry = INTERRUPT_MEM_LOC.0 + u16::from(interrupt) * 2;
+        emulator
+            .cpu_mut()
+            .memory
+            .write_word(table_entry, CODE_MEM_LOC.0 + handler_offset)
+            .unwrap();
+        emulator
+    }
+
+    #[test]
+    fn test_scanline_interrupt_waits_for_the_running_handler() {
+        let mut emulator = busy_handler(Interrupt::Scanline);
+
+        // the line compare register starts at 0, so the first line of every frame raises it
+        for _ in 0..3 {
+            emulator.step_frame(KeyStatus::reset()).unwrap();
+        }
+        assert_eq!(emulator.cpu().registers.fetch(Register::R5), 1);
+        assert_eq!(emulator.cpu().registers.fetch(Register::R6), 1);
+    }
+
+    #[test]
+    fn test_input_interrupt_is_held_until_the_handler_returns() {
+        let mut emulator = busy_handler(Interrupt::Input);
+        let right = KeyStatus::reset().with_right();
+        emulator.step_frame(KeyStatus::reset().with_left()).unwrap();
+        assert_eq!(emulator.cpu().registers.fetch(Register::R5), 1);
+
+        // the keys change while the handler runs, which leaves it on the third frame
+        emulator.step_frame(right).unwrap();
+        emulator.step_frame(right).unwrap();
+        assert_eq!(emulator.cpu().registers.fetch(Register::R5), 1);
+        assert_eq!(emulator.cpu().registers.fetch(Register::R6), 1);
+
+        // the change is raised on the first frame that starts outside of the handler
+        emulator.step_frame(right).unwrap();
+        assert_eq!(emulator.cpu().registers.fetch(Register::R5), 2);
+    }
+
+    #[test]
+    fn test_gradient_is_drawn_on_the_framebuffer() {
+        let rom = include_bytes!("../../samples/build/gradient");
+        let mut emulator = Emulator::new(rom, &EmulatorOptions::default()).unwrap();
+
+        // filling the whole framebuffer takes a few frames
+        for _ in 0..60 {
+            emulator.step_frame(KeyStatus::reset()).unwrap();
+        }
+        let frame = emulator.step_frame(KeyStatus::reset()).unwrap().to_vec();
+
+        // every 7 rows are drawn with the next color of the palette
+        let palette = emulator.palette();
+        for (row, pixels) in frame.chunks(SCREEN_WIDTH * 4).enumerate() {
+            let (r, g, b, _) = palette[row / 7];
+            let expected = if row < 7 { [0, 0, 0, 0xff] } else { [r, g, b, 0xff] };
+            assert!(pixels.chunks(4).all(|pixel| pixel == expected));
+        }
+    }
+
+    #[test]
+    fn test_oversized_sprites_are_reported() {
+        let sprites = vec![0; TILE_MEMORY + 1];
+        let rom = Rom {
+            sprites: sprites.into(),
+            ..test_rom(&[0xFF, 0x00])
+        };
+
+        let err = Emulator::new(&rom.to_bytes().unwrap(), &EmulatorOptions::default()).unwrap_err();
+        let expected = SizeError {
+            capacity: TILE_MEMORY,
+            length: TILE_MEMORY + 1,
+        };
+        assert!(matches!(err, Error::Sprites(err) if err == expected));
+        assert_eq!(
+            err.to_string(),
+            "rom sprites don't fit on tile memory, 8193 bytes don't fit on a memory of 8192 bytes"
+        );
+    }
+
+    #[test]
+    fn test_frame_hash_is_stable() {
+        assert_eq!(frame_hash(&[]), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(frame_hash(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_ne!(frame_hash(&[0, 0, 0, 0xff]), frame_hash(&[0, 0, 0xff, 0xff]));
+    }
+
+    #[test]
+    fn test_reload_starts_the_new_rom_over() {
+        let (serial, output) = SerialMem::capture();
+        let game = include_bytes!("../../samples/build/game");
+        let mut emulator = Emulator::with_serial(game, &EmulatorOptions::default(), serial).unwrap();
+        for _ in 0..5 {
+            emulator.step_frame(KeyStatus::reset()).unwrap();
+        }
+
+        let rom = include_bytes!("../../samples/build/serial");
+        emulator.reload(rom, &ReloadOptions::default()).unwrap();
+        assert_eq!(emulator.cpu().registers.fetch(Register::IP), CODE_MEM_LOC.0);
+        assert_eq!(emulator.checksum(), Rom::from_bytes(rom, true).unwrap().checksum());
+
+        // the serial port is kept, so the output of the new rom goes where the old one went
+        emulator.step_frame(KeyStatus::reset()).unwrap();
+        assert!(emulator.is_halted());
+        drop(emulator);
+        assert_eq!(output.contents(), "hello\n");
+    }
+
+    #[test]
+    fn test_reload_keeps_ip_and_ram() {
+        let rom = include_bytes!("../../samples/build/game");
+        let mut emulator = Emulator::new(rom, &EmulatorOptions::default()).unwrap();
+        for _ in 0..5 {
+            emulator.step_frame(KeyStatus::reset()).unwrap();
+        }
+        let state = emulator.cpu().snapshot();
+        let sprites = emulator.peek_range(SPRITE_MEM_LOC.0, SPRITE_MEMORY as u16).unwrap();
+
+        let options = ReloadOptions {
+            keep_ip: true,
+            keep_ram: true,
+        };
+        emulator.reload(rom, &options).unwrap();
+        assert_eq!(emulator.cpu().snapshot(), state);
+        assert_eq!(
+            emulator.peek_range(SPRITE_MEM_LOC.0, SPRITE_MEMORY as u16).unwrap(),
+            sprites
+        );
+
+        emulator.reload(rom, &ReloadOptions::default()).unwrap();
+        assert_ne!(emulator.cpu().snapshot(), state);
+        assert!(emulator
+            .peek_range(SPRITE_MEM_LOC.0, SPRITE_MEMORY as u16)
+            .unwrap()
+            .iter()
+            .all(|&byte| byte == 0));
+    }
+
+    #[test]
+    fn test_reload_half_written_rom() {
+        let rom = include_bytes!("../../samples/build/game");
+        let mut emulator = Emulator::new(rom, &EmulatorOptions::default()).unwrap();
+        emulator.step_frame(KeyStatus::reset()).unwrap();
+        let state = emulator.cpu().snapshot();
+
+        let serial = include_bytes!("../../samples/build/serial");
+        assert!(emulator
+            .reload(&serial[..serial.len() / 2], &ReloadOptions::default())
+            .is_err());
+        assert_eq!(emulator.cpu().snapshot(), state);
+        assert_eq!(emulator.checksum(), Rom::from_bytes(rom, true).unwrap().checksum());
+    }
+
+    #[test]
+    fn test_halted_program_keeps_last_frame() {
+        let (serial, output) = SerialMem::capture();
+        let rom = include_bytes!("../../samples/build/serial");
+        let mut emulator = Emulator::with_serial(rom, &EmulatorOptions::default(), serial).unwrap();
+
+        let frame = emulator.step_frame(KeyStatus::reset()).unwrap().to_vec();
+        assert!(emulator.is_halted());
+        assert_eq!(emulator.step_frame(KeyStatus::reset()).unwrap(), frame);
+
+        drop(emulator);
+        assert_eq!(output.contents(), "hello\n");
+    }
+}
@@ -0,0 +1,99 @@
+use std::path::PathBuf;
+
+use crate::memory::{SizeError, CODE_MEMORY};
+use crate::renderer;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Everything that can go wrong while loading or running a rom.
+#[derive(Debug)]
+pub enum Error {
+    /// A file couldn't be read or created, such as the rom or the serial log
+    Io { path: PathBuf, source: std::io::Error },
+    /// The rom file is malformed
+    Rom(aya_rom::RomError),
+    /// The rom sprites don't fit on tile memory
+    Sprites(SizeError),
+    /// The rom initial background doesn't fit on background memory
+    Background(SizeError),
+    /// The rom initial interface doesn't fit on interface memory
+    Interface(SizeError),
+    /// The rom data sections don't fit on code memory along with the code
+    Data { code: usize, data: usize },
+    /// The program did something the cpu can't execute
+    Cpu(aya_cpu::error::Error),
+    /// A device of the console, such as the input latch or the audio registers, couldn't be
+    /// accessed
+    Memory(aya_cpu::memory::Error),
+    /// A frame couldn't be drawn or presented
+    Render(renderer::Error),
+    /// A screenshot couldn't be encoded as a bitmap
+    Screenshot(aya_bitmap::Error),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Io { path, source } => write!(f, "{}: {source}", path.display()),
+            Error::Rom(err) => write!(f, "invalid rom: {err}"),
+            Error::Sprites(err) => write!(f, "rom sprites don't fit on tile memory, {err}"),
+            Error::Background(err) => write!(f, "rom background doesn't fit on background memory, {err}"),
+            Error::Interface(err) => write!(f, "rom interface doesn't fit on interface memory, {err}"),
+            Error::Data { code, data } => write!(
+                f,
+                "rom data doesn't fit on code memory, {data} bytes of data and {code} bytes of code are over the {CODE_MEMORY} bytes it holds"
+            ),
+            Error::Cpu(err) => write!(f, "cpu fault: {err}"),
+            Error::Memory(err) => write!(f, "{err}"),
+            Error::Render(err) => write!(f, "{err}"),
+            Error::Screenshot(err) => write!(f, "screenshot couldn't be encoded, {err}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            Error::Rom(err) => Some(err),
+            Error::Sprites(err) => Some(err),
+            Error::Background(err) => Some(err),
+            Error::Interface(err) => Some(err),
+            Error::Data { .. } => None,
+            Error::Cpu(err) => Some(err),
+            Error::Memory(err) => Some(err),
+            Error::Render(err) => Some(err),
+            Error::Screenshot(err) => Some(err),
+        }
+    }
+}
+
+impl From<aya_rom::RomError> for Error {
+    fn from(err: aya_rom::RomError) -> Self {
+        Self::Rom(err)
+    }
+}
+
+impl From<SizeError> for Error {
+    fn from(err: SizeError) -> Self {
+        Self::Sprites(err)
+    }
+}
+
+impl From<aya_cpu::error::Error> for Error {
+    fn from(err: aya_cpu::error::Error) -> Self {
+        Self::Cpu(err)
+    }
+}
+
+impl From<aya_cpu::memory::Error> for Error {
+    fn from(err: aya_cpu::memory::Error) -> Self {
+        Self::Memory(err)
+    }
+}
+
+impl From<renderer::Error> for Error {
+    fn from(err: renderer::Error) -> Self {
+        Self::Render(err)
+    }
+}
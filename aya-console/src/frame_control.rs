@@ -0,0 +1,67 @@
+use aya_cpu::memory::{Addressable, Result};
+
+use crate::memory::FRAME_CONTROL_MEM_LOC;
+
+/// Largest frame skip a program can request, presenting every 8th frame.
+pub const MAX_FRAME_SKIP: u8 = 8;
+
+const FRAME_SKIP_OFFSET: u16 = 0;
+const MEASURED_FPS_OFFSET: u16 = 1;
+
+/// Reads how many frames pass for every presented one, values outside of `1..=MAX_FRAME_SKIP` are
+/// clamped and written back so the program reads the mode the console is actually in.
+pub fn frame_skip(memory: &mut impl Addressable) -> Result<u8> {
+    let address = FRAME_CONTROL_MEM_LOC.0 + FRAME_SKIP_OFFSET;
+    let requested = memory.read(address)?;
+    let frame_skip = requested.clamp(1, MAX_FRAME_SKIP);
+    if frame_skip != requested {
+        memory.write(address, frame_skip)?;
+    }
+    Ok(frame_skip)
+}
+
+/// Publishes the measured rate of presented frames, rounded to whole frames per second.
+pub fn report_fps(memory: &mut impl Addressable, fps: f32) -> Result<()> {
+    memory.write(FRAME_CONTROL_MEM_LOC.0 + MEASURED_FPS_OFFSET, fps.round() as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::memory_mapper::{FrameControlMem, MappingMode, MemoryMapper};
+    use crate::memory::LinearMemory;
+
+    fn memory() -> MemoryMapper {
+        let mut memory = MemoryMapper::default();
+        memory
+            .map(
+                FrameControlMem::from(LinearMemory::default()),
+                FRAME_CONTROL_MEM_LOC.0,
+                FRAME_CONTROL_MEM_LOC.1,
+                MappingMode::Remap,
+            )
+            .unwrap();
+        memory
+    }
+
+    #[test]
+    fn test_frame_skip_is_clamped() {
+        let mut memory = memory();
+        assert_eq!(frame_skip(&mut memory).unwrap(), 1);
+        assert_eq!(memory.read(FRAME_CONTROL_MEM_LOC.0).unwrap(), 1);
+
+        memory.write(FRAME_CONTROL_MEM_LOC.0, 2).unwrap();
+        assert_eq!(frame_skip(&mut memory).unwrap(), 2);
+
+        memory.write(FRAME_CONTROL_MEM_LOC.0, 200).unwrap();
+        assert_eq!(frame_skip(&mut memory).unwrap(), MAX_FRAME_SKIP);
+        assert_eq!(memory.read(FRAME_CONTROL_MEM_LOC.0).unwrap(), MAX_FRAME_SKIP);
+    }
+
+    #[test]
+    fn test_report_fps() {
+        let mut memory = memory();
+        report_fps(&mut memory, 29.6).unwrap();
+        assert_eq!(memory.read(FRAME_CONTROL_MEM_LOC.1).unwrap(), 30);
+    }
+}
@@ -0,0 +1,77 @@
+use aya_cpu::memory::{Addressable, Result};
+
+use crate::memory::{FRAMEBUFFER_MEMORY, FRAMEBUFFER_MEM_LOC, VIDEO_MODE_MEM_LOC};
+use crate::renderer::{SCREEN_HEIGHT, SCREEN_WIDTH};
+
+/// Value of the video mode register that draws the screen from the framebuffer.
+pub const FRAMEBUFFER_MODE: u8 = 1;
+
+// two pixels are packed on every byte of the framebuffer
+const _: () = assert!(FRAMEBUFFER_MEMORY * 2 == SCREEN_WIDTH * SCREEN_HEIGHT);
+
+/// Where the screen is drawn from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VideoMode {
+    /// The background, sprite and interface layers, built from tiles
+    Tiles,
+    /// The framebuffer, replacing every tile layer
+    Framebuffer,
+}
+
+impl VideoMode {
+    /// Reads the video mode register, any value other than [`FRAMEBUFFER_MODE`] draws tiles.
+    pub fn read(memory: &impl Addressable) -> Result<Self> {
+        match memory.peek(VIDEO_MODE_MEM_LOC.0)? {
+            FRAMEBUFFER_MODE => Ok(VideoMode::Framebuffer),
+            _ => Ok(VideoMode::Tiles),
+        }
+    }
+}
+
+/// Palette index of every pixel on the framebuffer, row by row from the top left corner. Pixels
+/// are packed two per byte with the left one on the upper nibble, like on tiles.
+pub fn pixels(memory: &impl Addressable) -> Result<Vec<u8>> {
+    let mut pixels = Vec::with_capacity(SCREEN_WIDTH * SCREEN_HEIGHT);
+    for offset in 0..FRAMEBUFFER_MEMORY as u16 {
+        let byte = memory.peek(FRAMEBUFFER_MEM_LOC.0 + offset)?;
+        pixels.extend([byte >> 4, byte & 0xf]);
+    }
+    Ok(pixels)
+}
+
+#[cfg(test)]
+mod tests {
+    use aya_cpu::MEMORY_SIZE;
+
+    use super::*;
+    use crate::memory::LinearMemory;
+
+    #[test]
+    fn test_video_mode() {
+        let mut memory = LinearMemory::<MEMORY_SIZE>::default();
+        assert_eq!(VideoMode::read(&memory).unwrap(), VideoMode::Tiles);
+
+        memory.write(VIDEO_MODE_MEM_LOC.0, FRAMEBUFFER_MODE).unwrap();
+        assert_eq!(VideoMode::read(&memory).unwrap(), VideoMode::Framebuffer);
+
+        memory.write(VIDEO_MODE_MEM_LOC.0, 7).unwrap();
+        assert_eq!(VideoMode::read(&memory).unwrap(), VideoMode::Tiles);
+    }
+
+    #[test]
+    fn test_pixels() {
+        let mut memory = LinearMemory::<MEMORY_SIZE>::default();
+        memory.write(FRAMEBUFFER_MEM_LOC.0, 0x12).unwrap();
+        // the first byte of the second row
+        memory
+            .write(FRAMEBUFFER_MEM_LOC.0 + SCREEN_WIDTH as u16 / 2, 0xF0)
+            .unwrap();
+        memory.write(FRAMEBUFFER_MEM_LOC.1, 0x0A).unwrap();
+
+        let pixels = pixels(&memory).unwrap();
+        assert_eq!(pixels.len(), SCREEN_WIDTH * SCREEN_HEIGHT);
+        assert_eq!(pixels[..3], [1, 2, 0]);
+        assert_eq!(pixels[SCREEN_WIDTH..SCREEN_WIDTH + 2], [0xF, 0]);
+        assert_eq!(pixels[pixels.len() - 2..], [0, 0xA]);
+    }
+}
@@ -0,0 +1,95 @@
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Frames between two looks at the rom file, about four times a second
+const POLL_FRAMES: u32 = 15;
+
+/// Modification time and size of a rom file, a rom that changed has a different version
+type Version = (SystemTime, u64);
+
+/// Watches a rom file for new versions by polling it every few frames, so it works the same on
+/// every platform without a file watching service.
+#[derive(Debug)]
+pub struct RomWatcher {
+    path: PathBuf,
+    frames: u32,
+    /// Version last loaded, the running one
+    loaded: Option<Version>,
+    /// Last version that failed to load, so its error is only reported once
+    failed: Option<Version>,
+}
+
+impl RomWatcher {
+    /// Watches `path`, whose current version is the one running.
+    pub fn new(path: PathBuf) -> Self {
+        let loaded = version(&path);
+        Self {
+            path,
+            frames: 0,
+            loaded,
+            failed: None,
+        }
+    }
+
+    /// Contents of the rom file when it changed since the last version that loaded. A version
+    /// that fails to load is read again on the next poll, as it may have been caught half written.
+    pub fn poll(&mut self) -> Option<(Vec<u8>, Version)> {
+        self.frames += 1;
+        if self.frames < POLL_FRAMES {
+            return None;
+        }
+        self.frames = 0;
+
+        let version = version(&self.path).filter(|version| Some(*version) != self.loaded)?;
+        let rom = std::fs::read(&self.path).ok()?;
+        Some((rom, version))
+    }
+
+    pub fn loaded(&mut self, version: Version) {
+        self.loaded = Some(version);
+        self.failed = None;
+    }
+
+    /// Records that `version` failed to load, returns whether it's the first time it did.
+    pub fn failed(&mut self, version: Version) -> bool {
+        self.failed.replace(version) != Some(version)
+    }
+}
+
+fn version(path: &Path) -> Option<Version> {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn poll(watcher: &mut RomWatcher) -> Option<(Vec<u8>, Version)> {
+        (0..POLL_FRAMES).find_map(|_| watcher.poll())
+    }
+
+    #[test]
+    fn test_watch_rom_file() {
+        let path = std::env::temp_dir().join(format!("aya-hot-reload-{}", std::process::id()));
+        std::fs::write(&path, [1, 2, 3]).unwrap();
+        let mut watcher = RomWatcher::new(path.clone());
+        assert_eq!(poll(&mut watcher), None);
+
+        // a size change is a new version even when the modification time didn't move
+        std::fs::write(&path, [1, 2, 3, 4]).unwrap();
+        let (rom, version) = poll(&mut watcher).unwrap();
+        assert_eq!(rom, [1, 2, 3, 4]);
+
+        // until it loads, the same version is read again
+        assert!(watcher.failed(version));
+        assert!(poll(&mut watcher).is_some());
+        assert!(!watcher.failed(version));
+
+        watcher.loaded(version);
+        assert_eq!(poll(&mut watcher), None);
+
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(poll(&mut watcher), None);
+    }
+}
@@ -1,13 +1,74 @@
+#[cfg(feature = "renderer-raylib")]
 mod raylib;
+#[cfg(feature = "renderer-soft")]
+mod soft;
+#[cfg(feature = "renderer-web")]
+mod web;
 
+#[cfg(feature = "renderer-raylib")]
 pub use raylib::RaylibInput;
+#[cfg(feature = "renderer-soft")]
+pub use soft::SoftInput;
+#[cfg(feature = "renderer-web")]
+pub use web::WebInput;
+
+macro_rules! buttons {
+    ($(($const:ident, $bit:expr, $name:expr, $is:ident, $with:ident)),* $(,)?) => {
+        impl KeyStatus {
+            $(
+                #[doc = concat!("Bit of the ", $name, " button on the input byte.")]
+                pub const $const: u8 = $bit;
+            )*
+
+            /// Bit and name of every button, from the highest bit to the lowest.
+            pub const BUTTONS: [(u8, &'static str); 8] = [$((Self::$const, $name)),*];
+
+            $(
+                #[doc = concat!("Whether the ", $name, " button is pressed.")]
+                pub fn $is(&self) -> bool {
+                    self.is_pressed(Self::$const)
+                }
+
+                #[doc = concat!("The same status with the ", $name, " button pressed.")]
+                pub fn $with(mut self) -> Self {
+                    self.mask_on(Self::$const);
+                    self
+                }
+            )*
+        }
+    };
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct KeyStatus(u8);
 
+buttons! {
+    (LEFT, 7, "left", is_left, with_left),
+    (DOWN, 6, "down", is_down, with_down),
+    (UP, 5, "up", is_up, with_up),
+    (RIGHT, 4, "right", is_right, with_right),
+    (MAIN, 3, "main", is_main, with_main),
+    (SECONDARY, 2, "secondary", is_secondary, with_secondary),
+    (PAUSE, 1, "pause", is_pause, with_pause),
+    (SELECT, 0, "select", is_select, with_select),
+}
+
+/// Prints the names of the pressed buttons separated by spaces, or `none` when no button is.
 impl std::fmt::Display for KeyStatus {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.0)
+        let mut pressed = self.pressed().peekable();
+        if pressed.peek().is_none() {
+            return write!(f, "none");
+        }
+
+        for (idx, name) in pressed.enumerate() {
+            if idx > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{name}")?;
+        }
+
+        Ok(())
     }
 }
 
@@ -19,6 +80,30 @@ impl KeyStatus {
     pub fn mask_on(&mut self, bit: u8) {
         self.0 |= 1 << bit;
     }
+
+    pub fn is_pressed(&self, bit: u8) -> bool {
+        self.0 & (1 << bit) != 0
+    }
+
+    /// Names of the pressed buttons, from the highest bit to the lowest.
+    pub fn pressed(&self) -> impl Iterator<Item = &'static str> + '_ {
+        Self::BUTTONS
+            .iter()
+            .filter(|(bit, _)| self.is_pressed(*bit))
+            .map(|(_, name)| *name)
+    }
+}
+
+/// Source of `std/input.aya`, an assembly module with the bit and mask of every button as exported
+/// constants, so programs and the console always agree on the input byte.
+pub fn input_module() -> String {
+    let mut module = String::from("; generated from aya_console::KeyStatus, do not edit by hand\n");
+    for (bit, name) in KeyStatus::BUTTONS {
+        let name = name.to_uppercase();
+        module.push_str(&format!("+const {name} = ${bit:02X}\n"));
+        module.push_str(&format!("+const {name}_MASK = ${:02X}\n", 1u8 << bit));
+    }
+    module
 }
 
 impl From<KeyStatus> for u8 {
@@ -27,38 +112,83 @@ impl From<KeyStatus> for u8 {
     }
 }
 
+/// Console actions bound to keys, they are handled by the console itself and never reach the
+/// program being run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hotkey {
+    SaveState,
+    LoadState,
+    Screenshot,
+    Pause,
+    FrameAdvance,
+    Overlay,
+    Fullscreen,
+}
+
 pub trait Input {
     fn poll(&self) -> KeyStatus;
+    fn hotkey(&self) -> Option<Hotkey>;
 
     fn key_left_pressed(&self, status: &mut KeyStatus) {
-        status.mask_on(7);
+        status.mask_on(KeyStatus::LEFT);
     }
 
     fn key_down_pressed(&self, status: &mut KeyStatus) {
-        status.mask_on(6);
+        status.mask_on(KeyStatus::DOWN);
     }
 
     fn key_up_pressed(&self, status: &mut KeyStatus) {
-        status.mask_on(5);
+        status.mask_on(KeyStatus::UP);
     }
 
     fn key_right_pressed(&self, status: &mut KeyStatus) {
-        status.mask_on(4);
+        status.mask_on(KeyStatus::RIGHT);
     }
 
     fn key_main_pressed(&self, status: &mut KeyStatus) {
-        status.mask_on(3);
+        status.mask_on(KeyStatus::MAIN);
     }
 
     fn key_secondary_pressed(&self, status: &mut KeyStatus) {
-        status.mask_on(2);
+        status.mask_on(KeyStatus::SECONDARY);
     }
 
     fn key_pause_pressed(&self, status: &mut KeyStatus) {
-        status.mask_on(1);
+        status.mask_on(KeyStatus::PAUSE);
     }
 
     fn key_select_pressed(&self, status: &mut KeyStatus) {
-        status.mask_on(0);
+        status.mask_on(KeyStatus::SELECT);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_button_accessors() {
+        let status = KeyStatus::reset().with_left().with_select();
+        assert_eq!(u8::from(status), 0b1000_0001);
+        assert!(status.is_left() && status.is_select());
+        assert!(!status.is_right() && !status.is_main());
+        assert!(status.is_pressed(KeyStatus::LEFT));
+    }
+
+    #[test]
+    fn test_display_pressed_buttons() {
+        assert_eq!(KeyStatus::reset().to_string(), "none");
+        assert_eq!(KeyStatus::reset().with_main().with_up().to_string(), "up main");
+    }
+
+    #[test]
+    fn test_input_module_is_up_to_date() {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../std/input.aya");
+        if std::env::var_os("AYA_UPDATE_STD").is_some() {
+            std::fs::write(&path, input_module()).unwrap();
+        }
+
+        let module = std::fs::read_to_string(&path).unwrap_or_default();
+        assert!(module == input_module(), "std/input.aya is out of date, run `just std`");
     }
 }
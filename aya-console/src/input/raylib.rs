@@ -1,6 +1,6 @@
 use raylib::ffi::KeyboardKey;
 
-use super::{Input, KeyStatus};
+use super::{Hotkey, Input, KeyStatus};
 use crate::renderer::raylib::{HANDLE, NO_DRAWING_HANDLE};
 
 #[derive(Default)]
@@ -49,4 +49,38 @@ impl Input for RaylibInput {
 
         key_status
     }
+
+    fn hotkey(&self) -> Option<Hotkey> {
+        let handle = HANDLE.get().expect(NO_DRAWING_HANDLE).read().expect(NO_DRAWING_HANDLE);
+
+        if handle.is_key_pressed(KeyboardKey::KEY_F5) {
+            return Some(Hotkey::SaveState);
+        }
+
+        if handle.is_key_pressed(KeyboardKey::KEY_F8) {
+            return Some(Hotkey::LoadState);
+        }
+
+        if handle.is_key_pressed(KeyboardKey::KEY_F12) {
+            return Some(Hotkey::Screenshot);
+        }
+
+        if handle.is_key_pressed(KeyboardKey::KEY_P) {
+            return Some(Hotkey::Pause);
+        }
+
+        if handle.is_key_pressed(KeyboardKey::KEY_N) {
+            return Some(Hotkey::FrameAdvance);
+        }
+
+        if handle.is_key_pressed(KeyboardKey::KEY_F3) {
+            return Some(Hotkey::Overlay);
+        }
+
+        if handle.is_key_pressed(KeyboardKey::KEY_F11) {
+            return Some(Hotkey::Fullscreen);
+        }
+
+        None
+    }
 }
@@ -0,0 +1,89 @@
+use minifb::{Key, KeyRepeat};
+
+use super::{Hotkey, Input, KeyStatus};
+use crate::renderer::soft::{NO_WINDOW, WINDOW};
+
+#[derive(Default)]
+pub struct SoftInput;
+
+impl Input for SoftInput {
+    fn poll(&self) -> KeyStatus {
+        let mut key_status = KeyStatus(0);
+
+        WINDOW.with(|cell| {
+            let window = cell.borrow();
+            let window = window.as_ref().expect(NO_WINDOW);
+
+            if window.is_key_down(Key::A) | window.is_key_down(Key::Left) {
+                self.key_left_pressed(&mut key_status);
+            }
+
+            if window.is_key_down(Key::S) | window.is_key_down(Key::Down) {
+                self.key_down_pressed(&mut key_status);
+            }
+
+            if window.is_key_down(Key::W) | window.is_key_down(Key::Up) {
+                self.key_up_pressed(&mut key_status);
+            }
+
+            if window.is_key_down(Key::D) | window.is_key_down(Key::Right) {
+                self.key_right_pressed(&mut key_status);
+            }
+
+            if window.is_key_down(Key::Space) {
+                self.key_main_pressed(&mut key_status);
+            }
+
+            if window.is_key_down(Key::C) {
+                self.key_secondary_pressed(&mut key_status);
+            }
+
+            if window.is_key_down(Key::Escape) {
+                self.key_pause_pressed(&mut key_status);
+            }
+
+            if window.is_key_down(Key::Tab) {
+                self.key_select_pressed(&mut key_status);
+            }
+        });
+
+        key_status
+    }
+
+    fn hotkey(&self) -> Option<Hotkey> {
+        WINDOW.with(|cell| {
+            let window = cell.borrow();
+            let window = window.as_ref().expect(NO_WINDOW);
+
+            if window.is_key_pressed(Key::F5, KeyRepeat::No) {
+                return Some(Hotkey::SaveState);
+            }
+
+            if window.is_key_pressed(Key::F8, KeyRepeat::No) {
+                return Some(Hotkey::LoadState);
+            }
+
+            if window.is_key_pressed(Key::F12, KeyRepeat::No) {
+                return Some(Hotkey::Screenshot);
+            }
+
+            if window.is_key_pressed(Key::P, KeyRepeat::No) {
+                return Some(Hotkey::Pause);
+            }
+
+            if window.is_key_pressed(Key::N, KeyRepeat::No) {
+                return Some(Hotkey::FrameAdvance);
+            }
+
+            if window.is_key_pressed(Key::F3, KeyRepeat::No) {
+                return Some(Hotkey::Overlay);
+            }
+
+            if window.is_key_pressed(Key::F11, KeyRepeat::No) {
+                return Some(Hotkey::Fullscreen);
+            }
+
+            None
+        })
+    }
+}
@@ -0,0 +1,150 @@
+use std::cell::RefCell;
+use std::collections::{HashSet, VecDeque};
+use std::rc::Rc;
+
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::JsCast;
+use web_sys::KeyboardEvent;
+
+use super::{Hotkey, Input, KeyStatus};
+
+type KeyListener = Closure<dyn FnMut(KeyboardEvent)>;
+
+/// Keys the page reported since the console last looked at them.
+#[derive(Debug, Default)]
+struct Keys {
+    /// `KeyboardEvent.code` of every key being held
+    held: HashSet<String>,
+    hotkeys: VecDeque<Hotkey>,
+}
+
+/// Console keys, by `KeyboardEvent.code`, mirroring the desktop bindings.
+fn is_console_key(code: &str) -> bool {
+    matches!(
+        code,
+        "KeyA"
+            | "ArrowLeft"
+            | "KeyS"
+            | "ArrowDown"
+            | "KeyW"
+            | "ArrowUp"
+            | "KeyD"
+            | "ArrowRight"
+            | "Space"
+            | "KeyC"
+            | "Escape"
+            | "Tab"
+    )
+}
+
+/// Save states and screenshots are written next to the rom, which the browser doesn't have, and
+/// the overlay times frames with a clock wasm doesn't provide, so only the hotkeys that need
+/// neither are bound.
+fn hotkey(code: &str) -> Option<Hotkey> {
+    match code {
+        "KeyP" => Some(Hotkey::Pause),
+        "KeyN" => Some(Hotkey::FrameAdvance),
+        "F11" => Some(Hotkey::Fullscreen),
+        _ => None,
+    }
+}
+
+/// Input read from keyboard events of the browser window, the listeners are registered when the
+/// input is created and removed once it is dropped.
+pub struct WebInput {
+    keys: Rc<RefCell<Keys>>,
+    key_down: KeyListener,
+    key_up: KeyListener,
+}
+
+impl Default for WebInput {
+    fn default() -> Self {
+        let keys = Rc::new(RefCell::new(Keys::default()));
+
+        let down_keys = keys.clone();
+        let key_down = Closure::<dyn FnMut(KeyboardEvent)>::new(move |event: KeyboardEvent| {
+            let code = event.code();
+            let mut keys = down_keys.borrow_mut();
+
+            if is_console_key(&code) {
+                // arrows and space would scroll the page, and tab would move the focus away
+                event.prevent_default();
+                keys.held.insert(code);
+            } else if let Some(hotkey) = hotkey(&code).filter(|_| !event.repeat()) {
+                event.prevent_default();
+                keys.hotkeys.push_back(hotkey);
+            }
+        });
+
+        let up_keys = keys.clone();
+        let key_up = Closure::<dyn FnMut(KeyboardEvent)>::new(move |event: KeyboardEvent| {
+            up_keys.borrow_mut().held.remove(&event.code());
+        });
+
+        if let Some(window) = web_sys::window() {
+            let registered = window
+                .add_event_listener_with_callback("keydown", key_down.as_ref().unchecked_ref())
+                .and_then(|_| window.add_event_listener_with_callback("keyup", key_up.as_ref().unchecked_ref()));
+            if let Err(err) = registered {
+                web_sys::console::error_1(&format!("failed to listen to the keyboard: {err:?}").into());
+            }
+        }
+
+        Self { keys, key_down, key_up }
+    }
+}
+
+impl Drop for WebInput {
+    fn drop(&mut self) {
+        if let Some(window) = web_sys::window() {
+            let _ = window.remove_event_listener_with_callback("keydown", self.key_down.as_ref().unchecked_ref());
+            let _ = window.remove_event_listener_with_callback("keyup", self.key_up.as_ref().unchecked_ref());
+        }
+    }
+}
+
+impl Input for WebInput {
+    fn poll(&self) -> KeyStatus {
+        let mut key_status = KeyStatus(0);
+        let keys = self.keys.borrow();
+        let held = |codes: &[&str]| codes.iter().any(|code| keys.held.contains(*code));
+
+        if held(&["KeyA", "ArrowLeft"]) {
+            self.key_left_pressed(&mut key_status);
+        }
+
+        if held(&["KeyS", "ArrowDown"]) {
+            self.key_down_pressed(&mut key_status);
+        }
+
+        if held(&["KeyW", "ArrowUp"]) {
+            self.key_up_pressed(&mut key_status);
+        }
+
+        if held(&["KeyD", "ArrowRight"]) {
+            self.key_right_pressed(&mut key_status);
+        }
+
+        if held(&["Space"]) {
+            self.key_main_pressed(&mut key_status);
+        }
+
+        if held(&["KeyC"]) {
+            self.key_secondary_pressed(&mut key_status);
+        }
+
+        if held(&["Escape"]) {
+            self.key_pause_pressed(&mut key_status);
+        }
+
+        if held(&["Tab"]) {
+            self.key_select_pressed(&mut key_status);
+        }
+
+        key_status
+    }
+
+    fn hotkey(&self) -> Option<Hotkey> {
+        self.keys.borrow_mut().hotkeys.pop_front()
+    }
+}
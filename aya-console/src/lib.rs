@@ -1,25 +1,83 @@
+// the web console and headless builds drive only part of the renderer and input abstractions the
+// desktop ones share
+#![cfg_attr(not(feature = "native"), allow(dead_code))]
+
+mod audio;
+mod collision;
+pub mod debugger;
+mod emulator;
+mod error;
+mod frame_control;
+mod framebuffer;
+#[cfg(feature = "native")]
+mod hot_reload;
 mod input;
+mod overlay;
 mod renderer;
-mod rom_loader;
+#[cfg(feature = "native")]
+mod savestate;
+mod scanline;
+#[cfg(feature = "native")]
+mod screenshot;
+#[cfg(feature = "renderer-web")]
+pub mod web;
 
-use std::path::Path;
+#[cfg(feature = "native")]
+use std::path::{Path, PathBuf};
+#[cfg(feature = "native")]
+use std::time::Instant;
 
-use aya_cpu::cpu::{ControlFlow, Cpu};
-use aya_cpu::memory::Addressable;
-use input::{Input, KeyStatus, RaylibInput};
+#[cfg(feature = "native")]
+use audio::Audio;
+#[cfg(feature = "renderer-raylib")]
+use audio::RaylibAudio;
+#[cfg(feature = "renderer-soft")]
+use audio::SilentAudio;
+use aya_cpu::cpu::Cpu;
+#[cfg(feature = "native")]
+use aya_cpu::trace::TraceWriter;
+use aya_rom::Rom;
+pub use emulator::{frame_hash, Emulator, EmulatorOptions, ReloadOptions};
+pub use error::{Error, Result};
+#[cfg(feature = "native")]
+use hot_reload::RomWatcher;
+#[cfg(feature = "renderer-raylib")]
+use input::RaylibInput;
+#[cfg(feature = "renderer-soft")]
+use input::SoftInput;
+pub use input::{input_module, KeyStatus};
+#[cfg(feature = "native")]
+use input::{Hotkey, Input};
 use memory::memory_mapper::{
-    BackgroundMem, InputMem, InterfaceMem, InterruptMem, MappingMode, MemoryMapper, ProgramMem, SpriteMem, StackMem,
-    TileMem,
+    AudioMem, BackgroundMem, BankMem, BankSelectMem, CollisionMem, Devices, FrameControlMem, FramebufferMem, InputMem,
+    InterfaceMem, InterruptMem, MappingMode, MemoryMapper, ProgramMem, SpriteMem, StackMem, TileMem, VideoMem,
+    VideoModeMem,
 };
+use memory::serial::SerialMem;
 use memory::{
-    Interrupt, LinearMemory, BG_MEMORY, BG_MEM_LOC, CODE_MEMORY, CODE_MEM_LOC, INPUT_MEMORY, INPUT_MEM_LOC,
-    INTERFACE_MEMORY, INTERRUPT_MEMORY, INTERRUPT_MEM_LOC, SPRITE_MEMORY, SPRITE_MEM_LOC, STACK_MEM_LOC, TILE_MEMORY,
-    TILE_MEM_LOC, UI_MEM_LOC,
+    data_addresses, LinearMemory, MemoryMap, RegionKind, AUDIO_MEMORY, BANK_SELECT_MEMORY, BG_MEMORY, CODE_MEMORY,
+    CODE_MEM_LOC, COLLISION_MEMORY, FRAMEBUFFER_MEMORY, FRAME_CONTROL_MEMORY, INPUT_MEMORY, INTERFACE_MEMORY,
+    INTERRUPT_MEMORY, INTERRUPT_MEM_LOC, SPRITE_MEMORY, STACK_MEMORY, STACK_START, TILE_MEMORY, VIDEO_MEMORY,
+    VIDEO_MODE_MEMORY,
 };
-use renderer::{RaylibRenderer, Renderer};
+#[cfg(feature = "native")]
+use overlay::{FpsCounter, FrameStats};
+#[cfg(feature = "native")]
+pub use renderer::Backend;
+#[cfg(feature = "renderer-raylib")]
+use renderer::RaylibRenderer;
+#[cfg(feature = "native")]
+use renderer::Renderer;
+#[cfg(feature = "renderer-soft")]
+use renderer::SoftRenderer;
+pub use renderer::{Error as RenderError, SCREEN_HEIGHT, SCREEN_WIDTH};
+#[cfg(feature = "native")]
+use savestate::SaveState;
 
-const CLOCK_CYCLE: usize = 2000;
+/// Cycles the cpu runs every frame
+pub const CLOCK_CYCLE: usize = 2000;
 const FPS: f32 = 60.0;
+pub const DEFAULT_SCALE: u16 = 4;
 
 pub mod memory;
 
@@ -42,122 +100,554 @@ pub static PALETTE: &[(u8, u8, u8, u8)] = &[
     (0xf6, 0x8b, 0x69, 0xff),
 ];
 
-pub fn run<P: AsRef<Path>>(rom_file: P) -> Result<(), Box<dyn std::error::Error>> {
-    let rom_file = std::fs::read(rom_file).unwrap();
-    let rom_file = rom_loader::load_from_file(&rom_file);
+/// Settings for running a rom, the defaults are what a player would want.
+#[cfg(feature = "native")]
+#[derive(Debug, Clone)]
+pub struct Options {
+    pub backend: Backend,
+    /// Initial window size as a multiple of the screen size, the window can be resized afterwards
+    pub scale: u16,
+    /// Runs roms even when their contents don't match the header checksum, useful while
+    /// developing tools that write roms
+    pub skip_checksum: bool,
+    /// Leaves code memory writable after the program is loaded, for self modifying programs
+    pub writable_code: bool,
+    /// Writes the serial port output to this file instead of stdout
+    pub serial_log: Option<PathBuf>,
+    /// Only draws the first sprites touching every line, like the sprite hardware of older
+    /// consoles did
+    pub sprite_line_limit: bool,
+    /// Writes a line for every instruction the cpu runs to this file, see [`TraceWriter`]
+    pub trace: Option<PathBuf>,
+    /// Loads the rom again whenever its file changes, without restarting the console
+    pub hot_reload: bool,
+    /// Carries on from where the program was when hot reloading, instead of starting it over
+    pub keep_ip: bool,
+    /// Keeps the memory the program drew on when hot reloading, such as backgrounds and sprites
+    pub keep_ram: bool,
+    /// Runs this many frames without a window, printing the [`frame_hash`] of each instead
+    pub frame_hash: Option<usize>,
+}
+
+#[cfg(feature = "native")]
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            backend: Backend::default(),
+            scale: DEFAULT_SCALE,
+            skip_checksum: false,
+            writable_code: false,
+            serial_log: None,
+            sprite_line_limit: false,
+            trace: None,
+            hot_reload: false,
+            keep_ip: false,
+            keep_ram: false,
+            frame_hash: None,
+        }
+    }
+}
+
+#[cfg(feature = "native")]
+impl Options {
+    /// The options that concern the console itself rather than the window it runs on.
+    pub fn emulator_options(&self) -> EmulatorOptions {
+        EmulatorOptions {
+            skip_checksum: self.skip_checksum,
+            writable_code: self.writable_code,
+            sprite_line_limit: self.sprite_line_limit,
+        }
+    }
+
+    pub fn reload_options(&self) -> ReloadOptions {
+        ReloadOptions {
+            keep_ip: self.keep_ip,
+            keep_ram: self.keep_ram,
+        }
+    }
+}
+
+#[cfg(feature = "native")]
+pub fn run<P: AsRef<Path>>(rom_file: P) -> Result<()> {
+    run_with_options(rom_file, &Options::default())
+}
+
+#[cfg(feature = "native")]
+pub fn run_with_options<P: AsRef<Path>>(rom_file: P, options: &Options) -> Result<()> {
+    if let Some(frames) = options.frame_hash {
+        return print_frame_hashes(rom_file.as_ref(), frames, options);
+    }
+
+    match options.backend {
+        #[cfg(feature = "renderer-raylib")]
+        Backend::Raylib => run_with::<RaylibRenderer, RaylibInput, RaylibAudio, _>(rom_file, options),
+        #[cfg(feature = "renderer-soft")]
+        Backend::Soft => run_with::<SoftRenderer, SoftInput, SilentAudio, _>(rom_file, options),
+    }
+}
 
-    let memory = setup_memory(&rom_file);
-    let mut cpu = Cpu::new(memory, CODE_MEM_LOC.0, STACK_MEM_LOC.1, INTERRUPT_MEM_LOC.0);
-    cpu.load_into_address(rom_file.code, CODE_MEM_LOC.0).unwrap();
+/// Runs `frames` frames of a rom without any frontend or input and returns the [`frame_hash`] of
+/// every composed frame. Frames are composed the same way whichever window or backend would show
+/// them, so the hashes only change when what the console draws does.
+pub fn render_rom_frames(rom: &[u8], frames: usize) -> Result<Vec<u64>> {
+    let mut emulator = Emulator::new(rom, &EmulatorOptions::default())?;
+    (0..frames)
+        .map(|_| Ok(frame_hash(emulator.step_frame(KeyStatus::reset())?)))
+        .collect()
+}
 
-    let scale = 4;
-    let mut renderer = RaylibRenderer::start(rom_file.name, FPS, scale);
+/// Prints the frame number and the [`frame_hash`] of the first `frames` frames of a rom, one
+/// frame per line.
+#[cfg(feature = "native")]
+fn print_frame_hashes(rom_path: &Path, frames: usize, options: &Options) -> Result<()> {
+    let rom = std::fs::read(rom_path).map_err(|source| Error::Io {
+        path: rom_path.to_path_buf(),
+        source,
+    })?;
 
-    renderer.draw_frame(&mut cpu.memory)?;
+    let mut emulator = Emulator::with_serial(&rom, &options.emulator_options(), serial_port(options)?)?;
+    for frame in 0..frames {
+        let hash = frame_hash(emulator.step_frame(KeyStatus::reset())?);
+        println!("{frame} {hash:016x}");
+    }
+
+    Ok(())
+}
+
+/// The serial port writing to the log file of `options`, or to stdout without one.
+#[cfg(feature = "native")]
+fn serial_port(options: &Options) -> Result<SerialMem> {
+    let Some(path) = &options.serial_log else {
+        return Ok(SerialMem::default());
+    };
+
+    let log = std::fs::File::create(path).map_err(|source| Error::Io {
+        path: path.clone(),
+        source,
+    })?;
+    Ok(SerialMem::new(Box::new(log)))
+}
+
+/// Execution state of the main loop, a paused console keeps presenting the last frame without
+/// running the cpu, and a step runs a single frame before pausing again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Execution {
+    Running,
+    Paused,
+    Step,
+}
+
+#[cfg(feature = "native")]
+fn run_with<R, I, A, P>(rom_file: P, options: &Options) -> Result<()>
+where
+    R: Renderer,
+    I: Input + Default,
+    A: Audio,
+    P: AsRef<Path>,
+{
+    let rom_path = rom_file.as_ref().to_path_buf();
+    let state_path = savestate::state_path(&rom_path);
+    let rom_file = std::fs::read(&rom_path).map_err(|source| Error::Io {
+        path: rom_path.clone(),
+        source,
+    })?;
+
+    let mut emulator = Emulator::with_serial(&rom_file, &options.emulator_options(), serial_port(options)?)?;
+    if let Some(path) = &options.trace {
+        let trace = std::fs::File::create(path).map_err(|source| Error::Io {
+            path: path.clone(),
+            source,
+        })?;
+        emulator.set_trace(Some(TraceWriter::new(Box::new(std::io::BufWriter::new(trace)))));
+    }
+    let mut checksum = emulator.checksum();
+    let mut watcher = options.hot_reload.then(|| RomWatcher::new(rom_path.clone()));
+
+    let mut renderer = R::start(emulator.title(), FPS, options.scale.max(1))?;
+    renderer.set_sprite_line_limit(options.sprite_line_limit);
+    renderer.set_palette(emulator.palette());
+    let input = I::default();
+    let mut audio = A::start();
+    let mut execution = Execution::Running;
+    let mut show_overlay = false;
+    let mut stats = FrameStats::default();
+    let mut fps_counter = FpsCounter::default();
+    let mut frames_since_draw = 0;
+
+    let dirty = emulator.take_dirty();
+    emulator.draw_on(&mut renderer, &dirty)?;
 
     while !renderer.should_close() {
-        let key_status = RaylibInput.poll();
-        cpu.memory.write(INPUT_MEM_LOC.0, key_status)?;
+        let key_status = input.poll();
+
+        if let Some(watcher) = watcher.as_mut() {
+            if let Some((rom, version)) = watcher.poll() {
+                match emulator.reload(&rom, &options.reload_options()) {
+                    Ok(()) => {
+                        watcher.loaded(version);
+                        checksum = emulator.checksum();
+                        renderer.set_palette(emulator.palette());
+                        renderer.invalidate();
+                        println!("reloaded {}", rom_path.display());
+                    }
+                    Err(err) if watcher.failed(version) => eprintln!("failed to reload, retrying: {err}"),
+                    Err(_) => {}
+                }
+            }
+        }
+
+        match input.hotkey() {
+            Some(Hotkey::SaveState) => match SaveState::capture(emulator.cpu(), checksum).save(&state_path) {
+                Ok(()) => println!("saved state to {}", state_path.display()),
+                Err(err) => eprintln!("failed to save state: {err}"),
+            },
+            Some(Hotkey::LoadState) => {
+                match SaveState::load(&state_path, checksum).and_then(|state| state.apply(emulator.cpu_mut())) {
+                    Ok(()) => renderer.invalidate(),
+                    Err(err) => eprintln!("failed to load state: {err}"),
+                }
+            }
+            Some(Hotkey::Screenshot) => {
+                let path = screenshot::screenshot_path(&rom_path, emulator.name());
+                match screenshot::save(&path, &renderer.capture_frame()) {
+                    Ok(()) => println!("saved screenshot to {}", path.display()),
+                    Err(err) => eprintln!("failed to save screenshot: {err}"),
+                }
+            }
+            Some(Hotkey::Pause) => {
+                execution = match execution {
+                    Execution::Running => Execution::Paused,
+                    Execution::Paused | Execution::Step => Execution::Running,
+                };
+                renderer.set_paused(execution != Execution::Running);
+            }
+            Some(Hotkey::Overlay) => {
+                show_overlay = !show_overlay;
+                renderer.set_overlay(show_overlay.then_some(stats));
+            }
+            Some(Hotkey::Fullscreen) => renderer.toggle_fullscreen(),
+            Some(Hotkey::FrameAdvance) if execution == Execution::Paused => execution = Execution::Step,
+            Some(Hotkey::FrameAdvance) | None => {}
+        }
 
         if renderer.should_draw() {
-            renderer.draw_frame(&mut cpu.memory)?;
+            // the register is read once per frame, so a new frame skip applies from the frame after
+            // the program wrote it
+            stats.frame_skip = frame_control::frame_skip(&mut emulator.cpu_mut().memory)?;
+            frames_since_draw += 1;
+
+            if frames_since_draw < stats.frame_skip {
+                renderer.skip_frame();
+            } else {
+                frames_since_draw = 0;
+                let dirty = emulator.take_dirty();
+                stats.fps = fps_counter.tick();
+                frame_control::report_fps(&mut emulator.cpu_mut().memory, stats.fps)?;
+                if show_overlay {
+                    stats.dirty_tiles = dirty.cells();
+                    renderer.set_overlay(Some(stats));
+                }
+
+                let draw_start = Instant::now();
+                emulator.draw_on(&mut renderer, &dirty)?;
+                stats.draw_time = draw_start.elapsed();
+            }
         }
 
-        for _ in 0..CLOCK_CYCLE {
-            if let ControlFlow::Halt(_) = cpu.step()? {
-                return Ok(());
-            };
+        // the input byte is left alone while paused, so the program sees the same input it had
+        // when execution stopped
+        if execution == Execution::Paused {
+            continue;
         }
 
-        cpu.memory.write(INPUT_MEM_LOC.0, KeyStatus::reset())?;
-        cpu.handle_interrupt(Interrupt::AfterFrame)?;
+        match emulator.run_frame(key_status)? {
+            Some(instructions) => stats.instructions = instructions,
+            // a halted program can still be replaced by a new version of it
+            None if watcher.is_some() => {}
+            None => return Ok(()),
+        }
+
+        audio.play(emulator.apu_mut());
+
+        if execution == Execution::Step {
+            execution = Execution::Paused;
+        }
     }
 
     Ok(())
 }
 
-fn setup_memory(rom: &rom_loader::Rom) -> impl Addressable {
+/// Loads the rom code and data into memory, with execution starting at the entry point of the rom.
+fn setup_cpu(rom: &Rom, memory: MemoryMapper) -> Result<Cpu<MemoryMapper>> {
+    let mut cpu = Cpu::new(memory, CODE_MEM_LOC.0, STACK_START, INTERRUPT_MEM_LOC.0);
+    cpu.load_into_address(rom.code, CODE_MEM_LOC.0)?;
+
+    // data sits at the end of code memory, which must still have room for the code before it
+    let data_size = rom.data.iter().map(|data| data.len()).sum::<usize>();
+    let addresses = data_addresses(rom.data.iter().map(|data| data.len()))
+        .filter(|_| rom.code.len() + data_size <= CODE_MEMORY)
+        .ok_or(Error::Data {
+            code: rom.code.len(),
+            data: data_size,
+        })?;
+    for (data, address) in rom.data.iter().zip(addresses) {
+        cpu.load_into_address(data, address)?;
+    }
+
+    cpu.set_entry_point(rom.entry_point);
+    Ok(cpu)
+}
+
+/// Maps every region of the console memory, with the rom sprites loaded on tile memory.
+fn setup_memory(rom: &Rom, serial: SerialMem) -> Result<MemoryMapper> {
     let mut memory_mapper = MemoryMapper::default();
+    let mut serial = Some(serial);
+
+    for region in MemoryMap::default().iter() {
+        let device: Devices = match region.kind {
+            RegionKind::Tiles => TileMem::from(LinearMemory::<TILE_MEMORY>::try_from_slice(&rom.sprites)?).into(),
+            RegionKind::Sprites => SpriteMem::from(LinearMemory::<SPRITE_MEMORY>::default()).into(),
+            RegionKind::Code => ProgramMem::from(LinearMemory::<CODE_MEMORY>::default()).into(),
+            RegionKind::Background => {
+                let background = rom.background.unwrap_or_default();
+                BackgroundMem::from(LinearMemory::<BG_MEMORY>::try_from_slice(background).map_err(Error::Background)?)
+                    .into()
+            }
+            RegionKind::Interface => {
+                let interface = rom.interface.unwrap_or_default();
+                InterfaceMem::from(
+                    LinearMemory::<INTERFACE_MEMORY>::try_from_slice(interface).map_err(Error::Interface)?,
+                )
+                .into()
+            }
+            RegionKind::Interrupts => InterruptMem::from(LinearMemory::<INTERRUPT_MEMORY>::default()).into(),
+            RegionKind::Input => InputMem::from(LinearMemory::<INPUT_MEMORY>::default()).into(),
+            RegionKind::Audio => AudioMem::from(LinearMemory::<AUDIO_MEMORY>::default()).into(),
+            RegionKind::Video => VideoMem::from(LinearMemory::<VIDEO_MEMORY>::default()).into(),
+            RegionKind::Serial => serial.take().expect("serial is mapped once").into(),
+            RegionKind::BankSelect => BankSelectMem::from(LinearMemory::<BANK_SELECT_MEMORY>::default()).into(),
+            RegionKind::FrameControl => FrameControlMem::from(LinearMemory::<FRAME_CONTROL_MEMORY>::default()).into(),
+            RegionKind::Collision => CollisionMem::from(LinearMemory::<COLLISION_MEMORY>::default()).into(),
+            RegionKind::VideoMode => VideoModeMem::from(LinearMemory::<VIDEO_MODE_MEMORY>::default()).into(),
+            RegionKind::Framebuffer => FramebufferMem::from(LinearMemory::<FRAMEBUFFER_MEMORY>::default()).into(),
+            RegionKind::Bank => BankMem::new(&rom.banks).into(),
+            RegionKind::Stack => StackMem::from(LinearMemory::<STACK_MEMORY>::default()).into(),
+        };
+
+        // every device is indexed from its start, program memory included so the whole 16KiB of it
+        // is addressable
+        memory_mapper.map(device, region.start, region.end, MappingMode::Remap)?;
+    }
+
+    Ok(memory_mapper)
+}
+
+#[cfg(test)]
+mod tests {
+    use aya_cpu::cpu::ControlFlow;
+    use aya_cpu::instruction::Instruction;
+    use aya_cpu::memory::{Addressable, CapturedOutput};
+    use aya_cpu::register::Register;
+    use memory::{Interrupt, BG_MEM_LOC, INPUT_MEM_LOC, UI_MEM_LOC};
+
+    use super::*;
+
+    /// Loads a rom without any frontend, the serial port output is captured.
+    fn load_headless(rom: &[u8]) -> (Cpu<MemoryMapper>, CapturedOutput) {
+        let rom = Rom::from_bytes(rom, true).unwrap();
+        let (serial, output) = SerialMem::capture();
+        let cpu = setup_cpu(&rom, setup_memory(&rom, serial).unwrap()).unwrap();
+        (cpu, output)
+    }
+
+    /// Runs a rom without any frontend until it halts, returning everything written to the
+    /// serial port.
+    fn run_headless(rom: &[u8]) -> String {
+        let (mut cpu, output) = load_headless(rom);
+        while let ControlFlow::Continue = cpu.step().unwrap() {}
+
+        drop(cpu);
+        output.contents()
+    }
+
+    /// A rom running `code` with every other section left out, tests fill in what they need.
+    pub(crate) fn test_rom(code: &[u8]) -> Rom<'_> {
+        Rom {
+            version: aya_rom::VERSION,
+            name: "test",
+            author: None,
+            description: None,
+            entry_point: 0,
+            cycles_per_frame: 0,
+            palette: None,
+            code,
+            sprites: Default::default(),
+            compressed_sprites: false,
+            banks: vec![],
+            data: vec![],
+            background: None,
+            interface: None,
+        }
+    }
+
+    fn step(cpu: &mut Cpu<MemoryMapper>, instructions: usize) {
+        for _ in 0..instructions {
+            assert!(matches!(cpu.step().unwrap(), ControlFlow::Continue));
+        }
+    }
+
+    #[test]
+    fn test_render_rom_frames() {
+        let rom = include_bytes!("../../samples/build/gradient");
+        let hashes = render_rom_frames(rom, 61).unwrap();
+        assert_eq!(hashes.len(), 61);
+        assert_eq!(render_rom_frames(rom, 61).unwrap(), hashes);
+        assert_ne!(hashes[0], hashes[60]);
+
+        // once drawn, every 7 rows of the gradient take the next color of the palette
+        let palette = Emulator::new(rom, &EmulatorOptions::default())
+            .unwrap()
+            .palette()
+            .to_vec();
+        let frame = (0..SCREEN_HEIGHT)
+            .flat_map(|row| {
+                let (r, g, b, _) = if row < 7 { (0, 0, 0, 0) } else { palette[row / 7] };
+                [r, g, b, 0xff].repeat(SCREEN_WIDTH)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(hashes[60], frame_hash(&frame));
+    }
+
+    #[test]
+    fn test_serial_output() {
+        let output = run_headless(include_bytes!("../../samples/build/serial"));
+        assert_eq!(output, "hello\n");
+    }
+
+    #[test]
+    fn test_input_interrupt() {
+        const PLAYER_X: u16 = 0x2001;
+        let (mut cpu, _) = load_headless(include_bytes!("../../samples/build/input"));
+        step(&mut cpu, 20);
+        assert_eq!(cpu.memory.read(PLAYER_X).unwrap(), 0x70);
+
+        // right pressed
+        cpu.memory.write(INPUT_MEM_LOC.0, 0x10).unwrap();
+        cpu.handle_interrupt(Interrupt::Input).unwrap();
+        step(&mut cpu, 20);
+        assert_eq!(cpu.memory.read(PLAYER_X).unwrap(), 0x78);
+
+        // left pressed
+        cpu.memory.write(INPUT_MEM_LOC.0, 0x80).unwrap();
+        cpu.handle_interrupt(Interrupt::Input).unwrap();
+        step(&mut cpu, 20);
+        assert_eq!(cpu.memory.read(PLAYER_X).unwrap(), 0x70);
+
+        // without the interrupt the program never looks at the input
+        cpu.memory.write(INPUT_MEM_LOC.0, 0x10).unwrap();
+        step(&mut cpu, 20);
+        assert_eq!(cpu.memory.read(PLAYER_X).unwrap(), 0x70);
+    }
+
+    #[test]
+    fn test_stack_round_trips_at_startup() {
+        let code = [
+            Instruction::MovLitReg(Register::R3, 0x1234),
+            Instruction::PushLit(0xABCD),
+            Instruction::PushReg(Register::R3),
+            Instruction::PopReg(Register::R1),
+            Instruction::PopReg(Register::R2),
+            Instruction::Halt(0),
+        ]
+        .iter()
+        .flat_map(Instruction::encode)
+        .collect::<Vec<_>>();
+        let rom = test_rom(&code);
+        let rom = rom.to_bytes().unwrap();
+
+        let (mut cpu, _) = load_headless(&rom);
+        assert_eq!(cpu.registers.fetch(Register::SP), STACK_START);
+        while let ControlFlow::Continue = cpu.step().unwrap() {}
+
+        assert_eq!(cpu.registers.fetch(Register::R1), 0x1234);
+        assert_eq!(cpu.registers.fetch(Register::R2), 0xABCD);
+        assert_eq!(cpu.registers.fetch(Register::SP), STACK_START);
+        assert_eq!(cpu.memory.read_word(STACK_START).unwrap(), 0xABCD);
+    }
 
-    let tile_memory = LinearMemory::<TILE_MEMORY>::from(rom.sprites);
-    memory_mapper
-        .map(
-            TileMem::from(tile_memory),
-            TILE_MEM_LOC.0,
-            TILE_MEM_LOC.1,
-            MappingMode::Remap,
-        )
-        .unwrap();
-
-    let sprite_memory = LinearMemory::<SPRITE_MEMORY>::default();
-    memory_mapper
-        .map(
-            SpriteMem::from(sprite_memory),
-            SPRITE_MEM_LOC.0,
-            SPRITE_MEM_LOC.1,
-            MappingMode::Remap,
-        )
-        .unwrap();
-
-    let code_memory = LinearMemory::<CODE_MEMORY>::default();
-    memory_mapper
-        .map(
-            ProgramMem::from(code_memory),
-            CODE_MEM_LOC.0,
-            CODE_MEM_LOC.1,
-            MappingMode::Direct,
-        )
-        .unwrap();
-
-    let bg_memory = LinearMemory::<BG_MEMORY>::default();
-    memory_mapper
-        .map(
-            BackgroundMem::from(bg_memory),
-            BG_MEM_LOC.0,
-            BG_MEM_LOC.1 + 1,
-            MappingMode::Remap,
-        )
-        .unwrap();
-
-    let ui_memory = LinearMemory::<INTERFACE_MEMORY>::default();
-    memory_mapper
-        .map(
-            InterfaceMem::from(ui_memory),
-            UI_MEM_LOC.0,
-            UI_MEM_LOC.1,
-            MappingMode::Remap,
-        )
-        .unwrap();
-
-    let interrupt_memory = LinearMemory::<INTERRUPT_MEMORY>::default();
-    memory_mapper
-        .map(
-            InterruptMem::from(interrupt_memory),
-            INTERRUPT_MEM_LOC.0,
-            INTERRUPT_MEM_LOC.1,
-            MappingMode::Remap,
-        )
-        .unwrap();
-
-    let input_memory = LinearMemory::<INPUT_MEMORY>::default();
-    memory_mapper
-        .map(
-            InputMem::from(input_memory),
-            INPUT_MEM_LOC.0,
-            INPUT_MEM_LOC.1,
-            MappingMode::Remap,
-        )
-        .unwrap();
-
-    let stack_memory = LinearMemory::default();
-    memory_mapper
-        .map(
-            StackMem::from(stack_memory),
-            STACK_MEM_LOC.0,
-            STACK_MEM_LOC.1,
-            MappingMode::Remap,
-        )
-        .unwrap();
-
-    memory_mapper
+    #[test]
+    fn test_execution_starts_at_entry_point() {
+        // mov r1, $0101 is skipped, as the rom starts at mov r2, $0202 right after it
+        let code = [0x11, 0x02, 0x01, 0x01, 0x11, 0x03, 0x02, 0x02, 0xFF, 0x00];
+        let rom = Rom {
+            entry_point: 4,
+            ..test_rom(&code)
+        };
+        let rom = rom.to_bytes().unwrap();
+
+        let (mut cpu, _) = load_headless(&rom);
+        assert_eq!(cpu.registers.fetch(Register::IP), CODE_MEM_LOC.0 + 4);
+        while let ControlFlow::Continue = cpu.step().unwrap() {}
+        assert_eq!(cpu.registers.fetch(Register::R1), 0);
+        assert_eq!(cpu.registers.fetch(Register::R2), 0x0202);
+    }
+
+    #[test]
+    fn test_data_is_loaded_at_the_end_of_code_memory() {
+        let (level, text) = ([0x44; 6], [0x55; 2]);
+        let rom = Rom {
+            data: vec![&level, &text],
+            ..test_rom(&[0xFF, 0x00])
+        };
+
+        let (cpu, _) = load_headless(&rom.to_bytes().unwrap());
+        assert_eq!(
+            data_addresses([6, 2]).unwrap(),
+            [CODE_MEM_LOC.1 - 7, CODE_MEM_LOC.1 - 1]
+        );
+        for address in CODE_MEM_LOC.1 - 7..CODE_MEM_LOC.1 - 1 {
+            assert_eq!(cpu.memory.read(address).unwrap(), 0x44);
+        }
+        assert_eq!(cpu.memory.read_word(CODE_MEM_LOC.1 - 1).unwrap(), 0x5555);
+
+        // the code and data share code memory
+        let code = vec![0; CODE_MEMORY - 7];
+        let rom = Rom { code: &code, ..rom };
+        let (serial, _) = SerialMem::capture();
+        let err = setup_cpu(&rom, setup_memory(&rom, serial).unwrap()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "rom data doesn't fit on code memory, 8 bytes of data and 16377 bytes of code are over the 16384 bytes it holds"
+        );
+    }
+
+    #[test]
+    fn test_initial_screen_is_copied_into_memory() {
+        let (background, interface) = ([0x03; 30], [0x07; 2]);
+        let rom = Rom {
+            background: Some(&background),
+            interface: Some(&interface),
+            ..test_rom(&[0xFF, 0x00])
+        };
+
+        let (cpu, _) = load_headless(&rom.to_bytes().unwrap());
+        assert_eq!(cpu.memory.read(BG_MEM_LOC.0).unwrap(), 0x03);
+        assert_eq!(cpu.memory.read(BG_MEM_LOC.0 + 29).unwrap(), 0x03);
+        // the cells past the section start empty
+        assert_eq!(cpu.memory.read(BG_MEM_LOC.0 + 30).unwrap(), 0x00);
+        assert_eq!(cpu.memory.read_word(UI_MEM_LOC.0).unwrap(), 0x0707);
+        assert_eq!(cpu.memory.read(UI_MEM_LOC.0 + 2).unwrap(), 0x00);
+
+        let large = [0x01; INTERFACE_MEMORY + 1];
+        let rom = Rom {
+            interface: Some(&large),
+            ..rom
+        };
+        let (serial, _) = SerialMem::capture();
+        let err = setup_memory(&rom, serial).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "rom interface doesn't fit on interface memory, 421 bytes don't fit on a memory of 420 bytes"
+        );
+    }
 }
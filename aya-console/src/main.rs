@@ -1,4 +1,54 @@
-fn main() -> Result<(), Box<dyn std::error::Error>> {
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
     let rom_file = std::env::args().nth(1).unwrap();
-    aya_console::run(rom_file)
+    let serial_log = std::env::args()
+        .skip_while(|arg| arg != "--serial-log")
+        .nth(1)
+        .map(Into::into);
+    let trace = std::env::args()
+        .skip_while(|arg| arg != "--trace")
+        .nth(1)
+        .map(Into::into);
+    let frame_hash = match std::env::args().skip_while(|arg| arg != "--frame-hash").nth(1) {
+        Some(frames) => match frames.parse() {
+            Ok(frames) => Some(frames),
+            _ => {
+                eprintln!("frame hash takes a number of frames, got '{frames}'");
+                return ExitCode::FAILURE;
+            }
+        },
+        None => None,
+    };
+    let scale = match std::env::args().skip_while(|arg| arg != "--scale").nth(1) {
+        Some(scale) => match scale.parse() {
+            Ok(scale) if scale > 0 => scale,
+            _ => {
+                eprintln!("scale must be a positive number, got '{scale}'");
+                return ExitCode::FAILURE;
+            }
+        },
+        None => aya_console::DEFAULT_SCALE,
+    };
+    let options = aya_console::Options {
+        scale,
+        skip_checksum: std::env::args().any(|arg| arg == "--skip-checksum"),
+        writable_code: std::env::args().any(|arg| arg == "--writable-code"),
+        serial_log,
+        sprite_line_limit: std::env::args().any(|arg| arg == "--sprite-limit"),
+        trace,
+        hot_reload: std::env::args().any(|arg| arg == "--hot-reload"),
+        keep_ip: std::env::args().any(|arg| arg == "--keep-ip"),
+        keep_ram: std::env::args().any(|arg| arg == "--keep-ram"),
+        frame_hash,
+        ..Default::default()
+    };
+
+    match aya_console::run_with_options(rom_file, &options) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{err}");
+            ExitCode::FAILURE
+        }
+    }
 }
@@ -0,0 +1,79 @@
+/// Set of tilemap cells, stored as a bitset, that were written since the renderer last drew them.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DirtyCells {
+    bits: Vec<u64>,
+}
+
+impl DirtyCells {
+    pub fn insert(&mut self, cell: usize) {
+        let (word, bit) = (cell / 64, cell % 64);
+        if word >= self.bits.len() {
+            self.bits.resize(word + 1, 0);
+        }
+        self.bits[word] |= 1 << bit;
+    }
+
+    pub fn contains(&self, cell: usize) -> bool {
+        self.bits
+            .get(cell / 64)
+            .is_some_and(|word| word & (1 << (cell % 64)) != 0)
+    }
+
+    /// Number of dirty cells.
+    pub fn len(&self) -> usize {
+        self.bits.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits.iter().all(|word| *word == 0)
+    }
+
+    /// Dirty cells in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.bits.iter().enumerate().flat_map(|(word_idx, word)| {
+            (0..64)
+                .filter(move |bit| word & (1 << bit) != 0)
+                .map(move |bit| word_idx * 64 + bit)
+        })
+    }
+}
+
+/// Everything written since the last frame that affects how tilemaps are drawn.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DirtyRegions {
+    pub background: DirtyCells,
+    pub interface: DirtyCells,
+    /// Set when tile memory was written, every cell may look different so tilemaps must be
+    /// redrawn in full
+    pub tiles: bool,
+}
+
+impl DirtyRegions {
+    /// Number of tilemap cells that changed.
+    pub fn cells(&self) -> usize {
+        self.background.len() + self.interface.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dirty_cells() {
+        let mut cells = DirtyCells::default();
+        assert!(cells.is_empty());
+
+        cells.insert(3);
+        cells.insert(130);
+        cells.insert(3);
+        cells.insert(64);
+
+        assert!(!cells.is_empty());
+        assert_eq!(cells.len(), 3);
+        assert!(cells.contains(64));
+        assert!(!cells.contains(65));
+        assert!(!cells.contains(1000));
+        assert_eq!(cells.iter().collect::<Vec<_>>(), [3, 64, 130]);
+    }
+}
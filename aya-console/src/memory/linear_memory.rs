@@ -1,4 +1,4 @@
-use aya_cpu::memory::{Addressable, Result};
+use aya_cpu::memory::{Addressable, Error, Result};
 use aya_cpu::word::Word;
 
 #[derive(Debug)]
@@ -12,13 +12,43 @@ impl<const SIZE: usize> Default for LinearMemory<SIZE> {
     }
 }
 
+impl<const SIZE: usize> LinearMemory<SIZE> {
+    /// Creates a memory holding `contents` at its start, the bytes past them are zeroed.
+    pub fn try_from_slice(contents: &[u8]) -> std::result::Result<Self, SizeError> {
+        let mut memory = [0; SIZE];
+        memory
+            .get_mut(..contents.len())
+            .ok_or(SizeError {
+                capacity: SIZE,
+                length: contents.len(),
+            })?
+            .copy_from_slice(contents);
+        Ok(Self { memory })
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.memory
+    }
+
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+        &mut self.memory
+    }
+}
+
 impl<const SIZE: usize> Addressable for LinearMemory<SIZE> {
+    fn len(&self) -> usize {
+        SIZE
+    }
+
     fn read<W>(&self, address: W) -> Result<u8>
     where
         W: Into<Word> + Copy,
     {
         let address = address.into();
-        Ok(self.memory[usize::from(address)])
+        self.memory
+            .get(usize::from(address))
+            .copied()
+            .ok_or(Error::InvalidAddress(address.into()))
     }
 
     fn write<W>(&mut self, address: W, byte: impl Into<u8>) -> Result<()>
@@ -26,15 +56,102 @@ impl<const SIZE: usize> Addressable for LinearMemory<SIZE> {
         W: Into<Word> + Copy,
     {
         let address = address.into();
-        self.memory[usize::from(address)] = byte.into();
+        let cell = self
+            .memory
+            .get_mut(usize::from(address))
+            .ok_or(Error::InvalidAddress(address.into()))?;
+        *cell = byte.into();
         Ok(())
     }
+
+    fn read_word<W>(&self, address: W) -> Result<u16>
+    where
+        W: Into<Word> + Copy,
+    {
+        let address = address.into();
+        let start = usize::from(address);
+        match self.memory.get(start..start + 2) {
+            Some(&[lower, upper]) => Ok(u16::from_le_bytes([lower, upper])),
+            // words past the end fail on the same byte they would when read one byte at a time
+            _ => Ok(u16::from_le_bytes([self.read(address)?, self.read(address.next()?)?])),
+        }
+    }
+
+    fn write_word<W>(&mut self, address: W, word: u16) -> Result<()>
+    where
+        W: Into<Word> + Copy,
+    {
+        let address = address.into();
+        let start = usize::from(address);
+        match self.memory.get_mut(start..start + 2) {
+            Some(bytes) => {
+                bytes.copy_from_slice(&word.to_le_bytes());
+                Ok(())
+            }
+            None => {
+                let [lower, upper] = word.to_le_bytes();
+                self.write(address, lower)?;
+                self.write(address.next()?, upper)
+            }
+        }
+    }
 }
 
-impl<const SIZE: usize> From<&[u8]> for LinearMemory<SIZE> {
-    fn from(value: &[u8]) -> Self {
-        let mut memory = [0; SIZE];
-        memory[..value.len()].copy_from_slice(&value[..value.len()]);
-        Self { memory }
+/// Returned when the contents a memory is filled with don't fit on it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SizeError {
+    /// Size of the memory, in bytes
+    pub capacity: usize,
+    /// Size of the contents, in bytes
+    pub length: usize,
+}
+
+impl std::fmt::Display for SizeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} bytes don't fit on a memory of {} bytes",
+            self.length, self.capacity
+        )
+    }
+}
+
+impl std::error::Error for SizeError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_word_at_the_top() {
+        let mut memory = LinearMemory::<4>::default();
+        memory.write_word(2u16, 0xABCD).unwrap();
+        assert_eq!(memory.as_bytes(), &[0, 0, 0xCD, 0xAB]);
+        assert_eq!(memory.read_word(2u16).unwrap(), 0xABCD);
+
+        // the upper byte would land past the end of the memory
+        assert!(matches!(memory.write_word(3u16, 0x1234), Err(Error::InvalidAddress(4))));
+        assert!(matches!(memory.read_word(3u16), Err(Error::InvalidAddress(4))));
+        // the lower byte is still written before the upper one fails, like two byte writes do
+        assert_eq!(memory.as_bytes(), &[0, 0, 0xCD, 0x34]);
+    }
+
+    #[test]
+    fn test_try_from_slice_exact_fit() {
+        let memory = LinearMemory::<4>::try_from_slice(&[1, 2, 3, 4]).unwrap();
+        assert_eq!(memory.as_bytes(), &[1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_try_from_slice_short() {
+        let memory = LinearMemory::<4>::try_from_slice(&[1, 2]).unwrap();
+        assert_eq!(memory.as_bytes(), &[1, 2, 0, 0]);
+    }
+
+    #[test]
+    fn test_try_from_slice_oversized() {
+        let err = LinearMemory::<4>::try_from_slice(&[1, 2, 3, 4, 5]).unwrap_err();
+        assert_eq!(err, SizeError { capacity: 4, length: 5 });
+        assert_eq!(err.to_string(), "5 bytes don't fit on a memory of 4 bytes");
     }
 }
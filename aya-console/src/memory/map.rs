@@ -0,0 +1,181 @@
+use super::{
+    AUDIO_MEM_LOC, BANK_MEM_LOC, BANK_SELECT_MEM_LOC, BG_MEM_LOC, CODE_MEM_LOC, COLLISION_MEM_LOC, FRAMEBUFFER_MEM_LOC,
+    FRAME_CONTROL_MEM_LOC, INPUT_MEM_LOC, INTERRUPT_MEM_LOC, SERIAL_MEM_LOC, SPRITE_MEM_LOC, STACK_MEM_LOC,
+    TILE_MEM_LOC, UI_MEM_LOC, VIDEO_MEM_LOC, VIDEO_MODE_MEM_LOC,
+};
+
+/// What a region of the memory map holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RegionKind {
+    Tiles,
+    Sprites,
+    Code,
+    Background,
+    Interface,
+    Interrupts,
+    Input,
+    Audio,
+    Video,
+    Serial,
+    BankSelect,
+    FrameControl,
+    Collision,
+    VideoMode,
+    Framebuffer,
+    Bank,
+    Stack,
+}
+
+/// A named range of addresses, `end` is inclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Region {
+    pub name: &'static str,
+    pub start: u16,
+    pub end: u16,
+    pub kind: RegionKind,
+}
+
+impl Region {
+    const fn new(name: &'static str, (start, end): (u16, u16), kind: RegionKind) -> Self {
+        Self { name, start, end, kind }
+    }
+
+    pub fn contains(&self, address: u16) -> bool {
+        (self.start..=self.end).contains(&address)
+    }
+
+    /// Size of the region in bytes.
+    #[allow(clippy::len_without_is_empty, reason = "regions always hold at least one byte")]
+    pub fn len(&self) -> usize {
+        (self.end - self.start) as usize + 1
+    }
+}
+
+/// Every region mapped by the console, sorted by address.
+const REGIONS: &[Region] = &[
+    Region::new("tiles", TILE_MEM_LOC, RegionKind::Tiles),
+    Region::new("sprites", SPRITE_MEM_LOC, RegionKind::Sprites),
+    Region::new("code", CODE_MEM_LOC, RegionKind::Code),
+    Region::new("background", BG_MEM_LOC, RegionKind::Background),
+    Region::new("interface", UI_MEM_LOC, RegionKind::Interface),
+    Region::new("interrupts", INTERRUPT_MEM_LOC, RegionKind::Interrupts),
+    Region::new("input", INPUT_MEM_LOC, RegionKind::Input),
+    Region::new("audio", AUDIO_MEM_LOC, RegionKind::Audio),
+    Region::new("video", VIDEO_MEM_LOC, RegionKind::Video),
+    Region::new("serial", SERIAL_MEM_LOC, RegionKind::Serial),
+    Region::new("bank select", BANK_SELECT_MEM_LOC, RegionKind::BankSelect),
+    Region::new("frame control", FRAME_CONTROL_MEM_LOC, RegionKind::FrameControl),
+    Region::new("collision", COLLISION_MEM_LOC, RegionKind::Collision),
+    Region::new("video mode", VIDEO_MODE_MEM_LOC, RegionKind::VideoMode),
+    Region::new("framebuffer", FRAMEBUFFER_MEM_LOC, RegionKind::Framebuffer),
+    Region::new("bank", BANK_MEM_LOC, RegionKind::Bank),
+    Region::new("stack", STACK_MEM_LOC, RegionKind::Stack),
+];
+
+/// Layout of the console memory, the console maps its devices from it so tools reading it see
+/// exactly what programs do.
+#[derive(Debug, Clone, Copy)]
+pub struct MemoryMap {
+    regions: &'static [Region],
+}
+
+impl Default for MemoryMap {
+    fn default() -> Self {
+        Self { regions: REGIONS }
+    }
+}
+
+impl MemoryMap {
+    pub fn iter(&self) -> impl Iterator<Item = &Region> {
+        self.regions.iter()
+    }
+
+    /// Region holding `address`, addresses outside of every region are unmapped.
+    pub fn region_for(&self, address: u16) -> Option<&Region> {
+        self.regions.iter().find(|region| region.contains(address))
+    }
+
+    pub fn region(&self, kind: RegionKind) -> &Region {
+        self.regions
+            .iter()
+            .find(|region| region.kind == kind)
+            .expect("every region kind is part of the memory map")
+    }
+}
+
+impl<'map> IntoIterator for &'map MemoryMap {
+    type Item = &'map Region;
+    type IntoIter = std::slice::Iter<'map, Region>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.regions.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::{
+        AUDIO_MEMORY, BANK_MEMORY, BANK_SELECT_MEMORY, BG_MEMORY, CODE_MEMORY, COLLISION_MEMORY, FRAMEBUFFER_MEMORY,
+        FRAME_CONTROL_MEMORY, INPUT_MEMORY, INTERFACE_MEMORY, INTERRUPT_MEMORY, SERIAL_MEMORY, SPRITE_MEMORY,
+        STACK_MEMORY, TILE_MEMORY, VIDEO_MEMORY, VIDEO_MODE_MEMORY,
+    };
+
+    #[test]
+    fn test_regions_are_sorted_and_disjoint() {
+        let map = MemoryMap::default();
+        for (region, next) in map.iter().zip(map.iter().skip(1)) {
+            assert!(region.start <= region.end, "{} ends before it starts", region.name);
+            assert!(region.end < next.start, "{} overlaps {}", region.name, next.name);
+        }
+    }
+
+    #[test]
+    fn test_regions_match_device_sizes() {
+        let map = MemoryMap::default();
+        let sizes = [
+            (RegionKind::Tiles, TILE_MEMORY),
+            (RegionKind::Sprites, SPRITE_MEMORY),
+            (RegionKind::Code, CODE_MEMORY),
+            (RegionKind::Background, BG_MEMORY),
+            (RegionKind::Interface, INTERFACE_MEMORY),
+            (RegionKind::Interrupts, INTERRUPT_MEMORY),
+            (RegionKind::Input, INPUT_MEMORY),
+            (RegionKind::Audio, AUDIO_MEMORY),
+            (RegionKind::Video, VIDEO_MEMORY),
+            (RegionKind::Serial, SERIAL_MEMORY),
+            (RegionKind::BankSelect, BANK_SELECT_MEMORY),
+            (RegionKind::FrameControl, FRAME_CONTROL_MEMORY),
+            (RegionKind::Collision, COLLISION_MEMORY),
+            (RegionKind::VideoMode, VIDEO_MODE_MEMORY),
+            (RegionKind::Framebuffer, FRAMEBUFFER_MEMORY),
+            (RegionKind::Bank, BANK_MEMORY),
+            (RegionKind::Stack, STACK_MEMORY),
+        ];
+
+        assert_eq!(map.iter().count(), sizes.len());
+        for (kind, size) in sizes {
+            assert_eq!(map.region(kind).len(), size, "{kind:?}");
+        }
+    }
+
+    #[test]
+    fn test_region_for() {
+        let map = MemoryMap::default();
+        assert_eq!(map.region_for(0x0000).unwrap().kind, RegionKind::Tiles);
+        assert_eq!(map.region_for(0x2280).unwrap().kind, RegionKind::Code);
+        assert_eq!(map.region_for(0x627F).unwrap().kind, RegionKind::Code);
+        assert_eq!(map.region_for(0x678D).unwrap().name, "serial");
+        assert_eq!(map.region_for(0x678E).unwrap().kind, RegionKind::BankSelect);
+        assert_eq!(map.region_for(0x6790).unwrap().kind, RegionKind::FrameControl);
+        assert_eq!(map.region_for(0x6794).unwrap().kind, RegionKind::Collision);
+        assert_eq!(map.region_for(0x6795).unwrap().kind, RegionKind::VideoMode);
+        assert_eq!(map.region_for(0x8000).unwrap().kind, RegionKind::Framebuffer);
+        assert_eq!(map.region_for(0x9FFF).unwrap().kind, RegionKind::Framebuffer);
+        assert_eq!(map.region_for(0xA000).unwrap().kind, RegionKind::Bank);
+        assert_eq!(map.region_for(0xFFFF).unwrap().kind, RegionKind::Stack);
+        // foreground memory is not mapped yet
+        assert!(map.region_for(0x6500).is_none());
+        assert!(map.region_for(0x6800).is_none());
+    }
+}
@@ -2,10 +2,14 @@ use std::collections::VecDeque;
 
 use aya_cpu::memory::{Addressable, Error, Result};
 use aya_cpu::word::Word;
+use aya_cpu::MEMORY_SIZE;
 
+use super::dirty::{DirtyCells, DirtyRegions};
+use super::serial::SerialMem;
 use super::{
-    LinearMemory, BG_MEMORY, CODE_MEMORY, INPUT_MEMORY, INTERFACE_MEMORY, INTERRUPT_MEMORY, SPRITE_MEMORY,
-    STACK_MEMORY, TILE_MEMORY,
+    LinearMemory, AUDIO_MEMORY, BANK_MEMORY, BANK_MEM_LOC, BANK_SELECT_MEMORY, BG_MEMORY, CODE_MEMORY, CODE_MEM_LOC,
+    COLLISION_MEMORY, FRAMEBUFFER_MEMORY, FRAME_CONTROL_MEMORY, INPUT_MEMORY, INTERFACE_MEMORY, INTERRUPT_MEMORY,
+    SPRITE_MEMORY, STACK_MEMORY, TILE_MEMORY, VIDEO_MEMORY, VIDEO_MODE_MEMORY,
 };
 
 macro_rules! device {
@@ -13,6 +17,16 @@ macro_rules! device {
         #[derive(Debug)]
         pub struct $name(LinearMemory<$size>);
 
+        impl $name {
+            pub fn as_bytes(&self) -> &[u8] {
+                self.0.as_bytes()
+            }
+
+            pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+                self.0.as_bytes_mut()
+            }
+        }
+
         impl From<LinearMemory<$size>> for $name {
             fn from(mem: LinearMemory<$size>) -> Self {
                 Self(mem)
@@ -20,6 +34,10 @@ macro_rules! device {
         }
 
         impl Addressable for $name {
+            fn len(&self) -> usize {
+                $size
+            }
+
             fn write<W>(&mut self, address: W, byte: impl Into<u8>) -> Result<()>
             where
                 W: Into<Word> + Copy,
@@ -34,6 +52,13 @@ macro_rules! device {
                 self.0.read(address)
             }
 
+            fn peek<W>(&self, address: W) -> Result<u8>
+            where
+                W: Into<Word> + Copy,
+            {
+                self.0.peek(address)
+            }
+
             fn write_word<W>(&mut self, address: W, word: u16) -> Result<()>
             where
                 W: Into<Word> + Copy,
@@ -51,14 +76,280 @@ macro_rules! device {
     };
 }
 
-device!(TileMem, TILE_MEMORY);
+/// Like [`device!`], but records every written byte so renderers know what changed since they
+/// last drew it.
+macro_rules! tracked_device {
+    ($name:ident, $size:expr) => {
+        #[derive(Debug)]
+        pub struct $name {
+            memory: LinearMemory<$size>,
+            dirty: DirtyCells,
+        }
+
+        impl $name {
+            pub fn as_bytes(&self) -> &[u8] {
+                self.memory.as_bytes()
+            }
+
+            pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+                self.memory.as_bytes_mut()
+            }
+
+            /// Returns the bytes written since the last call and starts tracking anew.
+            pub fn take_dirty(&mut self) -> DirtyCells {
+                std::mem::take(&mut self.dirty)
+            }
+        }
+
+        impl From<LinearMemory<$size>> for $name {
+            fn from(memory: LinearMemory<$size>) -> Self {
+                Self {
+                    memory,
+                    dirty: DirtyCells::default(),
+                }
+            }
+        }
+
+        impl Addressable for $name {
+            fn len(&self) -> usize {
+                $size
+            }
+
+            fn write<W>(&mut self, address: W, byte: impl Into<u8>) -> Result<()>
+            where
+                W: Into<Word> + Copy,
+            {
+                self.memory.write(address, byte)?;
+                let address: Word = address.into();
+                self.dirty.insert(address.into());
+                Ok(())
+            }
+
+            fn read<W>(&self, address: W) -> Result<u8>
+            where
+                W: Into<Word> + Copy,
+            {
+                self.memory.read(address)
+            }
+
+            fn peek<W>(&self, address: W) -> Result<u8>
+            where
+                W: Into<Word> + Copy,
+            {
+                self.memory.peek(address)
+            }
+
+            fn write_word<W>(&mut self, address: W, word: u16) -> Result<()>
+            where
+                W: Into<Word> + Copy,
+            {
+                self.memory.write_word(address, word)?;
+                let address: Word = address.into();
+                self.dirty.insert(address.into());
+                self.dirty.insert(usize::from(address) + 1);
+                Ok(())
+            }
+
+            fn read_word<W>(&self, address: W) -> Result<u16>
+            where
+                W: Into<Word> + Copy,
+            {
+                self.memory.read_word(address)
+            }
+        }
+    };
+}
+
+tracked_device!(TileMem, TILE_MEMORY);
 device!(SpriteMem, SPRITE_MEMORY);
-device!(ProgramMem, CODE_MEMORY);
-device!(BackgroundMem, BG_MEMORY);
-device!(InterfaceMem, INTERFACE_MEMORY);
+tracked_device!(BackgroundMem, BG_MEMORY);
+tracked_device!(InterfaceMem, INTERFACE_MEMORY);
 device!(InterruptMem, INTERRUPT_MEMORY);
 device!(InputMem, INPUT_MEMORY);
+device!(AudioMem, AUDIO_MEMORY);
+device!(VideoMem, VIDEO_MEMORY);
 device!(StackMem, STACK_MEMORY);
+device!(BankSelectMem, BANK_SELECT_MEMORY);
+device!(FrameControlMem, FRAME_CONTROL_MEMORY);
+device!(CollisionMem, COLLISION_MEMORY);
+device!(VideoModeMem, VIDEO_MODE_MEMORY);
+device!(FramebufferMem, FRAMEBUFFER_MEMORY);
+
+/// Program memory, writes are rejected while it is write protected so a stray store can't
+/// overwrite the running program.
+#[derive(Debug)]
+pub struct ProgramMem {
+    memory: LinearMemory<CODE_MEMORY>,
+    write_protected: bool,
+}
+
+impl ProgramMem {
+    pub fn set_write_protected(&mut self, write_protected: bool) {
+        self.write_protected = write_protected;
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        self.memory.as_bytes()
+    }
+
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+        self.memory.as_bytes_mut()
+    }
+}
+
+impl From<LinearMemory<CODE_MEMORY>> for ProgramMem {
+    fn from(memory: LinearMemory<CODE_MEMORY>) -> Self {
+        Self {
+            memory,
+            write_protected: false,
+        }
+    }
+}
+
+impl Addressable for ProgramMem {
+    fn len(&self) -> usize {
+        CODE_MEMORY
+    }
+
+    fn write<W>(&mut self, address: W, byte: impl Into<u8>) -> Result<()>
+    where
+        W: Into<Word> + Copy,
+    {
+        if self.write_protected {
+            return Err(write_protected(address.into()));
+        }
+        self.memory.write(address, byte)
+    }
+
+    fn read<W>(&self, address: W) -> Result<u8>
+    where
+        W: Into<Word> + Copy,
+    {
+        self.memory.read(address)
+    }
+
+    fn peek<W>(&self, address: W) -> Result<u8>
+    where
+        W: Into<Word> + Copy,
+    {
+        self.memory.peek(address)
+    }
+
+    fn write_word<W>(&mut self, address: W, word: u16) -> Result<()>
+    where
+        W: Into<Word> + Copy,
+    {
+        if self.write_protected {
+            return Err(write_protected(address.into()));
+        }
+        self.memory.write_word(address, word)
+    }
+
+    fn read_word<W>(&self, address: W) -> Result<u16>
+    where
+        W: Into<Word> + Copy,
+    {
+        self.memory.read_word(address)
+    }
+}
+
+/// Rejected write at `address` of program memory, which is indexed from the start of code memory,
+/// naming the console address the program wrote to.
+fn write_protected(address: Word) -> Error {
+    Error::WriteProtected(CODE_MEM_LOC.0.wrapping_add(address.into()).into())
+}
+
+/// Code banks of the rom, every bank stays resident while only the selected one is visible through
+/// the bank window. Like program memory, banks reject writes while write protected.
+#[derive(Debug)]
+pub struct BankMem {
+    /// Every bank back to back, each padded to the size of the bank window
+    banks: Vec<u8>,
+    selected: usize,
+    write_protected: bool,
+}
+
+impl BankMem {
+    pub fn new(banks: &[&[u8]]) -> Self {
+        let mut memory = vec![0; banks.len() * BANK_MEMORY];
+        for (bank, code) in memory.chunks_mut(BANK_MEMORY).zip(banks) {
+            bank[..code.len()].copy_from_slice(code);
+        }
+
+        Self {
+            banks: memory,
+            selected: 0,
+            write_protected: false,
+        }
+    }
+
+    /// Number of banks the rom has.
+    pub fn bank_count(&self) -> usize {
+        self.banks.len() / BANK_MEMORY
+    }
+
+    /// Maps `bank` at the bank window, selecting a bank the rom doesn't have leaves the window unmapped.
+    pub fn select(&mut self, bank: u8) {
+        self.selected = bank as usize;
+    }
+
+    pub fn selected(&self) -> u8 {
+        self.selected as u8
+    }
+
+    pub fn set_write_protected(&mut self, write_protected: bool) {
+        self.write_protected = write_protected;
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.banks
+    }
+
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+        &mut self.banks
+    }
+
+    /// Index of `address` within every bank, fails when no bank is mapped or the address is past
+    /// the bank window.
+    fn index(&self, address: Word) -> Result<usize> {
+        if usize::from(address) >= BANK_MEMORY {
+            return Err(Error::InvalidAddress(address.into()));
+        }
+        if self.selected >= self.bank_count() {
+            return Err(Error::UnmappedAddress(address + BANK_MEM_LOC.0.into()));
+        }
+        Ok(self.selected * BANK_MEMORY + usize::from(address))
+    }
+}
+
+impl Addressable for BankMem {
+    /// Size of the bank window, only one bank is addressable at a time.
+    fn len(&self) -> usize {
+        BANK_MEMORY
+    }
+
+    fn write<W>(&mut self, address: W, byte: impl Into<u8>) -> Result<()>
+    where
+        W: Into<Word> + Copy,
+    {
+        let address = address.into();
+        if self.write_protected {
+            return Err(Error::WriteProtected(address + BANK_MEM_LOC.0.into()));
+        }
+        let index = self.index(address)?;
+        self.banks[index] = byte.into();
+        Ok(())
+    }
+
+    fn read<W>(&self, address: W) -> Result<u8>
+    where
+        W: Into<Word> + Copy,
+    {
+        let address = address.into();
+        let index = self.index(address)?;
+        Ok(self.banks[index])
+    }
+}
 
 macro_rules! devices {
     ($($variant:ident => $type:ty),* $(,)?) => {
@@ -68,7 +359,27 @@ macro_rules! devices {
             $($variant($type),)*
         }
 
+        impl Devices {
+            pub fn as_bytes(&self) -> &[u8] {
+                match self {
+                    $(Devices::$variant(mem) => mem.as_bytes(),)*
+                }
+            }
+
+            pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+                match self {
+                    $(Devices::$variant(mem) => mem.as_bytes_mut(),)*
+                }
+            }
+        }
+
         impl Addressable for Devices {
+            fn len(&self) -> usize {
+                match self {
+                    $(Devices::$variant(mem) => mem.len(),)*
+                }
+            }
+
             fn write<W>(&mut self, address: W, byte: impl Into<u8>) -> Result<()>
             where
                 W: Into<Word> + Copy,
@@ -87,6 +398,15 @@ macro_rules! devices {
                 }
             }
 
+            fn peek<W>(&self, address: W) -> Result<u8>
+            where
+                W: Into<Word> + Copy,
+            {
+                match self {
+                    $(Devices::$variant(mem) => mem.peek(address),)*
+                }
+            }
+
             fn write_word<W>(&mut self, address: W, word: u16) -> Result<()>
             where
                 W: Into<Word> + Copy,
@@ -122,6 +442,15 @@ devices! {
     Interface => InterfaceMem,
     Interrupt => InterruptMem,
     Input => InputMem,
+    Audio => AudioMem,
+    Video => VideoMem,
+    Serial => SerialMem,
+    BankSelect => BankSelectMem,
+    FrameControl => FrameControlMem,
+    Collision => CollisionMem,
+    VideoMode => VideoModeMem,
+    Framebuffer => FramebufferMem,
+    Bank => BankMem,
     Stack => StackMem,
 }
 
@@ -145,27 +474,144 @@ pub struct MemoryMapper {
 }
 
 impl MemoryMapper {
+    /// Maps `device` from `start` to `end`, inclusive. Fails when the device is too small to back
+    /// every address of the region.
     pub fn map<W, D>(&mut self, device: D, start: W, end: W, mapping_mode: MappingMode) -> Result<()>
     where
         W: Into<Word>,
         D: Into<Devices>,
     {
+        let (device, start, end) = (device.into(), start.into(), end.into());
+        let size = usize::from(end) - usize::from(start) + 1;
+        match mapping_mode {
+            MappingMode::Remap => device.check_range(0u16, size)?,
+            MappingMode::Direct => device.check_range(start, size)?,
+        }
+
         self.regions.push_front(MappedRegion {
-            device: device.into(),
-            start: start.into(),
-            end: end.into(),
+            device,
+            start,
+            end,
             mapping_mode,
         });
 
         Ok(())
     }
 
+    /// Copies the contents of every mapped device, in mapping order.
+    pub fn snapshot(&self) -> Vec<u8> {
+        self.regions
+            .iter()
+            .flat_map(|region| region.device.as_bytes().iter().copied())
+            .collect()
+    }
+
+    /// Overwrites every mapped device with the contents of a previous [`MemoryMapper::snapshot`],
+    /// returns false without touching memory when `bytes` doesn't match the size of the mapping.
+    pub fn restore(&mut self, bytes: &[u8]) -> bool {
+        let size = self
+            .regions
+            .iter()
+            .map(|region| region.device.as_bytes().len())
+            .sum::<usize>();
+        if size != bytes.len() {
+            return false;
+        }
+
+        let mut offset = 0;
+        for region in self.regions.iter_mut() {
+            let device = region.device.as_bytes_mut();
+            device.copy_from_slice(&bytes[offset..offset + device.len()]);
+            offset += device.len();
+        }
+        self.select_bank();
+
+        true
+    }
+
+    /// Swaps in the devices of `other` for every region whose current device `replace` accepts,
+    /// both mappers must map the same regions in the same order, like two consoles set up from
+    /// different roms do.
+    pub fn replace_devices(&mut self, other: MemoryMapper, replace: impl Fn(&Devices) -> bool) {
+        debug_assert!(self
+            .regions
+            .iter()
+            .map(|region| (region.start, region.end))
+            .eq(other.regions.iter().map(|region| (region.start, region.end))));
+
+        for (region, other) in self.regions.iter_mut().zip(other.regions) {
+            if replace(&region.device) {
+                region.device = other.device;
+            }
+        }
+        self.select_bank();
+    }
+
+    /// Enables or disables the write protection of every mapped program memory and code bank.
+    pub fn set_program_write_protected(&mut self, write_protected: bool) {
+        for region in self.regions.iter_mut() {
+            match &mut region.device {
+                Devices::Program(program) => program.set_write_protected(write_protected),
+                Devices::Bank(banks) => banks.set_write_protected(write_protected),
+                _ => {}
+            }
+        }
+    }
+
+    /// Maps the bank written on the bank select register at the bank window.
+    fn select_bank(&mut self) {
+        let Some(bank) = self.regions.iter().find_map(|region| match &region.device {
+            Devices::BankSelect(select) => Some(select.as_bytes()[0]),
+            _ => None,
+        }) else {
+            return;
+        };
+
+        for region in self.regions.iter_mut() {
+            if let Devices::Bank(banks) = &mut region.device {
+                banks.select(bank);
+            }
+        }
+    }
+
+    /// Collects what every mapped tile, background and interface memory recorded as written since
+    /// the last call.
+    pub fn take_dirty(&mut self) -> DirtyRegions {
+        let mut dirty = DirtyRegions::default();
+        for region in self.regions.iter_mut() {
+            match &mut region.device {
+                Devices::Tile(tiles) => dirty.tiles |= !tiles.take_dirty().is_empty(),
+                Devices::Background(background) => dirty.background = background.take_dirty(),
+                Devices::Interface(interface) => dirty.interface = interface.take_dirty(),
+                _ => {}
+            }
+        }
+        dirty
+    }
+
     fn find_region(&self, address: Word) -> Option<&MappedRegion> {
         self.regions
             .iter()
             .find(|region| address >= region.start && address <= region.end)
     }
 
+    /// Fails like writing `address` would, without writing it.
+    fn check_write(&self, address: Word) -> Result<()> {
+        let Some(region) = self.find_region(address) else {
+            return Err(Error::UnmappedAddress(address));
+        };
+        let address = match region.mapping_mode {
+            MappingMode::Remap => address - region.start,
+            MappingMode::Direct => address,
+        };
+        match &region.device {
+            Devices::Program(program) if program.write_protected => Err(write_protected(address)),
+            Devices::Bank(bank) if bank.write_protected => Err(Error::WriteProtected(address + BANK_MEM_LOC.0.into())),
+            Devices::Bank(bank) => bank.index(address).map(|_| ()),
+            _ => Ok(()),
+        }
+    }
+
     fn find_region_mut(&mut self, address: Word) -> Option<&mut MappedRegion> {
         self.regions
             .iter_mut()
@@ -174,6 +620,12 @@ impl MemoryMapper {
 }
 
 impl Addressable for MemoryMapper {
+    /// The mapper spans the whole address space, addresses outside every region are unmapped
+    /// rather than out of bounds.
+    fn len(&self) -> usize {
+        MEMORY_SIZE
+    }
+
     fn read<W>(&self, address: W) -> Result<u8>
     where
         W: Into<Word> + Copy,
@@ -189,6 +641,21 @@ impl Addressable for MemoryMapper {
         region.device.read(address)
     }
 
+    fn peek<W>(&self, address: W) -> Result<u8>
+    where
+        W: Into<Word> + Copy,
+    {
+        let address = address.into();
+        let Some(region) = self.find_region(address) else {
+            return Err(Error::UnmappedAddress(address));
+        };
+        let address = match region.mapping_mode {
+            MappingMode::Remap => address - region.start,
+            MappingMode::Direct => address,
+        };
+        region.device.peek(address)
+    }
+
     fn write<W>(&mut self, address: W, byte: impl Into<u8>) -> Result<()>
     where
         W: Into<Word> + Copy,
@@ -203,7 +670,11 @@ impl Addressable for MemoryMapper {
             MappingMode::Remap => address - region.start,
             MappingMode::Direct => address,
         };
-        region.device.write(address, byte)
+        region.device.write(address, byte)?;
+        if matches!(region.device, Devices::BankSelect(_)) {
+            self.select_bank();
+        }
+        Ok(())
     }
 
     fn read_word<W>(&self, address: W) -> Result<u16>
@@ -214,6 +685,10 @@ impl Addressable for MemoryMapper {
         let Some(region) = self.find_region(address) else {
             return Err(Error::UnmappedAddress(address));
         };
+        // a word starting on the last byte of a region continues on whatever is mapped after it
+        if address == region.end {
+            return Ok(u16::from_le_bytes([self.read(address)?, self.read(address.next()?)?]));
+        }
         let address = match region.mapping_mode {
             MappingMode::Remap => address - region.start,
             MappingMode::Direct => address,
@@ -229,10 +704,332 @@ impl Addressable for MemoryMapper {
         let Some(region) = self.find_region_mut(address) else {
             return Err(Error::UnmappedAddress(address));
         };
+        // a word on the last byte of a region is written whole or not at all
+        if address == region.end {
+            let next = address.next()?;
+            self.check_write(address)?;
+            self.check_write(next)?;
+            let [lower, upper] = word.to_le_bytes();
+            self.write(address, lower)?;
+            return self.write(next, upper);
+        }
         let address = match region.mapping_mode {
             MappingMode::Remap => address - region.start,
             MappingMode::Direct => address,
         };
-        region.device.write_word(address, word)
+        region.device.write_word(address, word)?;
+        if matches!(region.device, Devices::BankSelect(_)) {
+            self.select_bank();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::{
+        AUDIO_MEM_LOC, BANK_SELECT_MEM_LOC, BG_MEM_LOC, CODE_MEM_LOC, COLLISION_MEM_LOC, FG_MEM_LOC,
+        FRAMEBUFFER_MEM_LOC, FRAME_CONTROL_MEM_LOC, INPUT_MEM_LOC, INTERRUPT_MEM_LOC, SERIAL_MEM_LOC, SPRITE_MEM_LOC,
+        STACK_MEM_LOC, STACK_START, TILE_MEM_LOC, UI_MEM_LOC, VIDEO_MEM_LOC, VIDEO_MODE_MEM_LOC,
+    };
+
+    /// One of every device with the region the console maps it at.
+    fn console_devices() -> Vec<(Devices, (u16, u16))> {
+        vec![
+            (TileMem::from(LinearMemory::default()).into(), TILE_MEM_LOC),
+            (SpriteMem::from(LinearMemory::default()).into(), SPRITE_MEM_LOC),
+            (ProgramMem::from(LinearMemory::default()).into(), CODE_MEM_LOC),
+            (BackgroundMem::from(LinearMemory::default()).into(), BG_MEM_LOC),
+            (InterfaceMem::from(LinearMemory::default()).into(), UI_MEM_LOC),
+            (InterruptMem::from(LinearMemory::default()).into(), INTERRUPT_MEM_LOC),
+            (InputMem::from(LinearMemory::default()).into(), INPUT_MEM_LOC),
+            (AudioMem::from(LinearMemory::default()).into(), AUDIO_MEM_LOC),
+            (VideoMem::from(LinearMemory::default()).into(), VIDEO_MEM_LOC),
+            (SerialMem::capture().0.into(), SERIAL_MEM_LOC),
+            (BankSelectMem::from(LinearMemory::default()).into(), BANK_SELECT_MEM_LOC),
+            (
+                FrameControlMem::from(LinearMemory::default()).into(),
+                FRAME_CONTROL_MEM_LOC,
+            ),
+            (CollisionMem::from(LinearMemory::default()).into(), COLLISION_MEM_LOC),
+            (VideoModeMem::from(LinearMemory::default()).into(), VIDEO_MODE_MEM_LOC),
+            (
+                FramebufferMem::from(LinearMemory::default()).into(),
+                FRAMEBUFFER_MEM_LOC,
+            ),
+            (BankMem::new(&[&[]]).into(), BANK_MEM_LOC),
+            (StackMem::from(LinearMemory::default()).into(), STACK_MEM_LOC),
+        ]
+    }
+
+    #[test]
+    fn test_small_devices_in_the_address_space() {
+        for (device, (start, end)) in console_devices() {
+            let len = device.len();
+            assert_eq!(len, usize::from(end - start) + 1, "device at 0x{start:04X}");
+            assert!(matches!(
+                device.read(len as u16),
+                Err(Error::InvalidAddress(_) | Error::UnmappedAddress(_))
+            ));
+
+            let mut memory = MemoryMapper::default();
+            memory.map(device, start, end, MappingMode::Remap).unwrap();
+            assert_eq!(memory.len(), MEMORY_SIZE);
+            memory.check_range(start, len).unwrap();
+            assert!(memory.read(end).is_ok(), "device at 0x{start:04X}");
+            if end < u16::MAX {
+                assert!(matches!(memory.read(end + 1), Err(Error::UnmappedAddress(_))));
+            }
+        }
+    }
+
+    #[test]
+    fn test_map_region_larger_than_device() {
+        for (device, (start, end)) in console_devices() {
+            let len = device.len();
+            let mut memory = MemoryMapper::default();
+            // mapping one byte past the device would leave the last address without backing
+            let start = start.checked_sub(1).unwrap_or(start);
+            let end = if start == 0 { end + 1 } else { end };
+            let err = memory.map(device, start, end, MappingMode::Remap).unwrap_err();
+            assert!(
+                matches!(err, Error::OutOfBounds { size, len: l, .. } if size == len + 1 && l == len),
+                "device at 0x{start:04X}"
+            );
+        }
+
+        // directly mapped devices are indexed by the whole address, which only fits on full size memories
+        let mut memory = MemoryMapper::default();
+        let err = memory
+            .map(
+                InputMem::from(LinearMemory::default()),
+                INPUT_MEM_LOC.0,
+                INPUT_MEM_LOC.1,
+                MappingMode::Direct,
+            )
+            .unwrap_err();
+        assert!(matches!(err, Error::OutOfBounds { len: INPUT_MEMORY, .. }));
+    }
+
+    fn setup_program_memory() -> MemoryMapper {
+        let mut memory = MemoryMapper::default();
+        memory
+            .map(
+                ProgramMem::from(LinearMemory::default()),
+                CODE_MEM_LOC.0,
+                CODE_MEM_LOC.1,
+                MappingMode::Remap,
+            )
+            .unwrap();
+        memory
+    }
+
+    #[test]
+    fn test_write_word_at_the_top() {
+        let mut memory = MemoryMapper::default();
+        memory
+            .map(
+                StackMem::from(LinearMemory::default()),
+                STACK_MEM_LOC.0,
+                STACK_MEM_LOC.1,
+                MappingMode::Remap,
+            )
+            .unwrap();
+
+        memory.write_word(STACK_START, 0xABCD).unwrap();
+        assert_eq!(memory.read(STACK_MEM_LOC.1).unwrap(), 0xAB);
+        assert_eq!(memory.read_word(STACK_START).unwrap(), 0xABCD);
+
+        // a word at the last byte has no room for its upper byte
+        assert!(memory.write_word(STACK_MEM_LOC.1, 0x1234).is_err());
+        assert!(memory.read_word(STACK_MEM_LOC.1).is_err());
+    }
+
+    #[test]
+    fn test_words_across_regions() {
+        let mut memory = MemoryMapper::default();
+        memory
+            .map(
+                BackgroundMem::from(LinearMemory::default()),
+                BG_MEM_LOC.0,
+                BG_MEM_LOC.1,
+                MappingMode::Remap,
+            )
+            .unwrap();
+        memory
+            .map(
+                InterfaceMem::from(LinearMemory::default()),
+                UI_MEM_LOC.0,
+                UI_MEM_LOC.1,
+                MappingMode::Remap,
+            )
+            .unwrap();
+
+        // the foreground sits between both, the word on the last background byte needs it
+        let err = memory.write_word(BG_MEM_LOC.1, 0x1234).unwrap_err();
+        assert!(matches!(err, Error::UnmappedAddress(address) if address == Word::from(BG_MEM_LOC.1 + 1)));
+        assert_eq!(memory.read(BG_MEM_LOC.1).unwrap(), 0x00);
+
+        // words within a region match two byte accesses
+        memory.write_word(UI_MEM_LOC.0, 0xABCD).unwrap();
+        assert_eq!(memory.read(UI_MEM_LOC.0).unwrap(), 0xCD);
+        assert_eq!(memory.read(UI_MEM_LOC.0 + 1).unwrap(), 0xAB);
+        assert_eq!(memory.read_word(UI_MEM_LOC.0).unwrap(), 0xABCD);
+
+        // a word on the last byte of a region continues on the next one
+        memory
+            .map(
+                BackgroundMem::from(LinearMemory::default()),
+                FG_MEM_LOC.0,
+                FG_MEM_LOC.1,
+                MappingMode::Remap,
+            )
+            .unwrap();
+        memory.write_word(FG_MEM_LOC.1, 0x5678).unwrap();
+        assert_eq!(memory.read(FG_MEM_LOC.1).unwrap(), 0x78);
+        assert_eq!(memory.read(UI_MEM_LOC.0).unwrap(), 0x56);
+        assert_eq!(memory.read_word(FG_MEM_LOC.1).unwrap(), 0x5678);
+
+        let dirty = memory.take_dirty();
+        assert!(dirty.interface.iter().any(|cell| cell == 0));
+    }
+
+    #[test]
+    fn test_write_protected_program() {
+        let mut memory = setup_program_memory();
+        memory.write(CODE_MEM_LOC.0, 0xAB).unwrap();
+        memory.set_program_write_protected(true);
+
+        let err = memory.write(CODE_MEM_LOC.0, 0xCD).unwrap_err();
+        assert!(matches!(err, Error::WriteProtected(address) if address == Word::from(CODE_MEM_LOC.0)));
+        assert!(matches!(
+            memory.write_word(CODE_MEM_LOC.0, 0xCDCD),
+            Err(Error::WriteProtected(_))
+        ));
+        assert_eq!(memory.read(CODE_MEM_LOC.0).unwrap(), 0xAB);
+
+        // nor half of a word ending on it
+        memory
+            .map(
+                SpriteMem::from(LinearMemory::default()),
+                SPRITE_MEM_LOC.0,
+                SPRITE_MEM_LOC.1,
+                MappingMode::Remap,
+            )
+            .unwrap();
+        let err = memory.write_word(SPRITE_MEM_LOC.1, 0x1234).unwrap_err();
+        assert!(matches!(err, Error::WriteProtected(address) if address == Word::from(CODE_MEM_LOC.0)));
+        assert_eq!(memory.read(SPRITE_MEM_LOC.1).unwrap(), 0x00);
+    }
+
+    #[test]
+    fn test_writable_program() {
+        let mut memory = setup_program_memory();
+        memory.set_program_write_protected(false);
+
+        memory.write(CODE_MEM_LOC.0, 0xCD).unwrap();
+        memory.write_word(CODE_MEM_LOC.0 + 2, 0xBEEF).unwrap();
+        assert_eq!(memory.read(CODE_MEM_LOC.0).unwrap(), 0xCD);
+        assert_eq!(memory.read_word(CODE_MEM_LOC.0 + 2).unwrap(), 0xBEEF);
+    }
+
+    #[test]
+    fn test_take_dirty() {
+        let mut memory = MemoryMapper::default();
+        memory
+            .map(
+                TileMem::from(LinearMemory::default()),
+                TILE_MEM_LOC.0,
+                TILE_MEM_LOC.1,
+                MappingMode::Remap,
+            )
+            .unwrap();
+        memory
+            .map(
+                BackgroundMem::from(LinearMemory::default()),
+                BG_MEM_LOC.0,
+                BG_MEM_LOC.1,
+                MappingMode::Remap,
+            )
+            .unwrap();
+        memory
+            .map(
+                InterfaceMem::from(LinearMemory::default()),
+                UI_MEM_LOC.0,
+                UI_MEM_LOC.1,
+                MappingMode::Remap,
+            )
+            .unwrap();
+        assert_eq!(memory.take_dirty(), DirtyRegions::default());
+
+        memory.write(BG_MEM_LOC.0 + 3, 1).unwrap();
+        memory.write_word(UI_MEM_LOC.0 + 10, 0x0101).unwrap();
+        let dirty = memory.take_dirty();
+        assert_eq!(dirty.background.iter().collect::<Vec<_>>(), [3]);
+        assert_eq!(dirty.interface.iter().collect::<Vec<_>>(), [10, 11]);
+        assert!(!dirty.tiles);
+        assert_eq!(dirty.cells(), 3);
+
+        memory.write(TILE_MEM_LOC.0, 0x11).unwrap();
+        let dirty = memory.take_dirty();
+        assert!(dirty.tiles);
+        assert_eq!(dirty.cells(), 0);
+        assert_eq!(memory.take_dirty(), DirtyRegions::default());
+    }
+
+    fn setup_banks(banks: &[&[u8]]) -> MemoryMapper {
+        let mut memory = MemoryMapper::default();
+        memory
+            .map(
+                BankSelectMem::from(LinearMemory::default()),
+                BANK_SELECT_MEM_LOC.0,
+                BANK_SELECT_MEM_LOC.1,
+                MappingMode::Remap,
+            )
+            .unwrap();
+        memory
+            .map(BankMem::new(banks), BANK_MEM_LOC.0, BANK_MEM_LOC.1, MappingMode::Remap)
+            .unwrap();
+        memory
+    }
+
+    #[test]
+    fn test_bank_select() {
+        let mut memory = setup_banks(&[&[0x11, 0x12], &[0x21, 0x22]]);
+        assert_eq!(memory.read_word(BANK_MEM_LOC.0).unwrap(), 0x1211);
+        assert_eq!(memory.read(BANK_MEM_LOC.1).unwrap(), 0);
+
+        memory.write(BANK_SELECT_MEM_LOC.0, 1).unwrap();
+        assert_eq!(memory.read_word(BANK_MEM_LOC.0).unwrap(), 0x2221);
+
+        // banks past the ones on the rom leave the window unmapped
+        memory.write(BANK_SELECT_MEM_LOC.0, 2).unwrap();
+        let err = memory.read(BANK_MEM_LOC.0 + 1).unwrap_err();
+        assert!(matches!(err, Error::UnmappedAddress(address) if address == Word::from(BANK_MEM_LOC.0 + 1)));
+    }
+
+    #[test]
+    fn test_write_protected_banks() {
+        let mut memory = setup_banks(&[&[0x11]]);
+        memory.write(BANK_MEM_LOC.0, 0xAB).unwrap();
+        memory.set_program_write_protected(true);
+
+        assert!(matches!(
+            memory.write(BANK_MEM_LOC.0, 0xCD),
+            Err(Error::WriteProtected(address)) if address == Word::from(BANK_MEM_LOC.0)
+        ));
+        assert_eq!(memory.read(BANK_MEM_LOC.0).unwrap(), 0xAB);
+    }
+
+    #[test]
+    fn test_restore_keeps_selected_bank() {
+        let mut memory = setup_banks(&[&[0x11], &[0x21]]);
+        memory.write(BANK_SELECT_MEM_LOC.0, 1).unwrap();
+        let snapshot = memory.snapshot();
+
+        memory.write(BANK_SELECT_MEM_LOC.0, 0).unwrap();
+        assert!(memory.restore(&snapshot));
+        assert_eq!(memory.read(BANK_MEM_LOC.0).unwrap(), 0x21);
     }
 }
@@ -1,7 +1,14 @@
+mod dirty;
 mod linear_memory;
+mod map;
 pub mod memory_mapper;
+pub mod serial;
 
-pub use linear_memory::LinearMemory;
+pub use dirty::{DirtyCells, DirtyRegions};
+pub use linear_memory::{LinearMemory, SizeError};
+pub use map::{MemoryMap, Region, RegionKind};
+
+use crate::framebuffer::FRAMEBUFFER_MODE;
 
 const KB: usize = 1024;
 const KB8: usize = KB * 8;
@@ -14,8 +21,25 @@ pub const BG_MEMORY: usize = 420;
 pub const INTERFACE_MEMORY: usize = 420;
 pub const INTERRUPT_MEMORY: usize = 16;
 pub const INPUT_MEMORY: usize = 1;
+pub const AUDIO_MEMORY: usize = 12;
+pub const VIDEO_MEMORY: usize = 4;
+pub const SERIAL_MEMORY: usize = 1;
+pub const BANK_SELECT_MEMORY: usize = 1;
+pub const FRAME_CONTROL_MEMORY: usize = 2;
+pub const COLLISION_MEMORY: usize = 4;
+pub const VIDEO_MODE_MEMORY: usize = 1;
+pub const FRAMEBUFFER_MEMORY: usize = 13440;
+pub const BANK_MEMORY: usize = KB16;
 pub const STACK_MEMORY: usize = KB8;
 
+// roms are checked against the memory they are loaded into by aya-rom, which can't see these
+const _: () = assert!(
+    TILE_MEMORY == aya_rom::MAX_SPRITES_SIZE
+        && BANK_MEMORY == aya_rom::MAX_BANK_SIZE
+        && BG_MEMORY == aya_rom::MAX_BACKGROUND_SIZE
+        && INTERFACE_MEMORY == aya_rom::MAX_INTERFACE_SIZE
+);
+
 /// 8KIB Tile memory
 pub const TILE_MEM_LOC: (u16, u16) = (0x0000, 0x1FFF);
 
@@ -40,13 +64,138 @@ pub const INTERRUPT_MEM_LOC: (u16, u16) = (0x676C, 0x677B);
 ///   1B Input mapping
 pub const INPUT_MEM_LOC: (u16, u16) = (0x677C, 0x677C);
 
+///  12B Audio registers
+pub const AUDIO_MEM_LOC: (u16, u16) = (0x677D, 0x6788);
+
+///   4B Video registers
+pub const VIDEO_MEM_LOC: (u16, u16) = (0x6789, 0x678C);
+
+///   1B Serial debug port
+pub const SERIAL_MEM_LOC: (u16, u16) = (0x678D, 0x678D);
+
+///   1B Bank select register, the code bank mapped at the bank window
+pub const BANK_SELECT_MEM_LOC: (u16, u16) = (0x678E, 0x678E);
+
+///   2B Frame control, the frame skip requested by the program and the measured frame rate
+pub const FRAME_CONTROL_MEM_LOC: (u16, u16) = (0x678F, 0x6790);
+
+///   4B Collision registers, the sprite collisions found on the last frame
+pub const COLLISION_MEM_LOC: (u16, u16) = (0x6791, 0x6794);
+
+///   1B Video mode, whether the screen is drawn from the tile layers or from the framebuffer
+pub const VIDEO_MODE_MEM_LOC: (u16, u16) = (0x6795, 0x6795);
+
+/// 13KiB Framebuffer, every pixel of the screen at 4 bits per pixel, drawn on the framebuffer mode
+pub const FRAMEBUFFER_MEM_LOC: (u16, u16) = (0x6B80, 0x9FFF);
+
+/// 16KB Bank window, where the selected code bank of the rom is mapped
+pub const BANK_MEM_LOC: (u16, u16) = (0xA000, 0xDFFF);
+
 /// 8KiB Stack memory
 pub const STACK_MEM_LOC: (u16, u16) = (0xE000, 0xFFFF);
 
+/// Where the stack pointer starts, the stack grows down and the pointer always holds the address
+/// of the next word pushed, so it starts at the topmost word of stack memory rather than its last
+/// byte
+pub const STACK_START: u16 = STACK_MEM_LOC.1 - 1;
+
+/// Every region of the memory map under the name programs know it by on `std/console.aya`, the
+/// name of its `*_MEM_LOC` constant without the suffix, sorted by address.
+const MODULE_REGIONS: &[(&str, (u16, u16))] = &[
+    ("TILE_MEM", TILE_MEM_LOC),
+    ("SPRITE_MEM", SPRITE_MEM_LOC),
+    ("CODE_MEM", CODE_MEM_LOC),
+    ("BG_MEM", BG_MEM_LOC),
+    ("FG_MEM", FG_MEM_LOC),
+    ("UI_MEM", UI_MEM_LOC),
+    ("INTERRUPT_MEM", INTERRUPT_MEM_LOC),
+    ("INPUT_MEM", INPUT_MEM_LOC),
+    ("AUDIO_MEM", AUDIO_MEM_LOC),
+    ("VIDEO_MEM", VIDEO_MEM_LOC),
+    ("SERIAL_MEM", SERIAL_MEM_LOC),
+    ("BANK_SELECT_MEM", BANK_SELECT_MEM_LOC),
+    ("FRAME_CONTROL_MEM", FRAME_CONTROL_MEM_LOC),
+    ("COLLISION_MEM", COLLISION_MEM_LOC),
+    ("VIDEO_MODE_MEM", VIDEO_MODE_MEM_LOC),
+    ("FRAMEBUFFER_MEM", FRAMEBUFFER_MEM_LOC),
+    ("BANK_MEM", BANK_MEM_LOC),
+    ("STACK_MEM", STACK_MEM_LOC),
+];
+
+/// Handler of every interrupt under the name programs know it by on `std/console.aya`.
+const MODULE_INTERRUPTS: &[(&str, Interrupt)] = &[
+    ("AFTER_FRAME_INTERRUPT", Interrupt::AfterFrame),
+    ("SCANLINE_INTERRUPT", Interrupt::Scanline),
+    ("INPUT_INTERRUPT", Interrupt::Input),
+];
+
+// every constant of the generated module must be a 16 bit number, and a map with overlapping
+// regions would have programs write to one device through the other
+const _: () = assert!(module_regions_are_valid(MODULE_REGIONS));
+const _: () = assert!((Interrupt::Input as usize + 1) * 2 <= INTERRUPT_MEMORY);
+
+/// Whether `regions` are sorted, disjoint and each of a size that fits in 16 bits.
+const fn module_regions_are_valid(regions: &[(&str, (u16, u16))]) -> bool {
+    let mut i = 0;
+    while i < regions.len() {
+        let (_, (start, end)) = regions[i];
+        if start > end || (end - start) as usize + 1 > u16::MAX as usize {
+            return false;
+        }
+        if i + 1 < regions.len() && end >= regions[i + 1].1 .0 {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// Source of `std/console.aya`, an assembly module exporting the first address, last address and
+/// size of every memory region and the address of every interrupt handler, so programs can import
+/// the memory map rather than hardcode it.
+pub fn console_module() -> String {
+    let mut module = String::from("; generated from the aya_console memory map, do not edit by hand\n");
+    for (name, (start, end)) in MODULE_REGIONS {
+        module.push_str(&format!("+const {name} = ${start:04X}\n"));
+        module.push_str(&format!("+const {name}_END = ${end:04X}\n"));
+        module.push_str(&format!("+const {name}_SIZE = ${:04X}\n", end - start + 1));
+    }
+    module.push_str(&format!("+const STACK_START = ${STACK_START:04X}\n"));
+    for (name, interrupt) in MODULE_INTERRUPTS {
+        let address = INTERRUPT_MEM_LOC.0 + u16::from(*interrupt) * 2;
+        module.push_str(&format!("+const {name} = ${address:04X}\n"));
+    }
+    module.push_str(&format!("+const FRAMEBUFFER_MODE = ${FRAMEBUFFER_MODE:02X}\n"));
+    module
+}
+
+/// Address every data section of a rom is loaded at, back to back up to the end of code memory, so
+/// they stay put however much the code grows. `None` when they take more than code memory.
+pub fn data_addresses(sizes: impl IntoIterator<Item = usize>) -> Option<Vec<u16>> {
+    let sizes = sizes.into_iter().collect::<Vec<_>>();
+    let total = sizes.iter().sum::<usize>();
+    let mut address = (CODE_MEM_LOC.1 as usize + 1).checked_sub(total)?;
+    if address < CODE_MEM_LOC.0 as usize {
+        return None;
+    }
+
+    let addresses = sizes
+        .iter()
+        .map(|size| {
+            let start = address as u16;
+            address += size;
+            start
+        })
+        .collect();
+    Some(addresses)
+}
+
 #[repr(u16)]
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
 pub enum Interrupt {
     AfterFrame,
+    Scanline,
+    Input,
 }
 
 impl From<Interrupt> for u16 {
@@ -54,3 +203,44 @@ impl From<Interrupt> for u16 {
         value as u16
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use aya_cpu::instruction::Instruction;
+
+    use super::*;
+
+    #[test]
+    fn test_console_module_is_up_to_date() {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../std/console.aya");
+        if std::env::var_os("AYA_UPDATE_STD").is_some() {
+            std::fs::write(&path, console_module()).unwrap();
+        }
+
+        let module = std::fs::read_to_string(&path).unwrap_or_default();
+        assert!(
+            module == console_module(),
+            "std/console.aya is out of date, run `just std`"
+        );
+    }
+
+    #[test]
+    fn test_console_module_constants() {
+        let code = String::from(
+            "import \"./console.aya\" Console &[$0000] {}\n\
+             mov8 &[!Console.SERIAL_MEM], $0A\n\
+             mov &[!Console.INPUT_INTERRUPT], !Console.FRAMEBUFFER_MEM_SIZE\n",
+        );
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../std/main.aya");
+        let (bytecode, _) = aya_assembly::assemble_with_symbols(code, path).unwrap();
+
+        let expected = [
+            Instruction::Mov8LitMem(SERIAL_MEM_LOC.0.into(), 0x0A),
+            Instruction::MovLitMem((INTERRUPT_MEM_LOC.0 + 4).into(), FRAMEBUFFER_MEMORY as u16),
+        ];
+        assert_eq!(
+            bytecode,
+            expected.iter().flat_map(Instruction::encode).collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,122 @@
+use std::io::Write;
+
+use aya_cpu::memory::{Addressable, CapturedOutput, OutputMemory, Result};
+use aya_cpu::word::Word;
+
+use super::{LinearMemory, SERIAL_MEMORY};
+
+/// One byte debug port, every byte written to it goes to an [`OutputMemory`], which writes it to
+/// the sink one line at a time.
+pub struct SerialMem {
+    /// Last byte written to the port, kept so the device takes part on save states
+    register: LinearMemory<SERIAL_MEMORY>,
+    output: OutputMemory,
+}
+
+impl SerialMem {
+    pub fn new(sink: Box<dyn Write>) -> Self {
+        Self {
+            register: LinearMemory::default(),
+            output: OutputMemory::new(sink),
+        }
+    }
+
+    /// Creates a port whose output is kept in memory, so it can be inspected through the returned
+    /// [`CapturedOutput`].
+    pub fn capture() -> (Self, CapturedOutput) {
+        let (output, captured) = OutputMemory::capture();
+        let serial = Self {
+            register: LinearMemory::default(),
+            output,
+        };
+        (serial, captured)
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        self.register.as_bytes()
+    }
+
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+        self.register.as_bytes_mut()
+    }
+}
+
+impl Default for SerialMem {
+    fn default() -> Self {
+        Self::new(Box::new(std::io::stdout()))
+    }
+}
+
+impl std::fmt::Debug for SerialMem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SerialMem")
+            .field("register", &self.register)
+            .field("output", &self.output)
+            .finish()
+    }
+}
+
+impl Addressable for SerialMem {
+    fn len(&self) -> usize {
+        SERIAL_MEMORY
+    }
+
+    fn write<W>(&mut self, address: W, byte: impl Into<u8>) -> Result<()>
+    where
+        W: Into<Word> + Copy,
+    {
+        let byte = byte.into();
+        self.register.write(address, byte)?;
+        self.output.write(0, byte)
+    }
+
+    fn read<W>(&self, address: W) -> Result<u8>
+    where
+        W: Into<Word> + Copy,
+    {
+        self.register.read(address)
+    }
+
+    fn peek<W>(&self, address: W) -> Result<u8>
+    where
+        W: Into<Word> + Copy,
+    {
+        self.register.peek(address)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_str(serial: &mut SerialMem, text: &str) {
+        for byte in text.bytes() {
+            serial.write(0, byte).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_flush_on_newline() {
+        let (mut serial, output) = SerialMem::capture();
+
+        write_str(&mut serial, "hello");
+        assert_eq!(output.contents(), "");
+        assert_eq!(serial.read(0).unwrap(), b'o');
+
+        write_str(&mut serial, "\nworld");
+        assert_eq!(output.contents(), "hello\n");
+
+        drop(serial);
+        assert_eq!(output.contents(), "hello\nworld");
+    }
+
+    #[test]
+    fn test_only_the_port_is_addressable() {
+        let (mut serial, output) = SerialMem::capture();
+        assert!(serial.write(1, b'a').is_err());
+        assert!(serial.read(1).is_err());
+
+        drop(serial);
+        assert_eq!(output.contents(), "");
+    }
+}
@@ -0,0 +1,191 @@
+use std::time::Duration;
+#[cfg(feature = "native")]
+use std::time::Instant;
+
+pub const GLYPH_WIDTH: usize = 3;
+pub const GLYPH_HEIGHT: usize = 5;
+/// Blank pixels between glyphs and between lines
+const SPACING: usize = 1;
+
+/// Built-in font used by the overlay, so it can be drawn no matter what the rom holds on its tile
+/// memory. Every glyph row uses its 3 lowest bits, the highest of them being the leftmost pixel.
+#[rustfmt::skip]
+const FONT: [[u8; GLYPH_HEIGHT]; 39] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111], // 0
+    [0b010, 0b110, 0b010, 0b010, 0b111], // 1
+    [0b111, 0b001, 0b111, 0b100, 0b111], // 2
+    [0b111, 0b001, 0b111, 0b001, 0b111], // 3
+    [0b101, 0b101, 0b111, 0b001, 0b001], // 4
+    [0b111, 0b100, 0b111, 0b001, 0b111], // 5
+    [0b111, 0b100, 0b111, 0b101, 0b111], // 6
+    [0b111, 0b001, 0b001, 0b001, 0b001], // 7
+    [0b111, 0b101, 0b111, 0b101, 0b111], // 8
+    [0b111, 0b101, 0b111, 0b001, 0b111], // 9
+    [0b010, 0b101, 0b111, 0b101, 0b101], // A
+    [0b110, 0b101, 0b110, 0b101, 0b110], // B
+    [0b011, 0b100, 0b100, 0b100, 0b011], // C
+    [0b110, 0b101, 0b101, 0b101, 0b110], // D
+    [0b111, 0b100, 0b110, 0b100, 0b111], // E
+    [0b111, 0b100, 0b110, 0b100, 0b100], // F
+    [0b011, 0b100, 0b101, 0b101, 0b011], // G
+    [0b101, 0b101, 0b111, 0b101, 0b101], // H
+    [0b111, 0b010, 0b010, 0b010, 0b111], // I
+    [0b001, 0b001, 0b001, 0b101, 0b010], // J
+    [0b101, 0b101, 0b110, 0b101, 0b101], // K
+    [0b100, 0b100, 0b100, 0b100, 0b111], // L
+    [0b101, 0b111, 0b111, 0b101, 0b101], // M
+    [0b110, 0b101, 0b101, 0b101, 0b101], // N
+    [0b010, 0b101, 0b101, 0b101, 0b010], // O
+    [0b110, 0b101, 0b110, 0b100, 0b100], // P
+    [0b010, 0b101, 0b101, 0b110, 0b011], // Q
+    [0b110, 0b101, 0b110, 0b101, 0b101], // R
+    [0b011, 0b100, 0b010, 0b001, 0b110], // S
+    [0b111, 0b010, 0b010, 0b010, 0b010], // T
+    [0b101, 0b101, 0b101, 0b101, 0b111], // U
+    [0b101, 0b101, 0b101, 0b101, 0b010], // V
+    [0b101, 0b101, 0b111, 0b111, 0b101], // W
+    [0b101, 0b101, 0b010, 0b101, 0b101], // X
+    [0b101, 0b101, 0b010, 0b010, 0b010], // Y
+    [0b111, 0b001, 0b010, 0b100, 0b111], // Z
+    [0b000, 0b000, 0b000, 0b000, 0b010], // .
+    [0b000, 0b010, 0b000, 0b010, 0b000], // :
+    [0b000, 0b000, 0b000, 0b000, 0b000], // space
+];
+
+/// Characters without a glyph are drawn as a space.
+fn glyph(ch: char) -> &'static [u8; GLYPH_HEIGHT] {
+    let index = match ch.to_ascii_uppercase() {
+        ch @ '0'..='9' => ch as usize - '0' as usize,
+        ch @ 'A'..='Z' => ch as usize - 'A' as usize + 10,
+        '.' => 36,
+        ':' => 37,
+        _ => 38,
+    };
+    &FONT[index]
+}
+
+/// Size in pixels of `lines` once drawn with the built-in font.
+pub fn text_size(lines: &[String]) -> (usize, usize) {
+    let columns = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+    let width = (columns * (GLYPH_WIDTH + SPACING)).saturating_sub(SPACING);
+    let height = (lines.len() * (GLYPH_HEIGHT + SPACING)).saturating_sub(SPACING);
+    (width, height)
+}
+
+/// Position of every lit pixel of `lines` drawn with the built-in font, relative to the top left
+/// corner of the text.
+pub fn text_pixels(lines: &[String]) -> impl Iterator<Item = (usize, usize)> + '_ {
+    lines.iter().enumerate().flat_map(|(row, line)| {
+        line.chars().enumerate().flat_map(move |(column, ch)| {
+            let left = column * (GLYPH_WIDTH + SPACING);
+            let top = row * (GLYPH_HEIGHT + SPACING);
+            glyph(ch).iter().enumerate().flat_map(move |(y, bits)| {
+                (0..GLYPH_WIDTH)
+                    .filter(move |x| bits & (1 << (GLYPH_WIDTH - 1 - x)) != 0)
+                    .map(move |x| (left + x, top + y))
+            })
+        })
+    })
+}
+
+/// Performance figures shown by the overlay.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct FrameStats {
+    /// Frames presented over the last second
+    pub fps: f32,
+    /// Time the renderer took to draw the last frame
+    pub draw_time: Duration,
+    /// Instructions the cpu executed on the last frame
+    pub instructions: usize,
+    /// Background and interface cells that changed since the last drawn frame
+    pub dirty_tiles: usize,
+    /// Frames that pass for every presented one, as requested by the program
+    pub frame_skip: u8,
+}
+
+impl FrameStats {
+    pub fn lines(&self) -> Vec<String> {
+        vec![
+            format!("FPS {:.1}", self.fps),
+            format!("DRAW {:.2}MS", self.draw_time.as_secs_f64() * 1000.0),
+            format!("INST {}", self.instructions),
+            format!("DIRTY {}", self.dirty_tiles),
+            format!("SKIP {}", self.frame_skip),
+        ]
+    }
+}
+
+/// Counts presented frames, updating the measured rate once every second.
+#[cfg(feature = "native")]
+#[derive(Debug)]
+pub struct FpsCounter {
+    frames: usize,
+    second_start: Instant,
+    fps: f32,
+}
+
+#[cfg(feature = "native")]
+impl Default for FpsCounter {
+    fn default() -> Self {
+        Self {
+            frames: 0,
+            second_start: Instant::now(),
+            fps: 0.0,
+        }
+    }
+}
+
+#[cfg(feature = "native")]
+impl FpsCounter {
+    /// Records a presented frame and returns the current rate.
+    pub fn tick(&mut self) -> f32 {
+        self.frames += 1;
+
+        let elapsed = self.second_start.elapsed();
+        if elapsed >= Duration::from_secs(1) {
+            self.fps = self.frames as f32 / elapsed.as_secs_f32();
+            self.frames = 0;
+            self.second_start = Instant::now();
+        }
+
+        self.fps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_pixels() {
+        let lines = vec!["1".to_string(), " .".to_string()];
+        assert_eq!(text_size(&lines), (7, 11));
+
+        let pixels = text_pixels(&lines).collect::<Vec<_>>();
+        let one = [(1, 0), (0, 1), (1, 1), (1, 2), (1, 3), (0, 4), (1, 4), (2, 4)];
+        assert_eq!(pixels[..one.len()], one);
+        // the dot sits on the bottom row of the second glyph of the second line
+        assert_eq!(pixels[one.len()..], [(5, 10)]);
+    }
+
+    #[test]
+    fn test_unknown_characters_are_blank() {
+        assert_eq!(text_pixels(&["#?".to_string()]).count(), 0);
+        assert_eq!(glyph('s'), glyph('S'));
+    }
+
+    #[test]
+    fn test_stats_lines() {
+        let stats = FrameStats {
+            fps: 59.94,
+            draw_time: Duration::from_micros(1250),
+            instructions: 2000,
+            dirty_tiles: 12,
+            frame_skip: 2,
+        };
+        assert_eq!(
+            stats.lines(),
+            ["FPS 59.9", "DRAW 1.25MS", "INST 2000", "DIRTY 12", "SKIP 2"]
+        );
+    }
+}
@@ -1,20 +1,67 @@
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// What referenced a tile, used to give context on rendering errors.
+#[cfg(feature = "renderer-raylib")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TileUser {
+    /// Slot of the sprite on sprite memory
+    Sprite(u16),
+    /// Address of the tilemap cell
+    Tilemap(u16),
+}
+
+#[cfg(feature = "renderer-raylib")]
+impl std::fmt::Display for TileUser {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TileUser::Sprite(slot) => write!(f, "sprite slot {slot}"),
+            TileUser::Tilemap(address) => write!(f, "tilemap cell 0x{address:04X}"),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum Error {
-    Memory,
+    /// Memory the frame is drawn from couldn't be read
+    Memory(aya_cpu::memory::Error),
+    #[cfg(feature = "renderer-raylib")]
+    MissingTile { tile: u8, user: TileUser },
+    #[cfg(feature = "renderer-raylib")]
+    Texture(String),
+    #[cfg(feature = "renderer-soft")]
+    Window(String),
+    #[cfg(feature = "renderer-web")]
+    Canvas(String),
 }
 
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{self:?}")
+        match self {
+            #[cfg(feature = "renderer-raylib")]
+            Error::MissingTile { tile, user } => write!(f, "tile {tile} used by {user} has no cached texture"),
+            #[cfg(feature = "renderer-raylib")]
+            Error::Texture(message) => write!(f, "failed to create tile texture: {message}"),
+            #[cfg(feature = "renderer-soft")]
+            Error::Window(message) => write!(f, "{message}"),
+            #[cfg(feature = "renderer-web")]
+            Error::Canvas(message) => write!(f, "failed to draw on the canvas: {message}"),
+            Error::Memory(err) => write!(f, "failed to read the frame from memory: {err}"),
+        }
     }
 }
 
-impl std::error::Error for Error {}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Memory(err) => Some(err),
+            #[cfg(any(feature = "renderer-raylib", feature = "renderer-soft", feature = "renderer-web"))]
+            _ => None,
+        }
+    }
+}
 
 impl From<aya_cpu::memory::Error> for Error {
-    fn from(_: aya_cpu::memory::Error) -> Self {
-        Self::Memory
+    fn from(err: aya_cpu::memory::Error) -> Self {
+        Self::Memory(err)
     }
 }
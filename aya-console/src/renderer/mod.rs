@@ -1,13 +1,173 @@
 mod error;
+mod raster;
+#[cfg(feature = "renderer-raylib")]
 pub mod raylib;
+#[cfg(feature = "renderer-soft")]
+pub mod soft;
+pub(crate) mod sprite;
+#[cfg(feature = "renderer-web")]
+pub mod web;
 
 use aya_cpu::memory::Addressable;
+pub use error::Error;
 use error::Result;
+
+use crate::memory::{DirtyCells, DirtyRegions};
+use crate::overlay::FrameStats;
+use crate::scanline::BackgroundState;
+pub(crate) use raster::Rasterizer;
+#[cfg(feature = "renderer-raylib")]
 pub use raylib::RaylibRenderer;
+#[cfg(feature = "renderer-soft")]
+pub use soft::SoftRenderer;
+#[cfg(feature = "renderer-web")]
+pub use web::WebRenderer;
+
+pub(crate) const TILES_WIDTH: u16 = 30;
+const TILES_HEIGHT: u16 = 14;
+const BYTES_PER_TILE: u16 = 32;
+pub(crate) const SPRITE_WIDTH: u16 = 8;
+pub(crate) const SPRITE_HEIGHT: u16 = 8;
+
+pub const SCREEN_WIDTH: usize = TILES_WIDTH as usize * SPRITE_WIDTH as usize;
+pub const SCREEN_HEIGHT: usize = TILES_HEIGHT as usize * SPRITE_HEIGHT as usize;
+
+const X_MIRROR_MASK: u8 = 0b00000001;
+const Y_MIRROR_MASK: u8 = 0b00000010;
+pub(crate) const SPRITE_ENABLE_MASK: u8 = 0b00000100;
+pub(crate) const SPRITE_ORIGIN_MASK: u8 = 0b00001000;
+/// Pixels the position of sprites with the offset origin is moved up and left by
+const SPRITE_ORIGIN_OFFSET: i16 = 8;
 
 pub trait Renderer {
-    fn start(name: &str, fps: f32, scale: u16) -> Self;
+    /// Opens whatever the frames are presented on, such as a window, titled `name`.
+    fn start(name: &str, fps: f32, scale: u16) -> Result<Self>
+    where
+        Self: Sized;
     fn should_close(&self) -> bool;
     fn should_draw(&self) -> bool;
-    fn draw_frame(&mut self, memory: &mut impl Addressable) -> Result<()>;
+    /// Draws a frame, `background` holds the background state latched for every visible line and
+    /// `dirty` what was written since the previous frame. Tilemaps are kept on persistent layers,
+    /// so only their dirty cells are redrawn.
+    fn draw_frame(
+        &mut self,
+        memory: &mut impl Addressable,
+        background: &[BackgroundState],
+        dirty: &DirtyRegions,
+    ) -> Result<()>;
+    /// Drops anything derived from memory, such as cached tiles or tilemap layers, so the next
+    /// frame reflects memory that was replaced wholesale.
+    fn invalidate(&mut self) {}
+    /// Lets the time of a frame pass without drawing it, for programs that asked to present only
+    /// every few frames.
+    fn skip_frame(&mut self) {}
+    /// Returns the last presented frame as `SCREEN_WIDTH * SCREEN_HEIGHT` RGBA pixels, row by row
+    /// from the top left corner.
+    fn capture_frame(&self) -> Vec<u8>;
+    /// Toggles the paused indicator, which is drawn on top of every frame and never touches the
+    /// console memory.
+    fn set_paused(&mut self, paused: bool);
+    /// Sets the performance figures drawn on top of every frame, `None` hides the overlay. Like
+    /// the paused indicator it never touches the console memory.
+    fn set_overlay(&mut self, stats: Option<FrameStats>);
+    /// Enables the per line sprite limit, only the first [`sprite::SPRITES_PER_LINE`] sprites
+    /// touching a line are drawn on it.
+    fn set_sprite_line_limit(&mut self, enabled: bool);
+    /// Replaces the colors tiles are drawn with, for roms made for their own palette. `palette`
+    /// holds 16 RGBA colors, the 0th one is still transparent.
+    fn set_palette(&mut self, palette: &[(u8, u8, u8, u8)]);
+    /// Switches between windowed and fullscreen, the playfield keeps its aspect ratio with black
+    /// bars around it.
+    fn toggle_fullscreen(&mut self);
+}
+
+/// Cells of a tilemap with `size` cells that must be redrawn, every one of them on a full redraw.
+fn cells_to_redraw(dirty: &DirtyCells, full_redraw: bool, size: usize) -> Vec<usize> {
+    match full_redraw {
+        true => (0..size).collect(),
+        false => dirty.iter().filter(|cell| *cell < size).collect(),
+    }
+}
+
+/// Placement of the playfield inside the window, scaled up by the largest integer factor that
+/// fits and centered on it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Viewport {
+    pub scale: u16,
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Viewport {
+    /// Fits the playfield on a window of the given size, windows smaller than the playfield still
+    /// draw it at its original size.
+    pub fn fit(window_width: usize, window_height: usize) -> Self {
+        let scale = (window_width / SCREEN_WIDTH).min(window_height / SCREEN_HEIGHT).max(1);
+        let width = SCREEN_WIDTH * scale;
+        let height = SCREEN_HEIGHT * scale;
+
+        Self {
+            scale: scale as u16,
+            x: (window_width as i32 - width as i32).max(0) / 2,
+            y: (window_height as i32 - height as i32).max(0) / 2,
+        }
+    }
+}
+
+/// Which renderer (and matching input) implementation drives the console window. Only the
+/// backends enabled through cargo features are available, raylib being the default one.
+#[cfg(feature = "native")]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    #[cfg(feature = "renderer-raylib")]
+    #[cfg_attr(feature = "renderer-raylib", default)]
+    Raylib,
+    #[cfg(feature = "renderer-soft")]
+    #[cfg_attr(not(feature = "renderer-raylib"), default)]
+    Soft,
+}
+
+#[cfg(feature = "native")]
+impl std::fmt::Display for Backend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            #[cfg(feature = "renderer-raylib")]
+            Backend::Raylib => write!(f, "raylib"),
+            #[cfg(feature = "renderer-soft")]
+            Backend::Soft => write!(f, "soft"),
+        }
+    }
+}
+
+#[cfg(feature = "native")]
+impl std::str::FromStr for Backend {
+    type Err = String;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        match value {
+            #[cfg(feature = "renderer-raylib")]
+            "raylib" => Ok(Backend::Raylib),
+            #[cfg(feature = "renderer-soft")]
+            "soft" => Ok(Backend::Soft),
+            _ => Err(format!("renderer '{value}' is not available in this build")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_viewport_fit() {
+        let exact = Viewport::fit(SCREEN_WIDTH * 4, SCREEN_HEIGHT * 4);
+        assert_eq!(exact, Viewport { scale: 4, x: 0, y: 0 });
+
+        // a wide window is limited by its height, leaving bars on the sides
+        let wide = Viewport::fit(2560, 800);
+        assert_eq!(wide, Viewport { scale: 7, x: 440, y: 8 });
+
+        let tiny = Viewport::fit(100, 50);
+        assert_eq!(tiny, Viewport { scale: 1, x: 0, y: 0 });
+    }
 }
@@ -0,0 +1,487 @@
+use aya_cpu::memory::Addressable;
+
+use super::error::Result;
+use super::sprite::sprite_table;
+use super::{
+    cells_to_redraw, BYTES_PER_TILE, SCREEN_HEIGHT, SCREEN_WIDTH, SPRITE_HEIGHT, SPRITE_WIDTH, TILES_WIDTH,
+    X_MIRROR_MASK, Y_MIRROR_MASK,
+};
+use crate::framebuffer::{self, VideoMode};
+use crate::memory::{DirtyRegions, BG_MEMORY, BG_MEM_LOC, INTERFACE_MEMORY, TILE_MEM_LOC, UI_MEM_LOC};
+use crate::overlay::{self, FrameStats};
+use crate::scanline::BackgroundState;
+use crate::PALETTE;
+
+/// Draws every layer of a frame on the cpu into a `SCREEN_WIDTH * SCREEN_HEIGHT` framebuffer of
+/// `0xRRGGBB` colors, for renderers that only need to present the finished frame.
+#[derive(Debug)]
+pub struct Rasterizer {
+    framebuffer: Vec<u32>,
+    /// Palette indices of the background tilemap, kept between frames so only dirty cells are
+    /// redrawn
+    background_layer: Vec<u8>,
+    /// Palette indices of the interface tilemap, kept like the background one
+    interface_layer: Vec<u8>,
+    /// Set until the layers are drawn for the first time, or after being invalidated
+    full_redraw: bool,
+    sprite_line_limit: bool,
+    paused: bool,
+    overlay: Option<FrameStats>,
+    /// Colors the palette indices on the layers and tiles are drawn with
+    palette: Vec<(u8, u8, u8, u8)>,
+}
+
+fn to_framebuffer_color((r, g, b, _): (u8, u8, u8, u8)) -> u32 {
+    ((r as u32) << 16) | ((g as u32) << 8) | b as u32
+}
+
+fn from_framebuffer_color(color: u32) -> [u8; 4] {
+    let [_, r, g, b] = color.to_be_bytes();
+    [r, g, b, 0xff]
+}
+
+/// Draws the tiles referenced by `cells` of a tilemap onto its layer, transparent pixels included
+/// so whatever the cell held before is replaced.
+fn draw_layer_cells(
+    layer: &mut [u8],
+    memory: &mut impl Addressable,
+    section_location: u16,
+    cells: &[usize],
+) -> Result<()> {
+    for &cell in cells {
+        let tile_idx = memory.peek(section_location + cell as u16)?;
+        let left = cell % TILES_WIDTH as usize * SPRITE_WIDTH as usize;
+        let top = cell / TILES_WIDTH as usize * SPRITE_HEIGHT as usize;
+
+        for tile_y in 0..SPRITE_HEIGHT {
+            let row = (top + tile_y as usize) * SCREEN_WIDTH + left;
+            for tile_x in 0..SPRITE_WIDTH {
+                layer[row + tile_x as usize] = Rasterizer::tile_pixel(memory, tile_idx, tile_x, tile_y)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+impl Default for Rasterizer {
+    fn default() -> Self {
+        Self {
+            framebuffer: vec![0; SCREEN_WIDTH * SCREEN_HEIGHT],
+            background_layer: vec![0; SCREEN_WIDTH * SCREEN_HEIGHT],
+            interface_layer: vec![0; SCREEN_WIDTH * SCREEN_HEIGHT],
+            full_redraw: true,
+            sprite_line_limit: false,
+            paused: false,
+            overlay: None,
+            palette: PALETTE.to_vec(),
+        }
+    }
+}
+
+// the emulator only rasterizes frames, presenting them is left to the frontends
+#[cfg_attr(not(any(feature = "renderer-soft", feature = "renderer-web")), allow(dead_code))]
+impl Rasterizer {
+    /// Last rasterized frame as RGBA pixels, in the same order as [`Rasterizer::framebuffer`].
+    pub fn to_rgba(&self) -> Vec<u8> {
+        let mut pixels = Vec::new();
+        self.copy_rgba(&mut pixels);
+        pixels
+    }
+
+    pub fn invalidate(&mut self) {
+        self.full_redraw = true;
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    pub fn set_overlay(&mut self, stats: Option<FrameStats>) {
+        self.overlay = stats;
+    }
+}
+
+impl Rasterizer {
+    /// Last rasterized frame, row by row from the top left corner.
+    pub fn framebuffer(&self) -> &[u32] {
+        &self.framebuffer
+    }
+
+    /// Replaces the contents of `pixels` with the last rasterized frame as RGBA pixels, reusing
+    /// its allocation.
+    pub fn copy_rgba(&self, pixels: &mut Vec<u8>) {
+        pixels.clear();
+        pixels.extend(
+            self.framebuffer()
+                .iter()
+                .flat_map(|color| from_framebuffer_color(*color)),
+        );
+    }
+
+    pub fn set_sprite_line_limit(&mut self, enabled: bool) {
+        self.sprite_line_limit = enabled;
+    }
+
+    pub fn set_palette(&mut self, palette: &[(u8, u8, u8, u8)]) {
+        // layers hold palette indices, so they are still valid with the new colors
+        self.palette = palette.to_vec();
+    }
+
+    fn tile_pixel(memory: &mut impl Addressable, tile_idx: u8, x: u16, y: u16) -> Result<u8> {
+        let address = TILE_MEM_LOC.0 + tile_idx as u16 * BYTES_PER_TILE + y * (SPRITE_WIDTH / 2) + x / 2;
+        let byte = memory.peek(address)?;
+        Ok(if x.is_multiple_of(2) { byte >> 4 } else { byte & 0xf })
+    }
+
+    /// Draws a tile with its top left corner at `(x, y)`, skipping the rows whose bit is unset on
+    /// `rows`.
+    fn render_tile(
+        &mut self,
+        memory: &mut impl Addressable,
+        tile_idx: u8,
+        (x, y): (i32, i32),
+        flags: u8,
+        rows: u8,
+    ) -> Result<()> {
+        for tile_y in (0..SPRITE_HEIGHT).filter(|row| rows & (1 << row) != 0) {
+            for tile_x in 0..SPRITE_WIDTH {
+                let source_x = match flags & X_MIRROR_MASK {
+                    0 => tile_x,
+                    _ => SPRITE_WIDTH - 1 - tile_x,
+                };
+                let source_y = match flags & Y_MIRROR_MASK {
+                    0 => tile_y,
+                    _ => SPRITE_HEIGHT - 1 - tile_y,
+                };
+
+                let color_idx = Self::tile_pixel(memory, tile_idx, source_x, source_y)?;
+                // the 0th palette color is transparent
+                if color_idx == 0 {
+                    continue;
+                }
+
+                let pixel_x = x + tile_x as i32;
+                let pixel_y = y + tile_y as i32;
+                if !(0..SCREEN_WIDTH as i32).contains(&pixel_x) || !(0..SCREEN_HEIGHT as i32).contains(&pixel_y) {
+                    continue;
+                }
+
+                let idx = pixel_y as usize * SCREEN_WIDTH + pixel_x as usize;
+                self.framebuffer[idx] = to_framebuffer_color(self.palette[color_idx as usize]);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Redraws the dirty cells of both tilemap layers, every cell when tiles changed since any of
+    /// them may look different now.
+    fn update_layers(&mut self, memory: &mut impl Addressable, dirty: &DirtyRegions) -> Result<()> {
+        let full_redraw = self.full_redraw || dirty.tiles;
+
+        let cells = cells_to_redraw(&dirty.background, full_redraw, BG_MEMORY);
+        draw_layer_cells(&mut self.background_layer, memory, BG_MEM_LOC.0, &cells)?;
+        let cells = cells_to_redraw(&dirty.interface, full_redraw, INTERFACE_MEMORY);
+        draw_layer_cells(&mut self.interface_layer, memory, UI_MEM_LOC.0, &cells)?;
+
+        self.full_redraw = false;
+        Ok(())
+    }
+
+    fn render_background(&mut self, background: &[BackgroundState]) {
+        for (pixel_y, state) in background.iter().enumerate() {
+            let y = (pixel_y + state.scroll_y as usize) % SCREEN_HEIGHT;
+            let layer_row = &self.background_layer[y * SCREEN_WIDTH..(y + 1) * SCREEN_WIDTH];
+
+            for pixel_x in 0..SCREEN_WIDTH {
+                let color_idx = layer_row[(pixel_x + state.scroll_x as usize) % SCREEN_WIDTH];
+                // the 0th palette color is transparent
+                if color_idx == 0 {
+                    continue;
+                }
+
+                self.framebuffer[pixel_y * SCREEN_WIDTH + pixel_x] =
+                    to_framebuffer_color(self.palette[color_idx as usize]);
+            }
+        }
+    }
+
+    fn render_interface(&mut self) {
+        for (pixel, color_idx) in self.framebuffer.iter_mut().zip(&self.interface_layer) {
+            if *color_idx != 0 {
+                *pixel = to_framebuffer_color(self.palette[*color_idx as usize]);
+            }
+        }
+    }
+
+    /// Draws a pause symbol, two bars over a dark box, on the top right corner of the screen.
+    fn render_paused_indicator(&mut self) {
+        const SIZE: usize = 9;
+        let left = SCREEN_WIDTH - SIZE - 2;
+        let top = 2;
+
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                let is_bar = (1..SIZE - 1).contains(&y) && matches!(x, 2 | 3 | 5 | 6);
+                let color = if is_bar { 0xFFFFFF } else { 0x000000 };
+                self.framebuffer[(top + y) * SCREEN_WIDTH + left + x] = color;
+            }
+        }
+    }
+
+    /// Draws the performance overlay with the built-in font on the top left corner of the screen.
+    fn render_overlay(&mut self, stats: &FrameStats) {
+        const MARGIN: usize = 2;
+        let lines = stats.lines();
+        let (width, height) = overlay::text_size(&lines);
+
+        for y in 0..(height + MARGIN * 2).min(SCREEN_HEIGHT) {
+            let row = y * SCREEN_WIDTH;
+            let end = (width + MARGIN * 2).min(SCREEN_WIDTH);
+            self.framebuffer[row..row + end].fill(0x000000);
+        }
+
+        for (x, y) in overlay::text_pixels(&lines) {
+            let (x, y) = (x + MARGIN, y + MARGIN);
+            if x < SCREEN_WIDTH && y < SCREEN_HEIGHT {
+                self.framebuffer[y * SCREEN_WIDTH + x] = 0xFFFFFF;
+            }
+        }
+    }
+
+    /// Draws the framebuffer in place of every tile layer.
+    fn render_framebuffer(&mut self, memory: &mut impl Addressable) -> Result<()> {
+        for (pixel, color_idx) in self.framebuffer.iter_mut().zip(framebuffer::pixels(&*memory)?) {
+            // the 0th palette color is transparent, so it shows the blank screen like on the layers
+            if color_idx != 0 {
+                *pixel = to_framebuffer_color(self.palette[color_idx as usize]);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn render_sprites(&mut self, memory: &mut impl Addressable) -> Result<()> {
+        for sprite in sprite_table(&*memory, self.sprite_line_limit)? {
+            let position = (sprite.x as i32, sprite.y as i32);
+            self.render_tile(memory, sprite.tile, position, sprite.flags, sprite.rows)?;
+        }
+
+        Ok(())
+    }
+
+    /// Draws every layer into the framebuffer, or the console framebuffer alone when its video
+    /// mode is selected.
+    pub fn rasterize(
+        &mut self,
+        memory: &mut impl Addressable,
+        background: &[BackgroundState],
+        dirty: &DirtyRegions,
+    ) -> Result<()> {
+        // layers keep following their tilemaps, so switching back to tiles draws them up to date
+        self.update_layers(memory, dirty)?;
+
+        self.framebuffer.fill(0);
+        match VideoMode::read(&*memory)? {
+            VideoMode::Tiles => {
+                self.render_background(background);
+                self.render_sprites(memory)?;
+                self.render_interface();
+            }
+            VideoMode::Framebuffer => self.render_framebuffer(memory)?,
+        }
+
+        if self.paused {
+            self.render_paused_indicator();
+        }
+
+        if let Some(stats) = self.overlay {
+            self.render_overlay(&stats);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use aya_cpu::MEMORY_SIZE;
+
+    use super::*;
+    use crate::framebuffer::FRAMEBUFFER_MODE;
+    use crate::memory::{LinearMemory, FRAMEBUFFER_MEM_LOC, SPRITE_MEM_LOC, VIDEO_MODE_MEM_LOC};
+    use crate::renderer::{SPRITE_ENABLE_MASK, SPRITE_ORIGIN_MASK};
+
+    /// Memory holding a tile of every color on each background cell, with the interface drawing
+    /// over the first row.
+    fn static_screen() -> LinearMemory<MEMORY_SIZE> {
+        let mut memory = LinearMemory::default();
+        for tile in 0..16u16 {
+            for byte in 0..BYTES_PER_TILE {
+                memory
+                    .write(
+                        TILE_MEM_LOC.0 + tile * BYTES_PER_TILE + byte,
+                        (tile as u8) << 4 | byte as u8 & 0xf,
+                    )
+                    .unwrap();
+            }
+        }
+        for cell in 0..BG_MEMORY as u16 {
+            memory.write(BG_MEM_LOC.0 + cell, (cell % 16) as u8).unwrap();
+        }
+        for cell in 0..TILES_WIDTH {
+            memory.write(UI_MEM_LOC.0 + cell, 3).unwrap();
+        }
+        memory
+    }
+
+    #[test]
+    fn test_dirty_redraw_matches_full_redraw() {
+        let background = [BackgroundState::default(); SCREEN_HEIGHT];
+        let mut memory = static_screen();
+        let mut rasterizer = Rasterizer::default();
+        rasterizer
+            .rasterize(&mut memory, &background, &DirtyRegions::default())
+            .unwrap();
+
+        memory.write(BG_MEM_LOC.0 + 100, 7).unwrap();
+        memory.write(UI_MEM_LOC.0 + 2, 0).unwrap();
+        let mut dirty = DirtyRegions::default();
+        dirty.background.insert(100);
+        dirty.interface.insert(2);
+        rasterizer.rasterize(&mut memory, &background, &dirty).unwrap();
+
+        let mut fresh = Rasterizer::default();
+        fresh
+            .rasterize(&mut memory, &background, &DirtyRegions::default())
+            .unwrap();
+        assert!(rasterizer.framebuffer == fresh.framebuffer);
+
+        // cells that were not reported as dirty keep what was drawn before
+        memory.write(BG_MEM_LOC.0 + 200, 9).unwrap();
+        rasterizer
+            .rasterize(&mut memory, &background, &DirtyRegions::default())
+            .unwrap();
+        assert!(rasterizer.framebuffer == fresh.framebuffer);
+
+        // written tiles may be used by any cell, so everything is redrawn
+        let dirty = DirtyRegions {
+            tiles: true,
+            ..Default::default()
+        };
+        rasterizer.rasterize(&mut memory, &background, &dirty).unwrap();
+        assert!(rasterizer.framebuffer != fresh.framebuffer);
+    }
+
+    #[test]
+    fn test_cleared_sprite_table_draws_only_the_background() {
+        let background = [BackgroundState::default(); SCREEN_HEIGHT];
+        let mut memory = LinearMemory::<MEMORY_SIZE>::default();
+        // tile 0 is opaque, so any sprite using it would show, tile 1 fills the background and the
+        // interface uses the blank tile 2
+        for byte in 0..BYTES_PER_TILE {
+            memory.write(TILE_MEM_LOC.0 + byte, 0x77).unwrap();
+            memory.write(TILE_MEM_LOC.0 + BYTES_PER_TILE + byte, 0x55).unwrap();
+        }
+        for cell in 0..BG_MEMORY as u16 {
+            memory.write(BG_MEM_LOC.0 + cell, 1).unwrap();
+        }
+        for cell in 0..INTERFACE_MEMORY as u16 {
+            memory.write(UI_MEM_LOC.0 + cell, 2).unwrap();
+        }
+
+        let mut rasterizer = Rasterizer::default();
+        rasterizer
+            .rasterize(&mut memory, &background, &DirtyRegions::default())
+            .unwrap();
+        let background_color = rasterizer.framebuffer[SCREEN_WIDTH * 50 + 50];
+        assert!(rasterizer.framebuffer.iter().all(|&color| color == background_color));
+
+        memory.write(SPRITE_MEM_LOC.0 + 3, SPRITE_ENABLE_MASK).unwrap();
+        rasterizer
+            .rasterize(&mut memory, &background, &DirtyRegions::default())
+            .unwrap();
+        assert_ne!(rasterizer.framebuffer[0], background_color);
+    }
+
+    #[test]
+    fn test_sprites_are_clipped_at_every_edge() {
+        let background = [BackgroundState::default(); SCREEN_HEIGHT];
+        let mut memory = LinearMemory::<MEMORY_SIZE>::default();
+        // tile 0 is blank on every layer and the sprites use the opaque tile 1
+        for byte in 0..BYTES_PER_TILE {
+            memory.write(TILE_MEM_LOC.0 + BYTES_PER_TILE + byte, 0x55).unwrap();
+        }
+
+        // stored positions and flags of a sprite half past each edge, and the screen position of
+        // its top left corner
+        let (width, height) = (SCREEN_WIDTH as i32, SCREEN_HEIGHT as i32);
+        let origin = SPRITE_ENABLE_MASK | SPRITE_ORIGIN_MASK;
+        let cases = [
+            ((4, 58), origin, (-4, 50)),
+            ((width as u8 + 4, 58), origin, (width - 4, 50)),
+            ((58, 4), origin, (50, -4)),
+            ((58, height as u8 + 4), origin, (50, height - 4)),
+            // without the offset origin, sprites past the right edge are clipped instead of wrapping
+            ((width as u8 - 4, 50), SPRITE_ENABLE_MASK, (width - 4, 50)),
+        ];
+
+        for ((x, y), flags, (left, top)) in cases {
+            memory.write(SPRITE_MEM_LOC.0, 1).unwrap();
+            memory.write(SPRITE_MEM_LOC.0 + 1, x).unwrap();
+            memory.write(SPRITE_MEM_LOC.0 + 2, y).unwrap();
+            memory.write(SPRITE_MEM_LOC.0 + 3, flags).unwrap();
+
+            let mut rasterizer = Rasterizer::default();
+            rasterizer
+                .rasterize(&mut memory, &background, &DirtyRegions::default())
+                .unwrap();
+
+            for (idx, &color) in rasterizer.framebuffer.iter().enumerate() {
+                let (pixel_x, pixel_y) = ((idx % SCREEN_WIDTH) as i32, (idx / SCREEN_WIDTH) as i32);
+                let inside = (left..left + 8).contains(&pixel_x) && (top..top + 8).contains(&pixel_y);
+                assert_eq!(color != 0, inside, "pixel {pixel_x},{pixel_y} of sprite at {x},{y}");
+            }
+            let drawn = rasterizer.framebuffer.iter().filter(|&&color| color != 0).count();
+            assert_eq!(drawn, 32);
+        }
+    }
+
+    #[test]
+    fn test_framebuffer_mode_replaces_the_layers() {
+        let background = [BackgroundState::default(); SCREEN_HEIGHT];
+        let mut memory = static_screen();
+        memory.write(VIDEO_MODE_MEM_LOC.0, FRAMEBUFFER_MODE).unwrap();
+        // the two leftmost pixels of the second row
+        memory
+            .write(FRAMEBUFFER_MEM_LOC.0 + SCREEN_WIDTH as u16 / 2, 0x5A)
+            .unwrap();
+
+        let mut rasterizer = Rasterizer::default();
+        rasterizer
+            .rasterize(&mut memory, &background, &DirtyRegions::default())
+            .unwrap();
+
+        let palette = rasterizer.palette.clone();
+        assert_eq!(rasterizer.framebuffer[SCREEN_WIDTH], to_framebuffer_color(palette[5]));
+        assert_eq!(
+            rasterizer.framebuffer[SCREEN_WIDTH + 1],
+            to_framebuffer_color(palette[0xA])
+        );
+        // every other pixel is transparent, nothing from the layers shows through
+        let drawn = rasterizer.framebuffer.iter().filter(|&&color| color != 0).count();
+        assert_eq!(drawn, 2);
+
+        memory.write(VIDEO_MODE_MEM_LOC.0, 0).unwrap();
+        rasterizer
+            .rasterize(&mut memory, &background, &DirtyRegions::default())
+            .unwrap();
+        let mut tiles = Rasterizer::default();
+        memory.write(FRAMEBUFFER_MEM_LOC.0, 0xFF).unwrap();
+        tiles
+            .rasterize(&mut memory, &background, &DirtyRegions::default())
+            .unwrap();
+        assert!(rasterizer.framebuffer == tiles.framebuffer);
+    }
+}
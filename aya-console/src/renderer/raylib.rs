@@ -4,22 +4,23 @@ use std::time::{Duration, Instant};
 
 use aya_cpu::memory::Addressable;
 use raylib::color::Color;
-use raylib::drawing::{RaylibDraw, RaylibDrawHandle};
+use raylib::drawing::{RaylibDraw, RaylibDrawHandle, RaylibScissorModeExt, RaylibTextureModeExt};
 use raylib::ffi::{PixelFormat, Rectangle, Vector2};
-use raylib::texture::{Image, Texture2D};
+use raylib::texture::{Image, RenderTexture2D, Texture2D};
 use raylib::{RaylibHandle, RaylibThread};
 
-use super::error::Result;
-use super::Renderer;
-use crate::memory::{BG_MEMORY, BG_MEM_LOC, INTERFACE_MEMORY, SPRITE_MEM_LOC, TILE_MEM_LOC, UI_MEM_LOC};
+use super::error::{Error, Result, TileUser};
+use super::sprite::sprite_table;
+use super::{
+    cells_to_redraw, Renderer, Viewport, BYTES_PER_TILE, SCREEN_HEIGHT, SCREEN_WIDTH, SPRITE_HEIGHT, SPRITE_WIDTH,
+    TILES_HEIGHT, TILES_WIDTH, X_MIRROR_MASK, Y_MIRROR_MASK,
+};
+use crate::framebuffer::{self, VideoMode};
+use crate::memory::{DirtyRegions, BG_MEMORY, BG_MEM_LOC, INTERFACE_MEMORY, TILE_MEM_LOC, UI_MEM_LOC};
+use crate::overlay::{self, FrameStats};
+use crate::scanline::{self, BackgroundState};
 use crate::PALETTE;
 
-const TILES_WIDTH: u16 = 30;
-const TILES_HEIGHT: u16 = 14;
-const BYTES_PER_TILE: u16 = 32;
-const SPRITE_WIDTH: u16 = 8;
-const SPRITE_HEIGHT: u16 = 8;
-
 pub static HANDLE: OnceLock<Arc<RwLock<RaylibHandle>>> = OnceLock::new();
 pub static NO_DRAWING_HANDLE: &str = "tried to draw with no drawing handle";
 
@@ -31,9 +32,6 @@ enum TextureFlags {
     MirrorY,
 }
 
-const X_MIRROR_MASK: u8 = 0b00000001;
-const Y_MIRROR_MASK: u8 = 0b00000010;
-
 impl IntoFlags for TextureFlags {
     fn into_flags(self) -> Vec<TextureFlags> {
         match self {
@@ -84,12 +82,24 @@ impl std::ops::BitOr for TextureFlags {
 
 #[derive(Debug)]
 pub struct RaylibRenderer {
-    scale: u16,
+    viewport: Viewport,
     thread: RaylibThread,
     frame_start: Instant,
     frame_duration: Duration,
     textures: HashMap<u8, Texture2D>,
     has_cached_tiles: bool,
+    /// Background tilemap drawn at its original size, kept between frames so only dirty cells are
+    /// redrawn
+    background_layer: Option<RenderTexture2D>,
+    /// Interface tilemap, kept like the background one
+    interface_layer: Option<RenderTexture2D>,
+    /// Set until the layers are drawn for the first time, or after being invalidated
+    full_redraw: bool,
+    sprite_line_limit: bool,
+    paused: bool,
+    overlay: Option<FrameStats>,
+    /// Colors cached tile textures are drawn with
+    palette: Vec<(u8, u8, u8, u8)>,
 }
 
 trait FromColor {
@@ -115,9 +125,9 @@ impl RaylibRenderer {
         let mut pixel_data = vec![0u8; (SPRITE_WIDTH * SPRITE_HEIGHT * 4) as usize];
 
         for byte_idx in 0..BYTES_PER_TILE {
-            let tile_byte = memory.read(tile_address + byte_idx)?;
-            let color_left = PALETTE[(tile_byte >> 4) as usize];
-            let color_right = PALETTE[(tile_byte & 0xf) as usize];
+            let tile_byte = memory.peek(tile_address + byte_idx)?;
+            let color_left = self.palette[(tile_byte >> 4) as usize];
+            let color_right = self.palette[(tile_byte & 0xf) as usize];
 
             let x = (byte_idx % 4) * 2;
             let y = byte_idx / 4;
@@ -129,98 +139,180 @@ impl RaylibRenderer {
             pixel_data[idx_right..idx_right + 4].copy_from_slice(&color_right.to_color_array());
         }
 
-        let mut image = Image::gen_image_color(SPRITE_WIDTH as i32, SPRITE_HEIGHT as i32, Color::BLANK);
+        let texture = self.load_texture(handle, SPRITE_WIDTH as i32, SPRITE_HEIGHT as i32, &pixel_data)?;
+        self.textures.insert(tile_idx, texture);
+
+        Ok(())
+    }
+
+    /// Loads a texture from `width * height` RGBA pixels, row by row from the top left corner.
+    fn load_texture(&self, handle: &mut RaylibHandle, width: i32, height: i32, pixel_data: &[u8]) -> Result<Texture2D> {
+        let mut image = Image::gen_image_color(width, height, Color::BLANK);
         image.format = PixelFormat::PIXELFORMAT_UNCOMPRESSED_R8G8B8A8 as i32;
         unsafe {
             let data_ptr = image.data as *mut u8;
-            let num_bytes = (SPRITE_WIDTH * SPRITE_HEIGHT * 4) as usize;
+            let num_bytes = (width * height * 4) as usize;
             std::ptr::copy_nonoverlapping(pixel_data.as_ptr(), data_ptr, num_bytes);
         }
 
-        let texture = handle.load_texture_from_image(&self.thread, &image).unwrap();
-        self.textures.insert(tile_idx, texture);
-
-        Ok(())
+        handle
+            .load_texture_from_image(&self.thread, &image)
+            .map_err(|err| Error::Texture(err.to_string()))
     }
 
-    fn render_background(
-        &mut self,
-        memory: &mut impl Addressable,
-        draw_handle: &mut RaylibDrawHandle,
-        scale: u16,
-    ) -> Result<()> {
-        self.draw_memory_section(memory, draw_handle, BG_MEM_LOC.0, BG_MEMORY as u16, scale)
-        //Ok(())
+    /// Loads the console framebuffer as a texture, it changes freely between frames so it is
+    /// loaded again on every one of them.
+    fn framebuffer_texture(&self, handle: &mut RaylibHandle, memory: &impl Addressable) -> Result<Texture2D> {
+        let pixel_data = framebuffer::pixels(memory)?
+            .into_iter()
+            .flat_map(|color_idx| self.palette[color_idx as usize].to_color_array())
+            .collect::<Vec<_>>();
+        self.load_texture(handle, SCREEN_WIDTH as i32, SCREEN_HEIGHT as i32, &pixel_data)
     }
 
-    fn render_foreground(
-        &mut self,
-        memory: &mut impl Addressable,
+    fn render_background(
+        &self,
         draw_handle: &mut RaylibDrawHandle,
-        scale: u16,
-    ) -> Result<()> {
-        self.draw_memory_section(memory, draw_handle, BG_MEM_LOC.0, BG_MEMORY as u16, scale)
+        background: &[BackgroundState],
+        viewport: Viewport,
+    ) {
+        let Some(layer) = &self.background_layer else {
+            return;
+        };
+        let scale = viewport.scale as i32;
+
+        for (lines, state) in scanline::bands(background) {
+            let mut scissor = draw_handle.begin_scissor_mode(
+                viewport.x,
+                viewport.y + lines.start as i32 * scale,
+                SCREEN_WIDTH as i32 * scale,
+                lines.len() as i32 * scale,
+            );
+
+            // the background wraps around, so when scrolled a second copy fills the uncovered area
+            let copies_x = if state.scroll_x == 0 { 1 } else { 2 };
+            let copies_y = if state.scroll_y == 0 { 1 } else { 2 };
+
+            for copy_y in 0..copies_y {
+                for copy_x in 0..copies_x {
+                    let offset_x = viewport.x + (copy_x * SCREEN_WIDTH as i32 - state.scroll_x as i32) * scale;
+                    let offset_y = viewport.y + (copy_y * SCREEN_HEIGHT as i32 - state.scroll_y as i32) * scale;
+                    draw_layer(&mut scissor, layer, offset_x, offset_y, viewport.scale);
+                }
+            }
+        }
     }
 
     fn render_sprites(
         &mut self,
         memory: &mut impl Addressable,
         draw_handle: &mut RaylibDrawHandle,
-        scale: u16,
+        viewport: Viewport,
     ) -> Result<()> {
-        for i in 0..40 {
-            let sprite_addr = SPRITE_MEM_LOC.0 + i * 16;
-            let tile_idx = memory.read(sprite_addr)?;
-            let sprite_x = memory.read(sprite_addr + 1)?;
-            let sprite_y = memory.read(sprite_addr + 2)?;
-            let sprite_flags = memory.read(sprite_addr + 3)?;
-            let texture = self.textures.get(&tile_idx).unwrap();
-
-            self.render_texture(
-                texture,
-                sprite_x as u16 * scale,
-                sprite_y as u16 * scale,
-                draw_handle,
-                scale,
-                sprite_flags,
-            )?;
+        let scale = viewport.scale as i32;
+        // sprites can be placed past the edges of the playfield, which must not draw over the bars
+        let mut scissor = draw_handle.begin_scissor_mode(
+            viewport.x,
+            viewport.y,
+            SCREEN_WIDTH as i32 * scale,
+            SCREEN_HEIGHT as i32 * scale,
+        );
+
+        for sprite in sprite_table(&*memory, self.sprite_line_limit)? {
+            let texture = cached_texture(&self.textures, sprite.tile, TileUser::Sprite(sprite.slot))?;
+            let x = viewport.x + sprite.x as i32 * scale;
+            let y = viewport.y + sprite.y as i32 * scale;
+
+            match sprite.rows {
+                u8::MAX => self.render_texture(texture, x, y, &mut scissor, viewport.scale, sprite.flags)?,
+                rows => {
+                    for row in (0..SPRITE_HEIGHT).filter(|row| rows & (1 << row) != 0) {
+                        render_sprite_row(&mut scissor, texture, (x, y), row, viewport.scale, sprite.flags);
+                    }
+                }
+            }
         }
 
         Ok(())
     }
 
-    fn render_interface(
-        &mut self,
-        memory: &mut impl Addressable,
-        draw_handle: &mut RaylibDrawHandle,
-        scale: u16,
-    ) -> Result<()> {
-        self.draw_memory_section(memory, draw_handle, UI_MEM_LOC.0, INTERFACE_MEMORY as u16, scale)
+    fn render_interface(&self, draw_handle: &mut RaylibDrawHandle, viewport: Viewport) {
+        if let Some(layer) = &self.interface_layer {
+            draw_layer(draw_handle, layer, viewport.x, viewport.y, viewport.scale);
+        }
+    }
+
+    fn load_layer(&self, handle: &mut RaylibHandle) -> Result<RenderTexture2D> {
+        handle
+            .load_render_texture(&self.thread, SCREEN_WIDTH as u32, SCREEN_HEIGHT as u32)
+            .map_err(|err| Error::Texture(err.to_string()))
     }
 
-    fn draw_memory_section(
+    /// Redraws the dirty cells of both tilemap layers, every cell when tiles changed since any of
+    /// them may look different now.
+    fn update_layers(
         &mut self,
+        handle: &mut RaylibHandle,
         memory: &mut impl Addressable,
-        draw_handle: &mut RaylibDrawHandle,
-        section_location: u16,
-        section_size: u16,
-        scale: u16,
+        dirty: &DirtyRegions,
     ) -> Result<()> {
-        for idx in 0..section_size {
-            let tile_idx = memory.read(section_location + idx)?;
-            let tile_x = idx % TILES_WIDTH * SPRITE_WIDTH * scale;
-            let tile_y = idx / TILES_WIDTH * SPRITE_WIDTH * scale;
-            self.render_tile(tile_idx, tile_x, tile_y, draw_handle, scale)?;
+        let full_redraw = self.full_redraw || dirty.tiles;
+        // layers are taken out while drawing, so one lost to an error is loaded again and redrawn
+        // in full on the next frame
+        self.full_redraw = true;
+
+        let mut background_layer = match self.background_layer.take() {
+            Some(layer) => layer,
+            None => self.load_layer(handle)?,
+        };
+        {
+            let mut target = handle.begin_texture_mode(&self.thread, &mut background_layer);
+            if full_redraw {
+                target.clear_background(Color::BLACK);
+            }
+
+            for cell in cells_to_redraw(&dirty.background, full_redraw, BG_MEMORY) {
+                let address = BG_MEM_LOC.0 + cell as u16;
+                let tile_idx = memory.peek(address)?;
+                let texture = cached_texture(&self.textures, tile_idx, TileUser::Tilemap(address))?;
+                let (x, y) = cell_position(cell);
+                // the background sits over black, so painting the cell black erases what it held
+                target.draw_rectangle(x, y, SPRITE_WIDTH as i32, SPRITE_HEIGHT as i32, Color::BLACK);
+                self.render_texture(texture, x, y, &mut target, 1, TextureFlags::Normal)?;
+            }
+        }
+        self.background_layer = Some(background_layer);
+
+        let mut interface_layer = match self.interface_layer.take() {
+            Some(layer) => layer,
+            None => self.load_layer(handle)?,
+        };
+        // drawing can't turn a single cell transparent again, so the interface is cleared and
+        // redrawn as a whole whenever any of its cells changes
+        if full_redraw || !dirty.interface.is_empty() {
+            let mut target = handle.begin_texture_mode(&self.thread, &mut interface_layer);
+            target.clear_background(Color::BLANK);
+
+            for cell in 0..INTERFACE_MEMORY {
+                let address = UI_MEM_LOC.0 + cell as u16;
+                let tile_idx = memory.peek(address)?;
+                let texture = cached_texture(&self.textures, tile_idx, TileUser::Tilemap(address))?;
+                let (x, y) = cell_position(cell);
+                self.render_texture(texture, x, y, &mut target, 1, TextureFlags::Normal)?;
+            }
         }
+        self.interface_layer = Some(interface_layer);
+
+        self.full_redraw = false;
         Ok(())
     }
 
     fn render_texture(
         &self,
         texture: &Texture2D,
-        x: u16,
-        y: u16,
-        draw_handle: &mut RaylibDrawHandle,
+        x: i32,
+        y: i32,
+        draw_handle: &mut impl RaylibDraw,
         scale: u16,
         texture_flags: impl IntoFlags,
     ) -> Result<()> {
@@ -238,8 +330,8 @@ impl RaylibRenderer {
         }
 
         let source = Rectangle {
-            x: x as f32,
-            y: y as f32,
+            x: 0.0,
+            y: 0.0,
             width,
             height,
         };
@@ -255,19 +347,6 @@ impl RaylibRenderer {
         Ok(())
     }
 
-    fn render_tile(
-        &mut self,
-        tile_idx: u8,
-        x: u16,
-        y: u16,
-        draw_handle: &mut RaylibDrawHandle,
-        scale: u16,
-    ) -> Result<()> {
-        let texture = self.textures.get(&tile_idx).unwrap();
-        self.render_texture(texture, x, y, draw_handle, scale, TextureFlags::Normal)?;
-        Ok(())
-    }
-
     fn cache_tiles(&mut self, handle: &mut RaylibHandle, memory: &mut impl Addressable) -> Result<()> {
         for idx in 0..=255 {
             self.tile_to_texture(handle, idx, memory)?;
@@ -276,8 +355,112 @@ impl RaylibRenderer {
     }
 }
 
+/// Looks up the texture of a tile, the cache is expected to hold every tile so a missing one is
+/// reported instead of silently drawing nothing.
+fn cached_texture<T>(textures: &HashMap<u8, T>, tile: u8, user: TileUser) -> Result<&T> {
+    textures.get(&tile).ok_or(Error::MissingTile { tile, user })
+}
+
+/// Draws a single row of a sprite with its top left corner at `(x, y)`, `row` counts from the top
+/// of the sprite as shown on screen.
+fn render_sprite_row(
+    draw_handle: &mut impl RaylibDraw,
+    texture: &Texture2D,
+    (x, y): (i32, i32),
+    row: u16,
+    scale: u16,
+    flags: u8,
+) {
+    let source_row = match flags & Y_MIRROR_MASK {
+        0 => row,
+        _ => SPRITE_HEIGHT - 1 - row,
+    };
+    let source_width = match flags & X_MIRROR_MASK {
+        0 => SPRITE_WIDTH as f32,
+        _ => -(SPRITE_WIDTH as f32),
+    };
+
+    let source = Rectangle {
+        x: 0.0,
+        y: source_row as f32,
+        width: source_width,
+        height: 1.0,
+    };
+    let dest = Rectangle {
+        x: x as f32,
+        y: (y + (row * scale) as i32) as f32,
+        width: (SPRITE_WIDTH * scale) as f32,
+        height: scale as f32,
+    };
+    draw_handle.draw_texture_pro(texture, source, dest, Vector2 { x: 0.0, y: 0.0 }, 0.0, Color::WHITE);
+}
+
+/// Top left corner of a tilemap cell on its layer.
+fn cell_position(cell: usize) -> (i32, i32) {
+    let x = cell % TILES_WIDTH as usize * SPRITE_WIDTH as usize;
+    let y = cell / TILES_WIDTH as usize * SPRITE_HEIGHT as usize;
+    (x as i32, y as i32)
+}
+
+/// Draws a tilemap layer scaled up with its top left corner at `(x, y)`.
+fn draw_layer(draw_handle: &mut impl RaylibDraw, layer: &RenderTexture2D, x: i32, y: i32, scale: u16) {
+    // render textures are stored upside down, so the source is flipped back
+    let source = Rectangle {
+        x: 0.0,
+        y: 0.0,
+        width: SCREEN_WIDTH as f32,
+        height: -(SCREEN_HEIGHT as f32),
+    };
+    let dest = Rectangle {
+        x: x as f32,
+        y: y as f32,
+        width: (SCREEN_WIDTH * scale as usize) as f32,
+        height: (SCREEN_HEIGHT * scale as usize) as f32,
+    };
+    draw_handle.draw_texture_pro(layer, source, dest, Vector2 { x: 0.0, y: 0.0 }, 0.0, Color::WHITE);
+}
+
+fn render_paused_indicator(draw_handle: &mut RaylibDrawHandle, viewport: Viewport) {
+    let scale = viewport.scale as i32;
+    let font_size = 8 * scale;
+    let text_width = draw_handle.measure_text("PAUSED", font_size);
+    let x = viewport.x + SCREEN_WIDTH as i32 * scale - text_width - 4 * scale;
+    let y = viewport.y + 4 * scale;
+
+    draw_handle.draw_rectangle(
+        x - 2 * scale,
+        y - 2 * scale,
+        text_width + 4 * scale,
+        font_size + 4 * scale,
+        Color::BLACK,
+    );
+    draw_handle.draw_text("PAUSED", x, y, font_size, Color::WHITE);
+}
+
+/// Draws the performance overlay with the built-in font on the top left corner of the screen.
+fn render_overlay(draw_handle: &mut RaylibDrawHandle, stats: &FrameStats, viewport: Viewport) {
+    const MARGIN: i32 = 2;
+    let scale = viewport.scale as i32;
+    let lines = stats.lines();
+    let (width, height) = overlay::text_size(&lines);
+
+    draw_handle.draw_rectangle(
+        viewport.x,
+        viewport.y,
+        (width as i32 + MARGIN * 2) * scale,
+        (height as i32 + MARGIN * 2) * scale,
+        Color::BLACK,
+    );
+
+    for (x, y) in overlay::text_pixels(&lines) {
+        let x = viewport.x + (x as i32 + MARGIN) * scale;
+        let y = viewport.y + (y as i32 + MARGIN) * scale;
+        draw_handle.draw_rectangle(x, y, scale, scale, Color::WHITE);
+    }
+}
+
 impl Renderer for RaylibRenderer {
-    fn start(name: &str, fps: f32, scale: u16) -> Self {
+    fn start(name: &str, fps: f32, scale: u16) -> Result<Self> {
         let (handle, thread) = raylib::init()
             .size(
                 TILES_WIDTH as i32 * SPRITE_WIDTH as i32 * scale as i32,
@@ -292,14 +475,21 @@ impl Renderer for RaylibRenderer {
 
         HANDLE.get_or_init(|| Arc::new(RwLock::new(handle)));
 
-        Self {
-            scale,
+        Ok(Self {
+            viewport: Viewport { scale, x: 0, y: 0 },
             thread,
             frame_start,
             frame_duration,
             has_cached_tiles: false,
+            background_layer: None,
+            interface_layer: None,
+            full_redraw: true,
+            sprite_line_limit: false,
+            paused: false,
+            overlay: None,
+            palette: PALETTE.to_vec(),
             textures: HashMap::with_capacity(255),
-        }
+        })
     }
 
     fn should_close(&self) -> bool {
@@ -313,22 +503,128 @@ impl Renderer for RaylibRenderer {
         self.frame_start.elapsed() >= self.frame_duration
     }
 
-    fn draw_frame(&mut self, memory: &mut impl Addressable) -> Result<()> {
+    fn draw_frame(
+        &mut self,
+        memory: &mut impl Addressable,
+        background: &[BackgroundState],
+        dirty: &DirtyRegions,
+    ) -> Result<()> {
         let mut handle = HANDLE.get().expect(NO_DRAWING_HANDLE).write().expect(NO_DRAWING_HANDLE);
-        if !self.has_cached_tiles {
+        if !self.has_cached_tiles || dirty.tiles {
             self.cache_tiles(&mut handle, memory)?;
             self.has_cached_tiles = true;
         }
+        // layers keep following their tilemaps, so switching back to tiles draws them up to date
+        self.update_layers(&mut handle, memory, dirty)?;
+        let framebuffer = match VideoMode::read(&*memory)? {
+            VideoMode::Tiles => None,
+            VideoMode::Framebuffer => Some(self.framebuffer_texture(&mut handle, memory)?),
+        };
+
+        // recomputed every frame, so resizing the window or going fullscreen rescales the playfield
+        self.viewport = Viewport::fit(handle.get_screen_width() as usize, handle.get_screen_height() as usize);
 
         let mut draw_handle = handle.begin_drawing(&self.thread);
         draw_handle.clear_background(Color::BLACK);
 
-        self.render_background(memory, &mut draw_handle, self.scale)?;
-        self.render_sprites(memory, &mut draw_handle, self.scale)?;
-        self.render_foreground(memory, &mut draw_handle, self.scale)?;
-        self.render_interface(memory, &mut draw_handle, self.scale)?;
+        match &framebuffer {
+            None => {
+                self.render_background(&mut draw_handle, background, self.viewport);
+                self.render_sprites(memory, &mut draw_handle, self.viewport)?;
+                self.render_interface(&mut draw_handle, self.viewport);
+            }
+            Some(texture) => {
+                let Viewport { scale, x, y } = self.viewport;
+                self.render_texture(texture, x, y, &mut draw_handle, scale, TextureFlags::Normal)?;
+            }
+        }
+
+        if self.paused {
+            render_paused_indicator(&mut draw_handle, self.viewport);
+        }
+
+        if let Some(stats) = &self.overlay {
+            render_overlay(&mut draw_handle, stats, self.viewport);
+        }
 
         self.frame_start = Instant::now();
         Ok(())
     }
+
+    fn invalidate(&mut self) {
+        self.textures.clear();
+        self.has_cached_tiles = false;
+        self.full_redraw = true;
+    }
+
+    fn skip_frame(&mut self) {
+        self.frame_start = Instant::now();
+    }
+
+    fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    fn set_sprite_line_limit(&mut self, enabled: bool) {
+        self.sprite_line_limit = enabled;
+    }
+
+    fn set_overlay(&mut self, stats: Option<FrameStats>) {
+        self.overlay = stats;
+    }
+
+    fn set_palette(&mut self, palette: &[(u8, u8, u8, u8)]) {
+        // cached textures hold colors, so every tile is drawn again with the new ones
+        self.palette = palette.to_vec();
+        self.invalidate();
+    }
+
+    fn toggle_fullscreen(&mut self) {
+        let handle = HANDLE.get().expect(NO_DRAWING_HANDLE).read().expect(NO_DRAWING_HANDLE);
+        // borderless windowed covers the monitor without changing its video mode, the viewport
+        // takes care of the bars
+        handle.toggle_borderless_windowed();
+    }
+
+    fn capture_frame(&self) -> Vec<u8> {
+        let handle = HANDLE.get().expect(NO_DRAWING_HANDLE).read().expect(NO_DRAWING_HANDLE);
+
+        // the screen holds the frame scaled up inside the viewport, with black bars around it
+        let mut image = handle.load_image_from_screen(&self.thread);
+        image.crop(Rectangle {
+            x: self.viewport.x as f32,
+            y: self.viewport.y as f32,
+            width: (SCREEN_WIDTH * self.viewport.scale as usize) as f32,
+            height: (SCREEN_HEIGHT * self.viewport.scale as usize) as f32,
+        });
+        image.resize_nn(SCREEN_WIDTH as i32, SCREEN_HEIGHT as i32);
+
+        image
+            .get_image_data()
+            .iter()
+            .flat_map(|color| [color.r, color.g, color.b, color.a])
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_cached_texture() {
+        let textures = HashMap::from([(0, "first"), (2, "third")]);
+
+        assert_eq!(*cached_texture(&textures, 2, TileUser::Sprite(0)).unwrap(), "third");
+
+        let err = cached_texture(&textures, 1, TileUser::Sprite(7)).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::MissingTile {
+                tile: 1,
+                user: TileUser::Sprite(7)
+            }
+        ));
+        assert_eq!(err.to_string(), "tile 1 used by sprite slot 7 has no cached texture");
+    }
 }
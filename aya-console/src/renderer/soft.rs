@@ -0,0 +1,164 @@
+use std::cell::RefCell;
+use std::time::{Duration, Instant};
+
+use aya_cpu::memory::Addressable;
+use minifb::{ScaleMode, Window, WindowOptions};
+
+use super::error::{Error, Result};
+use super::raster::Rasterizer;
+use super::{Renderer, Viewport, SCREEN_HEIGHT, SCREEN_WIDTH};
+use crate::memory::DirtyRegions;
+use crate::overlay::FrameStats;
+use crate::scanline::BackgroundState;
+
+thread_local! {
+    // minifb windows are neither `Send` nor `Sync`, so unlike the raylib handle the window lives
+    // in a thread local shared between the renderer and the input
+    pub static WINDOW: RefCell<Option<Window>> = const { RefCell::new(None) };
+}
+pub static NO_WINDOW: &str = "tried to use the window before it was created";
+
+/// Software renderer that rasterizes every layer into a `u32` framebuffer, which is scaled up by
+/// an integer factor and handed over to minifb to be centered on the window.
+#[derive(Debug)]
+pub struct SoftRenderer {
+    frame_start: Instant,
+    frame_duration: Duration,
+    rasterizer: Rasterizer,
+    /// Framebuffer scaled up to fit the window
+    scaled: Vec<u32>,
+}
+
+/// Scales a `SCREEN_WIDTH * SCREEN_HEIGHT` framebuffer up by `scale` into `scaled`.
+fn upscale(framebuffer: &[u32], scale: usize, scaled: &mut Vec<u32>) {
+    scaled.clear();
+    for row in framebuffer.as_chunks::<SCREEN_WIDTH>().0 {
+        let row_start = scaled.len();
+        for color in row {
+            scaled.extend(std::iter::repeat_n(*color, scale));
+        }
+        for _ in 1..scale {
+            scaled.extend_from_within(row_start..row_start + SCREEN_WIDTH * scale);
+        }
+    }
+}
+
+impl SoftRenderer {
+    fn new(frame_duration: Duration) -> Self {
+        Self {
+            frame_start: Instant::now(),
+            frame_duration,
+            rasterizer: Rasterizer::default(),
+            scaled: Vec::new(),
+        }
+    }
+}
+
+impl Renderer for SoftRenderer {
+    fn start(name: &str, fps: f32, scale: u16) -> Result<Self> {
+        let options = WindowOptions {
+            resize: true,
+            scale_mode: ScaleMode::Center,
+            ..Default::default()
+        };
+        let window = Window::new(
+            name,
+            SCREEN_WIDTH * scale as usize,
+            SCREEN_HEIGHT * scale as usize,
+            options,
+        )
+        .map_err(|err| Error::Window(format!("failed to create window: {err}")))?;
+
+        WINDOW.with(|cell| *cell.borrow_mut() = Some(window));
+
+        Ok(Self::new(Duration::from_secs_f64(1.0 / fps as f64)))
+    }
+
+    fn should_close(&self) -> bool {
+        WINDOW.with(|cell| cell.borrow().as_ref().map(|w| !w.is_open()).unwrap_or(false))
+    }
+
+    fn should_draw(&self) -> bool {
+        self.frame_start.elapsed() >= self.frame_duration
+    }
+
+    fn draw_frame(
+        &mut self,
+        memory: &mut impl Addressable,
+        background: &[BackgroundState],
+        dirty: &DirtyRegions,
+    ) -> Result<()> {
+        self.rasterizer.rasterize(memory, background, dirty)?;
+
+        WINDOW.with(|cell| {
+            let mut window = cell.borrow_mut();
+            let window = window.as_mut().expect(NO_WINDOW);
+
+            // recomputed every frame, so resizing the window rescales the playfield
+            let (width, height) = window.get_size();
+            let scale = Viewport::fit(width, height).scale as usize;
+            upscale(self.rasterizer.framebuffer(), scale, &mut self.scaled);
+
+            window
+                .update_with_buffer(&self.scaled, SCREEN_WIDTH * scale, SCREEN_HEIGHT * scale)
+                .map_err(|err| Error::Window(err.to_string()))
+        })?;
+
+        self.frame_start = Instant::now();
+        Ok(())
+    }
+
+    fn invalidate(&mut self) {
+        self.rasterizer.invalidate();
+    }
+
+    fn skip_frame(&mut self) {
+        self.frame_start = Instant::now();
+    }
+
+    fn set_paused(&mut self, paused: bool) {
+        self.rasterizer.set_paused(paused);
+    }
+
+    fn set_sprite_line_limit(&mut self, enabled: bool) {
+        self.rasterizer.set_sprite_line_limit(enabled);
+    }
+
+    fn set_overlay(&mut self, stats: Option<FrameStats>) {
+        self.rasterizer.set_overlay(stats);
+    }
+
+    fn set_palette(&mut self, palette: &[(u8, u8, u8, u8)]) {
+        self.rasterizer.set_palette(palette);
+    }
+
+    fn toggle_fullscreen(&mut self) {
+        eprintln!("fullscreen is not supported by the soft renderer, maximize the window instead");
+    }
+
+    fn capture_frame(&self) -> Vec<u8> {
+        self.rasterizer.to_rgba()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_upscale() {
+        let mut framebuffer = vec![0; SCREEN_WIDTH * SCREEN_HEIGHT];
+        framebuffer[1] = 0xFF0000;
+        framebuffer[SCREEN_WIDTH] = 0x00FF00;
+
+        let mut scaled = vec![];
+        upscale(&framebuffer, 2, &mut scaled);
+        assert_eq!(scaled.len(), framebuffer.len() * 4);
+
+        let width = SCREEN_WIDTH * 2;
+        assert_eq!(scaled[..4], [0, 0, 0xFF0000, 0xFF0000]);
+        assert_eq!(scaled[width..width + 4], [0, 0, 0xFF0000, 0xFF0000]);
+        assert_eq!(scaled[width * 2..width * 2 + 3], [0x00FF00, 0x00FF00, 0]);
+        assert_eq!(scaled[width * 3..width * 3 + 3], [0x00FF00, 0x00FF00, 0]);
+    }
+}
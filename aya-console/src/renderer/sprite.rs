@@ -0,0 +1,161 @@
+use aya_cpu::memory::Addressable;
+
+use super::error::Result;
+use super::{
+    BYTES_PER_TILE, SCREEN_HEIGHT, SPRITE_ENABLE_MASK, SPRITE_HEIGHT, SPRITE_ORIGIN_MASK, SPRITE_ORIGIN_OFFSET,
+};
+use crate::memory::{SPRITE_MEMORY, SPRITE_MEM_LOC, TILE_MEMORY};
+
+/// Sprite slots on sprite memory
+pub const SPRITE_COUNT: usize = 40;
+/// Bytes taken by every slot, the console reads the first `SPRITE_ATTRIBUTES` and leaves the rest
+/// for programs to use as they please
+pub const SPRITE_STRIDE: usize = SPRITE_MEMORY / SPRITE_COUNT;
+const SPRITE_ATTRIBUTES: usize = 4;
+/// Sprites drawn on a single line when the per line limit is enabled
+pub const SPRITES_PER_LINE: usize = 10;
+const TILE_COUNT: usize = TILE_MEMORY / BYTES_PER_TILE as usize;
+
+const _: () = assert!(SPRITE_COUNT * SPRITE_STRIDE == SPRITE_MEMORY);
+const _: () = assert!(SPRITE_ATTRIBUTES <= SPRITE_STRIDE);
+
+/// A sprite slot as laid out on sprite memory: tile index, x, y and flags. The position is where
+/// the sprite is on screen, so it is negative for sprites past the top or left edges.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sprite {
+    pub slot: u16,
+    pub tile: u8,
+    pub x: i16,
+    pub y: i16,
+    pub flags: u8,
+    /// Rows of the sprite to draw, bit `n` is the `n`th row from the top as shown on screen
+    pub rows: u8,
+}
+
+impl Sprite {
+    fn read(memory: &impl Addressable, slot: u16) -> Result<Self> {
+        let address = SPRITE_MEM_LOC.0 + slot * SPRITE_STRIDE as u16;
+        let flags = memory.peek(address + 3)?;
+        // the offset origin stores positions 8 pixels past the screen ones, so sprites can enter
+        // from the top and left edges
+        let origin = match flags & SPRITE_ORIGIN_MASK {
+            0 => 0,
+            _ => SPRITE_ORIGIN_OFFSET,
+        };
+
+        Ok(Self {
+            slot,
+            tile: memory.peek(address)?,
+            x: memory.peek(address + 1)? as i16 - origin,
+            y: memory.peek(address + 2)? as i16 - origin,
+            flags,
+            rows: u8::MAX,
+        })
+    }
+}
+
+/// Reads every enabled sprite slot in drawing order. Sprites using a tile past tile memory are
+/// skipped, and with `line_limit` only the first [`SPRITES_PER_LINE`] sprites touching a line are drawn on
+/// it, like the sprite hardware of older consoles.
+pub fn sprite_table(memory: &impl Addressable, line_limit: bool) -> Result<Vec<Sprite>> {
+    let mut sprites = Vec::with_capacity(SPRITE_COUNT);
+    for slot in 0..SPRITE_COUNT as u16 {
+        let sprite = Sprite::read(memory, slot)?;
+        if sprite.flags & SPRITE_ENABLE_MASK == SPRITE_ENABLE_MASK && (sprite.tile as usize) < TILE_COUNT {
+            sprites.push(sprite);
+        }
+    }
+
+    if line_limit {
+        let mut sprites_on_line = [0; SCREEN_HEIGHT];
+        for sprite in sprites.iter_mut() {
+            for row in 0..SPRITE_HEIGHT as usize {
+                // lines past the playfield are never drawn, so they don't count towards the limit
+                let line = sprite.y + row as i16;
+                let Some(count) = usize::try_from(line)
+                    .ok()
+                    .and_then(|line| sprites_on_line.get_mut(line))
+                else {
+                    continue;
+                };
+
+                match *count < SPRITES_PER_LINE {
+                    true => *count += 1,
+                    false => sprite.rows &= !(1 << row),
+                }
+            }
+        }
+    }
+
+    Ok(sprites)
+}
+
+#[cfg(test)]
+mod tests {
+    use aya_cpu::MEMORY_SIZE;
+
+    use super::*;
+    use crate::memory::LinearMemory;
+
+    fn write_sprite(memory: &mut LinearMemory<MEMORY_SIZE>, slot: u16, [tile, x, y, flags]: [u8; SPRITE_ATTRIBUTES]) {
+        let address = SPRITE_MEM_LOC.0 + slot * SPRITE_STRIDE as u16;
+        for (offset, byte) in [tile, x, y, flags].into_iter().enumerate() {
+            memory.write(address + offset as u16, byte).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_sprite_table() {
+        let mut memory = LinearMemory::default();
+        write_sprite(&mut memory, 1, [7, 16, 32, 0b111]);
+        write_sprite(&mut memory, SPRITE_COUNT as u16 - 1, [2, 200, 100, 0b100]);
+        // disabled slots are never drawn, whatever else they hold
+        write_sprite(&mut memory, 2, [3, 8, 8, 0b011]);
+
+        let sprites = sprite_table(&memory, false).unwrap();
+        assert_eq!(sprites.len(), 2);
+        assert_eq!(
+            sprites[0],
+            Sprite {
+                slot: 1,
+                tile: 7,
+                x: 16,
+                y: 32,
+                flags: 0b111,
+                rows: u8::MAX
+            }
+        );
+        assert_eq!(
+            (sprites[1].slot, sprites[1].x, sprites[1].y),
+            (SPRITE_COUNT as u16 - 1, 200, 100)
+        );
+    }
+
+    #[test]
+    fn test_sprite_line_limit() {
+        let mut memory = LinearMemory::default();
+        for slot in 0..SPRITE_COUNT as u16 {
+            // every sprite sits four lines lower than the previous one
+            write_sprite(&mut memory, slot, [1, 0, (slot * 4) as u8, SPRITE_ENABLE_MASK]);
+        }
+        // fills the first 8 lines with sprites from the last slots
+        for slot in SPRITE_COUNT as u16 - 12..SPRITE_COUNT as u16 {
+            write_sprite(&mut memory, slot, [1, 0, 0, SPRITE_ENABLE_MASK]);
+        }
+
+        let unlimited = sprite_table(&memory, false).unwrap();
+        assert!(unlimited.iter().all(|sprite| sprite.rows == u8::MAX));
+
+        let limited = sprite_table(&memory, true).unwrap();
+        // slot 0 covers the first 8 lines and slot 1 lines 4-7 too, so 9 of the last slots fit on
+        // lines 0-3 but only 8 on lines 4-7
+        let drawn = limited[SPRITE_COUNT - 12..]
+            .iter()
+            .map(|sprite| sprite.rows)
+            .collect::<Vec<_>>();
+        assert_eq!(drawn[..8], [u8::MAX; 8]);
+        assert_eq!(drawn[8], 0b00001111);
+        assert_eq!(drawn[9..], [0; 3]);
+        assert!(limited[..SPRITE_COUNT - 12].iter().all(|sprite| sprite.rows == u8::MAX));
+    }
+}
@@ -0,0 +1,156 @@
+use aya_cpu::memory::Addressable;
+use wasm_bindgen::{Clamped, JsCast};
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, ImageData};
+
+use super::error::{Error, Result};
+use super::raster::Rasterizer;
+use super::{Renderer, SCREEN_HEIGHT, SCREEN_WIDTH};
+use crate::memory::DirtyRegions;
+use crate::overlay::FrameStats;
+use crate::scanline::BackgroundState;
+
+/// Id of the canvas the console draws on, one is appended to the page body when it has none.
+pub static CANVAS_ID: &str = "aya";
+
+/// Renderer for the browser, frames are rasterized like on the soft renderer and put on a canvas
+/// of the screen size, which the page scales up with css. Frame pacing is left to the page, which
+/// asks for frames from `requestAnimationFrame`.
+#[derive(Debug)]
+pub struct WebRenderer {
+    canvas: HtmlCanvasElement,
+    context: CanvasRenderingContext2d,
+    rasterizer: Rasterizer,
+}
+
+fn canvas_error(err: wasm_bindgen::JsValue) -> Error {
+    Error::Canvas(format!("{err:?}"))
+}
+
+/// Finds the console canvas on the page, creating it when missing.
+fn find_canvas() -> Result<HtmlCanvasElement> {
+    let document = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or_else(|| Error::Canvas("there is no document to draw on".into()))?;
+
+    let canvas = match document.get_element_by_id(CANVAS_ID) {
+        Some(canvas) => canvas,
+        None => {
+            let canvas = document.create_element("canvas").map_err(canvas_error)?;
+            canvas.set_id(CANVAS_ID);
+            let body = document
+                .body()
+                .ok_or_else(|| Error::Canvas("the page has no body".into()))?;
+            body.append_child(&canvas).map_err(canvas_error)?;
+            canvas
+        }
+    };
+
+    canvas
+        .dyn_into()
+        .map_err(|_| Error::Canvas(format!("the element with id '{CANVAS_ID}' is not a canvas")))
+}
+
+impl WebRenderer {
+    fn new(name: &str, scale: u16) -> Result<Self> {
+        let canvas = find_canvas()?;
+        canvas.set_width(SCREEN_WIDTH as u32);
+        canvas.set_height(SCREEN_HEIGHT as u32);
+
+        let style = canvas.style();
+        let width = format!("{}px", SCREEN_WIDTH * scale as usize);
+        let height = format!("{}px", SCREEN_HEIGHT * scale as usize);
+        style.set_property("width", &width).map_err(canvas_error)?;
+        style.set_property("height", &height).map_err(canvas_error)?;
+        // scaling up must keep the pixels sharp, like the integer scaling on the desktop
+        style
+            .set_property("image-rendering", "pixelated")
+            .map_err(canvas_error)?;
+
+        if let Some(document) = canvas.owner_document() {
+            document.set_title(name);
+        }
+
+        let context = canvas
+            .get_context("2d")
+            .map_err(canvas_error)?
+            .ok_or_else(|| Error::Canvas("the canvas has no 2d context".into()))?
+            .dyn_into()
+            .map_err(|context| canvas_error(context.into()))?;
+
+        Ok(Self {
+            canvas,
+            context,
+            rasterizer: Rasterizer::default(),
+        })
+    }
+}
+
+impl Renderer for WebRenderer {
+    fn start(name: &str, _fps: f32, scale: u16) -> Result<Self> {
+        Self::new(name, scale)
+    }
+
+    fn should_close(&self) -> bool {
+        false
+    }
+
+    fn should_draw(&self) -> bool {
+        true
+    }
+
+    fn draw_frame(
+        &mut self,
+        memory: &mut impl Addressable,
+        background: &[BackgroundState],
+        dirty: &DirtyRegions,
+    ) -> Result<()> {
+        self.rasterizer.rasterize(memory, background, dirty)?;
+
+        let pixels = self.rasterizer.to_rgba();
+        let image =
+            ImageData::new_with_u8_clamped_array_and_sh(Clamped(&pixels), SCREEN_WIDTH as u32, SCREEN_HEIGHT as u32)
+                .map_err(canvas_error)?;
+        self.context.put_image_data(&image, 0.0, 0.0).map_err(canvas_error)
+    }
+
+    fn invalidate(&mut self) {
+        self.rasterizer.invalidate();
+    }
+
+    fn set_paused(&mut self, paused: bool) {
+        self.rasterizer.set_paused(paused);
+    }
+
+    fn set_sprite_line_limit(&mut self, enabled: bool) {
+        self.rasterizer.set_sprite_line_limit(enabled);
+    }
+
+    fn set_overlay(&mut self, stats: Option<FrameStats>) {
+        self.rasterizer.set_overlay(stats);
+    }
+
+    fn set_palette(&mut self, palette: &[(u8, u8, u8, u8)]) {
+        self.rasterizer.set_palette(palette);
+    }
+
+    fn toggle_fullscreen(&mut self) {
+        let Some(document) = self.canvas.owner_document() else {
+            return;
+        };
+
+        let result = match document.fullscreen_element() {
+            Some(_) => {
+                document.exit_fullscreen();
+                Ok(())
+            }
+            None => self.canvas.request_fullscreen(),
+        };
+        if let Err(err) = result {
+            web_sys::console::error_1(&format!("failed to toggle fullscreen: {err:?}").into());
+        }
+    }
+
+    fn capture_frame(&self) -> Vec<u8> {
+        self.rasterizer.to_rgba()
+    }
+}
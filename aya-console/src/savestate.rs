@@ -0,0 +1,216 @@
+use std::path::{Path, PathBuf};
+
+use aya_cpu::cpu::{Cpu, CpuState};
+use aya_cpu::register::Register;
+
+use crate::memory::memory_mapper::MemoryMapper;
+
+const MAGIC: &[u8; 4] = b"AYAS";
+/// Bumped whenever the layout of the state file changes, older files are refused instead of being
+/// loaded as garbage
+const VERSION: u8 = 1;
+const HEADER_SIZE: usize = MAGIC.len() + 1 + 4;
+const CPU_STATE_SIZE: usize = Register::len() * 2 + 1;
+
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    InvalidFile,
+    UnsupportedVersion(u8),
+    RomMismatch,
+    MemoryLayout,
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Io(err) => write!(f, "{err}"),
+            Error::InvalidFile => write!(f, "file is not an aya save state"),
+            Error::UnsupportedVersion(version) => write!(f, "save state version {version} is not supported"),
+            Error::RomMismatch => write!(f, "save state was created by a different rom"),
+            Error::MemoryLayout => write!(f, "save state memory doesn't match the console memory layout"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Save states live next to the rom they were created from.
+pub fn state_path(rom_file: &Path) -> PathBuf {
+    rom_file.with_extension("state")
+}
+
+/// Full machine state, the cpu registers and the contents of every mapped device. Offsets below
+/// are in hexadecimal.
+///
+/// | BYTE  | DESCRIPTION                                         |
+/// |-------|-----------------------------------------------------|
+/// | 00-03 | Magic `AYAS`                                        |
+/// | 04    | Version                                             |
+/// | 05-08 | Rom checksum, as declared on the rom (little endian) |
+/// | 09-22 | Registers, in register order (little endian words)  |
+/// | 23    | Whether the cpu was inside an interrupt handler     |
+/// | 24-   | Memory, every device in mapping order               |
+#[derive(Debug, PartialEq, Eq)]
+pub struct SaveState {
+    checksum: u32,
+    cpu: CpuState,
+    memory: Vec<u8>,
+}
+
+impl SaveState {
+    pub fn capture(cpu: &Cpu<MemoryMapper>, checksum: u32) -> Self {
+        Self {
+            checksum,
+            cpu: cpu.snapshot(),
+            memory: cpu.memory.snapshot(),
+        }
+    }
+
+    pub fn apply(&self, cpu: &mut Cpu<MemoryMapper>) -> Result<()> {
+        if !cpu.memory.restore(&self.memory) {
+            return Err(Error::MemoryLayout);
+        }
+        cpu.restore(&self.cpu);
+        Ok(())
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, self.encode())?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path, checksum: u32) -> Result<Self> {
+        let bytes = std::fs::read(path)?;
+        Self::decode(&bytes, checksum)
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_SIZE + CPU_STATE_SIZE + self.memory.len());
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&self.checksum.to_le_bytes());
+        for register in self.cpu.registers {
+            bytes.extend_from_slice(&register.to_le_bytes());
+        }
+        bytes.push(self.cpu.in_interrupt as u8);
+        bytes.extend_from_slice(&self.memory);
+        bytes
+    }
+
+    fn decode(bytes: &[u8], checksum: u32) -> Result<Self> {
+        if bytes.len() < HEADER_SIZE + CPU_STATE_SIZE || &bytes[0..MAGIC.len()] != MAGIC {
+            return Err(Error::InvalidFile);
+        }
+
+        let version = bytes[4];
+        if version != VERSION {
+            return Err(Error::UnsupportedVersion(version));
+        }
+
+        let state_checksum = u32::from_le_bytes(bytes[5..9].try_into().unwrap());
+        if state_checksum != checksum {
+            return Err(Error::RomMismatch);
+        }
+
+        let cpu_state = &bytes[HEADER_SIZE..HEADER_SIZE + CPU_STATE_SIZE];
+        let mut registers = [0; Register::len()];
+        let (words, _) = cpu_state.as_chunks::<2>();
+        for (register, word) in registers.iter_mut().zip(words) {
+            *register = u16::from_le_bytes(*word);
+        }
+
+        Ok(Self {
+            checksum,
+            cpu: CpuState {
+                registers,
+                in_interrupt: cpu_state[CPU_STATE_SIZE - 1] != 0,
+            },
+            memory: bytes[HEADER_SIZE + CPU_STATE_SIZE..].to_vec(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use aya_cpu::memory::Addressable;
+
+    use super::*;
+    use crate::memory::memory_mapper::{MappingMode, SpriteMem, StackMem};
+    use crate::memory::{LinearMemory, SPRITE_MEM_LOC, STACK_MEM_LOC, STACK_START};
+
+    fn setup_cpu() -> Cpu<MemoryMapper> {
+        let mut memory = MemoryMapper::default();
+        memory
+            .map(
+                SpriteMem::from(LinearMemory::default()),
+                SPRITE_MEM_LOC.0,
+                SPRITE_MEM_LOC.1,
+                MappingMode::Remap,
+            )
+            .unwrap();
+        memory
+            .map(
+                StackMem::from(LinearMemory::default()),
+                STACK_MEM_LOC.0,
+                STACK_MEM_LOC.1,
+                MappingMode::Remap,
+            )
+            .unwrap();
+        Cpu::new(memory, SPRITE_MEM_LOC.0, STACK_START, SPRITE_MEM_LOC.0)
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let mut cpu = setup_cpu();
+        cpu.memory.write(SPRITE_MEM_LOC.0 + 4, 0xAB).unwrap();
+        cpu.memory.write(STACK_MEM_LOC.1, 0xCD).unwrap();
+        let state = SaveState::capture(&cpu, 0x1234);
+
+        let decoded = SaveState::decode(&state.encode(), 0x1234).unwrap();
+        assert_eq!(decoded, state);
+
+        let mut other = setup_cpu();
+        decoded.apply(&mut other).unwrap();
+        assert_eq!(other.memory.read(SPRITE_MEM_LOC.0 + 4).unwrap(), 0xAB);
+        assert_eq!(other.memory.read(STACK_MEM_LOC.1).unwrap(), 0xCD);
+        assert_eq!(other.snapshot(), cpu.snapshot());
+    }
+
+    #[test]
+    fn test_refuse_mismatched_states() {
+        let cpu = setup_cpu();
+        let bytes = SaveState::capture(&cpu, 0x1234).encode();
+
+        assert!(matches!(SaveState::decode(&bytes, 0x4321), Err(Error::RomMismatch)));
+        assert!(matches!(
+            SaveState::decode(&bytes[..8], 0x1234),
+            Err(Error::InvalidFile)
+        ));
+
+        let mut outdated = bytes.clone();
+        outdated[4] = VERSION + 1;
+        assert!(matches!(
+            SaveState::decode(&outdated, 0x1234),
+            Err(Error::UnsupportedVersion(_))
+        ));
+
+        let truncated = SaveState::decode(&bytes[..bytes.len() - 1], 0x1234).unwrap();
+        assert!(matches!(truncated.apply(&mut setup_cpu()), Err(Error::MemoryLayout)));
+    }
+}
@@ -0,0 +1,115 @@
+#[cfg(any(test, feature = "renderer-raylib"))]
+use std::ops::Range;
+
+use aya_cpu::memory::{Addressable, Result};
+
+use crate::memory::VIDEO_MEM_LOC;
+use crate::renderer::SCREEN_HEIGHT;
+use crate::CLOCK_CYCLE;
+
+/// Virtual scanlines in a frame, the lines past the visible ones act as the vertical blank.
+pub const LINES_PER_FRAME: usize = 125;
+pub const CYCLES_PER_LINE: usize = CLOCK_CYCLE / LINES_PER_FRAME;
+
+// every cycle of a frame must belong to a line, and the line number has to fit the line register
+const _: () = assert!(CYCLES_PER_LINE * LINES_PER_FRAME == CLOCK_CYCLE);
+const _: () = assert!(LINES_PER_FRAME > SCREEN_HEIGHT && LINES_PER_FRAME <= u8::MAX as usize);
+
+const LINE_OFFSET: u16 = 0;
+const LINE_COMPARE_OFFSET: u16 = 1;
+const SCROLL_X_OFFSET: u16 = 2;
+const SCROLL_Y_OFFSET: u16 = 3;
+
+/// Background state that can change between scanlines, it is latched at the start of every visible
+/// line so programs can alter it from a scanline interrupt.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct BackgroundState {
+    pub scroll_x: u8,
+    pub scroll_y: u8,
+}
+
+impl BackgroundState {
+    pub fn latch(memory: &impl Addressable) -> Result<Self> {
+        Ok(Self {
+            scroll_x: memory.read(VIDEO_MEM_LOC.0 + SCROLL_X_OFFSET)?,
+            scroll_y: memory.read(VIDEO_MEM_LOC.0 + SCROLL_Y_OFFSET)?,
+        })
+    }
+}
+
+/// Publishes the line being processed and reports whether it matches the line compare register,
+/// meaning a scanline interrupt should be raised.
+pub fn begin_line(memory: &mut impl Addressable, line: usize) -> Result<bool> {
+    memory.write(VIDEO_MEM_LOC.0 + LINE_OFFSET, line as u8)?;
+    let line_compare = memory.read(VIDEO_MEM_LOC.0 + LINE_COMPARE_OFFSET)?;
+    Ok(line_compare as usize == line)
+}
+
+/// Cycles `line` runs for on a frame of `cycles_per_frame` cycles, the ones that don't split evenly
+/// go to the first lines so every cycle of the frame belongs to a line.
+pub fn line_cycles(cycles_per_frame: usize, line: usize) -> usize {
+    cycles_per_frame / LINES_PER_FRAME + usize::from(line < cycles_per_frame % LINES_PER_FRAME)
+}
+
+/// Groups consecutive lines sharing the same background state into bands, so renderers can draw
+/// every band at once.
+#[cfg(any(test, feature = "renderer-raylib"))]
+pub fn bands(lines: &[BackgroundState]) -> impl Iterator<Item = (Range<usize>, BackgroundState)> + '_ {
+    let mut start = 0;
+    lines.chunk_by(|a, b| a == b).map(move |band| {
+        let range = start..start + band.len();
+        start = range.end;
+        (range, band[0])
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use aya_cpu::MEMORY_SIZE;
+
+    use super::*;
+    use crate::memory::LinearMemory;
+
+    #[test]
+    fn test_begin_line() {
+        let mut memory = LinearMemory::<MEMORY_SIZE>::default();
+        memory.write(VIDEO_MEM_LOC.0 + LINE_COMPARE_OFFSET, 10).unwrap();
+
+        assert!(!begin_line(&mut memory, 9).unwrap());
+        assert_eq!(memory.read(VIDEO_MEM_LOC.0 + LINE_OFFSET).unwrap(), 9);
+        assert!(begin_line(&mut memory, 10).unwrap());
+        assert_eq!(memory.read(VIDEO_MEM_LOC.0 + LINE_OFFSET).unwrap(), 10);
+    }
+
+    #[test]
+    fn test_line_cycles() {
+        assert_eq!(line_cycles(CLOCK_CYCLE, 0), CYCLES_PER_LINE);
+        assert_eq!((line_cycles(130, 4), line_cycles(130, 5)), (2, 1));
+        for cycles in [CLOCK_CYCLE, 130, 7, 0] {
+            let total = (0..LINES_PER_FRAME)
+                .map(|line| line_cycles(cycles, line))
+                .sum::<usize>();
+            assert_eq!(total, cycles);
+        }
+    }
+
+    #[test]
+    fn test_bands() {
+        let scrolled = BackgroundState {
+            scroll_x: 4,
+            scroll_y: 0,
+        };
+        let mut lines = vec![BackgroundState::default(); 10];
+        lines[4..8].fill(scrolled);
+
+        let bands = bands(&lines).collect::<Vec<_>>();
+        assert_eq!(
+            bands,
+            vec![
+                (0..4, BackgroundState::default()),
+                (4..8, scrolled),
+                (8..10, BackgroundState::default()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,60 @@
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use aya_bitmap::Color;
+
+use crate::error::{Error, Result};
+use crate::renderer::{SCREEN_HEIGHT, SCREEN_WIDTH};
+
+/// Screenshots are written next to the rom, named after it and the moment they were taken. The
+/// name comes from the rom header, so anything but letters, digits, `-` and `_` is replaced to keep
+/// the screenshot on the rom directory.
+pub fn screenshot_path(rom_file: &Path, rom_name: &str) -> PathBuf {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    let name = rom_name
+        .chars()
+        .map(|ch| match ch.is_alphanumeric() || ch == '-' || ch == '_' {
+            true => ch,
+            false => '_',
+        })
+        .collect::<String>();
+    rom_file.with_file_name(format!("{name}-{timestamp}.bmp"))
+}
+
+/// Writes a frame captured by a renderer as a bitmap.
+pub fn save(path: &Path, frame: &[u8]) -> Result<()> {
+    let (pixels, _) = frame.as_chunks::<4>();
+    let pixels = pixels
+        .iter()
+        .map(|[r, g, b, _]| Color::new(*r, *g, *b))
+        .collect::<Vec<_>>();
+
+    let bitmap =
+        aya_bitmap::encode_rgb(SCREEN_WIDTH as u32, SCREEN_HEIGHT as u32, &pixels).map_err(Error::Screenshot)?;
+    std::fs::write(path, bitmap).map_err(|source| Error::Io {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_screenshot_path() {
+        let path = screenshot_path(Path::new("games/jump.rom"), "jump_2-demo");
+        assert_eq!(path.parent(), Some(Path::new("games")));
+        let file = path.file_name().unwrap().to_str().unwrap();
+        assert!(file.starts_with("jump_2-demo-") && file.ends_with(".bmp"), "{file}");
+
+        // names can't leave the rom directory
+        let path = screenshot_path(Path::new("games/jump.rom"), "../../etc/a b");
+        assert_eq!(path.parent(), Some(Path::new("games")));
+        let file = path.file_name().unwrap().to_str().unwrap();
+        assert!(file.starts_with("______etc_a_b-"), "{file}");
+    }
+}
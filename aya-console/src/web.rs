@@ -0,0 +1,96 @@
+use std::io::Write;
+
+use crate::audio::{Audio, SilentAudio};
+use crate::error::Result;
+use crate::input::{Hotkey, Input, WebInput};
+use crate::memory::serial::SerialMem;
+use crate::renderer::{Renderer, WebRenderer};
+use crate::{Emulator, EmulatorOptions, Execution, DEFAULT_SCALE, FPS};
+
+/// Sink for the serial port that logs every line the program writes to the browser console.
+struct ConsoleLog;
+
+impl Write for ConsoleLog {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let line = String::from_utf8_lossy(buf);
+        web_sys::console::log_1(&line.trim_end_matches('\n').into());
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Console running in the browser. Roms are loaded from their bytes, as there is no filesystem
+/// to read them from, and the page asks for frames one at a time, usually from
+/// `requestAnimationFrame`.
+pub struct WebConsole {
+    emulator: Emulator,
+    renderer: WebRenderer,
+    input: WebInput,
+    audio: SilentAudio,
+    execution: Execution,
+}
+
+impl WebConsole {
+    /// Loads a rom and draws its first frame on the console canvas.
+    pub fn new(rom: &[u8]) -> Result<Self> {
+        let serial = SerialMem::new(Box::new(ConsoleLog));
+        let mut emulator = Emulator::with_serial(rom, &EmulatorOptions::default(), serial)?;
+
+        let mut renderer = WebRenderer::start(emulator.title(), FPS, DEFAULT_SCALE)?;
+        renderer.set_palette(emulator.palette());
+
+        let dirty = emulator.take_dirty();
+        emulator.draw_on(&mut renderer, &dirty)?;
+
+        Ok(Self {
+            emulator,
+            renderer,
+            input: WebInput::default(),
+            audio: SilentAudio::start(),
+            execution: Execution::Running,
+        })
+    }
+
+    /// Runs the cpu for a frame and presents it, returns `false` once the program halted, after
+    /// which frames are no longer run.
+    pub fn frame(&mut self) -> Result<bool> {
+        if self.emulator.is_halted() {
+            return Ok(false);
+        }
+
+        let key_status = self.input.poll();
+
+        match self.input.hotkey() {
+            Some(Hotkey::Pause) => {
+                self.execution = match self.execution {
+                    Execution::Running => Execution::Paused,
+                    Execution::Paused | Execution::Step => Execution::Running,
+                };
+                self.renderer.set_paused(self.execution != Execution::Running);
+            }
+            Some(Hotkey::Fullscreen) => self.renderer.toggle_fullscreen(),
+            Some(Hotkey::FrameAdvance) if self.execution == Execution::Paused => self.execution = Execution::Step,
+            _ => {}
+        }
+
+        if self.execution != Execution::Paused {
+            if self.emulator.run_frame(key_status)?.is_none() {
+                return Ok(false);
+            }
+
+            self.audio.play(self.emulator.apu_mut());
+
+            if self.execution == Execution::Step {
+                self.execution = Execution::Paused;
+            }
+        }
+
+        let dirty = self.emulator.take_dirty();
+        self.emulator.draw_on(&mut self.renderer, &dirty)?;
+
+        Ok(true)
+    }
+}
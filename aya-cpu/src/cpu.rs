@@ -1,16 +1,35 @@
-use crate::error::Result;
-use crate::instruction::{Instruction, InstructionSize};
+use crate::error::{Error, Result};
+use crate::instruction::Instruction;
 use crate::memory::Addressable;
-use crate::op_code::OpCode;
 use crate::register::{Register, Registers};
+use crate::trace::TraceWriter;
 use crate::word::Word;
 
 #[derive(Debug)]
 pub enum ControlFlow {
-    Halt(u16),
+    /// The program halted with this code
+    Halt(u8),
     Continue,
 }
 
+/// How a run with an instruction limit ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunOutcome {
+    /// The program halted with `code` after running this many `instructions`, the `hlt` included
+    Halt { code: u8, instructions: u64 },
+    /// The program was still running when the limit was reached, the instruction pointer is the
+    /// address of the next instruction it would run
+    InstructionLimitReached(u16),
+}
+
+/// Execution state of the cpu besides its memory, enough to resume a program from the point it
+/// was captured at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CpuState {
+    pub registers: [u16; Register::len()],
+    pub in_interrupt: bool,
+}
+
 #[derive(Debug)]
 pub struct Cpu<A: Addressable> {
     pub registers: Registers,
@@ -18,349 +37,130 @@ pub struct Cpu<A: Addressable> {
     start_address: Word,
     in_interrupt: bool,
     interrupt_table: Word,
+    trace: Option<TraceWriter>,
 }
 
 impl<A: Addressable> Cpu<A> {
-    pub fn new<W>(memory: A, start_address: W, stack_address: W, interrupt_table: W) -> Self
+    /// Creates a cpu that starts executing at `start_address`. The stack grows down from
+    /// `stack_top`, the address the first pushed word is written at, so it must leave room for a
+    /// whole word before the end of stack memory.
+    pub fn new<W>(memory: A, start_address: W, stack_top: W, interrupt_table: W) -> Self
     where
         W: Into<Word> + Copy,
     {
         Self {
-            registers: Registers::new(start_address, stack_address),
+            registers: Registers::new(start_address, stack_top),
             memory,
             start_address: start_address.into(),
             in_interrupt: false,
             interrupt_table: interrupt_table.into(),
+            trace: None,
         }
     }
 
     pub fn load_into_address(&mut self, bytecode: impl AsRef<[u8]>, address: impl TryInto<Word>) -> Result<()> {
-        let mut address = match address.try_into() {
+        let address: Word = match address.try_into() {
             Ok(addr) => addr,
             Err(_) => unreachable!(),
         };
-        for byte in bytecode.as_ref() {
-            self.memory.write(address, *byte)?;
-            address = address.next()?;
+        let bytecode = bytecode.as_ref();
+        // nothing is written when the bytecode doesn't fit, rather than failing half way through
+        self.memory.check_range(address, bytecode.len())?;
+        for (offset, byte) in bytecode.iter().enumerate() {
+            self.memory
+                .write(u16::from(address).wrapping_add(offset as u16), *byte)?;
         }
         Ok(())
     }
 
-    pub fn run(&mut self) {
-        loop {
-            match self.step() {
-                Ok(ControlFlow::Halt(_)) => break,
-                Ok(ControlFlow::Continue) => {}
-                Err(e) => todo!("{e:?}"),
-            }
+    /// Starts execution `offset` bytes past the start address, jumps stay relative to the start
+    /// address so programs whose entry isn't their first instruction run unchanged.
+    pub fn set_entry_point(&mut self, offset: u16) {
+        let address = u16::from(self.start_address).wrapping_add(offset);
+        self.registers.set(Register::IP, address);
+    }
+
+    pub fn snapshot(&self) -> CpuState {
+        let mut registers = [0; Register::len()];
+        for register in Register::iter() {
+            registers[usize::from(register)] = self.registers.fetch(register);
+        }
+
+        CpuState {
+            registers,
+            in_interrupt: self.in_interrupt,
         }
     }
 
-    pub fn step(&mut self) -> Result<ControlFlow> {
-        let instruction = self.fetch()?;
-        self.execute(instruction)
+    pub fn restore(&mut self, state: &CpuState) {
+        for register in Register::iter() {
+            self.registers.set(register, state.registers[usize::from(register)]);
+        }
+        self.in_interrupt = state.in_interrupt;
     }
 
-    fn fetch(&mut self) -> Result<Instruction> {
-        let op = self.next_instruction(InstructionSize::Small)?;
-        let op = OpCode::try_from(op)?;
-        match op {
-            OpCode::MovLitReg => {
-                let reg = self.next_instruction(InstructionSize::Small)?;
-                let reg = Register::try_from(reg)?;
-                let val = self.next_instruction(InstructionSize::Word)?;
-                Ok(Instruction::MovLitReg(reg, val))
-            }
-            OpCode::MovRegReg => {
-                let reg_to = self.next_instruction(InstructionSize::Small)?;
-                let reg_to = Register::try_from(reg_to)?;
-                let reg_from = self.next_instruction(InstructionSize::Small)?;
-                let reg_from = Register::try_from(reg_from)?;
-                Ok(Instruction::MovRegReg(reg_to, reg_from))
-            }
-            OpCode::MovRegMem => {
-                let address = self.next_instruction(InstructionSize::Word)?;
-                let reg = self.next_instruction(InstructionSize::Small)?;
-                let reg = Register::try_from(reg)?;
-                Ok(Instruction::MovRegMem(reg, address.into()))
-            }
-            OpCode::MovLitMem => {
-                let address = self.next_instruction(InstructionSize::Word)?;
-                let val = self.next_instruction(InstructionSize::Word)?;
-                Ok(Instruction::MovLitMem(address.into(), val))
-            }
-            OpCode::MovMemReg => {
-                let reg = self.next_instruction(InstructionSize::Small)?;
-                let reg = Register::try_from(reg)?;
-                let address = self.next_instruction(InstructionSize::Word)?;
-                Ok(Instruction::MovMemReg(address.into(), reg))
-            }
-            OpCode::MovRegPtrReg => {
-                let reg_to = self.next_instruction(InstructionSize::Small)?;
-                let reg_to = Register::try_from(reg_to)?;
-                let reg_from = self.next_instruction(InstructionSize::Small)?;
-                let reg_from = Register::try_from(reg_from)?;
-                Ok(Instruction::MovRegPtrReg(reg_to, reg_from))
-            }
-            OpCode::MovLitRegPtr => {
-                let reg = self.next_instruction(InstructionSize::Small)?;
-                let lit = self.next_instruction(InstructionSize::Word)?;
-                let reg = Register::try_from(reg)?;
-                Ok(Instruction::MovLitRegPtr(reg, lit))
-            }
-            OpCode::Mov8LitReg => {
-                let reg = self.next_instruction(InstructionSize::Small)?;
-                let reg = Register::try_from(reg)?;
-                let val = self.next_instruction(InstructionSize::Small)?;
-                let val = (val & 0xFF) as u8;
-                Ok(Instruction::Mov8LitReg(reg, val))
-            }
-            OpCode::Mov8RegReg => {
-                let reg_from = self.next_instruction(InstructionSize::Small)?;
-                let reg_from = Register::try_from(reg_from)?;
-                let reg_to = self.next_instruction(InstructionSize::Small)?;
-                let reg_to = Register::try_from(reg_to)?;
-                Ok(Instruction::Mov8RegReg(reg_from, reg_to))
-            }
-            OpCode::Mov8RegMem => {
-                let address = self.next_instruction(InstructionSize::Word)?;
-                let reg = self.next_instruction(InstructionSize::Small)?;
-                let reg = Register::try_from(reg)?;
-                Ok(Instruction::Mov8RegMem(reg, address.into()))
-            }
-            OpCode::Mov8MemReg => {
-                let reg = self.next_instruction(InstructionSize::Small)?;
-                let reg = Register::try_from(reg)?;
-                let address = self.next_instruction(InstructionSize::Word)?;
-                Ok(Instruction::Mov8MemReg(address.into(), reg))
-            }
-            OpCode::Mov8LitMem => {
-                let address = self.next_instruction(InstructionSize::Word)?;
-                let val = self.next_instruction(InstructionSize::Small)?;
-                let val = (val & 0xFF) as u8;
-                Ok(Instruction::Mov8LitMem(address.into(), val))
-            }
-            OpCode::PushLit => {
-                let val = self.next_instruction(InstructionSize::Word)?;
-                Ok(Instruction::PushLit(val))
-            }
-            OpCode::PushReg => {
-                let reg = self.next_instruction(InstructionSize::Small)?;
-                let reg = Register::try_from(reg)?;
-                let val = self.registers.fetch(reg);
-                Ok(Instruction::PushLit(val))
-            }
-            OpCode::Pop => {
-                let reg = self.next_instruction(InstructionSize::Small)?;
-                let reg = Register::try_from(reg)?;
-                Ok(Instruction::PopReg(reg))
-            }
-            OpCode::Call => {
-                let word = self.next_instruction(InstructionSize::Word)?;
-                Ok(Instruction::Call(word.into()))
-            }
-            OpCode::Ret => Ok(Instruction::Ret),
-            OpCode::Halt => {
-                let code = self.next_instruction(InstructionSize::Small)?;
-                Ok(Instruction::Halt(code))
-            }
-            OpCode::AddRegReg => {
-                let r1 = self.next_instruction(InstructionSize::Small)?;
-                let r1 = Register::try_from(r1)?;
-                let r2 = self.next_instruction(InstructionSize::Small)?;
-                let r2 = Register::try_from(r2)?;
-                Ok(Instruction::AddRegReg(r1, r2))
-            }
-            OpCode::AddLitReg => {
-                let reg = self.next_instruction(InstructionSize::Small)?;
-                let reg = Register::try_from(reg)?;
-                let lit = self.next_instruction(InstructionSize::Word)?;
-                Ok(Instruction::AddLitReg(reg, lit))
-            }
-            OpCode::SubLitReg => {
-                let reg = self.next_instruction(InstructionSize::Small)?;
-                let reg = Register::try_from(reg)?;
-                let lit = self.next_instruction(InstructionSize::Word)?;
-                Ok(Instruction::SubLitReg(reg, lit))
-            }
-            OpCode::SubRegReg => {
-                let r1 = self.next_instruction(InstructionSize::Small)?;
-                let r1 = Register::try_from(r1)?;
-                let r2 = self.next_instruction(InstructionSize::Small)?;
-                let r2 = Register::try_from(r2)?;
-                Ok(Instruction::SubRegReg(r1, r2))
-            }
-            OpCode::IncReg => {
-                let reg = self.next_instruction(InstructionSize::Small)?;
-                let reg = Register::try_from(reg)?;
-                Ok(Instruction::IncReg(reg))
-            }
-            OpCode::DecReg => {
-                let reg = self.next_instruction(InstructionSize::Small)?;
-                let reg = Register::try_from(reg)?;
-                Ok(Instruction::DecReg(reg))
-            }
-            OpCode::MulLitReg => {
-                let reg = self.next_instruction(InstructionSize::Small)?;
-                let reg = Register::try_from(reg)?;
-                let lit = self.next_instruction(InstructionSize::Word)?;
-                Ok(Instruction::MulLitReg(reg, lit))
-            }
-            OpCode::MulRegReg => {
-                let r1 = self.next_instruction(InstructionSize::Small)?;
-                let r1 = Register::try_from(r1)?;
-                let r2 = self.next_instruction(InstructionSize::Small)?;
-                let r2 = Register::try_from(r2)?;
-                Ok(Instruction::MulRegReg(r1, r2))
-            }
+    /// Whether a handler is running, interrupts entered from it don't return to it but to the
+    /// program it interrupted.
+    pub fn in_interrupt(&self) -> bool {
+        self.in_interrupt
+    }
 
-            OpCode::LshLitReg => {
-                let reg = self.next_instruction(InstructionSize::Small)?;
-                let reg = Register::try_from(reg)?;
-                let lit = self.next_instruction(InstructionSize::Word)?;
-                Ok(Instruction::LshLitReg(reg, lit))
-            }
-            OpCode::LshRegReg => {
-                let r1 = self.next_instruction(InstructionSize::Small)?;
-                let r1 = Register::try_from(r1)?;
-                let r2 = self.next_instruction(InstructionSize::Small)?;
-                let r2 = Register::try_from(r2)?;
-                Ok(Instruction::LshRegReg(r1, r2))
-            }
-            OpCode::RshLitReg => {
-                let reg = self.next_instruction(InstructionSize::Small)?;
-                let reg = Register::try_from(reg)?;
-                let lit = self.next_instruction(InstructionSize::Word)?;
-                Ok(Instruction::RshLitReg(reg, lit))
-            }
-            OpCode::RshRegReg => {
-                let r1 = self.next_instruction(InstructionSize::Small)?;
-                let r1 = Register::try_from(r1)?;
-                let r2 = self.next_instruction(InstructionSize::Small)?;
-                let r2 = Register::try_from(r2)?;
-                Ok(Instruction::RshRegReg(r1, r2))
-            }
-            OpCode::AndLitReg => {
-                let reg = self.next_instruction(InstructionSize::Small)?;
-                let reg = Register::try_from(reg)?;
-                let lit = self.next_instruction(InstructionSize::Word)?;
-                Ok(Instruction::AndLitReg(reg, lit))
-            }
-            OpCode::AndRegReg => {
-                let r1 = self.next_instruction(InstructionSize::Small)?;
-                let r1 = Register::try_from(r1)?;
-                let r2 = self.next_instruction(InstructionSize::Small)?;
-                let r2 = Register::try_from(r2)?;
-                Ok(Instruction::AndRegReg(r1, r2))
-            }
-            OpCode::OrLitReg => {
-                let reg = self.next_instruction(InstructionSize::Small)?;
-                let reg = Register::try_from(reg)?;
-                let lit = self.next_instruction(InstructionSize::Word)?;
-                Ok(Instruction::OrLitReg(reg, lit))
-            }
-            OpCode::OrRegReg => {
-                let r1 = self.next_instruction(InstructionSize::Small)?;
-                let r1 = Register::try_from(r1)?;
-                let r2 = self.next_instruction(InstructionSize::Small)?;
-                let r2 = Register::try_from(r2)?;
-                Ok(Instruction::OrRegReg(r1, r2))
-            }
-            OpCode::XorLitReg => {
-                let reg = self.next_instruction(InstructionSize::Small)?;
-                let reg = Register::try_from(reg)?;
-                let lit = self.next_instruction(InstructionSize::Word)?;
-                Ok(Instruction::XorLitReg(reg, lit))
-            }
-            OpCode::XorRegReg => {
-                let r1 = self.next_instruction(InstructionSize::Small)?;
-                let r1 = Register::try_from(r1)?;
-                let r2 = self.next_instruction(InstructionSize::Small)?;
-                let r2 = Register::try_from(r2)?;
-                Ok(Instruction::XorRegReg(r1, r2))
-            }
-            OpCode::Not => {
-                let reg = self.next_instruction(InstructionSize::Small)?;
-                let reg = Register::try_from(reg)?;
-                Ok(Instruction::Not(reg))
+    /// Runs until the program halts, for as long as it takes, returning the code it halted with.
+    pub fn run(&mut self) -> Result<u8> {
+        loop {
+            if let ControlFlow::Halt(code) = self.step()? {
+                return Ok(code);
             }
+        }
+    }
 
-            OpCode::JeqLit => {
-                let jump_to = self.next_instruction(InstructionSize::Word)?;
-                let literal = self.next_instruction(InstructionSize::Word)?;
-                Ok(Instruction::JeqLit(jump_to.into(), literal))
-            }
-            OpCode::JeqReg => {
-                let jump_to = self.next_instruction(InstructionSize::Word)?;
-                let reg = self.next_instruction(InstructionSize::Small)?;
-                let reg = Register::try_from(reg)?;
-                Ok(Instruction::JeqReg(jump_to.into(), reg))
-            }
-            OpCode::JgtLit => {
-                let jump_to = self.next_instruction(InstructionSize::Word)?;
-                let literal = self.next_instruction(InstructionSize::Word)?;
-                Ok(Instruction::JgtLit(jump_to.into(), literal))
-            }
-            OpCode::JgtReg => {
-                let jump_to = self.next_instruction(InstructionSize::Word)?;
-                let reg = self.next_instruction(InstructionSize::Small)?;
-                let reg = Register::try_from(reg)?;
-                Ok(Instruction::JgtReg(jump_to.into(), reg))
-            }
-            OpCode::JneLit => {
-                let jump_to = self.next_instruction(InstructionSize::Word)?;
-                let literal = self.next_instruction(InstructionSize::Word)?;
-                Ok(Instruction::JneLit(jump_to.into(), literal))
-            }
-            OpCode::JneReg => {
-                let jump_to = self.next_instruction(InstructionSize::Word)?;
-                let reg = self.next_instruction(InstructionSize::Small)?;
-                let reg = Register::try_from(reg)?;
-                Ok(Instruction::JneReg(jump_to.into(), reg))
-            }
-            OpCode::JgeLit => {
-                let jump_to = self.next_instruction(InstructionSize::Word)?;
-                let literal = self.next_instruction(InstructionSize::Word)?;
-                Ok(Instruction::JgeLit(jump_to.into(), literal))
-            }
-            OpCode::JgeReg => {
-                let jump_to = self.next_instruction(InstructionSize::Word)?;
-                let reg = self.next_instruction(InstructionSize::Small)?;
-                let reg = Register::try_from(reg)?;
-                Ok(Instruction::JgeReg(jump_to.into(), reg))
-            }
-            OpCode::JleLit => {
-                let jump_to = self.next_instruction(InstructionSize::Word)?;
-                let literal = self.next_instruction(InstructionSize::Word)?;
-                Ok(Instruction::JleLit(jump_to.into(), literal))
+    /// Runs until the program halts or `max_instructions` ran, so a program stuck in a loop
+    /// can't hang whoever is running it.
+    pub fn run_bounded(&mut self, max_instructions: u64) -> Result<RunOutcome> {
+        for instructions in 1..=max_instructions {
+            if let ControlFlow::Halt(code) = self.step()? {
+                return Ok(RunOutcome::Halt { code, instructions });
             }
-            OpCode::JleReg => {
-                let jump_to = self.next_instruction(InstructionSize::Word)?;
-                let reg = self.next_instruction(InstructionSize::Small)?;
-                let reg = Register::try_from(reg)?;
-                Ok(Instruction::JleReg(jump_to.into(), reg))
-            }
-            OpCode::JltLit => {
-                let jump_to = self.next_instruction(InstructionSize::Word)?;
-                let literal = self.next_instruction(InstructionSize::Word)?;
-                Ok(Instruction::JltLit(jump_to.into(), literal))
-            }
-            OpCode::JltReg => {
-                let jump_to = self.next_instruction(InstructionSize::Word)?;
-                let reg = self.next_instruction(InstructionSize::Small)?;
-                let reg = Register::try_from(reg)?;
-                Ok(Instruction::JltReg(jump_to.into(), reg))
-            }
-            OpCode::Jmp => {
-                let jump_to = self.next_instruction(InstructionSize::Word)?;
-                Ok(Instruction::Jmp(jump_to.into()))
-            }
-            OpCode::Int => {
-                let address = self.next_instruction(InstructionSize::Small)?;
-                Ok(Instruction::Int(address))
+        }
+
+        Ok(RunOutcome::InstructionLimitReached(self.registers.fetch(Register::IP)))
+    }
+
+    /// Records every instruction the cpu runs from now on into `trace`, or stops tracing when
+    /// `None`.
+    pub fn set_trace(&mut self, trace: Option<TraceWriter>) {
+        self.trace = trace;
+    }
+
+    pub fn step(&mut self) -> Result<ControlFlow> {
+        if self.trace.is_none() {
+            let instruction = self.fetch()?;
+            return self.execute(instruction);
+        }
+
+        let before = self.snapshot();
+        let ip = self.registers.fetch(Register::IP);
+        let instruction = self.fetch()?;
+        let flow = self.execute(instruction)?;
+        let after = self.snapshot();
+        if let Some(trace) = self.trace.as_mut() {
+            // the instruction already ran, the trace is dropped so callers that carry on after
+            // the error run without it
+            if let Err(err) = trace.record(ip, &instruction, &before, &after) {
+                self.trace = None;
+                return Err(Error::Trace(err));
             }
-            OpCode::Rti => Ok(Instruction::Rti),
         }
+        Ok(flow)
+    }
+
+    fn fetch(&mut self) -> Result<Instruction> {
+        Instruction::decode_with(|| {
+            let ip = self.registers.fetch_word(Register::IP);
+            let byte = self.memory.read(ip)?;
+            self.registers.set(Register::IP, ip.next()?.into());
+            Ok(byte)
+        })
     }
 
     fn execute(&mut self, instruction: Instruction) -> Result<ControlFlow> {
@@ -381,6 +181,16 @@ impl<A: Addressable> Cpu<A> {
                 let value = self.memory.read_word(address)?;
                 self.registers.set(reg, value)
             }
+            Instruction::LoadRegPtr(to, address) => {
+                let address = self.registers.fetch(address);
+                let value = self.memory.read_word(address)?;
+                self.registers.set(to, value);
+            }
+            Instruction::LoadRegPtrOff(to, address, offset) => {
+                let address = self.registers.fetch(address).wrapping_add(offset);
+                let value = self.memory.read_word(address)?;
+                self.registers.set(to, value);
+            }
             Instruction::MovRegPtrReg(address, from) => {
                 let address = self.registers.fetch(address);
                 let val = self.registers.fetch(from);
@@ -602,6 +412,7 @@ impl<A: Addressable> Cpu<A> {
             }
 
             Instruction::PushLit(val) => self.push_stack(val)?,
+            Instruction::PushReg(reg) => self.push_stack(self.registers.fetch(reg))?,
             Instruction::PopReg(reg) => {
                 let val = self.pop_stack()?;
                 self.registers.set(reg, val);
@@ -612,6 +423,13 @@ impl<A: Addressable> Cpu<A> {
                 self.call_address(address.into())?;
             }
             Instruction::Ret => self.restore_stack()?,
+            // halting inside a handler would stop the machine with the state of the interrupted
+            // program still on the stack, handlers have to return with rti instead
+            Instruction::Halt(code) if self.in_interrupt => {
+                let size = Instruction::Halt(code).encode().len() as u16;
+                let address = self.registers.fetch(Register::IP).wrapping_sub(size);
+                return Err(Error::HaltInInterrupt(address));
+            }
             Instruction::Halt(code) => return Ok(ControlFlow::Halt(code)),
             Instruction::Int(interrupt) => self.handle_interrupt(interrupt)?,
             Instruction::Rti => {
@@ -622,23 +440,6 @@ impl<A: Addressable> Cpu<A> {
         Ok(ControlFlow::Continue)
     }
 
-    fn next_instruction(&mut self, size: InstructionSize) -> Result<u16> {
-        match size {
-            InstructionSize::Small => {
-                let reg_ptr = self.registers.fetch_word(Register::IP);
-                let val = self.memory.read(reg_ptr)?;
-                self.registers.set(Register::IP, reg_ptr.next()?.into());
-                Ok(val.into())
-            }
-            InstructionSize::Word => {
-                let reg_ptr = self.registers.fetch_word(Register::IP);
-                let val = self.memory.read_word(reg_ptr)?;
-                self.registers.set(Register::IP, reg_ptr.next_word()?.into());
-                Ok(val)
-            }
-        }
-    }
-
     fn call_address(&mut self, address: Word) -> Result<()> {
         self.save_stack()?;
         let address = address + self.start_address;
@@ -720,19 +521,27 @@ impl<A: Addressable> Cpu<A> {
         Ok(())
     }
 
+    /// Enters the handler of interrupt `idx`, unless its bit is cleared on the interrupt mask or
+    /// its entry on the interrupt table is empty.
     pub fn handle_interrupt(&mut self, idx: impl Into<u16>) -> Result<()> {
         let interrupt_idx = idx.into() & 0xF;
 
-        // if the interrupt is unmasked (its 0) on the interrupt mask, then we should not enter the
-        // interrupt handler
-        let is_unmasked = (1 << interrupt_idx) & self.registers.fetch(Register::IM);
-        if is_unmasked == 0 {
+        // interrupts whose bit is cleared on the interrupt mask are disabled, and never enter
+        // their handler
+        let is_enabled = (1 << interrupt_idx) & self.registers.fetch(Register::IM);
+        if is_enabled == 0 {
             return Ok(());
         }
 
         let handler_pointer = self.interrupt_table + (interrupt_idx * 2).into();
         let address = self.memory.read_word(handler_pointer)?;
 
+        // an empty entry on the interrupt table means the program installed no handler, as every
+        // interrupt is enabled until the program masks it
+        if address == 0 {
+            return Ok(());
+        }
+
         // if we are already within an interrupt (calling an interrupt from another), we don't save
         // the stack state
         if !self.in_interrupt {
@@ -749,32 +558,50 @@ impl<A: Addressable> Cpu<A> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::op_code::OpCode;
+    use crate::MEMORY_SIZE;
 
     struct Memory {
-        memory: [u8; u16::MAX as usize],
+        memory: Vec<u8>,
     }
 
     impl Memory {
         pub fn new() -> Self {
-            Self {
-                memory: [0; u16::MAX as usize],
-            }
+            Self::with_len(MEMORY_SIZE)
+        }
+
+        /// Memory answering only to its first `len` addresses.
+        pub fn with_len(len: usize) -> Self {
+            Self { memory: vec![0; len] }
         }
     }
 
     impl Addressable for Memory {
+        fn len(&self) -> usize {
+            self.memory.len()
+        }
+
         fn read<W>(&self, address: W) -> crate::memory::Result<u8>
         where
             W: Into<Word> + Copy,
         {
-            Ok(self.memory[usize::from(address.into())])
+            let address = address.into();
+            self.memory
+                .get(usize::from(address))
+                .copied()
+                .ok_or(crate::memory::Error::InvalidAddress(address.into()))
         }
 
         fn write<W>(&mut self, address: W, byte: impl Into<u8>) -> crate::memory::Result<()>
         where
             W: Into<Word> + Copy,
         {
-            self.memory[usize::from(address.into())] = byte.into();
+            let address = address.into();
+            let cell = self
+                .memory
+                .get_mut(usize::from(address))
+                .ok_or(crate::memory::Error::InvalidAddress(address.into()))?;
+            *cell = byte.into();
             Ok(())
         }
     }
@@ -787,7 +614,7 @@ mod tests {
         memory.write(0x0001, Register::R1).unwrap();
         memory.write_word(0x0002, 0x00FF).unwrap();
 
-        let mut cpu = Cpu::new(memory, 0, 0x8000, 0x1000);
+        let mut cpu = Cpu::new(memory, 0, 0x7FFE, 0x1000);
         cpu.step().unwrap();
         assert_eq!(cpu.registers.fetch(Register::R1), 0xFF);
     }
@@ -805,7 +632,7 @@ mod tests {
         memory.write(0x0005, Register::R2).unwrap();
         memory.write(0x0006, Register::R1).unwrap();
 
-        let mut cpu = Cpu::new(memory, 0, 0x8000, 0x1000);
+        let mut cpu = Cpu::new(memory, 0, 0x7FFE, 0x1000);
         cpu.step().unwrap();
         cpu.step().unwrap();
         assert_eq!(cpu.registers.fetch(Register::R2), 0xFF);
@@ -824,7 +651,7 @@ mod tests {
         memory.write_word(0x0005, 0x0100).unwrap();
         memory.write(0x0007, Register::R1).unwrap();
 
-        let mut cpu = Cpu::new(memory, 0, 0x8000, 0x1000);
+        let mut cpu = Cpu::new(memory, 0, 0x7FFE, 0x1000);
         cpu.step().unwrap();
         cpu.step().unwrap();
 
@@ -841,12 +668,77 @@ mod tests {
         memory.write(0x0001, Register::R1).unwrap();
         memory.write_word(0x0002, 0x0100).unwrap();
 
-        let mut cpu = Cpu::new(memory, 0, 0x8000, 0x1000);
+        let mut cpu = Cpu::new(memory, 0, 0x7FFE, 0x1000);
         cpu.step().unwrap();
 
         assert_eq!(cpu.registers.fetch(Register::R1), 0xabcd);
     }
 
+    #[test]
+    fn test_load_reg_ptr() {
+        let mut memory = Memory::new();
+        memory.write_word(0x0100, 0xabcd).unwrap();
+        memory.write_word(0x0104, 0x1234).unwrap();
+
+        // mov r2, $0100
+        memory.write(0x0000, OpCode::MovLitReg).unwrap();
+        memory.write(0x0001, Register::R2).unwrap();
+        memory.write_word(0x0002, 0x0100).unwrap();
+
+        // mov r1, &[r2]
+        memory.write(0x0004, OpCode::LoadRegPtr).unwrap();
+        memory.write(0x0005, Register::R1).unwrap();
+        memory.write(0x0006, Register::R2).unwrap();
+
+        // mov r3, &[r2 + $04]
+        memory.write(0x0007, OpCode::LoadRegPtrOff).unwrap();
+        memory.write(0x0008, Register::R3).unwrap();
+        memory.write(0x0009, Register::R2).unwrap();
+        memory.write_word(0x000A, 0x0004).unwrap();
+
+        let mut cpu = Cpu::new(memory, 0, 0x7FFE, 0x1000);
+        cpu.step().unwrap();
+        cpu.step().unwrap();
+        cpu.step().unwrap();
+
+        // loads only read memory, the address register is left as it was
+        assert_eq!(cpu.registers.fetch(Register::R1), 0xabcd);
+        assert_eq!(cpu.registers.fetch(Register::R2), 0x0100);
+        assert_eq!(cpu.registers.fetch(Register::R3), 0x1234);
+        assert_eq!(cpu.memory.read_word(0x0100).unwrap(), 0xabcd);
+    }
+
+    #[test]
+    fn test_mov_reg_ptr_reg_stores() {
+        let mut memory = Memory::new();
+        memory.write_word(0x0100, 0xabcd).unwrap();
+
+        // mov r1, $0100
+        memory.write(0x0000, OpCode::MovLitReg).unwrap();
+        memory.write(0x0001, Register::R1).unwrap();
+        memory.write_word(0x0002, 0x0100).unwrap();
+
+        // mov r2, $1234
+        memory.write(0x0004, OpCode::MovLitReg).unwrap();
+        memory.write(0x0005, Register::R2).unwrap();
+        memory.write_word(0x0006, 0x1234).unwrap();
+
+        // mov &[r1], r2
+        memory.write(0x0008, OpCode::MovRegPtrReg).unwrap();
+        memory.write(0x0009, Register::R1).unwrap();
+        memory.write(0x000A, Register::R2).unwrap();
+
+        let mut cpu = Cpu::new(memory, 0, 0x7FFE, 0x1000);
+        cpu.step().unwrap();
+        cpu.step().unwrap();
+        cpu.step().unwrap();
+
+        // the store writes the second register through the address in the first one
+        assert_eq!(cpu.memory.read_word(0x0100).unwrap(), 0x1234);
+        assert_eq!(cpu.registers.fetch(Register::R1), 0x0100);
+        assert_eq!(cpu.registers.fetch(Register::R2), 0x1234);
+    }
+
     #[test]
     fn test_jeq_reg() {
         let mut memory = Memory::new();
@@ -861,7 +753,7 @@ mod tests {
         memory.write_word(0x0005, 0x0100).unwrap();
         memory.write(0x0007, Register::R1).unwrap();
 
-        let mut cpu = Cpu::new(memory, 0, 0x8000, 0x1000);
+        let mut cpu = Cpu::new(memory, 0, 0x7FFE, 0x1000);
         cpu.registers.set(Register::Acc, 0xabcd);
         cpu.step().unwrap();
 
@@ -887,7 +779,7 @@ mod tests {
         memory.write_word(0x0006, 0x0100).unwrap();
         memory.write_word(0x0008, 0xabcd).unwrap();
 
-        let mut cpu = Cpu::new(memory, 0, 0x8000, 0x1000);
+        let mut cpu = Cpu::new(memory, 0, 0x7FFE, 0x1000);
         cpu.registers.set(Register::Acc, 0xabcd);
         cpu.step().unwrap();
 
@@ -912,7 +804,7 @@ mod tests {
         memory.write_word(0x0005, 0x0100).unwrap();
         memory.write(0x0007, Register::R1).unwrap();
 
-        let mut cpu = Cpu::new(memory, 0, 0x8000, 0x1000);
+        let mut cpu = Cpu::new(memory, 0, 0x7FFE, 0x1000);
         cpu.registers.set(Register::Acc, 0xabcd);
         cpu.registers.set(Register::R1, 0xabcc);
         cpu.step().unwrap();
@@ -939,7 +831,7 @@ mod tests {
         memory.write_word(0x0006, 0x0100).unwrap();
         memory.write_word(0x0008, 0xffff).unwrap();
 
-        let mut cpu = Cpu::new(memory, 0, 0x8000, 0x1000);
+        let mut cpu = Cpu::new(memory, 0, 0x7FFE, 0x1000);
         cpu.registers.set(Register::Acc, 0xabcd);
         cpu.step().unwrap();
 
@@ -964,7 +856,7 @@ mod tests {
         memory.write_word(0x0005, 0x0100).unwrap();
         memory.write(0x0007, Register::R1).unwrap();
 
-        let mut cpu = Cpu::new(memory, 0, 0x8000, 0x1000);
+        let mut cpu = Cpu::new(memory, 0, 0x7FFE, 0x1000);
         cpu.registers.set(Register::Acc, 0xabcd);
         cpu.registers.set(Register::R1, 0xabcd);
         cpu.step().unwrap();
@@ -991,7 +883,7 @@ mod tests {
         memory.write_word(0x0006, 0x0100).unwrap();
         memory.write_word(0x0008, 0xffff).unwrap();
 
-        let mut cpu = Cpu::new(memory, 0, 0x8000, 0x1000);
+        let mut cpu = Cpu::new(memory, 0, 0x7FFE, 0x1000);
         cpu.registers.set(Register::Acc, 0xabcd);
         cpu.step().unwrap();
 
@@ -1016,7 +908,7 @@ mod tests {
         memory.write_word(0x0005, 0x0100).unwrap();
         memory.write(0x0007, Register::R1).unwrap();
 
-        let mut cpu = Cpu::new(memory, 0, 0x8000, 0x1000);
+        let mut cpu = Cpu::new(memory, 0, 0x7FFE, 0x1000);
         cpu.registers.set(Register::Acc, 0xabcd);
         cpu.step().unwrap();
 
@@ -1042,7 +934,7 @@ mod tests {
         memory.write_word(0x0006, 0x0100).unwrap();
         memory.write_word(0x0008, 0xffff).unwrap();
 
-        let mut cpu = Cpu::new(memory, 0, 0x8000, 0x1000);
+        let mut cpu = Cpu::new(memory, 0, 0x7FFE, 0x1000);
         cpu.registers.set(Register::Acc, 0xabcd);
         cpu.step().unwrap();
 
@@ -1067,7 +959,7 @@ mod tests {
         memory.write_word(0x0005, 0x0100).unwrap();
         memory.write(0x0007, Register::R1).unwrap();
 
-        let mut cpu = Cpu::new(memory, 0, 0x8000, 0x1000);
+        let mut cpu = Cpu::new(memory, 0, 0x7FFE, 0x1000);
         cpu.registers.set(Register::Acc, 0xabcd);
         cpu.registers.set(Register::R1, 0xffff);
         cpu.step().unwrap();
@@ -1094,7 +986,7 @@ mod tests {
         memory.write_word(0x0006, 0x0100).unwrap();
         memory.write_word(0x0008, 0xabcd).unwrap();
 
-        let mut cpu = Cpu::new(memory, 0, 0x8000, 0x1000);
+        let mut cpu = Cpu::new(memory, 0, 0x7FFE, 0x1000);
         cpu.registers.set(Register::Acc, 0xabcd);
         cpu.step().unwrap();
 
@@ -1119,7 +1011,7 @@ mod tests {
         memory.write_word(0x0005, 0x0100).unwrap();
         memory.write(0x0007, Register::R1).unwrap();
 
-        let mut cpu = Cpu::new(memory, 0, 0x8000, 0x1000);
+        let mut cpu = Cpu::new(memory, 0, 0x7FFE, 0x1000);
         cpu.registers.set(Register::Acc, 0xabcd);
         cpu.registers.set(Register::R1, 0xabcd);
         cpu.step().unwrap();
@@ -1146,7 +1038,7 @@ mod tests {
         memory.write_word(0x0006, 0x0100).unwrap();
         memory.write_word(0x0008, 0xabcc).unwrap();
 
-        let mut cpu = Cpu::new(memory, 0, 0x8000, 0x1000);
+        let mut cpu = Cpu::new(memory, 0, 0x7FFE, 0x1000);
         cpu.registers.set(Register::Acc, 0xabcd);
         cpu.step().unwrap();
 
@@ -1165,9 +1057,197 @@ mod tests {
         memory.write(0x0000, OpCode::Jmp).unwrap();
         memory.write_word(0x0001, 0x0100).unwrap();
 
-        let mut cpu = Cpu::new(memory, 0, 0x8000, 0x1000);
+        let mut cpu = Cpu::new(memory, 0, 0x7FFE, 0x1000);
         cpu.step().unwrap();
 
         assert_eq!(cpu.registers.fetch(Register::IP), 0x0100);
     }
+
+    #[test]
+    fn test_snapshot_restore() {
+        let mut memory = Memory::new();
+
+        // mov r1, $ff
+        memory.write(0x0000, OpCode::MovLitReg).unwrap();
+        memory.write(0x0001, Register::R1).unwrap();
+        memory.write_word(0x0002, 0x00FF).unwrap();
+
+        let mut cpu = Cpu::new(memory, 0, 0x7FFE, 0x1000);
+        let state = cpu.snapshot();
+        cpu.step().unwrap();
+        assert_eq!(cpu.registers.fetch(Register::R1), 0xFF);
+
+        cpu.restore(&state);
+        assert_eq!(cpu.registers.fetch(Register::R1), 0x00);
+        assert_eq!(cpu.registers.fetch(Register::IP), 0x0000);
+        assert_eq!(cpu.snapshot(), state);
+    }
+
+    /// Cpu with `handler` installed for interrupt 1 at $0100, and the main program idling at
+    /// $0000.
+    fn interrupt_cpu(handler: &[Instruction]) -> Cpu<Memory> {
+        let mut cpu = Cpu::new(Memory::new(), 0, 0x7FFE, 0x1000);
+        cpu.load_into_address(Instruction::Jmp(0x0000.into()).encode(), 0x0000)
+            .unwrap();
+        let handler = handler.iter().flat_map(Instruction::encode).collect::<Vec<_>>();
+        cpu.load_into_address(handler, 0x0100).unwrap();
+        cpu.memory.write_word(0x1002, 0x0100).unwrap();
+        cpu
+    }
+
+    #[test]
+    fn test_interrupts_start_enabled() {
+        let mut cpu = interrupt_cpu(&[Instruction::Rti]);
+        assert_eq!(cpu.registers.fetch(Register::IM), 0xFFFF);
+
+        cpu.handle_interrupt(1u16).unwrap();
+        assert_eq!(cpu.registers.fetch(Register::IP), 0x0100);
+        cpu.step().unwrap();
+        assert_eq!(cpu.registers.fetch(Register::IP), 0x0000);
+    }
+
+    #[test]
+    fn test_masked_interrupt() {
+        let mut cpu = interrupt_cpu(&[Instruction::Rti]);
+        cpu.registers.set(Register::IM, !(1 << 1));
+
+        cpu.handle_interrupt(1u16).unwrap();
+        assert_eq!(cpu.registers.fetch(Register::IP), 0x0000);
+        assert!(!cpu.in_interrupt);
+    }
+
+    #[test]
+    fn test_interrupt_without_handler() {
+        let mut cpu = interrupt_cpu(&[Instruction::Rti]);
+
+        // only interrupt 1 has a handler
+        cpu.handle_interrupt(2u16).unwrap();
+        assert_eq!(cpu.registers.fetch(Register::IP), 0x0000);
+        assert!(!cpu.in_interrupt);
+    }
+
+    #[test]
+    fn test_halt_outside_handlers() {
+        let mut cpu = interrupt_cpu(&[Instruction::Rti]);
+        cpu.load_into_address(Instruction::Halt(0).encode(), 0x0000).unwrap();
+        assert!(matches!(cpu.step().unwrap(), ControlFlow::Halt(0)));
+
+        // once the handler returns, halting stops the machine again
+        cpu.registers.set(Register::IP, 0x0000);
+        cpu.handle_interrupt(1u16).unwrap();
+        cpu.step().unwrap();
+        assert!(matches!(cpu.step().unwrap(), ControlFlow::Halt(0)));
+    }
+
+    #[test]
+    fn test_run_bounded() {
+        // the main program jumps to itself forever
+        let mut cpu = interrupt_cpu(&[Instruction::Rti]);
+        let outcome = cpu.run_bounded(1_000).unwrap();
+        assert_eq!(outcome, RunOutcome::InstructionLimitReached(0x0000));
+
+        cpu.load_into_address(
+            [Instruction::MovLitReg(Register::R1, 0x0001), Instruction::Halt(3)]
+                .iter()
+                .flat_map(Instruction::encode)
+                .collect::<Vec<_>>(),
+            0x0000,
+        )
+        .unwrap();
+        assert_eq!(
+            cpu.run_bounded(1_000).unwrap(),
+            RunOutcome::Halt {
+                code: 3,
+                instructions: 2
+            }
+        );
+        assert_eq!(cpu.run_bounded(0).unwrap(), RunOutcome::InstructionLimitReached(0x0006));
+    }
+
+    #[test]
+    fn test_run_returns_errors() {
+        let mut cpu = interrupt_cpu(&[]);
+        cpu.load_into_address([0x00], 0x0000).unwrap();
+        assert!(matches!(cpu.run().unwrap_err(), Error::OpCode(_)));
+
+        let mut cpu = interrupt_cpu(&[]);
+        cpu.load_into_address(Instruction::Halt(7).encode(), 0x0000).unwrap();
+        assert_eq!(cpu.run().unwrap(), 7);
+    }
+
+    #[test]
+    fn test_halt_inside_handler() {
+        let mut cpu = interrupt_cpu(&[Instruction::MovLitReg(Register::R1, 0x0001), Instruction::Halt(0)]);
+
+        cpu.handle_interrupt(1u16).unwrap();
+        cpu.step().unwrap();
+        let err = cpu.step().unwrap_err();
+        assert!(matches!(err, Error::HaltInInterrupt(0x0104)));
+        assert_eq!(
+            err.to_string(),
+            "hlt at $0104 is inside an interrupt handler, handlers must return with rti"
+        );
+    }
+
+    #[test]
+    fn test_halt_inside_nested_handler() {
+        // the handler of interrupt 1 raises interrupt 2, whose handler halts
+        let mut cpu = interrupt_cpu(&[Instruction::Int(2), Instruction::Rti]);
+        cpu.load_into_address(Instruction::Halt(0).encode(), 0x0200).unwrap();
+        cpu.memory.write_word(0x1004, 0x0200).unwrap();
+
+        cpu.handle_interrupt(1u16).unwrap();
+        cpu.step().unwrap();
+        assert_eq!(cpu.registers.fetch(Register::IP), 0x0200);
+        assert!(matches!(cpu.step().unwrap_err(), Error::HaltInInterrupt(0x0200)));
+    }
+
+    #[test]
+    fn test_mask_changed_by_handler() {
+        let mut cpu = interrupt_cpu(&[Instruction::MovLitReg(Register::IM, 0x0000), Instruction::Rti]);
+
+        cpu.handle_interrupt(1u16).unwrap();
+        cpu.step().unwrap();
+        assert_eq!(cpu.registers.fetch(Register::IM), 0x0000);
+
+        // the handler masked every interrupt, including its own
+        cpu.handle_interrupt(1u16).unwrap();
+        assert_eq!(cpu.registers.fetch(Register::IP), 0x0104);
+        cpu.step().unwrap();
+        assert_eq!(cpu.registers.fetch(Register::IP), 0x0000);
+
+        // and the mask outlives the handler
+        cpu.handle_interrupt(1u16).unwrap();
+        assert_eq!(cpu.registers.fetch(Register::IP), 0x0000);
+    }
+
+    #[test]
+    fn test_load_at_the_top_of_memory() {
+        let mut cpu = Cpu::new(Memory::new(), 0, 0x7FFE, 0x1000);
+        cpu.load_into_address([1, 2, 3], 0xFFFDu16).unwrap();
+        assert_eq!(cpu.memory.read(0xFFFFu16).unwrap(), 3);
+    }
+
+    #[test]
+    fn test_load_into_small_memories() {
+        for len in [0, 1, 0x100, 0x4000] {
+            let mut cpu = Cpu::new(Memory::with_len(len), 0, 0, 0);
+            assert_eq!(cpu.memory.len(), len);
+
+            let fits = vec![0xAB; len];
+            cpu.load_into_address(&fits, 0u16).unwrap();
+
+            // one byte past the end fails before writing anything
+            let err = cpu
+                .load_into_address([0xCD; 2], len.saturating_sub(1) as u16)
+                .unwrap_err();
+            assert!(matches!(
+                err,
+                Error::Mem(crate::memory::Error::OutOfBounds { size: 2, len: l, .. }) if l == len
+            ));
+            if len > 0 {
+                assert_eq!(cpu.memory.read((len - 1) as u16).unwrap(), 0xAB);
+            }
+        }
+    }
 }
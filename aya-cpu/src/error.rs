@@ -7,15 +7,38 @@ pub enum Error {
     Mem(memory::Error),
     OpCode(op_code::Error),
     Register(register::Error),
+    /// `hlt` executed inside an interrupt handler, at the address it was fetched from
+    HaltInInterrupt(u16),
+    /// The instruction trace couldn't be written
+    Trace(std::io::Error),
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{self:?}")
+        match self {
+            Error::Mem(err) => write!(f, "{err}"),
+            Error::OpCode(err) => write!(f, "{err}"),
+            Error::Register(err) => write!(f, "{err}"),
+            Error::HaltInInterrupt(address) => write!(
+                f,
+                "hlt at ${address:04X} is inside an interrupt handler, handlers must return with rti"
+            ),
+            Error::Trace(err) => write!(f, "trace couldn't be written, {err}"),
+        }
     }
 }
 
-impl std::error::Error for Error {}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Mem(err) => Some(err),
+            Error::OpCode(err) => Some(err),
+            Error::Register(err) => Some(err),
+            Error::HaltInInterrupt(_) => None,
+            Error::Trace(err) => Some(err),
+        }
+    }
+}
 
 impl From<memory::Error> for Error {
     fn from(err: memory::Error) -> Self {
@@ -1,13 +1,12 @@
+use std::fmt;
+
+use crate::error::Result;
+use crate::memory;
+use crate::op_code::OpCode;
 use crate::register::Register;
 use crate::word::Word;
 
 #[derive(Debug, PartialEq, Eq, Copy, Clone)]
-pub enum InstructionSize {
-    Small,
-    Word,
-}
-
-#[derive(Debug)]
 #[repr(u8)]
 pub enum Instruction {
     MovLitReg(Register, u16),
@@ -24,6 +23,9 @@ pub enum Instruction {
     Mov8MemReg(Word, Register),
     Mov8LitMem(Word, u8),
 
+    LoadRegPtr(Register, Register),
+    LoadRegPtrOff(Register, Register, u16),
+
     AddRegReg(Register, Register),
     AddLitReg(Register, u16),
     SubRegReg(Register, Register),
@@ -60,11 +62,515 @@ pub enum Instruction {
     Jmp(Word),
 
     PushLit(u16),
+    PushReg(Register),
     PopReg(Register),
     Call(Word),
     CallRegPtr(Register),
     Ret,
-    Halt(u16),
+    /// Stops the program with a code telling whoever ran it how it went, 0 when it went fine
+    Halt(u8),
     Int(u16),
     Rti,
 }
+
+impl Instruction {
+    pub fn opcode(&self) -> OpCode {
+        match self {
+            Instruction::MovLitReg(..) => OpCode::MovLitReg,
+            Instruction::MovRegReg(..) => OpCode::MovRegReg,
+            Instruction::MovRegMem(..) => OpCode::MovRegMem,
+            Instruction::MovMemReg(..) => OpCode::MovMemReg,
+            Instruction::MovLitMem(..) => OpCode::MovLitMem,
+            Instruction::MovRegPtrReg(..) => OpCode::MovRegPtrReg,
+            Instruction::MovLitRegPtr(..) => OpCode::MovLitRegPtr,
+            Instruction::Mov8LitReg(..) => OpCode::Mov8LitReg,
+            Instruction::Mov8RegReg(..) => OpCode::Mov8RegReg,
+            Instruction::Mov8RegMem(..) => OpCode::Mov8RegMem,
+            Instruction::Mov8MemReg(..) => OpCode::Mov8MemReg,
+            Instruction::Mov8LitMem(..) => OpCode::Mov8LitMem,
+            Instruction::LoadRegPtr(..) => OpCode::LoadRegPtr,
+            Instruction::LoadRegPtrOff(..) => OpCode::LoadRegPtrOff,
+            Instruction::AddRegReg(..) => OpCode::AddRegReg,
+            Instruction::AddLitReg(..) => OpCode::AddLitReg,
+            Instruction::SubRegReg(..) => OpCode::SubRegReg,
+            Instruction::SubLitReg(..) => OpCode::SubLitReg,
+            Instruction::MulRegReg(..) => OpCode::MulRegReg,
+            Instruction::MulLitReg(..) => OpCode::MulLitReg,
+            Instruction::IncReg(..) => OpCode::IncReg,
+            Instruction::DecReg(..) => OpCode::DecReg,
+            Instruction::LshLitReg(..) => OpCode::LshLitReg,
+            Instruction::LshRegReg(..) => OpCode::LshRegReg,
+            Instruction::RshLitReg(..) => OpCode::RshLitReg,
+            Instruction::RshRegReg(..) => OpCode::RshRegReg,
+            Instruction::AndLitReg(..) => OpCode::AndLitReg,
+            Instruction::AndRegReg(..) => OpCode::AndRegReg,
+            Instruction::OrLitReg(..) => OpCode::OrLitReg,
+            Instruction::OrRegReg(..) => OpCode::OrRegReg,
+            Instruction::XorLitReg(..) => OpCode::XorLitReg,
+            Instruction::XorRegReg(..) => OpCode::XorRegReg,
+            Instruction::Not(..) => OpCode::Not,
+            Instruction::JeqLit(..) => OpCode::JeqLit,
+            Instruction::JeqReg(..) => OpCode::JeqReg,
+            Instruction::JgtLit(..) => OpCode::JgtLit,
+            Instruction::JgtReg(..) => OpCode::JgtReg,
+            Instruction::JneLit(..) => OpCode::JneLit,
+            Instruction::JneReg(..) => OpCode::JneReg,
+            Instruction::JgeLit(..) => OpCode::JgeLit,
+            Instruction::JgeReg(..) => OpCode::JgeReg,
+            Instruction::JleLit(..) => OpCode::JleLit,
+            Instruction::JleReg(..) => OpCode::JleReg,
+            Instruction::JltLit(..) => OpCode::JltLit,
+            Instruction::JltReg(..) => OpCode::JltReg,
+            Instruction::Jmp(..) => OpCode::Jmp,
+            Instruction::PushLit(..) => OpCode::PushLit,
+            Instruction::PushReg(..) => OpCode::PushReg,
+            Instruction::PopReg(..) => OpCode::Pop,
+            Instruction::Call(..) => OpCode::Call,
+            Instruction::CallRegPtr(..) => OpCode::CallRegPtr,
+            Instruction::Ret => OpCode::Ret,
+            Instruction::Halt(_) => OpCode::Halt,
+            Instruction::Int(..) => OpCode::Int,
+            Instruction::Rti => OpCode::Rti,
+        }
+    }
+
+    /// Bytes of the instruction as the cpu reads them from memory, starting with its op code.
+    /// Words are little endian.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = vec![u8::from(self.opcode())];
+        let word = |bytes: &mut Vec<u8>, word: u16| bytes.extend(word.to_le_bytes());
+
+        match *self {
+            Instruction::MovLitReg(reg, lit)
+            | Instruction::MovLitRegPtr(reg, lit)
+            | Instruction::AddLitReg(reg, lit)
+            | Instruction::SubLitReg(reg, lit)
+            | Instruction::MulLitReg(reg, lit)
+            | Instruction::LshLitReg(reg, lit)
+            | Instruction::RshLitReg(reg, lit)
+            | Instruction::AndLitReg(reg, lit)
+            | Instruction::OrLitReg(reg, lit)
+            | Instruction::XorLitReg(reg, lit) => {
+                bytes.push(reg.into());
+                word(&mut bytes, lit);
+            }
+            Instruction::MovRegReg(lhs, rhs)
+            | Instruction::MovRegPtrReg(lhs, rhs)
+            | Instruction::LoadRegPtr(lhs, rhs)
+            | Instruction::Mov8RegReg(lhs, rhs)
+            | Instruction::AddRegReg(lhs, rhs)
+            | Instruction::SubRegReg(lhs, rhs)
+            | Instruction::MulRegReg(lhs, rhs)
+            | Instruction::LshRegReg(lhs, rhs)
+            | Instruction::RshRegReg(lhs, rhs)
+            | Instruction::AndRegReg(lhs, rhs)
+            | Instruction::OrRegReg(lhs, rhs)
+            | Instruction::XorRegReg(lhs, rhs) => bytes.extend([u8::from(lhs), u8::from(rhs)]),
+            Instruction::MovRegMem(reg, address) | Instruction::Mov8RegMem(reg, address) => {
+                word(&mut bytes, address.into());
+                bytes.push(reg.into());
+            }
+            Instruction::MovMemReg(address, reg) | Instruction::Mov8MemReg(address, reg) => {
+                bytes.push(reg.into());
+                word(&mut bytes, address.into());
+            }
+            Instruction::MovLitMem(address, lit)
+            | Instruction::JeqLit(address, lit)
+            | Instruction::JgtLit(address, lit)
+            | Instruction::JneLit(address, lit)
+            | Instruction::JgeLit(address, lit)
+            | Instruction::JleLit(address, lit)
+            | Instruction::JltLit(address, lit) => {
+                word(&mut bytes, address.into());
+                word(&mut bytes, lit);
+            }
+            Instruction::LoadRegPtrOff(dst, address, offset) => {
+                bytes.extend([u8::from(dst), u8::from(address)]);
+                word(&mut bytes, offset);
+            }
+            Instruction::Mov8LitReg(reg, lit) => bytes.extend([u8::from(reg), lit]),
+            Instruction::Mov8LitMem(address, lit) => {
+                word(&mut bytes, address.into());
+                bytes.push(lit);
+            }
+            Instruction::JeqReg(address, reg)
+            | Instruction::JgtReg(address, reg)
+            | Instruction::JneReg(address, reg)
+            | Instruction::JgeReg(address, reg)
+            | Instruction::JleReg(address, reg)
+            | Instruction::JltReg(address, reg) => {
+                word(&mut bytes, address.into());
+                bytes.push(reg.into());
+            }
+            Instruction::IncReg(reg)
+            | Instruction::DecReg(reg)
+            | Instruction::Not(reg)
+            | Instruction::PushReg(reg)
+            | Instruction::PopReg(reg)
+            | Instruction::CallRegPtr(reg) => bytes.push(reg.into()),
+            Instruction::Jmp(address) | Instruction::Call(address) => word(&mut bytes, address.into()),
+            Instruction::PushLit(lit) | Instruction::Int(lit) => word(&mut bytes, lit),
+            Instruction::Halt(code) => bytes.push(code),
+            Instruction::Ret | Instruction::Rti => {}
+        }
+
+        bytes
+    }
+
+    /// Decodes the instruction at the start of `bytes`, along with how many bytes it takes.
+    pub fn decode(bytes: &[u8]) -> Result<(Instruction, usize)> {
+        let mut read = 0;
+        let instruction = Instruction::decode_with(|| {
+            let byte = bytes
+                .get(read)
+                .copied()
+                .ok_or(memory::Error::InvalidAddress(u16::try_from(read).unwrap_or(u16::MAX)))?;
+            read += 1;
+            Ok(byte)
+        })?;
+        Ok((instruction, read))
+    }
+
+    /// Decodes an instruction from the bytes handed by `next_byte` one at a time, so the cpu can
+    /// read them straight from memory.
+    pub(crate) fn decode_with(next_byte: impl FnMut() -> Result<u8>) -> Result<Instruction> {
+        let mut decoder = Decoder { next_byte };
+        let instruction = match OpCode::try_from(u16::from(decoder.byte()?))? {
+            OpCode::MovLitReg => Instruction::MovLitReg(decoder.register()?, decoder.word()?),
+            OpCode::MovRegReg => Instruction::MovRegReg(decoder.register()?, decoder.register()?),
+            OpCode::MovRegMem => {
+                let address = decoder.address()?;
+                Instruction::MovRegMem(decoder.register()?, address)
+            }
+            OpCode::MovMemReg => {
+                let reg = decoder.register()?;
+                Instruction::MovMemReg(decoder.address()?, reg)
+            }
+            OpCode::MovLitMem => Instruction::MovLitMem(decoder.address()?, decoder.word()?),
+            OpCode::MovRegPtrReg => Instruction::MovRegPtrReg(decoder.register()?, decoder.register()?),
+            OpCode::MovLitRegPtr => Instruction::MovLitRegPtr(decoder.register()?, decoder.word()?),
+            OpCode::Mov8LitReg => Instruction::Mov8LitReg(decoder.register()?, decoder.byte()?),
+            OpCode::Mov8RegReg => Instruction::Mov8RegReg(decoder.register()?, decoder.register()?),
+            OpCode::Mov8RegMem => {
+                let address = decoder.address()?;
+                Instruction::Mov8RegMem(decoder.register()?, address)
+            }
+            OpCode::Mov8MemReg => {
+                let reg = decoder.register()?;
+                Instruction::Mov8MemReg(decoder.address()?, reg)
+            }
+            OpCode::Mov8LitMem => Instruction::Mov8LitMem(decoder.address()?, decoder.byte()?),
+            OpCode::LoadRegPtr => Instruction::LoadRegPtr(decoder.register()?, decoder.register()?),
+            OpCode::LoadRegPtrOff => {
+                Instruction::LoadRegPtrOff(decoder.register()?, decoder.register()?, decoder.word()?)
+            }
+            OpCode::AddRegReg => Instruction::AddRegReg(decoder.register()?, decoder.register()?),
+            OpCode::AddLitReg => Instruction::AddLitReg(decoder.register()?, decoder.word()?),
+            OpCode::SubRegReg => Instruction::SubRegReg(decoder.register()?, decoder.register()?),
+            OpCode::SubLitReg => Instruction::SubLitReg(decoder.register()?, decoder.word()?),
+            OpCode::MulRegReg => Instruction::MulRegReg(decoder.register()?, decoder.register()?),
+            OpCode::MulLitReg => Instruction::MulLitReg(decoder.register()?, decoder.word()?),
+            OpCode::IncReg => Instruction::IncReg(decoder.register()?),
+            OpCode::DecReg => Instruction::DecReg(decoder.register()?),
+            OpCode::LshRegReg => Instruction::LshRegReg(decoder.register()?, decoder.register()?),
+            OpCode::LshLitReg => Instruction::LshLitReg(decoder.register()?, decoder.word()?),
+            OpCode::RshRegReg => Instruction::RshRegReg(decoder.register()?, decoder.register()?),
+            OpCode::RshLitReg => Instruction::RshLitReg(decoder.register()?, decoder.word()?),
+            OpCode::AndRegReg => Instruction::AndRegReg(decoder.register()?, decoder.register()?),
+            OpCode::AndLitReg => Instruction::AndLitReg(decoder.register()?, decoder.word()?),
+            OpCode::OrRegReg => Instruction::OrRegReg(decoder.register()?, decoder.register()?),
+            OpCode::OrLitReg => Instruction::OrLitReg(decoder.register()?, decoder.word()?),
+            OpCode::XorRegReg => Instruction::XorRegReg(decoder.register()?, decoder.register()?),
+            OpCode::XorLitReg => Instruction::XorLitReg(decoder.register()?, decoder.word()?),
+            OpCode::Not => Instruction::Not(decoder.register()?),
+            OpCode::PushReg => Instruction::PushReg(decoder.register()?),
+            OpCode::PushLit => Instruction::PushLit(decoder.word()?),
+            OpCode::Pop => Instruction::PopReg(decoder.register()?),
+            OpCode::Call => Instruction::Call(decoder.address()?),
+            OpCode::CallRegPtr => Instruction::CallRegPtr(decoder.register()?),
+            OpCode::Ret => Instruction::Ret,
+            OpCode::JeqReg => Instruction::JeqReg(decoder.address()?, decoder.register()?),
+            OpCode::JeqLit => Instruction::JeqLit(decoder.address()?, decoder.word()?),
+            OpCode::JgtReg => Instruction::JgtReg(decoder.address()?, decoder.register()?),
+            OpCode::JgtLit => Instruction::JgtLit(decoder.address()?, decoder.word()?),
+            OpCode::JneReg => Instruction::JneReg(decoder.address()?, decoder.register()?),
+            OpCode::JneLit => Instruction::JneLit(decoder.address()?, decoder.word()?),
+            OpCode::JgeReg => Instruction::JgeReg(decoder.address()?, decoder.register()?),
+            OpCode::JgeLit => Instruction::JgeLit(decoder.address()?, decoder.word()?),
+            OpCode::JleReg => Instruction::JleReg(decoder.address()?, decoder.register()?),
+            OpCode::JleLit => Instruction::JleLit(decoder.address()?, decoder.word()?),
+            OpCode::JltReg => Instruction::JltReg(decoder.address()?, decoder.register()?),
+            OpCode::JltLit => Instruction::JltLit(decoder.address()?, decoder.word()?),
+            OpCode::Jmp => Instruction::Jmp(decoder.address()?),
+            OpCode::Int => Instruction::Int(decoder.word()?),
+            OpCode::Rti => Instruction::Rti,
+            OpCode::Halt => Instruction::Halt(decoder.byte()?),
+        };
+        Ok(instruction)
+    }
+}
+
+/// Reads the operands of an instruction in the order they are laid out in memory.
+struct Decoder<F> {
+    next_byte: F,
+}
+
+impl<F: FnMut() -> Result<u8>> Decoder<F> {
+    fn byte(&mut self) -> Result<u8> {
+        (self.next_byte)()
+    }
+
+    fn word(&mut self) -> Result<u16> {
+        let lower = self.byte()?;
+        let upper = self.byte()?;
+        Ok(u16::from_le_bytes([lower, upper]))
+    }
+
+    fn address(&mut self) -> Result<Word> {
+        self.word().map(Word::from)
+    }
+
+    fn register(&mut self) -> Result<Register> {
+        Ok(Register::try_from(u16::from(self.byte()?))?)
+    }
+}
+
+/// Writes the op code name followed by the operands in encoding order, registers by name, literals
+/// as `$` prefixed hex, addresses as `&[$0000]` and registers holding addresses as `&[R1]`. Every
+/// instruction formats to a different line, so traces can be compared as text.
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.opcode())?;
+
+        match *self {
+            Instruction::MovLitReg(reg, lit)
+            | Instruction::AddLitReg(reg, lit)
+            | Instruction::SubLitReg(reg, lit)
+            | Instruction::MulLitReg(reg, lit)
+            | Instruction::LshLitReg(reg, lit)
+            | Instruction::RshLitReg(reg, lit)
+            | Instruction::AndLitReg(reg, lit)
+            | Instruction::OrLitReg(reg, lit)
+            | Instruction::XorLitReg(reg, lit) => write!(f, " {reg}, ${lit:04X}"),
+            Instruction::MovLitRegPtr(reg, lit) => write!(f, " &[{reg}], ${lit:04X}"),
+            Instruction::MovRegReg(lhs, rhs)
+            | Instruction::Mov8RegReg(lhs, rhs)
+            | Instruction::AddRegReg(lhs, rhs)
+            | Instruction::SubRegReg(lhs, rhs)
+            | Instruction::MulRegReg(lhs, rhs)
+            | Instruction::LshRegReg(lhs, rhs)
+            | Instruction::RshRegReg(lhs, rhs)
+            | Instruction::AndRegReg(lhs, rhs)
+            | Instruction::OrRegReg(lhs, rhs)
+            | Instruction::XorRegReg(lhs, rhs) => write!(f, " {lhs}, {rhs}"),
+            Instruction::MovRegPtrReg(address, reg) => write!(f, " &[{address}], {reg}"),
+            Instruction::LoadRegPtr(reg, address) => write!(f, " {reg}, &[{address}]"),
+            Instruction::LoadRegPtrOff(reg, address, offset) => write!(f, " {reg}, &[{address} + ${offset:04X}]"),
+            Instruction::MovRegMem(reg, address) | Instruction::Mov8RegMem(reg, address) => {
+                write!(f, " {reg}, &[${address:04X}]")
+            }
+            Instruction::MovMemReg(address, reg) | Instruction::Mov8MemReg(address, reg) => {
+                write!(f, " &[${address:04X}], {reg}")
+            }
+            Instruction::MovLitMem(address, lit)
+            | Instruction::JeqLit(address, lit)
+            | Instruction::JgtLit(address, lit)
+            | Instruction::JneLit(address, lit)
+            | Instruction::JgeLit(address, lit)
+            | Instruction::JleLit(address, lit)
+            | Instruction::JltLit(address, lit) => write!(f, " &[${address:04X}], ${lit:04X}"),
+            Instruction::Mov8LitReg(reg, lit) => write!(f, " {reg}, ${lit:02X}"),
+            Instruction::Mov8LitMem(address, lit) => write!(f, " &[${address:04X}], ${lit:02X}"),
+            Instruction::JeqReg(address, reg)
+            | Instruction::JgtReg(address, reg)
+            | Instruction::JneReg(address, reg)
+            | Instruction::JgeReg(address, reg)
+            | Instruction::JleReg(address, reg)
+            | Instruction::JltReg(address, reg) => write!(f, " &[${address:04X}], {reg}"),
+            Instruction::IncReg(reg) | Instruction::DecReg(reg) | Instruction::Not(reg) => write!(f, " {reg}"),
+            Instruction::PushReg(reg) | Instruction::PopReg(reg) => write!(f, " {reg}"),
+            Instruction::CallRegPtr(reg) => write!(f, " &[{reg}]"),
+            Instruction::Jmp(address) | Instruction::Call(address) => write!(f, " &[${address:04X}]"),
+            Instruction::PushLit(lit) | Instruction::Int(lit) => write!(f, " ${lit:04X}"),
+            Instruction::Halt(code) => write!(f, " ${code:02X}"),
+            Instruction::Ret | Instruction::Rti => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every instruction the cpu can decode, built with each of the given registers and literals
+    /// on every operand.
+    fn instructions(registers: &[Register], literals: &[u16]) -> Vec<Instruction> {
+        let mut instructions = vec![Instruction::Ret, Instruction::Rti];
+
+        for &reg in registers {
+            instructions.extend([
+                Instruction::IncReg(reg),
+                Instruction::DecReg(reg),
+                Instruction::Not(reg),
+                Instruction::PushReg(reg),
+                Instruction::PopReg(reg),
+                Instruction::CallRegPtr(reg),
+            ]);
+
+            for &other in registers {
+                instructions.extend([
+                    Instruction::MovRegReg(reg, other),
+                    Instruction::MovRegPtrReg(reg, other),
+                    Instruction::LoadRegPtr(reg, other),
+                    Instruction::Mov8RegReg(reg, other),
+                    Instruction::AddRegReg(reg, other),
+                    Instruction::SubRegReg(reg, other),
+                    Instruction::MulRegReg(reg, other),
+                    Instruction::LshRegReg(reg, other),
+                    Instruction::RshRegReg(reg, other),
+                    Instruction::AndRegReg(reg, other),
+                    Instruction::OrRegReg(reg, other),
+                    Instruction::XorRegReg(reg, other),
+                ]);
+            }
+
+            for &lit in literals {
+                instructions.extend(
+                    registers
+                        .iter()
+                        .map(|&other| Instruction::LoadRegPtrOff(reg, other, lit)),
+                );
+
+                let address = Word::from(lit);
+                let byte = lit.to_le_bytes()[0];
+                instructions.extend([
+                    Instruction::MovLitReg(reg, lit),
+                    Instruction::MovRegMem(reg, address),
+                    Instruction::MovMemReg(address, reg),
+                    Instruction::MovLitRegPtr(reg, lit),
+                    Instruction::Mov8LitReg(reg, byte),
+                    Instruction::Mov8RegMem(reg, address),
+                    Instruction::Mov8MemReg(address, reg),
+                    Instruction::AddLitReg(reg, lit),
+                    Instruction::SubLitReg(reg, lit),
+                    Instruction::MulLitReg(reg, lit),
+                    Instruction::LshLitReg(reg, lit),
+                    Instruction::RshLitReg(reg, lit),
+                    Instruction::AndLitReg(reg, lit),
+                    Instruction::OrLitReg(reg, lit),
+                    Instruction::XorLitReg(reg, lit),
+                    Instruction::JeqReg(address, reg),
+                    Instruction::JgtReg(address, reg),
+                    Instruction::JneReg(address, reg),
+                    Instruction::JgeReg(address, reg),
+                    Instruction::JleReg(address, reg),
+                    Instruction::JltReg(address, reg),
+                ]);
+            }
+        }
+
+        for &lit in literals {
+            let address = Word::from(lit);
+            instructions.extend([
+                Instruction::Jmp(address),
+                Instruction::Call(address),
+                Instruction::PushLit(lit),
+                Instruction::Int(lit),
+                Instruction::Halt(lit.to_le_bytes()[0]),
+            ]);
+
+            for &other in literals {
+                instructions.extend([
+                    Instruction::MovLitMem(address, other),
+                    Instruction::Mov8LitMem(address, other.to_le_bytes()[0]),
+                    Instruction::JeqLit(address, other),
+                    Instruction::JgtLit(address, other),
+                    Instruction::JneLit(address, other),
+                    Instruction::JgeLit(address, other),
+                    Instruction::JleLit(address, other),
+                    Instruction::JltLit(address, other),
+                ]);
+            }
+        }
+
+        instructions
+    }
+
+    fn usable_registers() -> Vec<Register> {
+        Register::iter()
+            .filter(|reg| !matches!(reg, Register::SP | Register::FP))
+            .collect()
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            Instruction::MovLitReg(Register::R1, 0xFF).to_string(),
+            "MovLitReg R1, $00FF"
+        );
+        assert_eq!(
+            Instruction::LoadRegPtrOff(Register::R1, Register::R2, 4).to_string(),
+            "LoadRegPtrOff R1, &[R2 + $0004]"
+        );
+        assert_eq!(
+            Instruction::Mov8LitMem(0x677C.into(), 1).to_string(),
+            "Mov8LitMem &[$677C], $01"
+        );
+        assert_eq!(Instruction::Rti.to_string(), "Rti");
+
+        // different instructions never format the same
+        let literals = [0x0000, 0x00FF, 0xC0D3];
+        let mut formatted = std::collections::HashMap::new();
+        for instruction in instructions(&usable_registers(), &literals) {
+            let previous = formatted.insert(instruction.to_string(), instruction);
+            assert!(previous.is_none_or(|previous| previous == instruction), "{instruction}");
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let literals = [0x0000, 0x0001, 0x00FF, 0x0100, 0xC0D3, 0xFFFF];
+
+        for instruction in instructions(&usable_registers(), &literals) {
+            let bytes = instruction.encode();
+            let (decoded, size) = Instruction::decode(&bytes).unwrap();
+            assert_eq!(decoded, instruction);
+            assert_eq!(size, bytes.len(), "{instruction:?}");
+        }
+    }
+
+    #[test]
+    fn test_decode_ignores_trailing_bytes() {
+        let mut bytes = Instruction::MovLitReg(Register::R1, 0xABCD).encode();
+        bytes.extend(Instruction::Halt(0).encode());
+
+        let (instruction, size) = Instruction::decode(&bytes).unwrap();
+        assert_eq!(instruction, Instruction::MovLitReg(Register::R1, 0xABCD));
+        assert_eq!(size, 4);
+        assert_eq!(Instruction::decode(&bytes[size..]).unwrap(), (Instruction::Halt(0), 2));
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        let bytes = Instruction::MovLitReg(Register::R1, 0xABCD).encode();
+        assert!(matches!(
+            Instruction::decode(&bytes[..3]),
+            Err(crate::error::Error::Mem(memory::Error::InvalidAddress(3)))
+        ));
+        assert!(matches!(Instruction::decode(&[]), Err(crate::error::Error::Mem(_))));
+        assert!(matches!(
+            Instruction::decode(&[0x00]),
+            Err(crate::error::Error::OpCode(_))
+        ));
+
+        for reg in [Register::SP, Register::FP] {
+            let bytes = Instruction::IncReg(reg).encode();
+            assert!(matches!(
+                Instruction::decode(&bytes),
+                Err(crate::error::Error::Register(
+                    crate::register::Error::ForbiddenRegister(_)
+                ))
+            ));
+        }
+    }
+}
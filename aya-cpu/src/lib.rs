@@ -4,6 +4,8 @@ pub mod instruction;
 pub mod memory;
 pub mod op_code;
 pub mod register;
+pub mod trace;
 pub mod word;
 
-pub const MEMORY_SIZE: usize = u16::MAX as usize;
+/// Amount of addresses a 16 bit address reaches, no device is larger than this.
+pub const MEMORY_SIZE: usize = u16::MAX as usize + 1;
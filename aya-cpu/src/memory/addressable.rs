@@ -1,7 +1,28 @@
-use super::Result;
+use super::{Error, Result};
 use crate::word::Word;
 
 pub trait Addressable {
+    /// Amount of addresses the device answers to, counting from zero. Never more than
+    /// [`MEMORY_SIZE`](crate::MEMORY_SIZE).
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Checks that `size` bytes starting at `address` fit on the device.
+    fn check_range<W>(&self, address: W, size: usize) -> Result<()>
+    where
+        W: Into<Word> + Copy,
+    {
+        let address = address.into();
+        let len = self.len();
+        match usize::from(address) + size <= len {
+            true => Ok(()),
+            false => Err(Error::OutOfBounds { address, size, len }),
+        }
+    }
+
     fn read<W>(&self, address: W) -> Result<u8>
     where
         W: Into<Word> + Copy;
@@ -10,6 +31,16 @@ pub trait Addressable {
     where
         W: Into<Word> + Copy;
 
+    /// Reads a byte without the side effects a device may have on reads, such as clearing a latch.
+    /// Meant for anything inspecting memory on behalf of the user, like renderers and debuggers,
+    /// devices with read side effects must override it.
+    fn peek<W>(&self, address: W) -> Result<u8>
+    where
+        W: Into<Word> + Copy,
+    {
+        self.read(address)
+    }
+
     fn read_word<W>(&self, address: W) -> Result<u16>
     where
         W: Into<Word> + Copy,
@@ -43,7 +74,9 @@ pub trait Addressable {
         let mut mem = Vec::with_capacity(size);
 
         for _ in 0..size {
-            mem.push(self.read_word(curr)?);
+            let first = self.peek(curr)?;
+            let second = self.peek(curr.next()?)?;
+            mem.push(u16::from_le_bytes([first, second]));
             let Ok(next) = curr.next_word() else {
                 break;
             };
@@ -53,3 +86,61 @@ pub trait Addressable {
         Ok(mem)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    /// Device that counts its reads, like a latch that is acknowledged by reading it.
+    #[derive(Default)]
+    struct Latch {
+        value: u8,
+        reads: Cell<usize>,
+    }
+
+    impl Addressable for Latch {
+        fn len(&self) -> usize {
+            1
+        }
+
+        fn read<W>(&self, _: W) -> Result<u8>
+        where
+            W: Into<Word> + Copy,
+        {
+            self.reads.set(self.reads.get() + 1);
+            Ok(self.value)
+        }
+
+        fn write<W>(&mut self, _: W, byte: impl Into<u8>) -> Result<()>
+        where
+            W: Into<Word> + Copy,
+        {
+            self.value = byte.into();
+            Ok(())
+        }
+
+        fn peek<W>(&self, _: W) -> Result<u8>
+        where
+            W: Into<Word> + Copy,
+        {
+            Ok(self.value)
+        }
+    }
+
+    #[test]
+    fn test_peek_has_no_side_effects() {
+        let mut latch = Latch::default();
+        latch.write(0u16, 0xAB).unwrap();
+
+        assert_eq!(latch.peek(0u16).unwrap(), 0xAB);
+        assert_eq!(latch.reads.get(), 0);
+
+        assert_eq!(latch.read(0u16).unwrap(), 0xAB);
+        assert_eq!(latch.reads.get(), 1);
+
+        latch.inspect_address(0u16, 4).unwrap();
+        assert_eq!(latch.reads.get(), 1);
+    }
+}
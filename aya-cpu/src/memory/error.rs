@@ -6,8 +6,17 @@ use crate::word::Word;
 pub enum Error {
     UnmappedAddress(Word),
     InvalidAddress(u16),
+    WriteProtected(Word),
+    /// `size` bytes starting at `address` go past the `len` bytes of a device
+    OutOfBounds {
+        address: Word,
+        size: usize,
+        len: usize,
+    },
     StackOverflow,
     StackUnderflow,
+    /// A device couldn't write what the program sent to it
+    Output(std::io::Error),
 }
 
 impl fmt::Display for Error {
@@ -15,12 +24,25 @@ impl fmt::Display for Error {
         match self {
             Error::UnmappedAddress(address) => write!(f, "address 0x{address:04X} is not mapped to any region"),
             Error::InvalidAddress(address) => write!(f, "address 0x{address:04X} is out of memory bounds"),
-            Error::StackOverflow => write!(f, "{self:?}"),
-            Error::StackUnderflow => write!(f, "{self:?}"),
+            Error::WriteProtected(address) => write!(f, "address 0x{address:04X} is write protected"),
+            Error::OutOfBounds { address, size, len } => write!(
+                f,
+                "{size} bytes at 0x{address:04X} go past the end of a device of {len} bytes"
+            ),
+            Error::StackOverflow => write!(f, "stack overflow, an address went past 0xFFFF"),
+            Error::StackUnderflow => write!(f, "stack underflow, an address went below 0x0000"),
+            Error::Output(err) => write!(f, "program output couldn't be written, {err}"),
         }
     }
 }
 
-impl std::error::Error for Error {}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Output(err) => Some(err),
+            _ => None,
+        }
+    }
+}
 
 pub type Result<T> = std::result::Result<T, Error>;
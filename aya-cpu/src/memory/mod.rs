@@ -1,5 +1,7 @@
 mod addressable;
 mod error;
+mod output;
 
 pub use addressable::Addressable;
 pub use error::{Error, Result};
+pub use output::{CapturedOutput, OutputMemory, OUTPUT_MEMORY_SIZE};
@@ -0,0 +1,246 @@
+use std::cell::RefCell;
+use std::io::Write;
+use std::rc::Rc;
+
+use super::{Addressable, Error, Result};
+use crate::word::Word;
+
+/// Amount of addresses taken by an [`OutputMemory`] once mapped.
+pub const OUTPUT_MEMORY_SIZE: usize = 2;
+
+/// Lines longer than this are flushed before the program writes a newline
+const OUTPUT_BUFFER_SIZE: usize = 256;
+
+/// Character device for running programs without a console. Every byte written to its first
+/// address is appended to the current line, which is flushed to the sink once the program writes
+/// a newline. Reading the device gives how many bytes were written to it so far as a little endian
+/// word, wrapping around, so programs can tell whether their output went through.
+///
+/// A line the sink fails to take is dropped, and the error is returned by the next write or
+/// [`OutputMemory::flush`].
+pub struct OutputMemory {
+    written: u16,
+    buffer: Vec<u8>,
+    sink: Box<dyn Write>,
+    error: Option<std::io::Error>,
+}
+
+impl OutputMemory {
+    pub fn new(sink: Box<dyn Write>) -> Self {
+        Self {
+            written: 0,
+            buffer: Vec::with_capacity(OUTPUT_BUFFER_SIZE),
+            sink,
+            error: None,
+        }
+    }
+
+    /// Creates a device whose output is kept in memory, so it can be inspected through the
+    /// returned [`CapturedOutput`].
+    pub fn capture() -> (Self, CapturedOutput) {
+        let output = CapturedOutput::default();
+        (Self::new(Box::new(output.clone())), output)
+    }
+
+    /// How many bytes were written to the device so far.
+    pub fn written(&self) -> u16 {
+        self.written
+    }
+
+    /// Writes the current line to the sink without waiting for a newline, returning the error of
+    /// any line that couldn't be written since the last one was returned.
+    pub fn flush(&mut self) -> Result<()> {
+        self.write_line();
+        self.take_error()
+    }
+
+    fn write_line(&mut self) {
+        let result = self.sink.write_all(&self.buffer).and_then(|_| self.sink.flush());
+        // only the first error is kept, the ones after it usually share its cause
+        if let Err(err) = result {
+            self.error.get_or_insert(err);
+        }
+        self.buffer.clear();
+    }
+
+    fn take_error(&mut self) -> Result<()> {
+        match self.error.take() {
+            Some(err) => Err(Error::Output(err)),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Default for OutputMemory {
+    fn default() -> Self {
+        Self::new(Box::new(std::io::stdout()))
+    }
+}
+
+impl std::fmt::Debug for OutputMemory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OutputMemory")
+            .field("written", &self.written)
+            .field("buffer", &self.buffer)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for OutputMemory {
+    fn drop(&mut self) {
+        // a program may halt in the middle of a line, which would otherwise be lost. There is
+        // no one left to return an error to, owners that care call flush before dropping it
+        if !self.buffer.is_empty() {
+            self.write_line();
+        }
+    }
+}
+
+impl Addressable for OutputMemory {
+    fn len(&self) -> usize {
+        OUTPUT_MEMORY_SIZE
+    }
+
+    fn write<W>(&mut self, address: W, byte: impl Into<u8>) -> Result<()>
+    where
+        W: Into<Word> + Copy,
+    {
+        match u16::from(address.into()) {
+            0 => {}
+            // only the first address takes bytes, the upper half of the counter is read only
+            1 => return Err(Error::WriteProtected(address.into())),
+            address => return Err(Error::InvalidAddress(address)),
+        }
+        self.take_error()?;
+
+        let byte = byte.into();
+        self.written = self.written.wrapping_add(1);
+        self.buffer.push(byte);
+
+        if byte == b'\n' || self.buffer.len() >= OUTPUT_BUFFER_SIZE {
+            self.write_line();
+        }
+
+        Ok(())
+    }
+
+    fn read<W>(&self, address: W) -> Result<u8>
+    where
+        W: Into<Word> + Copy,
+    {
+        let address = u16::from(address.into());
+        self.written
+            .to_le_bytes()
+            .get(usize::from(address))
+            .copied()
+            .ok_or(Error::InvalidAddress(address))
+    }
+}
+
+/// Shared handle to the output of a device created with [`OutputMemory::capture`].
+#[derive(Debug, Default, Clone)]
+pub struct CapturedOutput(Rc<RefCell<Vec<u8>>>);
+
+impl CapturedOutput {
+    /// Everything flushed by the device so far.
+    pub fn contents(&self) -> String {
+        String::from_utf8_lossy(&self.0.borrow()).into_owned()
+    }
+}
+
+impl Write for CapturedOutput {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_str(output: &mut OutputMemory, text: &str) {
+        for byte in text.bytes() {
+            output.write(0, byte).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_flush_on_newline() {
+        let (mut device, output) = OutputMemory::capture();
+
+        write_str(&mut device, "hello");
+        assert_eq!(output.contents(), "");
+
+        write_str(&mut device, "\nworld");
+        assert_eq!(output.contents(), "hello\n");
+
+        drop(device);
+        assert_eq!(output.contents(), "hello\nworld");
+    }
+
+    #[test]
+    fn test_flush_full_buffer() {
+        let (mut device, output) = OutputMemory::capture();
+
+        write_str(&mut device, &"a".repeat(OUTPUT_BUFFER_SIZE + 1));
+        assert_eq!(output.contents(), "a".repeat(OUTPUT_BUFFER_SIZE));
+    }
+
+    #[test]
+    fn test_bytes_written() {
+        let (mut device, _output) = OutputMemory::capture();
+        assert_eq!(device.read_word(0).unwrap(), 0);
+
+        write_str(&mut device, &"a".repeat(300));
+        assert_eq!(device.read_word(0).unwrap(), 300);
+        assert_eq!(device.written(), 300);
+
+        device.written = u16::MAX;
+        write_str(&mut device, "a");
+        assert_eq!(device.read_word(0).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_write_errors_are_kept() {
+        struct Broken;
+
+        impl Write for Broken {
+            fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::other("disk full"))
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        // the newline that failed to go through is taken, the error waits for the next write
+        let mut device = OutputMemory::new(Box::new(Broken));
+        write_str(&mut device, "a\n");
+        let err = device.write(0, b'b').unwrap_err();
+        assert_eq!(err.to_string(), "program output couldn't be written, disk full");
+        assert_eq!(device.written(), 2);
+
+        // once returned the error is gone, until another line fails
+        device.write(0, b'b').unwrap();
+        assert!(matches!(device.flush(), Err(Error::Output(_))));
+        assert!(device.flush().is_ok());
+    }
+
+    #[test]
+    fn test_invalid_access() {
+        let (mut device, output) = OutputMemory::capture();
+
+        assert!(matches!(device.write(1, b'a'), Err(Error::WriteProtected(_))));
+        assert!(matches!(device.write(2, b'a'), Err(Error::InvalidAddress(2))));
+        assert!(matches!(device.read(2), Err(Error::InvalidAddress(2))));
+
+        drop(device);
+        assert_eq!(output.contents(), "");
+    }
+}
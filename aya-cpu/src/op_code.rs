@@ -3,6 +3,16 @@ pub enum Error {
     InvalidValue(String),
 }
 
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::InvalidValue(msg) => write!(f, "{msg}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
 type Result = std::result::Result<OpCode, Error>;
 
 macro_rules! op_codes {
@@ -48,6 +58,9 @@ op_codes! {
     Mov8MemReg      = 0x1A,
     Mov8LitMem      = 0x1B,
 
+    LoadRegPtr      = 0x1C,
+    LoadRegPtrOff   = 0x1D,
+
     AddRegReg       = 0x20,
     AddLitReg       = 0x21,
     SubRegReg       = 0x22,
@@ -74,6 +87,7 @@ op_codes! {
     Pop             = 0x42,
     Call            = 0x43,
     Ret             = 0x44,
+    CallRegPtr      = 0x45,
 
     JeqReg          = 0x51,
     JeqLit          = 0x52,
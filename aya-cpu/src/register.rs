@@ -10,7 +10,9 @@ pub enum Error {
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{self:?}")
+        match self {
+            Error::InvalidRegister(msg) | Error::ForbiddenRegister(msg) => write!(f, "{msg}"),
+        }
     }
 }
 
@@ -33,6 +35,8 @@ pub enum Register {
     R8,
     SP,
     FP,
+    /// Interrupt mask, an interrupt is enabled while the bit of its index is set. Every
+    /// interrupt starts enabled
     IM,
 }
 
@@ -118,18 +122,15 @@ impl TryFrom<u16> for Register {
             8 => Ok(Register::R7),
             9 => Ok(Register::R8),
             1 => Ok(Register::IP),
-            11 => Err(Error::ForbiddenRegister(format!(
+            10 => Err(Error::ForbiddenRegister(format!(
                 "access to register {} is forbidden",
                 Register::SP
             ))),
-            12 => Err(Error::ForbiddenRegister(format!(
+            11 => Err(Error::ForbiddenRegister(format!(
                 "access to register {} is forbidden",
                 Register::FP
             ))),
-            13 => Err(Error::ForbiddenRegister(format!(
-                "access to register {} is forbidden",
-                Register::IM
-            ))),
+            12 => Ok(Register::IM),
             v => Err(Error::InvalidRegister(format!(
                 "value 0x{v:04X} is not a valid register number"
             ))),
@@ -145,28 +146,22 @@ impl TryFrom<u8> for Register {
     }
 }
 
+/// Registers are named as they are displayed, in any case, there are no other aliases.
 impl TryFrom<&str> for Register {
     type Error = Error;
 
     fn try_from(value: &str) -> Result<Self> {
-        match value {
-            "acc" | "ACC" => Ok(Self::Acc),
-            "ip" | "IP" => Ok(Self::IP),
-            "r1" | "R1" => Ok(Self::R1),
-            "r2" | "R2" => Ok(Self::R2),
-            "r3" | "R3" => Ok(Self::R3),
-            "r4" | "R4" => Ok(Self::R4),
-            "r5" | "R5" => Ok(Self::R5),
-            "r6" | "R6" => Ok(Self::R6),
-            "r7" | "R7" => Ok(Self::R7),
-            "r8" | "R8" => Ok(Self::R8),
-            "sp" | "SP" => Ok(Self::SP),
-            "fp" | "FP" => Ok(Self::FP),
-            "im" | "IM" => Ok(Self::IM),
-            _ => Err(Error::InvalidRegister(format!(
-                "value '{value}' is not a valid register name"
-            ))),
-        }
+        Register::iter()
+            .find(|register| register.to_string().eq_ignore_ascii_case(value))
+            .ok_or_else(|| {
+                let names = Register::iter()
+                    .map(|register| register.to_string())
+                    .collect::<Vec<_>>();
+                Error::InvalidRegister(format!(
+                    "'{value}' is not a valid register name, expected one of {}",
+                    names.join(", ")
+                ))
+            })
     }
 }
 
@@ -176,16 +171,16 @@ pub struct Registers {
 }
 
 impl Registers {
-    pub(crate) fn new(start_address: impl Into<Word>, stack_address: impl Into<Word>) -> Self {
+    pub(crate) fn new(start_address: impl Into<Word>, stack_top: impl Into<Word>) -> Self {
         let mut registers = Self {
             inner: [0; Register::len()],
         };
-        let stack_address = stack_address.into();
-        registers.inner[Register::FP as usize] = u16::from(stack_address) - 2;
-        registers.inner[Register::SP as usize] = u16::from(stack_address) - 2;
+        let stack_top = stack_top.into();
+        registers.inner[Register::FP as usize] = stack_top.into();
+        registers.inner[Register::SP as usize] = stack_top.into();
         let word = start_address.into();
         registers.inner[Register::IP as usize] = word.into();
-        registers.inner[Register::IM as usize] = 0x0000;
+        registers.inner[Register::IM as usize] = 0xFFFF;
         registers
     }
 
@@ -216,3 +211,28 @@ impl Registers {
         println!("{: <3} @ 0x{:04X}", register, self.fetch(register));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_register_names_round_trip() {
+        for register in Register::iter() {
+            let name = register.to_string();
+            assert_eq!(Register::try_from(name.as_str()).unwrap(), register);
+            assert_eq!(Register::try_from(name.to_lowercase().as_str()).unwrap(), register);
+        }
+        assert_eq!(Register::try_from("Acc").unwrap(), Register::Acc);
+        assert_eq!(Register::iter().count(), Register::len());
+    }
+
+    #[test]
+    fn test_invalid_register_name_lists_valid_ones() {
+        let err = Register::try_from("r9").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "'r9' is not a valid register name, expected one of ACC, IP, R1, R2, R3, R4, R5, R6, R7, R8, SP, FP, IM"
+        );
+    }
+}
@@ -0,0 +1,206 @@
+use std::fmt;
+use std::io::Write;
+
+use crate::cpu::CpuState;
+use crate::instruction::Instruction;
+use crate::register::Register;
+
+/// Writes a line for every instruction the cpu runs, meant to be diffed against the trace of
+/// another run to find where two of them stop behaving the same, such as
+///
+/// `2280 MovLitReg R1, $00FF | IP=2284 R1=00FF`
+///
+/// with the address the instruction was fetched from, the instruction and the value every register
+/// it changed holds after running it, the instruction pointer included. Values are hex numbers.
+pub struct TraceWriter {
+    sink: Box<dyn Write>,
+}
+
+impl TraceWriter {
+    pub fn new(sink: Box<dyn Write>) -> Self {
+        Self { sink }
+    }
+
+    /// Writes the line of `instruction`, fetched from `ip`, given the cpu state before and after
+    /// it ran.
+    pub fn record(
+        &mut self,
+        ip: u16,
+        instruction: &Instruction,
+        before: &CpuState,
+        after: &CpuState,
+    ) -> std::io::Result<()> {
+        write!(self.sink, "{ip:04X} {instruction} |")?;
+        for register in Register::iter() {
+            let index = usize::from(register);
+            if before.registers[index] != after.registers[index] {
+                write!(self.sink, " {register}={:04X}", after.registers[index])?;
+            }
+        }
+        writeln!(self.sink)
+    }
+}
+
+impl fmt::Debug for TraceWriter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TraceWriter").finish_non_exhaustive()
+    }
+}
+
+/// Line two traces disagree on, numbered from one. A trace that ended before the other has no
+/// line there.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    pub line: usize,
+    pub expected: Option<String>,
+    pub found: Option<String>,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let line = |line: &Option<String>| line.clone().unwrap_or_else(|| String::from("the end of the trace"));
+        write!(
+            f,
+            "traces diverge on line {}, expected `{}` but found `{}`",
+            self.line,
+            line(&self.expected),
+            line(&self.found)
+        )
+    }
+}
+
+/// First line where the `found` trace stops matching the `expected` one, `None` when both are the
+/// same.
+pub fn first_divergence(expected: &str, found: &str) -> Option<Divergence> {
+    let (mut expected, mut found) = (expected.lines(), found.lines());
+    let mut line = 0;
+    loop {
+        line += 1;
+        match (expected.next(), found.next()) {
+            (None, None) => return None,
+            (lhs, rhs) if lhs == rhs => {}
+            (lhs, rhs) => {
+                return Some(Divergence {
+                    line,
+                    expected: lhs.map(String::from),
+                    found: rhs.map(String::from),
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cpu::{Cpu, RunOutcome};
+    use crate::memory::{Addressable, CapturedOutput, Result};
+    use crate::word::Word;
+    use crate::MEMORY_SIZE;
+
+    struct Memory(Vec<u8>);
+
+    impl Addressable for Memory {
+        fn len(&self) -> usize {
+            self.0.len()
+        }
+
+        fn read<W>(&self, address: W) -> Result<u8>
+        where
+            W: Into<Word> + Copy,
+        {
+            Ok(self.0[usize::from(address.into())])
+        }
+
+        fn write<W>(&mut self, address: W, byte: impl Into<u8>) -> Result<()>
+        where
+            W: Into<Word> + Copy,
+        {
+            self.0[usize::from(address.into())] = byte.into();
+            Ok(())
+        }
+    }
+
+    fn trace(program: &[Instruction]) -> String {
+        let mut cpu = Cpu::new(Memory(vec![0; MEMORY_SIZE]), 0x0000, 0xFFFE, 0x1000);
+        cpu.load_into_address(program.iter().flat_map(Instruction::encode).collect::<Vec<_>>(), 0u16)
+            .unwrap();
+
+        let output = CapturedOutput::default();
+        cpu.set_trace(Some(TraceWriter::new(Box::new(output.clone()))));
+        assert!(matches!(cpu.run_bounded(100).unwrap(), RunOutcome::Halt { .. }));
+        output.contents()
+    }
+
+    #[test]
+    fn test_trace_lines() {
+        let trace = trace(&[
+            Instruction::MovLitReg(Register::R1, 0x00FF),
+            Instruction::MovRegReg(Register::R2, Register::R1),
+            Instruction::MovRegMem(Register::R2, 0x0100.into()),
+            Instruction::Halt(0),
+        ]);
+
+        assert_eq!(
+            trace,
+            "0000 MovLitReg R1, $00FF | IP=0004 R1=00FF\n\
+             0004 MovRegReg R2, R1 | IP=0007 R2=00FF\n\
+             0007 MovRegMem R2, &[$0100] | IP=000B\n\
+             000B Halt $00 | IP=000D\n"
+        );
+    }
+
+    #[test]
+    fn test_trace_errors_are_returned() {
+        struct Broken;
+
+        impl Write for Broken {
+            fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::other("disk full"))
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let mut cpu = Cpu::new(Memory(vec![0; MEMORY_SIZE]), 0x0000, 0xFFFE, 0x1000);
+        let program = [Instruction::IncReg(Register::R1), Instruction::Halt(0)];
+        cpu.load_into_address(program.iter().flat_map(Instruction::encode).collect::<Vec<_>>(), 0u16)
+            .unwrap();
+        cpu.set_trace(Some(TraceWriter::new(Box::new(Broken))));
+
+        let err = cpu.step().unwrap_err();
+        assert!(matches!(err, crate::error::Error::Trace(_)));
+        assert_eq!(err.to_string(), "trace couldn't be written, disk full");
+        // the instruction ran, and the program goes on without the trace
+        assert_eq!(cpu.registers.fetch(Register::R1), 1);
+        assert!(matches!(cpu.run_bounded(10).unwrap(), RunOutcome::Halt { .. }));
+    }
+
+    #[test]
+    fn test_compare_runs() {
+        let program = [
+            Instruction::MovLitReg(Register::R1, 0x1000),
+            Instruction::MovLitReg(Register::R2, 0xABCD),
+            Instruction::MovRegPtrReg(Register::R1, Register::R2),
+            Instruction::LoadRegPtr(Register::R3, Register::R1),
+            Instruction::Halt(0),
+        ];
+        let expected = trace(&program);
+        assert_eq!(first_divergence(&expected, &trace(&program)), None);
+
+        // storing through the other register writes somewhere else, which the load reads back
+        let mut swapped = program;
+        swapped[2] = Instruction::MovRegPtrReg(Register::R2, Register::R1);
+        let divergence = first_divergence(&expected, &trace(&swapped)).unwrap();
+        assert_eq!(divergence.line, 3);
+
+        let divergence = first_divergence(&expected, &trace(&program[3..])).unwrap();
+        assert_eq!(divergence.line, 1);
+        let divergence = first_divergence(&expected, expected.lines().next().unwrap()).unwrap();
+        assert_eq!(divergence.line, 2);
+        assert_eq!(divergence.found, None);
+        assert!(divergence.to_string().contains("found `the end of the trace`"));
+    }
+}
@@ -0,0 +1,564 @@
+mod lexer;
+mod parser;
+use std::path::{Path, PathBuf};
+
+use lexer::ByteOffset;
+use parser::Key;
+
+use crate::rom::PALETTE_COLORS;
+
+/// Rom file written when the config doesn't name one
+pub const DEFAULT_OUTPUT: &str = "a.out";
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Config {
+    pub code: String,
+    pub sprites: Vec<String>,
+    pub name: String,
+    pub author: Option<String>,
+    pub description: Option<String>,
+    pub output: String,
+    /// Write the expanded code next to the rom, with every import and generated symbol in one module
+    pub expand: bool,
+    /// Where the expanded code is written, `<output>.expanded.aya` when unset
+    pub expand_output: Option<String>,
+    /// Sprite colors must be exactly on the palette instead of being replaced by the nearest one
+    pub strict: bool,
+    /// Identical tiles are packed once, with every sprite using them pointing to the same tile
+    pub dedup: bool,
+    /// Screen sized image sliced into tiles, whose map is copied into background memory before the
+    /// first frame and handed to the code to fill it again
+    pub background: Option<String>,
+    /// Raw file holding the tile of every background cell, copied into background memory before
+    /// the first frame. Can't be used along with `background`, whose map is copied the same way
+    pub background_map: Option<String>,
+    /// Raw file holding the tile of every interface cell, copied into interface memory before the
+    /// first frame
+    pub interface_map: Option<String>,
+    /// Write the symbols of the rom next to it, for debuggers and other tools
+    pub map: bool,
+    /// Compress the sprite section, the console expands it back into tile memory when loading the rom
+    pub compress: bool,
+    /// Code banks, each assembled on its own to run from the bank window once selected
+    pub banks: Vec<String>,
+    /// Raw files loaded at the end of code memory, whose address and size are handed to the code
+    pub data: Vec<String>,
+    /// Colors sprites are quantized to and the console draws the rom with, the console palette
+    /// when unset
+    pub palette: Option<Palette>,
+    /// Directory relative paths are resolved against, the one holding the config file when read
+    /// from one, or empty to resolve them against the current directory
+    pub base_dir: PathBuf,
+    /// Sprite patterns that matched no file, which are warned about when the rom is packed
+    pub unmatched_sprites: Vec<String>,
+}
+
+/// Where the palette of a rom comes from.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Palette {
+    /// Colors written on the config, as RGBA
+    Colors(Vec<[u8; 4]>),
+    /// Image whose palette table holds the colors
+    Image(String),
+}
+
+impl Config {
+    /// Resolves `path` against the config directory, absolute paths are kept as they are.
+    pub fn resolve<P: AsRef<Path>>(&self, path: P) -> PathBuf {
+        self.base_dir.join(path)
+    }
+
+    pub fn code_path(&self) -> PathBuf {
+        self.resolve(&self.code)
+    }
+
+    pub fn sprite_paths(&self) -> impl Iterator<Item = PathBuf> + '_ {
+        self.sprites.iter().map(|sprite| self.resolve(sprite))
+    }
+
+    pub fn bank_paths(&self) -> impl Iterator<Item = PathBuf> + '_ {
+        self.banks.iter().map(|bank| self.resolve(bank))
+    }
+
+    pub fn data_paths(&self) -> impl Iterator<Item = PathBuf> + '_ {
+        self.data.iter().map(|data| self.resolve(data))
+    }
+
+    pub fn background_path(&self) -> Option<PathBuf> {
+        self.background.as_ref().map(|background| self.resolve(background))
+    }
+
+    pub fn background_map_path(&self) -> Option<PathBuf> {
+        self.background_map.as_ref().map(|map| self.resolve(map))
+    }
+
+    pub fn interface_map_path(&self) -> Option<PathBuf> {
+        self.interface_map.as_ref().map(|map| self.resolve(map))
+    }
+
+    pub fn output_path(&self) -> PathBuf {
+        self.resolve(&self.output)
+    }
+
+    pub fn expand_path(&self) -> PathBuf {
+        match &self.expand_output {
+            Some(path) => self.resolve(path),
+            None => {
+                let mut path = self.output_path().into_os_string();
+                path.push(".expanded.aya");
+                path.into()
+            }
+        }
+    }
+
+    pub(crate) fn from_keys(source: &str, keys: Vec<Key>, base_dir: PathBuf) -> miette::Result<Self> {
+        let code = extract_key(&keys, |key| {
+            let Key::Code(offset) = key else {
+                return None;
+            };
+            Some(*offset)
+        })
+        .expect("required keys are checked while parsing");
+        let code = source[std::ops::Range::<usize>::from(code)].to_string();
+
+        let sprites = extract_key(&keys, |key| {
+            let Key::Sprites(offsets) = key else {
+                return None;
+            };
+            Some(offsets.clone())
+        })
+        .unwrap_or_default();
+
+        let (sprites, unmatched_sprites) = expand_sprites(source, &sprites, &base_dir)?;
+
+        let name = extract_key(&keys, |key| {
+            let Key::Name(offset) = key else {
+                return None;
+            };
+            Some(*offset)
+        })
+        .expect("required keys are checked while parsing");
+        let name = source[std::ops::Range::<usize>::from(name)].to_string();
+
+        let output = extract_key(&keys, |key| {
+            let Key::Output(offset) = key else {
+                return None;
+            };
+            Some(*offset)
+        })
+        .map(|offset| source[std::ops::Range::<usize>::from(offset)].to_string())
+        .unwrap_or(String::from(DEFAULT_OUTPUT));
+
+        let author = extract_key(&keys, |key| {
+            let Key::Author(offset) = key else {
+                return None;
+            };
+            Some(*offset)
+        })
+        .map(|offset| source[std::ops::Range::<usize>::from(offset)].to_string());
+
+        let description = extract_key(&keys, |key| {
+            let Key::Description(offset) = key else {
+                return None;
+            };
+            Some(*offset)
+        })
+        .map(|offset| source[std::ops::Range::<usize>::from(offset)].to_string());
+
+        let expand = extract_key(&keys, |key| {
+            let Key::Expand(offset) = key else {
+                return None;
+            };
+            Some(*offset)
+        });
+        let expand = expand
+            .map(|offset| source[std::ops::Range::<usize>::from(offset)].to_string())
+            .map(|val| val == "true")
+            .unwrap_or(false);
+
+        let expand_output = extract_key(&keys, |key| {
+            let Key::ExpandOutput(offset) = key else {
+                return None;
+            };
+            Some(*offset)
+        })
+        .map(|offset| source[std::ops::Range::<usize>::from(offset)].to_string());
+
+        let dedup = extract_key(&keys, |key| {
+            let Key::Dedup(offset) = key else {
+                return None;
+            };
+            Some(*offset)
+        })
+        .is_some_and(|offset| &source[std::ops::Range::<usize>::from(offset)] == "true");
+
+        let background = extract_key(&keys, |key| {
+            let Key::Background(offset) = key else {
+                return None;
+            };
+            Some(*offset)
+        })
+        .map(|offset| source[std::ops::Range::<usize>::from(offset)].to_string());
+
+        let background_map = extract_key(&keys, |key| {
+            let Key::BackgroundMap(offset) = key else {
+                return None;
+            };
+            Some(*offset)
+        });
+        if let (Some(_), Some(offset)) = (&background, background_map) {
+            return Err(parser::bail(
+                source,
+                "[CONFIG_ERROR]: conflicting keys",
+                "the `background` image already fills background memory, remove one of them",
+                offset,
+            ));
+        }
+        let background_map = background_map.map(|offset| source[std::ops::Range::<usize>::from(offset)].to_string());
+
+        let interface_map = extract_key(&keys, |key| {
+            let Key::InterfaceMap(offset) = key else {
+                return None;
+            };
+            Some(*offset)
+        })
+        .map(|offset| source[std::ops::Range::<usize>::from(offset)].to_string());
+
+        let map = extract_key(&keys, |key| {
+            let Key::Map(offset) = key else {
+                return None;
+            };
+            Some(*offset)
+        })
+        .is_some_and(|offset| &source[std::ops::Range::<usize>::from(offset)] == "true");
+
+        let compress = extract_key(&keys, |key| {
+            let Key::Compress(offset) = key else {
+                return None;
+            };
+            Some(*offset)
+        })
+        .is_some_and(|offset| &source[std::ops::Range::<usize>::from(offset)] == "true");
+
+        let banks = extract_key(&keys, |key| {
+            let Key::Banks(offsets) = key else {
+                return None;
+            };
+            Some(offsets.clone())
+        })
+        .unwrap_or_default()
+        .into_iter()
+        .map(|offset| source[std::ops::Range::<usize>::from(offset)].to_string())
+        .collect();
+
+        let data = extract_key(&keys, |key| {
+            let Key::Data(offsets) = key else {
+                return None;
+            };
+            Some(offsets.clone())
+        })
+        .unwrap_or_default()
+        .into_iter()
+        .map(|offset| source[std::ops::Range::<usize>::from(offset)].to_string())
+        .collect();
+
+        let palette = palette_from_keys(source, &keys)?;
+
+        Ok(Self {
+            code,
+            sprites,
+            name,
+            author,
+            description,
+            output,
+            expand,
+            expand_output,
+            strict: false,
+            dedup,
+            background,
+            background_map,
+            interface_map,
+            map,
+            compress,
+            banks,
+            data,
+            palette,
+            base_dir,
+            unmatched_sprites,
+        })
+    }
+}
+
+/// Expands the glob patterns on the sprite list in place, every pattern is replaced by the files it
+/// matches sorted by path, as the sprite order decides their tile indices. Patterns matching no file
+/// are returned next to the list to be warned about, but the list must not end up empty because of
+/// them.
+fn expand_sprites(source: &str, offsets: &[ByteOffset], base_dir: &Path) -> miette::Result<(Vec<String>, Vec<String>)> {
+    let mut sprites = vec![];
+    let mut unmatched = vec![];
+    let mut has_patterns = false;
+
+    for &offset in offsets {
+        let sprite = &source[std::ops::Range::<usize>::from(offset)];
+        if !sprite.contains(['*', '?', '[']) {
+            sprites.push(sprite.to_string());
+            continue;
+        }
+
+        has_patterns = true;
+        let matches = match_glob(sprite, base_dir)
+            .map_err(|reason| parser::bail(source, "[CONFIG_ERROR]: invalid sprite pattern", &reason, offset))?;
+        if matches.is_empty() {
+            unmatched.push(sprite.to_string());
+        }
+        sprites.extend(matches);
+    }
+
+    if has_patterns && sprites.is_empty() {
+        let span = offsets.first().copied().unwrap_or_default();
+        return Err(parser::bail(
+            source,
+            "[CONFIG_ERROR]: no sprites found",
+            "none of the sprite patterns matched a file",
+            span,
+        ));
+    }
+
+    Ok((sprites, unmatched))
+}
+
+fn match_glob(pattern: &str, base_dir: &Path) -> Result<Vec<String>, String> {
+    // the config directory itself may contain glob metacharacters, so only the pattern is left unescaped
+    let full_pattern = match Path::new(pattern).is_absolute() || base_dir.as_os_str().is_empty() {
+        true => pattern.to_string(),
+        false => format!("{}/{pattern}", glob::Pattern::escape(&base_dir.to_string_lossy())),
+    };
+
+    let mut matches = glob::glob(&full_pattern)
+        .map_err(|err| err.msg.to_string())?
+        .filter_map(Result::ok)
+        .filter(|path| path.is_file())
+        .map(|path| match path.strip_prefix(base_dir) {
+            Ok(relative) => relative.to_string_lossy().into_owned(),
+            Err(_) => path.to_string_lossy().into_owned(),
+        })
+        .collect::<Vec<_>>();
+    matches.sort();
+
+    Ok(matches)
+}
+
+/// Reads the palette, either the path of an image or a list of exactly [`PALETTE_COLORS`] colors
+/// written as `#RRGGBB` or `#RRGGBBAA`.
+fn palette_from_keys(source: &str, keys: &[Key]) -> miette::Result<Option<Palette>> {
+    let palette = extract_key(keys, |key| match key {
+        Key::Palette(..) | Key::PaletteImage(_) => Some(key.clone()),
+        _ => None,
+    });
+
+    let (list, offsets) = match palette {
+        Some(Key::Palette(list, offsets)) => (list, offsets),
+        Some(Key::PaletteImage(offset)) => {
+            let path = source[std::ops::Range::<usize>::from(offset)].to_string();
+            return Ok(Some(Palette::Image(path)));
+        }
+        _ => return Ok(None),
+    };
+
+    if offsets.len() != PALETTE_COLORS {
+        let start = std::ops::Range::<usize>::from(list).start;
+        let end = offsets.last().map_or(list, |offset| *offset);
+        return Err(parser::bail(
+            source,
+            "[CONFIG_ERROR]: invalid palette",
+            &format!(
+                "palettes have {PALETTE_COLORS} colors, but this one has {}",
+                offsets.len()
+            ),
+            start..std::ops::Range::<usize>::from(end).end,
+        ));
+    }
+
+    let colors = offsets
+        .iter()
+        .map(|&offset| {
+            parse_color(&source[std::ops::Range::<usize>::from(offset)]).ok_or_else(|| {
+                parser::bail(
+                    source,
+                    "[CONFIG_ERROR]: invalid palette color",
+                    "colors are written as `#RRGGBB`, or `#RRGGBBAA` with alpha",
+                    offset,
+                )
+            })
+        })
+        .collect::<miette::Result<Vec<_>>>()?;
+
+    Ok(Some(Palette::Colors(colors)))
+}
+
+/// Parses a `#RRGGBB` or `#RRGGBBAA` color, colors without alpha are opaque.
+fn parse_color(color: &str) -> Option<[u8; 4]> {
+    let digits = color.strip_prefix('#')?;
+    if !matches!(digits.len(), 6 | 8) || !digits.bytes().all(|digit| digit.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let mut rgba = [0xff; 4];
+    for (channel, idx) in rgba.iter_mut().zip((0..digits.len()).step_by(2)) {
+        *channel = u8::from_str_radix(&digits[idx..idx + 2], 16).ok()?;
+    }
+    Some(rgba)
+}
+
+fn extract_key<T, F: FnMut(&Key) -> Option<T>>(keys: &[Key], f: F) -> Option<T> {
+    keys.iter().find_map(f)
+}
+
+/// Reads the config file at `path`, whose relative paths are resolved against the directory holding it.
+pub fn read_from_file<P: AsRef<Path>>(path: P) -> miette::Result<Config> {
+    let mut handle = std::fs::OpenOptions::new()
+        .read(true)
+        .open(&path)
+        .map_err(|err| miette::miette!("[CONFIG_ERROR]: failed to open {}: {err}", path.as_ref().display()))?;
+    let base_dir = std::path::absolute(&path)
+        .ok()
+        .and_then(|path| path.parent().map(Path::to_path_buf))
+        .unwrap_or_default();
+    decode_config(&mut handle, base_dir)
+}
+
+fn decode_config<R: std::io::Read>(handle: &mut R, base_dir: PathBuf) -> miette::Result<Config> {
+    let mut buffer = String::default();
+    handle
+        .read_to_string(&mut buffer)
+        .map_err(|err| miette::miette!("[CONFIG_ERROR]: failed to read config: {err}"))?;
+
+    let mut lexer = lexer::Lexer::new(&buffer);
+    let mut parser = parser::Parser::new(&buffer, &mut lexer).with_base_dir(base_dir);
+    let config = parser.parse()?;
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_config(dir: &str, source: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("aya.cfg");
+        std::fs::write(&path, source).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_paths_resolve_relative_to_config() {
+        let path = write_config(
+            "aya-packer-relative-config",
+            r#"
+                name = "hello"
+                code = "src/main.aya"
+                output = "build/game.rom"
+                sprites = ["assets/a.bmp", "/absolute/b.bmp"]
+            "#,
+        );
+        let dir = std::path::absolute(path.parent().unwrap()).unwrap();
+
+        // the current directory is the crate root, away from the config file
+        let config = read_from_file(&path).unwrap();
+
+        assert_eq!(config.base_dir, dir);
+        assert_eq!(config.code_path(), dir.join("src/main.aya"));
+        assert_eq!(config.output_path(), dir.join("build/game.rom"));
+        assert_eq!(
+            config.sprite_paths().collect::<Vec<_>>(),
+            [dir.join("assets/a.bmp"), PathBuf::from("/absolute/b.bmp")]
+        );
+    }
+
+    #[test]
+    fn test_paths_without_config_file_are_kept() {
+        let source = r#"
+            name = "hello"
+            code = "src/main.aya"
+            output = "game.rom"
+            sprites = "assets/a.bmp"
+        "#;
+        let config = decode_config(&mut source.as_bytes(), PathBuf::new()).unwrap();
+
+        assert_eq!(config.code_path(), PathBuf::from("src/main.aya"));
+        assert_eq!(config.output_path(), PathBuf::from("game.rom"));
+    }
+
+    #[test]
+    fn test_expand_path() {
+        let source = r#"
+            name = "hello"
+            code = "main.aya"
+            output = "build/game.rom"
+            expand = true
+        "#;
+        let config = decode_config(&mut source.as_bytes(), PathBuf::from("/game")).unwrap();
+        assert_eq!(config.expand_path(), PathBuf::from("/game/build/game.rom.expanded.aya"));
+
+        let source = format!("{source}\nexpand_output = \"debug/expanded.aya\"");
+        let config = decode_config(&mut source.as_bytes(), PathBuf::from("/game")).unwrap();
+        assert_eq!(config.expand_path(), PathBuf::from("/game/debug/expanded.aya"));
+    }
+
+    fn sprite_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(name);
+        std::fs::create_dir_all(dir.join("assets")).unwrap();
+        for file in ["c.bmp", "a.bmp", "b.bmp", "notes.txt"] {
+            std::fs::write(dir.join("assets").join(file), []).unwrap();
+        }
+        dir
+    }
+
+    fn config_with_sprites(dir: &Path, sprites: &str) -> miette::Result<Config> {
+        let source = format!("name = \"hello\"\ncode = \"main.aya\"\noutput = \"a.out\"\nsprites = {sprites}\n");
+        std::fs::write(dir.join("aya.cfg"), source).unwrap();
+        read_from_file(dir.join("aya.cfg"))
+    }
+
+    #[test]
+    fn test_sprite_globs_are_sorted() {
+        let dir = sprite_dir("aya-packer-sorted-globs");
+        let config = config_with_sprites(&dir, r#""assets/*.bmp""#).unwrap();
+        assert_eq!(config.sprites, ["assets/a.bmp", "assets/b.bmp", "assets/c.bmp"]);
+    }
+
+    #[test]
+    fn test_sprite_globs_keep_written_order() {
+        let dir = sprite_dir("aya-packer-mixed-globs");
+        let config = config_with_sprites(
+            &dir,
+            r#"["player.bmp", "assets/[bc].bmp", "assets/missing*.bmp", "assets/a.bmp"]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.sprites,
+            ["player.bmp", "assets/b.bmp", "assets/c.bmp", "assets/a.bmp"]
+        );
+        assert_eq!(config.unmatched_sprites, ["assets/missing*.bmp"]);
+    }
+
+    #[test]
+    fn test_empty_sprite_glob_expansion() {
+        let dir = sprite_dir("aya-packer-empty-globs");
+        let err = config_with_sprites(&dir, r#""assets/*.png""#).unwrap_err();
+        assert_eq!(err.to_string(), "[CONFIG_ERROR]: no sprites found");
+
+        let err = config_with_sprites(&dir, r#""assets/[.bmp""#).unwrap_err();
+        assert_eq!(err.to_string(), "[CONFIG_ERROR]: invalid sprite pattern");
+    }
+
+    #[test]
+    fn test_missing_config() {
+        let path = std::env::temp_dir().join("aya-packer-missing-config/aya.cfg");
+        let err = read_from_file(&path).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with(&format!("[CONFIG_ERROR]: failed to open {}", path.display())));
+    }
+}
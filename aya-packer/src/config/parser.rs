@@ -0,0 +1,690 @@
+use std::path::PathBuf;
+
+use crate::config::lexer::{ByteOffset, Kind, Lexer, TransposeRef};
+use crate::config::Config;
+
+#[derive(Debug)]
+pub struct Parser<'par> {
+    source: &'par str,
+    lexer: &'par mut Lexer<'par>,
+    base_dir: PathBuf,
+}
+
+/// Every key a config may have
+const KEYS: &[&str] = &[
+    "code",
+    "sprites",
+    "name",
+    "author",
+    "description",
+    "output",
+    "expand",
+    "expand_output",
+    "dedup",
+    "background",
+    "background_map",
+    "interface_map",
+    "map",
+    "compress",
+    "banks",
+    "data",
+    "palette",
+];
+
+/// Keys a config must have
+const REQUIRED_KEYS: &[&str] = &["code", "name"];
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+pub enum Key {
+    Code(ByteOffset),
+    Sprites(Vec<ByteOffset>),
+    Name(ByteOffset),
+    Author(ByteOffset),
+    Description(ByteOffset),
+    Output(ByteOffset),
+    Expand(ByteOffset),
+    ExpandOutput(ByteOffset),
+    Dedup(ByteOffset),
+    Background(ByteOffset),
+    BackgroundMap(ByteOffset),
+    InterfaceMap(ByteOffset),
+    Map(ByteOffset),
+    Compress(ByteOffset),
+    Banks(Vec<ByteOffset>),
+    Data(Vec<ByteOffset>),
+    /// Opening bracket of the color list, followed by every color on it
+    Palette(ByteOffset, Vec<ByteOffset>),
+    PaletteImage(ByteOffset),
+}
+
+impl std::fmt::Display for Key {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Key::Code(_) => write!(f, "code"),
+            Key::Sprites(_) => write!(f, "sprites"),
+            Key::Name(_) => write!(f, "name"),
+            Key::Author(_) => write!(f, "author"),
+            Key::Description(_) => write!(f, "description"),
+            Key::Output(_) => write!(f, "output"),
+            Key::Expand(_) => write!(f, "expand"),
+            Key::ExpandOutput(_) => write!(f, "expand_output"),
+            Key::Dedup(_) => write!(f, "dedup"),
+            Key::Background(_) => write!(f, "background"),
+            Key::BackgroundMap(_) => write!(f, "background_map"),
+            Key::InterfaceMap(_) => write!(f, "interface_map"),
+            Key::Map(_) => write!(f, "map"),
+            Key::Compress(_) => write!(f, "compress"),
+            Key::Banks(_) => write!(f, "banks"),
+            Key::Data(_) => write!(f, "data"),
+            Key::Palette(..) | Key::PaletteImage(_) => write!(f, "palette"),
+        }
+    }
+}
+
+impl<'par> Parser<'par> {
+    pub fn new(source: &'par str, lexer: &'par mut Lexer<'par>) -> Self {
+        Self {
+            source,
+            lexer,
+            base_dir: PathBuf::new(),
+        }
+    }
+
+    /// Directory the sprite globs are expanded from, the current directory when unset.
+    pub fn with_base_dir(mut self, base_dir: PathBuf) -> Self {
+        self.base_dir = base_dir;
+        self
+    }
+
+    pub fn parse(&mut self) -> miette::Result<Config> {
+        let mut keys = vec![];
+
+        while self.lexer.peek().is_some() {
+            keys.push(parse_key(self.source, self.lexer)?);
+        }
+
+        let missing = REQUIRED_KEYS
+            .iter()
+            .filter(|required| !keys.iter().any(|key| key.to_string() == **required))
+            .map(|key| format!("`{key}`"))
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            return Err(bail(
+                self.source,
+                "[CONFIG_ERROR]: missing required keys",
+                &format!("add {} to the config", missing.join(", ")),
+                0..0,
+            ));
+        }
+
+        Config::from_keys(self.source, keys, self.base_dir.clone())
+    }
+}
+
+fn parse_key<'par>(source: &'par str, lexer: &mut Lexer<'par>) -> miette::Result<Key> {
+    let Some(token) = lexer.next().transpose()? else {
+        return Err(bail(
+            source,
+            "[SYNTAX_ERROR]: unexpected end of file (EOF)",
+            "expected valid key for config",
+            source.len().saturating_sub(1)..source.len(),
+        ));
+    };
+
+    if token.kind != Kind::Ident {
+        return Err(bail(
+            source,
+            "[SYNTAX_ERROR]: unexpected token",
+            &format!("expected IDENT, found {}", token.kind),
+            token.offset,
+        ));
+    };
+
+    let ident = &source[std::ops::Range::<usize>::from(token.offset)];
+
+    let key = match ident {
+        "sprites" => Key::Sprites(parse_paths_key(source, lexer, ident)?),
+        "banks" => Key::Banks(parse_paths_key(source, lexer, ident)?),
+        "data" => Key::Data(parse_paths_key(source, lexer, ident)?),
+        "palette" => parse_palette(source, lexer, ident)?,
+        "code" => Key::Code(parse_value(source, lexer, ident, Kind::String)?),
+        "output" => Key::Output(parse_value(source, lexer, ident, Kind::String)?),
+        "name" => Key::Name(parse_value(source, lexer, ident, Kind::String)?),
+        "author" => Key::Author(parse_value(source, lexer, ident, Kind::String)?),
+        "description" => Key::Description(parse_value(source, lexer, ident, Kind::String)?),
+        "expand" => Key::Expand(parse_value(source, lexer, ident, Kind::Bool)?),
+        "expand_output" => Key::ExpandOutput(parse_value(source, lexer, ident, Kind::String)?),
+        "dedup" => Key::Dedup(parse_value(source, lexer, ident, Kind::Bool)?),
+        "background" => Key::Background(parse_value(source, lexer, ident, Kind::String)?),
+        "background_map" => Key::BackgroundMap(parse_value(source, lexer, ident, Kind::String)?),
+        "interface_map" => Key::InterfaceMap(parse_value(source, lexer, ident, Kind::String)?),
+        "map" => Key::Map(parse_value(source, lexer, ident, Kind::Bool)?),
+        "compress" => Key::Compress(parse_value(source, lexer, ident, Kind::Bool)?),
+        _ => {
+            let help = match closest_key(ident) {
+                Some(key) => format!("the key '{ident}' is not a valid config key, did you mean `{key}`?"),
+                None => format!("the key '{ident}' is not a valid config key"),
+            };
+            return Err(bail(source, "[SYNTAX_ERROR]: unexpected key", &help, token.offset));
+        }
+    };
+
+    Ok(key)
+}
+
+/// Parses the value of `key`, which must be of `kind`.
+fn parse_value<'par>(source: &'par str, lexer: &mut Lexer<'par>, key: &str, kind: Kind) -> miette::Result<ByteOffset> {
+    lexer.expect(Kind::Equal)?;
+
+    let Some(token) = lexer.next().transpose()? else {
+        return Err(bail(
+            source,
+            "[SYNTAX_ERROR]: unexpected end of file (EOF)",
+            &format!("expected {} for `{key}`", value_name(kind)),
+            source.len().saturating_sub(1)..source.len(),
+        ));
+    };
+
+    if token.kind != kind {
+        return Err(bail(
+            source,
+            "[TYPE_ERROR]: invalid value",
+            &format!("`{key}` expects {}, found {}", value_name(kind), value_name(token.kind)),
+            token.offset,
+        ));
+    }
+
+    Ok(token.offset)
+}
+
+fn value_name(kind: Kind) -> &'static str {
+    match kind {
+        Kind::String => "a string",
+        Kind::Bool => "a boolean",
+        Kind::LeftBracket => "a list",
+        Kind::Ident => "an identifier",
+        Kind::Equal | Kind::Comma | Kind::RightBracket => "a symbol",
+    }
+}
+
+/// The valid key closest to a misspelled one, if any is close enough.
+fn closest_key(ident: &str) -> Option<&'static str> {
+    KEYS.iter()
+        .map(|key| (edit_distance(ident, key), *key))
+        .filter(|(distance, key)| *distance <= key.len() / 3)
+        .min()
+        .map(|(_, key)| key)
+}
+
+fn edit_distance(lhs: &str, rhs: &str) -> usize {
+    let rhs = rhs.chars().collect::<Vec<_>>();
+    let mut previous = (0..=rhs.len()).collect::<Vec<_>>();
+
+    for (i, left) in lhs.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, right) in rhs.iter().enumerate() {
+            let substitution = previous[j] + usize::from(left != *right);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+
+    previous[rhs.len()]
+}
+
+/// Parses the palette, which is either the path of an image or a list of colors.
+fn parse_palette<'par>(source: &'par str, lexer: &mut Lexer<'par>, key: &str) -> miette::Result<Key> {
+    lexer.expect(Kind::Equal)?;
+
+    let Some(token) = lexer.next().transpose()? else {
+        return Err(bail(
+            source,
+            "[SYNTAX_ERROR]: unexpected end of file (EOF)",
+            &format!("expected a path or a list of colors for `{key}`"),
+            source.len().saturating_sub(1)..source.len(),
+        ));
+    };
+
+    match token.kind {
+        Kind::LeftBracket => Ok(Key::Palette(
+            token.offset,
+            parse_paths_array(source, lexer, key, "color")?,
+        )),
+        Kind::String => Ok(Key::PaletteImage(token.offset)),
+        kind => Err(bail(
+            source,
+            "[TYPE_ERROR]: invalid value",
+            &format!("`{key}` expects a string or a list, found {}", value_name(kind)),
+            token.offset,
+        )),
+    }
+}
+
+/// Parses the value of `key`, which is either a single path or a list of them.
+fn parse_paths_key<'par>(source: &'par str, lexer: &mut Lexer<'par>, key: &str) -> miette::Result<Vec<ByteOffset>> {
+    lexer.expect(Kind::Equal)?;
+
+    let Some(token) = lexer.next().transpose()? else {
+        return Err(bail(
+            source,
+            "[SYNTAX_ERROR]: unexpected end of file (EOF)",
+            &format!("expected a path for `{key}`"),
+            source.len().saturating_sub(1)..source.len(),
+        ));
+    };
+
+    let paths = match token.kind {
+        Kind::LeftBracket => parse_paths_array(source, lexer, key, "path")?,
+        Kind::String => vec![token.offset],
+        kind => {
+            return Err(bail(
+                source,
+                "[TYPE_ERROR]: invalid value",
+                &format!("`{key}` expects a string or a list, found {}", value_name(kind)),
+                token.offset,
+            ))
+        }
+    };
+
+    Ok(paths)
+}
+
+/// Parses the strings of a list up to its closing bracket, `item` names what they hold on errors.
+fn parse_paths_array<'par>(
+    source: &'par str,
+    lexer: &mut Lexer<'par>,
+    key: &str,
+    item: &str,
+) -> miette::Result<Vec<ByteOffset>> {
+    let mut offsets = vec![];
+
+    loop {
+        let Ok(Some(token)) = lexer.peek().transpose() else {
+            let Err(err) = lexer.next().transpose() else {
+                return Err(bail(
+                    source,
+                    "[SYNTAX_ERROR]: unexpected end of file (EOF)",
+                    &format!("expected a {item} for `{key}`"),
+                    source.len().saturating_sub(1)..source.len(),
+                ));
+            };
+            return Err(err);
+        };
+
+        let offset = match token.kind {
+            Kind::RightBracket => break,
+            Kind::String => parse_string(lexer)?,
+            _ => {
+                return Err(bail(
+                    source,
+                    "[SYNTAX_ERROR]: unexpected token",
+                    &format!("{item}s on `{key}` must be strings"),
+                    token.offset,
+                ));
+            }
+        };
+
+        let Ok(Some(next)) = lexer.peek().transpose() else {
+            let Err(err) = lexer.next().transpose() else {
+                return Err(bail(
+                    source,
+                    "[SYNTAX_ERROR]: unexpected end of file (EOF)",
+                    &format!("expected a {item} for `{key}`"),
+                    source.len().saturating_sub(1)..source.len(),
+                ));
+            };
+            return Err(err);
+        };
+
+        match next.kind {
+            Kind::RightBracket => {}
+            _ => _ = lexer.expect(Kind::Comma)?,
+        }
+
+        offsets.push(offset)
+    }
+
+    lexer.expect(Kind::RightBracket)?;
+
+    Ok(offsets)
+}
+
+fn parse_string(lexer: &mut Lexer) -> miette::Result<ByteOffset> {
+    let token = lexer.expect(Kind::String)?;
+    Ok(token.offset)
+}
+
+pub(super) fn bail<S: AsRef<str>>(
+    source: &str,
+    message: S,
+    help: S,
+    span: impl Into<miette::SourceSpan>,
+) -> miette::Error {
+    miette::Error::from(
+        miette::MietteDiagnostic::new(message.as_ref())
+            .with_labels(vec![miette::LabeledSpan::at(span, "this bit")])
+            .with_help(help.as_ref()),
+    )
+    .with_source_code(source.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::config::Palette;
+
+    fn make_sut(input: &str) -> Config {
+        let mut lexer = Lexer::new(input);
+        let mut parser = Parser::new(input, &mut lexer);
+        parser.parse().unwrap()
+    }
+
+    #[test]
+    fn test_simple_config() {
+        let input = r#"
+            name = "hello"
+            code = "main.aya"
+            output = "my_game.out"
+            sprites = "assets/spritesheet.bmp"
+        "#;
+        let expected = Config {
+            name: String::from("hello"),
+            author: None,
+            description: None,
+            output: String::from("my_game.out"),
+            code: String::from("main.aya"),
+            sprites: vec![String::from("assets/spritesheet.bmp")],
+            expand: false,
+            expand_output: None,
+            strict: false,
+            dedup: false,
+            background: None,
+            background_map: None,
+            interface_map: None,
+            map: false,
+            compress: false,
+            banks: vec![],
+            data: vec![],
+            palette: None,
+            base_dir: PathBuf::new(),
+            unmatched_sprites: vec![],
+        };
+
+        let config = make_sut(input);
+        assert_eq!(config, expected);
+    }
+
+    #[test]
+    fn test_sprite_array() {
+        let input = r#"
+            code = "main.aya"
+            name = "hello"
+            output = "my_game.out"
+            sprites = [
+                "assets/01.bmp",
+                "assets/02.bmp",
+                "assets/03.bmp",
+            ]
+        "#;
+        let expected = Config {
+            name: String::from("hello"),
+            author: None,
+            description: None,
+            code: String::from("main.aya"),
+            output: String::from("my_game.out"),
+            sprites: vec![
+                String::from("assets/01.bmp"),
+                String::from("assets/02.bmp"),
+                String::from("assets/03.bmp"),
+            ],
+            expand: false,
+            expand_output: None,
+            strict: false,
+            dedup: false,
+            background: None,
+            background_map: None,
+            interface_map: None,
+            map: false,
+            compress: false,
+            banks: vec![],
+            data: vec![],
+            palette: None,
+            base_dir: PathBuf::new(),
+            unmatched_sprites: vec![],
+        };
+
+        let config = make_sut(input);
+
+        assert_eq!(config, expected);
+    }
+
+    #[test]
+    fn test_metadata_keys() {
+        let input = r#"
+            name = "hello"
+            author = "someone"
+            description = "a small game"
+            code = "main.aya"
+            output = "my_game.out"
+            sprites = "assets/spritesheet.bmp"
+        "#;
+
+        let config = make_sut(input);
+        assert_eq!(config.author.as_deref(), Some("someone"));
+        assert_eq!(config.description.as_deref(), Some("a small game"));
+    }
+
+    #[test]
+    fn test_dedup_key() {
+        let input = r#"
+            name = "hello"
+            code = "main.aya"
+            output = "my_game.out"
+            sprites = "assets/spritesheet.bmp"
+            dedup = true
+        "#;
+
+        assert!(make_sut(input).dedup);
+        assert!(!make_sut(&input.replace("dedup = true", "")).dedup);
+        assert!(make_sut(&input.replace("dedup", "map")).map);
+        assert!(make_sut(&input.replace("dedup", "compress")).compress);
+    }
+
+    #[test]
+    fn test_background_key() {
+        let input = r#"
+            name = "hello"
+            code = "main.aya"
+            output = "my_game.out"
+            sprites = "assets/spritesheet.bmp"
+            background = "assets/title.bmp"
+        "#;
+
+        assert_eq!(make_sut(input).background.as_deref(), Some("assets/title.bmp"));
+    }
+
+    #[test]
+    fn test_screen_map_keys() {
+        let input = r#"
+            name = "hello"
+            code = "main.aya"
+            background_map = "maps/title.bin"
+            interface_map = "maps/hud.bin"
+        "#;
+
+        let config = make_sut(input);
+        assert_eq!(config.background_map.as_deref(), Some("maps/title.bin"));
+        assert_eq!(config.interface_map.as_deref(), Some("maps/hud.bin"));
+
+        let (message, help) = parse_error(&format!("{input}\nbackground = \"title.bmp\""));
+        assert_eq!(message, "[CONFIG_ERROR]: conflicting keys");
+        assert_eq!(
+            help,
+            "the `background` image already fills background memory, remove one of them"
+        );
+    }
+
+    #[test]
+    fn test_banks_key() {
+        let input = r#"
+            name = "hello"
+            code = "main.aya"
+            banks = ["levels.aya", "ending.aya"]
+        "#;
+
+        assert_eq!(make_sut(input).banks, ["levels.aya", "ending.aya"]);
+        assert_eq!(
+            make_sut(&input.replace("[\"levels.aya\", \"ending.aya\"]", "\"levels.aya\"")).banks,
+            ["levels.aya"]
+        );
+
+        let (message, help) = parse_error("name = \"hello\"\ncode = \"main.aya\"\nbanks = true");
+        assert_eq!(message, "[TYPE_ERROR]: invalid value");
+        assert_eq!(help, "`banks` expects a string or a list, found a boolean");
+    }
+
+    #[test]
+    fn test_data_key() {
+        let input = r#"
+            name = "hello"
+            code = "main.aya"
+            data = ["levels/level1.bin", "text/intro.bin"]
+        "#;
+
+        assert_eq!(make_sut(input).data, ["levels/level1.bin", "text/intro.bin"]);
+        assert!(make_sut(&input.replace("data", "banks")).data.is_empty());
+    }
+
+    #[test]
+    fn test_palette_key() {
+        let colors = (0..16).map(|idx| format!("\"#{idx:02x}1020\"")).collect::<Vec<_>>();
+        let input = format!(
+            "name = \"hello\"\ncode = \"main.aya\"\npalette = [{}]",
+            colors.join(", ")
+        );
+        let expected = (0..16).map(|idx| [idx, 0x10, 0x20, 0xff]).collect();
+        assert_eq!(make_sut(&input).palette, Some(Palette::Colors(expected)));
+
+        let input = input.replace("#0f1020", "#0f102080");
+        let Some(Palette::Colors(colors)) = make_sut(&input).palette else {
+            panic!("palette colors were not read");
+        };
+        assert_eq!(colors[15], [0x0f, 0x10, 0x20, 0x80]);
+
+        let input = "name = \"hello\"\ncode = \"main.aya\"\npalette = \"palette.bmp\"";
+        assert_eq!(
+            make_sut(input).palette,
+            Some(Palette::Image(String::from("palette.bmp")))
+        );
+    }
+
+    #[test]
+    fn test_invalid_palette() {
+        let input = "name = \"hello\"\ncode = \"main.aya\"\npalette = [\"#000000\", \"#9dc1c0\"]";
+        let (message, help) = parse_error(input);
+        assert_eq!(message, "[CONFIG_ERROR]: invalid palette");
+        assert_eq!(help, "palettes have 16 colors, but this one has 2");
+
+        let colors = ["\"#000000\""; 15].join(", ");
+        for color in ["#00000", "000000", "#00000g", "#+00000"] {
+            let input = format!("name = \"hello\"\ncode = \"main.aya\"\npalette = [{colors}, \"{color}\"]");
+            let (message, _) = parse_error(&input);
+            assert_eq!(message, "[CONFIG_ERROR]: invalid palette color");
+        }
+
+        let (_, help) = parse_error("palette = [\"#000000\", true]");
+        assert_eq!(help, "colors on `palette` must be strings");
+        let (_, help) = parse_error("palette = false");
+        assert_eq!(help, "`palette` expects a string or a list, found a boolean");
+    }
+
+    fn parse_error(input: &str) -> (String, String) {
+        let mut lexer = Lexer::new(input);
+        let err = Parser::new(input, &mut lexer).parse().unwrap_err();
+        let help = err.help().map(|help| help.to_string()).unwrap_or_default();
+        (err.to_string(), help)
+    }
+
+    #[test]
+    fn test_unknown_key_suggestion() {
+        let (message, help) = parse_error("name = \"hello\"\ncode = \"main.aya\"\nsprite = \"a.bmp\"");
+        assert_eq!(message, "[SYNTAX_ERROR]: unexpected key");
+        assert_eq!(
+            help,
+            "the key 'sprite' is not a valid config key, did you mean `sprites`?"
+        );
+
+        let (_, help) = parse_error("colors = \"a.pal\"");
+        assert_eq!(help, "the key 'colors' is not a valid config key");
+    }
+
+    #[test]
+    fn test_value_types() {
+        let (message, help) = parse_error("name = true");
+        assert_eq!(message, "[TYPE_ERROR]: invalid value");
+        assert_eq!(help, "`name` expects a string, found a boolean");
+
+        let (_, help) = parse_error("expand = \"yes\"");
+        assert_eq!(help, "`expand` expects a boolean, found a string");
+
+        let (_, help) = parse_error("code = [\"main.aya\"]");
+        assert_eq!(help, "`code` expects a string, found a list");
+
+        let (_, help) = parse_error("sprites = false");
+        assert_eq!(help, "`sprites` expects a string or a list, found a boolean");
+    }
+
+    #[test]
+    fn test_missing_required_keys() {
+        let (message, help) = parse_error("output = \"game.rom\"");
+        assert_eq!(message, "[CONFIG_ERROR]: missing required keys");
+        assert_eq!(help, "add `code`, `name` to the config");
+
+        let (_, help) = parse_error("name = \"hello\"");
+        assert_eq!(help, "add `code` to the config");
+    }
+
+    #[test]
+    fn test_optional_keys_defaults() {
+        let config = make_sut("name = \"hello\"\ncode = \"main.aya\"");
+        assert_eq!(config.output, "a.out");
+        assert!(config.sprites.is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_syntax_error() {
+        let input = r#"
+            code = "main.aya"
+            name = "hello"
+            output = "my_game.out"
+            sprites = [
+                "assets/01.bmp",
+                "assets/02.bmp"
+                "assets/03.bmp",
+            ]
+        "#;
+
+        make_sut(input);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_invalid_key() {
+        let input = r#"
+            code = "main.aya"
+            output = "my_game.out"
+            sprites = [
+                "assets/01.bmp",
+                "assets/02.bmp",
+                "assets/03.bmp",
+            ]
+            name = "my game"
+            invalid = "key"
+        "#;
+
+        make_sut(input);
+    }
+}
@@ -0,0 +1,96 @@
+use std::path::PathBuf;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Everything that can go wrong while building a rom.
+#[derive(Debug)]
+pub enum Error {
+    /// A file listed on the config couldn't be read
+    Io { path: PathBuf, source: std::io::Error },
+    /// A sprite image couldn't be decoded
+    Bitmap(aya_bitmap::Error),
+    /// Sprites couldn't be packed into tiles, or data files into data sections
+    Sprites(crate::rom::Error),
+    /// The code failed to assemble, the report points at the offending source
+    Assembly(miette::Report),
+    /// The header can't describe the rom, such as when the name is too long
+    Header(aya_rom::RomError),
+    /// A section doesn't fit the console memory it is loaded into
+    SectionTooLarge {
+        section: &'static str,
+        size: usize,
+        max: usize,
+        /// Config entry the section was built from, such as `code = "main.aya"`
+        entry: String,
+    },
+    /// The data files don't fit on code memory along with the code
+    DataTooLarge {
+        size: usize,
+        /// Bytes of code memory left after the code
+        free: usize,
+        /// Config entry the data was built from, such as `data = ["level.bin"]`
+        entry: String,
+    },
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Io { path, source } => write!(f, "{}: {source}", path.display()),
+            Error::Bitmap(err) => write!(f, "{err}"),
+            Error::Sprites(err) => write!(f, "{err}"),
+            Error::Assembly(report) => write!(f, "{report}"),
+            Error::Header(err) => write!(f, "{err}"),
+            Error::SectionTooLarge {
+                section,
+                size,
+                max,
+                entry,
+            } => write!(
+                f,
+                "{section} section takes {size} bytes but only {max} fit on {section} memory, it was built from `{entry}`"
+            ),
+            Error::DataTooLarge { size, free, entry } => write!(
+                f,
+                "data takes {size} bytes but only {free} are left on code memory after the code, it was built from `{entry}`"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            Error::Bitmap(err) => Some(err),
+            Error::Sprites(err) => Some(err),
+            Error::Assembly(_) => None,
+            Error::Header(err) => Some(err),
+            Error::SectionTooLarge { .. } | Error::DataTooLarge { .. } => None,
+        }
+    }
+}
+
+impl From<aya_bitmap::Error> for Error {
+    fn from(err: aya_bitmap::Error) -> Self {
+        Self::Bitmap(err)
+    }
+}
+
+impl From<crate::rom::Error> for Error {
+    fn from(err: crate::rom::Error) -> Self {
+        Self::Sprites(err)
+    }
+}
+
+impl From<miette::Report> for Error {
+    fn from(report: miette::Report) -> Self {
+        Self::Assembly(report)
+    }
+}
+
+impl From<aya_rom::RomError> for Error {
+    fn from(err: aya_rom::RomError) -> Self {
+        Self::Header(err)
+    }
+}
@@ -0,0 +1,790 @@
+pub mod config;
+mod error;
+mod report;
+pub mod rom;
+
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+
+use aya_assembly::symbols::{Symbol, SymbolMap};
+use aya_assembly::{AssembleBehavior, AssembleOutput};
+use aya_console::memory::{data_addresses, MemoryMap, RegionKind, BANK_MEM_LOC, CODE_MEMORY, CODE_MEM_LOC};
+use aya_rom::Rom;
+pub use config::Config;
+use config::Palette;
+pub use error::{Error, Result};
+pub use report::{Report, SectionUsage, UnusedSprite};
+use rom::{CompiledSprites, DataAsset, SpriteSource};
+
+/// Label execution starts at, code without it starts from its first byte
+const ENTRY_LABEL: &str = "start";
+
+/// Sprites and background packed into tiles, and the data files.
+struct Assets {
+    sprites: Vec<SpriteSource>,
+    compiled: CompiledSprites,
+    /// Tile of every background cell, when the config has a background
+    background: Option<Vec<u8>>,
+    /// Contents of the background map file, when the config has one
+    background_map: Option<Vec<u8>>,
+    /// Contents of the interface map file, when the config has one
+    interface_map: Option<Vec<u8>>,
+    /// Colors written on the rom as RGBA, when the config has a palette
+    palette: Option<Vec<[u8; 4]>>,
+    data: Vec<DataAsset>,
+}
+
+/// A rom built from a config, along with what the artist may want to know about it.
+#[derive(Debug)]
+pub struct Packed {
+    pub rom: Vec<u8>,
+    /// Sprite pixels that weren't on the palette and were replaced by the nearest palette color
+    pub approximated: usize,
+    /// Duplicated tiles left out of the rom, background tiles are always deduplicated while sprite
+    /// ones only when asked to
+    pub saved_tiles: usize,
+    /// Labels and data blocks of the code at their address on console memory
+    pub symbols: SymbolMap,
+    pub report: Report,
+    /// Problems that don't stop the rom from building, such as imports placing their module
+    /// outside of the memory the code runs from
+    pub warnings: Vec<miette::Report>,
+}
+
+/// Builds the rom described by `config`.
+pub fn build_rom(config: &Config) -> Result<Vec<u8>> {
+    pack(config).map(|packed| packed.rom)
+}
+
+/// Builds the rom described by `config`, reporting sprite colors that had to be approximated.
+pub fn pack(config: &Config) -> Result<Packed> {
+    let assets = load_assets(config)?;
+    let (path, code) = read_code(config, &assets)?;
+    let mut referenced = aya_assembly::referenced_names(code.clone(), &path)?;
+    let mut warnings = misplaced_imports(code.clone(), &path, RegionKind::Code)?;
+    warnings.extend(aya_assembly::warnings(code.clone(), &path)?);
+    warnings.extend(config.unmatched_sprites.iter().map(|pattern| {
+        miette::miette!(
+            severity = miette::Severity::Warning,
+            help = "check that the pattern is relative to the config file",
+            "sprite pattern `{pattern}` matched no files"
+        )
+    }));
+    let (code, symbols) = aya_assembly::assemble_with_symbols(code, &path)?;
+    let mut sections = vec![check_size(
+        "code",
+        RegionKind::Code,
+        code.len(),
+        format!("code = {:?}", config.code),
+    )?];
+    let entry_point = entry_point(&path, &symbols);
+    let mut symbols = symbols.iter().cloned().collect::<Vec<_>>();
+
+    // banks run from the bank window, so they are assembled as if code memory continued up to it
+    let mut banks = vec![];
+    for (name, path) in config.banks.iter().zip(config.bank_paths()) {
+        let code = read_bank(&path, &assets)?;
+        referenced.extend(aya_assembly::referenced_names(code.clone(), &path)?);
+        warnings.extend(misplaced_imports(code.clone(), &path, RegionKind::Bank)?);
+        warnings.extend(aya_assembly::warnings(code.clone(), &path)?);
+        let (bank, bank_symbols) = aya_assembly::assemble_at(code, &path, BANK_MEM_LOC.0 - CODE_MEM_LOC.0)?;
+        sections.push(check_size(
+            "bank",
+            RegionKind::Bank,
+            bank.len(),
+            format!("banks = {name:?}"),
+        )?);
+        symbols.extend(bank_symbols.iter().cloned());
+        banks.push(bank);
+    }
+
+    // data sits at the end of code memory, so it only fits on what the code leaves free
+    let data_size = assets.data.iter().map(|asset| asset.bytes.len()).sum::<usize>();
+    if !config.data.is_empty() {
+        let free = CODE_MEMORY.saturating_sub(code.len());
+        let entry = format!("data = {:?}", config.data);
+        if data_size > free {
+            return Err(Error::DataTooLarge {
+                size: data_size,
+                free,
+                entry,
+            });
+        }
+        sections.push(SectionUsage {
+            section: "data",
+            size: data_size,
+            max: free,
+            entry,
+        });
+    }
+
+    let compiled = assets.compiled;
+    // sprites are loaded expanded, so their size before compression is the one that must fit
+    let sprite_entry = match &config.background {
+        Some(background) => format!("sprites = {:?}, background = {background:?}", config.sprites),
+        None => format!("sprites = {:?}", config.sprites),
+    };
+    sections.push(check_size(
+        "sprite",
+        RegionKind::Tiles,
+        compiled.data.len(),
+        sprite_entry,
+    )?);
+
+    // the background map is copied into background memory before the first frame, whether it was
+    // compiled from the background image or read from a file
+    let background = assets.background.as_deref().or(assets.background_map.as_deref());
+    if let Some(map) = background {
+        let entry = match &config.background {
+            Some(image) => format!("background = {image:?}"),
+            None => format!(
+                "background_map = {:?}",
+                config.background_map.as_deref().unwrap_or_default()
+            ),
+        };
+        sections.push(check_size("background", RegionKind::Background, map.len(), entry)?);
+    }
+    if let (Some(map), Some(file)) = (&assets.interface_map, &config.interface_map) {
+        let entry = format!("interface_map = {file:?}");
+        sections.push(check_size("interface", RegionKind::Interface, map.len(), entry)?);
+    }
+
+    let palette = assets.palette.map(|colors| colors.concat());
+    let rom = Rom {
+        version: aya_rom::VERSION,
+        name: &config.name,
+        author: config.author.as_deref(),
+        description: config.description.as_deref(),
+        entry_point,
+        cycles_per_frame: aya_console::CLOCK_CYCLE as u32,
+        palette: palette.as_deref(),
+        code: &code,
+        sprites: Cow::Borrowed(&compiled.data),
+        compressed_sprites: config.compress,
+        banks: banks.iter().map(Vec::as_slice).collect(),
+        data: assets.data.iter().map(|asset| asset.bytes.as_slice()).collect(),
+        background,
+        interface: assets.interface_map.as_deref(),
+    };
+
+    Ok(Packed {
+        rom: rom.to_bytes()?,
+        approximated: compiled.approximated,
+        saved_tiles: compiled.saved_tiles,
+        symbols: console_symbols(config, SymbolMap::new(symbols)),
+        report: Report {
+            sections,
+            unused_sprites: report::unused_sprites(&assets.sprites, &referenced),
+        },
+        warnings,
+    })
+}
+
+/// Expands the code described by `config` into a single module, with every import resolved and
+/// every constant generated for named sprites and the background.
+pub fn expand(config: &Config) -> Result<String> {
+    let assets = load_assets(config)?;
+    let (path, code) = read_code(config, &assets)?;
+    let AssembleOutput::Codegen(code) = aya_assembly::assemble_code(code, AssembleBehavior::Codegen, &path)? else {
+        unreachable!();
+    };
+    Ok(code)
+}
+
+fn load_assets(config: &Config) -> Result<Assets> {
+    let mut sprites = vec![];
+    for path in config.sprite_paths() {
+        // errors name the absolute path so it is clear where the sprite was looked for
+        let path = std::path::absolute(&path).unwrap_or(path);
+        let bitmap = aya_bitmap::decode(&path)?;
+        sprites.extend(rom::split_sheet(&path, bitmap)?);
+    }
+
+    // sprites are quantized to the colors the rom is drawn with, so they can't disagree
+    let palette = match &config.palette {
+        Some(Palette::Colors(colors)) => Some(colors.clone()),
+        Some(Palette::Image(file)) => {
+            let bitmap = aya_bitmap::decode(config.resolve(file))?;
+            Some(rom::image_palette(&bitmap, file)?)
+        }
+        None => None,
+    };
+    let colors = rom::quantization_palette(palette.as_deref());
+
+    let tolerance = if config.strict { 0 } else { rom::COLOR_TOLERANCE };
+    let mut compiled = rom::compile_sprites(&sprites, &colors, tolerance, config.dedup)?;
+
+    let background = match config.background_path() {
+        Some(path) => {
+            let path = std::path::absolute(&path).unwrap_or(path);
+            let bitmap = aya_bitmap::decode(&path)?;
+            Some(rom::compile_background(&bitmap, &colors, tolerance, &mut compiled)?)
+        }
+        None => None,
+    };
+
+    let background_map = config.background_map_path().map(|path| read_file(&path)).transpose()?;
+    let interface_map = config.interface_map_path().map(|path| read_file(&path)).transpose()?;
+
+    let mut files = vec![];
+    for path in config.data_paths() {
+        let bytes = read_file(&path)?;
+        files.push((path, bytes));
+    }
+    // data that doesn't fit on code memory is rejected by `pack` once the code size is known
+    let addresses =
+        data_addresses(files.iter().map(|(_, bytes)| bytes.len())).unwrap_or_else(|| vec![CODE_MEM_LOC.0; files.len()]);
+    let data = rom::data_assets(files, &addresses)?;
+
+    Ok(Assets {
+        sprites,
+        compiled,
+        background,
+        background_map,
+        interface_map,
+        palette,
+        data,
+    })
+}
+
+fn read_file(path: &Path) -> Result<Vec<u8>> {
+    std::fs::read(path).map_err(|source| Error::Io {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+/// Reads the entry module of the code, with the symbols generated for the assets appended.
+fn read_code(config: &Config, assets: &Assets) -> Result<(PathBuf, String)> {
+    let path = config.code_path();
+    let code = read_source(&path)?;
+
+    // named sprites, data files and the background map are exposed to the code through symbols
+    // appended to the entry module
+    let mut constants = rom::sprite_constants(&assets.sprites, &assets.compiled.first_tiles);
+    constants.push_str(&rom::data_constants(&assets.data));
+    if let Some(map) = &assets.background {
+        constants.push_str(&rom::background_data(map));
+    }
+    let code = match constants.is_empty() {
+        true => code,
+        false => format!("{code}\n{constants}"),
+    };
+
+    Ok((path, code))
+}
+
+/// Reads the entry module of a bank, banks see the sprite and data constants but not the background
+/// map, whose data only needs to live in the main code.
+fn read_bank(path: &Path, assets: &Assets) -> Result<String> {
+    let code = read_source(path)?;
+    let mut constants = rom::sprite_constants(&assets.sprites, &assets.compiled.first_tiles);
+    constants.push_str(&rom::data_constants(&assets.data));
+    match constants.is_empty() {
+        true => Ok(code),
+        false => Ok(format!("{code}\n{constants}")),
+    }
+}
+
+fn read_source(path: &Path) -> Result<String> {
+    std::fs::read_to_string(path).map_err(|source| Error::Io {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+/// Checks that a section of `size` bytes fits the console memory region it is loaded into.
+fn check_size(section: &'static str, region: RegionKind, size: usize, entry: String) -> Result<SectionUsage> {
+    let max = MemoryMap::default().region(region).len();
+    match size > max {
+        true => Err(Error::SectionTooLarge {
+            section,
+            size,
+            max,
+            entry,
+        }),
+        false => Ok(SectionUsage {
+            section,
+            size,
+            max,
+            entry,
+        }),
+    }
+}
+
+/// Warns about imports placing their module outside of `region`, the memory the code is loaded
+/// into, as the console would never run it from there.
+fn misplaced_imports(code: String, path: &Path, region: RegionKind) -> Result<Vec<miette::Report>> {
+    let map = MemoryMap::default();
+    let expected = map.region(region);
+    // placements are offsets from the start of code memory, like every label
+    let first = expected.start - CODE_MEM_LOC.0;
+    let last = expected.end - CODE_MEM_LOC.0;
+    let help = format!(
+        "{} memory goes from ${:04X} to ${:04X}, so modules of this section must be placed from &[${first:04X}] to &[${last:04X}]",
+        expected.name, expected.start, expected.end
+    );
+
+    let warnings = aya_assembly::import_placements(code, path)?
+        .into_iter()
+        .filter_map(|placement| {
+            let address = CODE_MEM_LOC.0.checked_add(placement.address);
+            let found = match address.map(|address| (address, map.region_for(address))) {
+                Some((_, Some(found))) if found.kind == region => return None,
+                Some((address, Some(found))) => format!("at ${address:04X}, on {} memory", found.name),
+                Some((address, None)) => format!("at ${address:04X}, where no memory is mapped"),
+                None => String::from("past the end of console memory"),
+            };
+            let message = format!("module `{}` is placed {found}", placement.name);
+            Some(placement.warning(&message, &help))
+        })
+        .collect();
+
+    Ok(warnings)
+}
+
+/// Offset of the entry label of the entry module from the start of the code.
+fn entry_point(path: &Path, symbols: &SymbolMap) -> u16 {
+    let module = path.display().to_string();
+    symbols
+        .iter()
+        .find(|symbol| symbol.name == ENTRY_LABEL && symbol.module == module)
+        .map_or(0, |symbol| symbol.address)
+}
+
+/// Moves the symbols to where code is loaded on console memory, naming modules relative to the
+/// config directory.
+fn console_symbols(config: &Config, symbols: SymbolMap) -> SymbolMap {
+    let base_dir = std::path::absolute(&config.base_dir).unwrap_or_default();
+    SymbolMap::new(
+        symbols
+            .iter()
+            .map(|symbol| {
+                let module = Path::new(&symbol.module);
+                let module = module.strip_prefix(&base_dir).unwrap_or(module);
+                Symbol {
+                    address: CODE_MEM_LOC.0.wrapping_add(symbol.address),
+                    name: symbol.name.clone(),
+                    module: module.display().to_string(),
+                }
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A project on a temporary directory of its own, removed once the test is done with it.
+    struct Project {
+        dir: PathBuf,
+    }
+
+    impl Project {
+        /// Creates an empty project, the directory is named after `name` and made unique across
+        /// concurrent test runs.
+        fn new(name: &str) -> Self {
+            static COUNT: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+            let count = COUNT.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            let dir = std::env::temp_dir().join(format!("aya-packer-{name}-{}-{count}", std::process::id()));
+            std::fs::create_dir_all(&dir).unwrap();
+            Self { dir }
+        }
+
+        fn path(&self, file: &str) -> PathBuf {
+            self.dir.join(file)
+        }
+
+        /// Writes `file` along with the directories holding it.
+        fn write(&self, file: &str, contents: impl AsRef<[u8]>) {
+            let path = self.path(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, contents).unwrap();
+        }
+
+        /// Reads the `aya.cfg` of the project.
+        fn config(&self) -> Config {
+            config::read_from_file(self.path("aya.cfg")).unwrap()
+        }
+    }
+
+    impl Drop for Project {
+        fn drop(&mut self) {
+            _ = std::fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    #[test]
+    fn test_missing_sprite_names_absolute_path() {
+        let project = Project::new("missing-sprite");
+        project.write("main.aya", "hlt\n");
+        project.write(
+            "aya.cfg",
+            "name = \"hello\"\ncode = \"main.aya\"\noutput = \"game.rom\"\nsprites = \"assets/missing.bmp\"\n",
+        );
+
+        let config = project.config();
+        let err = build_rom(&config).unwrap_err();
+
+        let expected = std::path::absolute(project.path("assets/missing.bmp")).unwrap();
+        assert!(
+            err.to_string().starts_with(&format!("{}: ", expected.display())),
+            "{err}"
+        );
+
+        // the io error stays reachable through the source chain
+        let io = std::iter::successors(std::error::Error::source(&err), |err| err.source())
+            .find_map(|err| err.downcast_ref::<std::io::Error>())
+            .unwrap();
+        assert_eq!(io.kind(), std::io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_builds_are_reproducible() {
+        let config = concat!(env!("CARGO_MANIFEST_DIR"), "/../samples/aya.cfg");
+        let config = config::read_from_file(config).unwrap();
+
+        // every hash map is seeded differently, so any ordering leaking from one shows up here
+        let rom = build_rom(&config).unwrap();
+        for _ in 0..4 {
+            assert_eq!(build_rom(&config).unwrap(), rom);
+        }
+
+        let expanded = expand(&config).unwrap();
+        assert_eq!(expand(&config).unwrap(), expanded);
+    }
+
+    #[test]
+    fn test_symbols_are_on_console_memory() {
+        let config = concat!(env!("CARGO_MANIFEST_DIR"), "/../samples/aya.cfg");
+        let config = config::read_from_file(config).unwrap();
+
+        let symbols = pack(&config).unwrap().symbols;
+        let start = symbols.iter().find(|symbol| symbol.name == "start").unwrap();
+
+        assert_eq!(start.address, CODE_MEM_LOC.0);
+        assert_eq!(start.module, "main.s");
+    }
+
+    #[test]
+    fn test_banks_run_from_bank_window() {
+        let project = Project::new("banks");
+        project.write("main.aya", "start:\nmov r1, $0202\n");
+        project.write("bank.aya", "bank_start:\nmov r1, $0101\n");
+        project.write(
+            "aya.cfg",
+            "name = \"hello\"\ncode = \"main.aya\"\nbanks = [\"bank.aya\"]\n",
+        );
+
+        let config = project.config();
+        let packed = pack(&config).unwrap();
+        let rom = Rom::from_bytes(&packed.rom, true).unwrap();
+
+        assert_eq!(rom.code, [0x11, 0x02, 0x02, 0x02]);
+        assert_eq!(rom.banks, [[0x11, 0x02, 0x01, 0x01]]);
+        let bank_start = packed
+            .symbols
+            .iter()
+            .find(|symbol| symbol.name == "bank_start")
+            .unwrap();
+        assert_eq!(bank_start.address, BANK_MEM_LOC.0);
+        assert_eq!(bank_start.module, "bank.aya");
+    }
+
+    #[test]
+    fn test_data_is_packed_with_its_constants() {
+        let project = Project::new("data");
+        project.write(
+            "main.aya",
+            "mov r1, !ASSET_LEVEL1_ADDR\nmov r2, !ASSET_INTRO_LEN\nhlt\n",
+        );
+        project.write("levels/level1.bin", [1, 2, 3, 4]);
+        project.write("intro.bin", [5, 6]);
+        project.write(
+            "aya.cfg",
+            "name = \"hello\"\ncode = \"main.aya\"\ndata = [\"levels/level1.bin\", \"intro.bin\"]\n",
+        );
+
+        let config = project.config();
+        let packed = pack(&config).unwrap();
+        let rom = Rom::from_bytes(&packed.rom, true).unwrap();
+
+        // the level is loaded 6 bytes before the end of code memory, followed by the intro
+        let [low, high] = (CODE_MEM_LOC.1 - 5).to_le_bytes();
+        assert_eq!(rom.code, [0x11, 0x02, low, high, 0x11, 0x03, 0x02, 0x00, 0xFF, 0x00]);
+        assert_eq!(rom.data, [&[1, 2, 3, 4][..], &[5, 6][..]]);
+        let usage = packed
+            .report
+            .sections
+            .iter()
+            .find(|usage| usage.section == "data")
+            .unwrap();
+        assert_eq!((usage.size, usage.max), (6, CODE_MEMORY - 10));
+
+        // the data has to fit on what the code leaves free of code memory
+        project.write("intro.bin", vec![0; CODE_MEMORY - 8]);
+        let err = pack(&config).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "data takes {} bytes but only {} are left on code memory after the code, it was built from `data = [\"levels/level1.bin\", \"intro.bin\"]`",
+                CODE_MEMORY - 4,
+                CODE_MEMORY - 10
+            )
+        );
+
+        // even when it doesn't fit on code memory at all
+        project.write("intro.bin", vec![0; CODE_MEMORY]);
+        let err = pack(&config).unwrap_err();
+        assert!(
+            matches!(err, Error::DataTooLarge { size, free, .. } if size == CODE_MEMORY + 4 && free == CODE_MEMORY - 10)
+        );
+    }
+
+    #[test]
+    fn test_screen_maps_are_packed() {
+        let project = Project::new("screen-maps");
+        project.write("main.aya", "hlt\n");
+        project.write("title.bin", [1, 2, 3]);
+        project.write("hud.bin", [4, 5]);
+        project.write(
+            "aya.cfg",
+            "name = \"hello\"\ncode = \"main.aya\"\nbackground_map = \"title.bin\"\ninterface_map = \"hud.bin\"\n",
+        );
+
+        let config = project.config();
+        let packed = pack(&config).unwrap();
+        let rom = Rom::from_bytes(&packed.rom, true).unwrap();
+        assert_eq!(rom.background, Some(&[1, 2, 3][..]));
+        assert_eq!(rom.interface, Some(&[4, 5][..]));
+        let usage = packed
+            .report
+            .sections
+            .iter()
+            .find(|usage| usage.section == "interface")
+            .unwrap();
+        assert_eq!((usage.size, usage.max), (2, aya_console::memory::INTERFACE_MEMORY));
+
+        // maps are copied into their memory, which they have to fit
+        project.write("hud.bin", vec![0; aya_console::memory::INTERFACE_MEMORY + 1]);
+        assert_eq!(
+            build_rom(&config).unwrap_err().to_string(),
+            "interface section takes 421 bytes but only 420 fit on interface memory, it was built from `interface_map = \"hud.bin\"`"
+        );
+    }
+
+    #[test]
+    fn test_entry_point_is_start_label() {
+        let project = Project::new("entry-point");
+        project.write("main.aya", "helper:\nmov r1, $0101\nstart:\nmov r2, $0202\n");
+        project.write("aya.cfg", "name = \"hello\"\ncode = \"main.aya\"\n");
+
+        let config = project.config();
+        let rom = build_rom(&config).unwrap();
+        let rom = Rom::from_bytes(&rom, true).unwrap();
+        assert_eq!(rom.entry_point, 4);
+
+        // without a start label the code runs from its first byte
+        project.write("main.aya", "helper:\nmov r1, $0101\n");
+        let rom = build_rom(&config).unwrap();
+        assert_eq!(Rom::from_bytes(&rom, true).unwrap().entry_point, 0);
+    }
+
+    #[test]
+    fn test_rom_carries_config_metadata() {
+        let project = Project::new("metadata");
+        project.write("main.aya", "start:\nmov r1, $0101\n");
+        let sprite = aya_bitmap::encode_rgb(8, 8, &[aya_bitmap::Color::new(0x9d, 0xc1, 0xc0); 64]).unwrap();
+        project.write("sprite.bmp", sprite);
+        project.write(
+            "aya.cfg",
+            "name = \"hello\"\nauthor = \"someone\"\ndescription = \"a game about things\"\ncode = \"main.aya\"\nsprites = \"sprite.bmp\"\n",
+        );
+
+        let mut config = project.config();
+        config.compress = true;
+        let bytes = build_rom(&config).unwrap();
+        let rom = Rom::from_bytes(&bytes, true).unwrap();
+
+        assert_eq!(rom.version, aya_rom::VERSION);
+        assert_eq!(rom.name, "hello");
+        assert_eq!(rom.author, Some("someone"));
+        assert_eq!(rom.description, Some("a game about things"));
+        assert_eq!(rom.cycles_per_frame, aya_console::CLOCK_CYCLE as u32);
+        assert_eq!(rom.palette, None);
+        assert!(rom.compressed_sprites);
+        assert_eq!(*rom.sprites, [0x11; 32]);
+    }
+
+    #[test]
+    fn test_sections_must_fit_console_memory() {
+        let project = Project::new("section-sizes");
+        let big = format!(
+            "data8 BIG = {{ {} }}\n",
+            vec!["$01"; aya_console::memory::CODE_MEMORY + 1].join(", ")
+        );
+        project.write("big.aya", &big);
+        project.write("main.aya", "start:\nmov r1, $0101\n");
+
+        project.write("aya.cfg", "name = \"hello\"\ncode = \"big.aya\"\n");
+        let config = project.config();
+        assert_eq!(
+            build_rom(&config).unwrap_err().to_string(),
+            "code section takes 16385 bytes but only 16384 fit on code memory, it was built from `code = \"big.aya\"`"
+        );
+
+        project.write(
+            "aya.cfg",
+            "name = \"hello\"\ncode = \"main.aya\"\nbanks = [\"main.aya\", \"big.aya\"]\n",
+        );
+        let config = project.config();
+        assert_eq!(
+            build_rom(&config).unwrap_err().to_string(),
+            "bank section takes 16385 bytes but only 16384 fit on bank memory, it was built from `banks = \"big.aya\"`"
+        );
+    }
+
+    #[test]
+    fn test_misplaced_imports_are_reported() {
+        let project = Project::new("misplaced-imports");
+        project.write("helper.aya", "ret\n");
+        project.write("main.aya", "import \"./helper.aya\" Helper &[$0100] {}\nstart:\nhlt\n");
+        // the bank places its helper on code memory, where it is never loaded
+        project.write(
+            "bank.aya",
+            "import \"./helper.aya\" Helper &[$0100] {}\nbank_start:\nret\n",
+        );
+        project.write("aya.cfg", "name = \"hello\"\ncode = \"main.aya\"\n");
+
+        let config = project.config();
+        assert!(pack(&config).unwrap().warnings.is_empty());
+
+        project.write(
+            "aya.cfg",
+            "name = \"hello\"\ncode = \"main.aya\"\nbanks = [\"bank.aya\"]\n",
+        );
+        let config = project.config();
+        let warnings = pack(&config).unwrap().warnings;
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].to_string(),
+            "module `Helper` is placed at $2380, on code memory"
+        );
+        assert_eq!(warnings[0].severity(), Some(miette::Severity::Warning));
+        assert!(warnings[0]
+            .help()
+            .unwrap()
+            .to_string()
+            .ends_with("from &[$7D80] to &[$BD7F]"));
+
+        let bytes = CODE_MEM_LOC.0.wrapping_neg();
+        project.write(
+            "main.aya",
+            format!("import \"./helper.aya\" Helper &[${bytes:04X}] {{}}\nstart:\nhlt\n"),
+        );
+        let code = std::fs::read_to_string(project.path("main.aya")).unwrap();
+        let warnings = misplaced_imports(code, &project.path("main.aya"), RegionKind::Code).unwrap();
+        assert_eq!(
+            warnings[0].to_string(),
+            "module `Helper` is placed past the end of console memory"
+        );
+    }
+
+    #[test]
+    fn test_deprecated_syntax_is_reported() {
+        let project = Project::new("deprecated-syntax");
+        project.write("main.aya", "start:\nmov &[r1], &[r2]\nhlt\n");
+        project.write("aya.cfg", "name = \"hello\"\ncode = \"main.aya\"\n");
+
+        let config = project.config();
+        let warnings = pack(&config).unwrap().warnings;
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].to_string(), "`mov &[r1], &[r2]` is deprecated");
+    }
+
+    #[test]
+    fn test_unmatched_sprite_patterns_are_reported() {
+        let project = Project::new("unmatched-sprites");
+        project.write(
+            "main.aya",
+            "start:
+hlt
+",
+        );
+        let sprite = aya_bitmap::encode_rgb(8, 8, &[aya_bitmap::Color::new(0, 0, 0); 64]).unwrap();
+        project.write("sprite.bmp", sprite);
+        project.write(
+            "aya.cfg",
+            "name = \"hello\"\ncode = \"main.aya\"\nsprites = [\"sprite.bmp\", \"missing/*.bmp\"]\n",
+        );
+
+        let warnings = pack(&project.config()).unwrap().warnings;
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].to_string(),
+            "sprite pattern `missing/*.bmp` matched no files"
+        );
+        assert_eq!(warnings[0].severity(), Some(miette::Severity::Warning));
+    }
+
+    #[test]
+    fn test_sprites_use_config_palette() {
+        let project = Project::new("palette");
+        project.write("main.aya", "start:\nmov r1, $0101\n");
+        let sprite = aya_bitmap::encode_rgb(8, 8, &[aya_bitmap::Color::new(1, 2, 3); 64]).unwrap();
+        project.write("sprite.bmp", sprite);
+
+        // the sprite color is close to the transparent console color, but exactly the 5th one here
+        let mut colors = vec!["\"#ffffff\""; 16];
+        colors[5] = "\"#010203\"";
+        project.write(
+            "aya.cfg",
+            format!(
+                "name = \"hello\"\ncode = \"main.aya\"\nsprites = \"sprite.bmp\"\npalette = [{}]\n",
+                colors.join(", ")
+            ),
+        );
+
+        let mut config = project.config();
+        config.strict = true;
+        let rom = build_rom(&config).unwrap();
+        let rom = Rom::from_bytes(&rom, true).unwrap();
+
+        assert_eq!(*rom.sprites, [0x55; 32]);
+        assert_eq!(rom.palette_colors().unwrap()[5], (0x01, 0x02, 0x03, 0xff));
+    }
+
+    #[test]
+    fn test_report_unused_sprites() {
+        let project = Project::new("report");
+        let sheet = aya_bitmap::encode_rgb(24, 8, &[aya_bitmap::Color::new(0, 0, 0); 192]).unwrap();
+        project.write("sheet.bmp", sheet);
+        project.write("sheet.sheet", "player 0 0 8 8\nenemy 8 0 8 8\ncoin 16 0 8 8\n");
+        project.write("main.aya", "start:\nmov r1, !SPRITE_PLAYER\n");
+        project.write("bank.aya", "bank_start:\nmov r1, !SPRITE_COIN\n");
+        project.write(
+            "aya.cfg",
+            "name = \"hello\"\ncode = \"main.aya\"\nsprites = \"sheet.bmp\"\nbanks = [\"bank.aya\"]\n",
+        );
+
+        let config = project.config();
+        let report = pack(&config).unwrap().report;
+
+        assert_eq!(
+            report.unused_sprites,
+            [UnusedSprite {
+                file: format!("{}:enemy", project.path("sheet.bmp").display()),
+                constant: String::from("SPRITE_ENEMY"),
+            }]
+        );
+        let sections = report
+            .sections
+            .iter()
+            .map(|usage| (usage.section, usage.max))
+            .collect::<Vec<_>>();
+        assert_eq!(sections, [("code", 16384), ("bank", 16384), ("sprite", 8192)]);
+        // three 8x8 sprites, 32 bytes each
+        assert_eq!(report.sections[2].size, 96);
+    }
+}
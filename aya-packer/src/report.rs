@@ -0,0 +1,71 @@
+use std::collections::BTreeSet;
+
+use crate::rom::{self, SpriteSource};
+
+/// What a rom is made of, for artists trimming assets nothing uses anymore. Nothing on it changes
+/// the rom.
+#[derive(Debug, Default)]
+pub struct Report {
+    /// Size of every section next to the memory it is loaded into
+    pub sections: Vec<SectionUsage>,
+    /// Named sprites whose constant no code refers to
+    pub unused_sprites: Vec<UnusedSprite>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SectionUsage {
+    pub section: &'static str,
+    pub size: usize,
+    /// Bytes of console memory the section is loaded into
+    pub max: usize,
+    /// Config entry the section was built from
+    pub entry: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnusedSprite {
+    /// Sprite file followed by the region name
+    pub file: String,
+    pub constant: String,
+}
+
+/// Named sprites whose generated constant isn't among the `referenced` names.
+pub(crate) fn unused_sprites(sprites: &[SpriteSource], referenced: &BTreeSet<String>) -> Vec<UnusedSprite> {
+    sprites
+        .iter()
+        .filter_map(|sprite| {
+            let constant = rom::sprite_constant(sprite.name.as_ref()?);
+            (!referenced.contains(&constant)).then(|| UnusedSprite {
+                file: sprite.bitmap.file_name().unwrap_or("<memory>").to_string(),
+                constant,
+            })
+        })
+        .collect()
+}
+
+impl std::fmt::Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "sections:")?;
+        for SectionUsage {
+            section,
+            size,
+            max,
+            entry,
+        } in &self.sections
+        {
+            let percent = size * 100 / max.max(&1);
+            writeln!(f, "  {section:<8} {size:>6} / {max:<6} bytes {percent:>3}%  {entry}")?;
+        }
+
+        match self.unused_sprites.is_empty() {
+            true => write!(f, "every named sprite is used by the code"),
+            false => {
+                write!(f, "sprites no code refers to:")?;
+                for UnusedSprite { file, constant } in &self.unused_sprites {
+                    write!(f, "\n  {file} ({constant})")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
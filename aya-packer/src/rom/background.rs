@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+
+use aya_bitmap::{Bitmap, Color};
+use aya_console::memory::{BG_MEMORY, TILE_MEMORY};
+
+use super::error::{Error, Result};
+use super::sprites::{self, CompiledSprites, BYTES_PER_TILE};
+
+/// Background size in tiles, it covers the whole screen
+const COLUMNS: u32 = 30;
+const ROWS: u32 = 14;
+
+/// Background size in pixels
+pub const WIDTH: u32 = COLUMNS * 8;
+pub const HEIGHT: u32 = ROWS * 8;
+
+const _: () = assert!((COLUMNS * ROWS) as usize == BG_MEMORY);
+
+/// Name of the data block holding the background map on the generated assembly
+pub const BACKGROUND_LABEL: &str = "BACKGROUND";
+
+/// Slices a screen sized image into tiles quantized to `palette`, appending the ones not yet on tile memory to
+/// `compiled` and counting the others as saved. Returns the background map, the tile index of every background cell row-major,
+/// ready to be copied into background memory.
+pub fn compile_background(
+    background: &Bitmap,
+    palette: &[Color],
+    tolerance: u32,
+    compiled: &mut CompiledSprites,
+) -> Result<Vec<u8>> {
+    let width = background.info_header().width();
+    let height = background.info_header().height();
+    if (width, height) != (WIDTH, HEIGHT) {
+        return Err(Error::InvalidBackgroundSize {
+            file: sprites::sprite_name(background).into(),
+            width,
+            height,
+        });
+    }
+
+    let quantized = sprites::quantize_sprite(background, palette, tolerance)?;
+    compiled.approximated += quantized.approximated;
+
+    let (tiles, _) = compiled.data.as_chunks::<BYTES_PER_TILE>();
+    let tiles_before = tiles.len();
+    let mut known = HashMap::with_capacity(tiles.len());
+    for (idx, tile) in tiles.iter().enumerate() {
+        known.entry(*tile).or_insert(idx);
+    }
+
+    let tiles = background
+        .tiles(8, 8)
+        .expect("background size is a multiple of the tile size");
+    let mut map = Vec::with_capacity(BG_MEMORY);
+    for tile in tiles {
+        let packed = sprites::pack_tile(&tile, &quantized);
+        let idx = *known.entry(packed).or_insert_with(|| {
+            compiled.data.extend(packed);
+            compiled.data.len() / BYTES_PER_TILE - 1
+        });
+        map.push(idx);
+    }
+    compiled.saved_tiles += map.len() - (compiled.data.len() / BYTES_PER_TILE - tiles_before);
+
+    let max_tiles = TILE_MEMORY / BYTES_PER_TILE;
+    if compiled.data.len() > TILE_MEMORY {
+        return Err(Error::SpriteTooBig {
+            tiles: compiled.data.len() / BYTES_PER_TILE,
+            max_tiles,
+            file: sprites::sprite_name(background).into(),
+        });
+    }
+
+    Ok(map.into_iter().map(|idx| idx as u8).collect())
+}
+
+/// Assembly data block holding the background map, along with its size.
+pub fn background_data(map: &[u8]) -> String {
+    let values = map
+        .iter()
+        .map(|idx| format!("${idx:02X}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!(
+        "const {BACKGROUND_LABEL}_SIZE = ${:04X}\ndata8 {BACKGROUND_LABEL} = {{ {values} }}\n",
+        map.len()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rom::console_palette;
+
+    fn checkered(width: u32, height: u32) -> Bitmap {
+        // tiles alternate between the first two palette colors
+        let pixels = (0..height)
+            .flat_map(|y| (0..width).map(move |x| ((x / 8 + y / 8) % 2) as u8))
+            .collect::<Vec<_>>();
+        let bytes = aya_bitmap::encode_indexed(width, height, &console_palette()[..2], &pixels).unwrap();
+        aya_bitmap::decode_bytes(&bytes).unwrap().with_file_name("title.bmp")
+    }
+
+    fn compiled(data: Vec<u8>) -> CompiledSprites {
+        CompiledSprites {
+            data,
+            approximated: 0,
+            first_tiles: vec![0],
+            remap: vec![0],
+            saved_tiles: 0,
+        }
+    }
+
+    #[test]
+    fn test_background_reuses_tiles() {
+        // a sprite tile of the first palette color is already on tile memory
+        let mut compiled = compiled(vec![0; BYTES_PER_TILE]);
+
+        let map = compile_background(&checkered(WIDTH, HEIGHT), &console_palette(), 0, &mut compiled).unwrap();
+
+        let expected = (0..ROWS)
+            .flat_map(|row| (0..COLUMNS).map(move |col| ((col + row) % 2) as u8))
+            .collect::<Vec<_>>();
+        assert_eq!(map, expected);
+        assert_eq!(compiled.data.len(), 2 * BYTES_PER_TILE);
+        assert_eq!(compiled.data[BYTES_PER_TILE..], [0x11; BYTES_PER_TILE]);
+        assert_eq!(compiled.saved_tiles, 419);
+    }
+
+    #[test]
+    fn test_background_must_fill_the_screen() {
+        let err = compile_background(
+            &checkered(WIDTH, HEIGHT + 8),
+            &console_palette(),
+            0,
+            &mut compiled(vec![]),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "title.bmp: the background must be the size of the screen, 240x112 pixels, but it is 240x120"
+        );
+    }
+
+    #[test]
+    fn test_background_data() {
+        assert_eq!(
+            background_data(&[0, 1, 0x1F]),
+            "const BACKGROUND_SIZE = $0003\ndata8 BACKGROUND = { $00, $01, $1F }\n"
+        );
+    }
+}
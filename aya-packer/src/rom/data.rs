@@ -0,0 +1,84 @@
+use std::path::Path;
+
+use super::error::{Error, Result};
+
+/// A raw file packed as a data section, along with the address the console loads it at.
+#[derive(Debug)]
+pub struct DataAsset {
+    /// Name shared by the constants generated for the file, `ASSET_<FILE NAME>`
+    pub name: String,
+    pub bytes: Vec<u8>,
+    pub address: u16,
+}
+
+/// Name of the constants generated for the data file at `path`, its file name without extension
+/// in uppercase, with anything that can't be on an identifier replaced by underscores.
+pub fn data_constant(path: &Path) -> String {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let stem = stem
+        .chars()
+        .map(|c| match c.is_ascii_alphanumeric() {
+            true => c.to_ascii_uppercase(),
+            false => '_',
+        })
+        .collect::<String>();
+    format!("ASSET_{stem}")
+}
+
+/// Pairs every data file with the address it is loaded at, files whose constants would collide are
+/// rejected as the code couldn't tell them apart.
+pub fn data_assets<P: AsRef<Path>>(files: Vec<(P, Vec<u8>)>, addresses: &[u16]) -> Result<Vec<DataAsset>> {
+    let mut assets: Vec<DataAsset> = Vec::with_capacity(files.len());
+    for ((path, bytes), &address) in files.into_iter().zip(addresses) {
+        let name = data_constant(path.as_ref());
+        if assets.iter().any(|asset| asset.name == name) {
+            return Err(Error::DuplicateData {
+                file: path.as_ref().display().to_string(),
+                constant: name,
+            });
+        }
+        assets.push(DataAsset { name, bytes, address });
+    }
+    Ok(assets)
+}
+
+/// Assembly constants holding the address and length of every data file, as `ASSET_<NAME>_ADDR`
+/// and `ASSET_<NAME>_LEN`.
+pub fn data_constants(assets: &[DataAsset]) -> String {
+    assets
+        .iter()
+        .map(|asset| {
+            format!(
+                "const {name}_ADDR = ${:04X}\nconst {name}_LEN = ${:04X}\n",
+                asset.address,
+                asset.bytes.len(),
+                name = asset.name
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_data_constants() {
+        let files = vec![
+            (Path::new("levels/level-1.bin"), vec![1, 2, 3]),
+            (Path::new("text/intro.bin"), vec![4]),
+        ];
+        let assets = data_assets(files, &[0x6000, 0x6003]).unwrap();
+        assert_eq!(
+            data_constants(&assets),
+            "const ASSET_LEVEL_1_ADDR = $6000\nconst ASSET_LEVEL_1_LEN = $0003\nconst ASSET_INTRO_ADDR = $6003\nconst ASSET_INTRO_LEN = $0001\n"
+        );
+
+        let files = vec![(Path::new("a/intro.bin"), vec![]), (Path::new("b/intro.txt"), vec![])];
+        let err = data_assets(files, &[0x6000, 0x6000]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "b/intro.txt: data files need distinct names, as the constants of another one are already named ASSET_INTRO"
+        );
+    }
+}
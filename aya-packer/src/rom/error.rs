@@ -0,0 +1,82 @@
+use aya_bitmap::Color;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug)]
+pub enum Error {
+    /// A sprite pixel is farther than the tolerance from every palette color
+    UnknownColor {
+        /// Sprite file, followed by the region name for sprite sheet regions
+        file: String,
+        /// Tile holding the pixel, as column and row of 8x8 tiles within the sprite
+        tile: (u32, u32),
+        /// Pixel position within the sprite
+        x: u32,
+        y: u32,
+        color: Color,
+    },
+    /// The packed sprites take more tiles than fit on tile memory
+    SpriteTooBig {
+        tiles: usize,
+        max_tiles: usize,
+        /// First sprite, or the background, with a tile past the end of tile memory
+        file: String,
+    },
+    /// The background image isn't the size of the screen
+    InvalidBackgroundSize {
+        file: String,
+        width: u32,
+        height: u32,
+    },
+    /// The image the palette is read from has too few colors on its palette table
+    InvalidPalette {
+        file: String,
+        colors: usize,
+    },
+    /// Two data files would generate constants with the same name
+    DuplicateData {
+        file: String,
+        constant: String,
+    },
+    InvalidSpriteSize(String),
+    InvalidSheet(String),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::UnknownColor {
+                file,
+                tile: (col, row),
+                x,
+                y,
+                color,
+            } => write!(
+                f,
+                "{file}: pixel ({x}, {y}) on tile ({col}, {row}) has color #{color:X} which is too far from every palette color"
+            ),
+            Error::SpriteTooBig { tiles, max_tiles, file } => write!(
+                f,
+                "sprites take {tiles} tiles but only {max_tiles} fit on tile memory, {file} is the first image past the limit"
+            ),
+            Error::InvalidBackgroundSize { file, width, height } => write!(
+                f,
+                "{file}: the background must be the size of the screen, {}x{} pixels, but it is {width}x{height}",
+                super::background::WIDTH,
+                super::background::HEIGHT,
+            ),
+            Error::InvalidPalette { file, colors } => write!(
+                f,
+                "{file}: palette images must have at least {} colors on their palette table, it has {colors}",
+                super::palette::PALETTE_COLORS,
+            ),
+            Error::DuplicateData { file, constant } => write!(
+                f,
+                "{file}: data files need distinct names, as the constants of another one are already named {constant}"
+            ),
+            Error::InvalidSpriteSize(msg) | Error::InvalidSheet(msg) => write!(f, "{msg}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
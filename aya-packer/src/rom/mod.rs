@@ -0,0 +1,13 @@
+mod background;
+mod data;
+mod error;
+mod palette;
+mod sheet;
+mod sprites;
+
+pub use background::{background_data, compile_background, BACKGROUND_LABEL};
+pub use data::{data_assets, data_constant, data_constants, DataAsset};
+pub use error::Error;
+pub use palette::{console_palette, image_palette, quantization_palette, PALETTE_COLORS};
+pub use sheet::{split_sheet, sprite_constant, sprite_constants, SpriteSource};
+pub use sprites::{compile_sprites, CompiledSprites, COLOR_TOLERANCE};
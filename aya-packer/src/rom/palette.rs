@@ -0,0 +1,66 @@
+use aya_bitmap::{Bitmap, Color};
+use aya_console::PALETTE;
+use aya_rom::PALETTE_SIZE;
+
+use super::error::{Error, Result};
+
+/// Colors on a palette
+pub const PALETTE_COLORS: usize = PALETTE_SIZE / 4;
+
+/// Reads a palette from the palette table of `image`, its first [`PALETTE_COLORS`] colors are
+/// taken in order. Palette tables have no alpha, so every color is opaque.
+pub fn image_palette(image: &Bitmap, file: &str) -> Result<Vec<[u8; 4]>> {
+    let table = image.palette();
+    if table.len() < PALETTE_COLORS {
+        return Err(Error::InvalidPalette {
+            file: file.into(),
+            colors: table.len(),
+        });
+    }
+
+    let colors = table[..PALETTE_COLORS]
+        .iter()
+        .map(|&color| {
+            let [r, g, b] = color.into();
+            [r, g, b, 0xff]
+        })
+        .collect();
+    Ok(colors)
+}
+
+/// Colors sprites are quantized to, those of `palette` or the console ones without it.
+pub fn quantization_palette(palette: Option<&[[u8; 4]]>) -> Vec<Color> {
+    match palette {
+        Some(colors) => colors.iter().map(|&[r, g, b, _]| Color::new(r, g, b)).collect(),
+        None => console_palette(),
+    }
+}
+
+pub fn console_palette() -> Vec<Color> {
+    PALETTE.iter().map(|&(r, g, b, _)| Color::from((r, g, b))).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_palette() {
+        let mut table = console_palette();
+        table.reverse();
+        let image = aya_bitmap::encode_indexed(8, 8, &table, &[0; 64]).unwrap();
+        let image = aya_bitmap::decode_bytes(&image).unwrap();
+
+        let colors = image_palette(&image, "palette.bmp").unwrap();
+        assert_eq!(colors.len(), PALETTE_COLORS);
+        assert_eq!(colors[0], [0xf6, 0x8b, 0x69, 0xff]);
+        assert_eq!(quantization_palette(Some(&colors)), table);
+
+        let image = aya_bitmap::encode_indexed(8, 8, &table[..4], &[0; 64]).unwrap();
+        let image = aya_bitmap::decode_bytes(&image).unwrap();
+        assert_eq!(
+            image_palette(&image, "palette.bmp").unwrap_err().to_string(),
+            "palette.bmp: palette images must have at least 16 colors on their palette table, it has 4"
+        );
+    }
+}
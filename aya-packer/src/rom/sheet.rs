@@ -0,0 +1,70 @@
+use std::path::Path;
+
+use aya_bitmap::Bitmap;
+
+use super::error::{Error, Result};
+
+/// Extension of the sprite sheet description read next to a sprite, `player.bmp` is described
+/// by `player.sheet`
+pub const SHEET_EXTENSION: &str = "sheet";
+
+/// A sprite to pack, regions of sprite sheets keep their name so assembly can refer to them.
+#[derive(Debug)]
+pub struct SpriteSource {
+    pub name: Option<String>,
+    pub bitmap: Bitmap,
+}
+
+/// Splits a sprite into the regions listed on its sheet description, or keeps it whole when it
+/// has none.
+pub fn split_sheet(path: &Path, bitmap: Bitmap) -> Result<Vec<SpriteSource>> {
+    let sheet_path = path.with_extension(SHEET_EXTENSION);
+    let Ok(source) = std::fs::read_to_string(&sheet_path) else {
+        return Ok(vec![SpriteSource { name: None, bitmap }]);
+    };
+
+    let sheet_name = sheet_path.display();
+    let invalid = |err: aya_bitmap::Error| Error::InvalidSheet(format!("{sheet_name}: {err}"));
+
+    let regions = aya_bitmap::parse_sheet(&source, &bitmap).map_err(invalid)?;
+    let mut sprites = Vec::with_capacity(regions.len());
+    for region in regions {
+        if !is_identifier(&region.name) {
+            return Err(Error::InvalidSheet(format!(
+                "{sheet_name}: line {}: region '{}' must be a valid identifier to be used from code",
+                region.line, region.name
+            )));
+        }
+
+        let file_name = format!("{}:{}", bitmap.file_name().unwrap_or("<memory>"), region.name);
+        sprites.push(SpriteSource {
+            bitmap: bitmap.crop(region.rect).map_err(invalid)?.with_file_name(file_name),
+            name: Some(region.name),
+        });
+    }
+
+    Ok(sprites)
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Assembly constants holding the first tile of every named sprite, as `SPRITE_<NAME>`.
+pub fn sprite_constants(sprites: &[SpriteSource], first_tiles: &[usize]) -> String {
+    sprites
+        .iter()
+        .zip(first_tiles)
+        .filter_map(|(sprite, tile)| {
+            let name = sprite_constant(sprite.name.as_ref()?);
+            Some(format!("const {name} = ${tile:02X}\n"))
+        })
+        .collect()
+}
+
+/// Name of the constant generated for the sprite region `name`.
+pub fn sprite_constant(name: &str) -> String {
+    format!("SPRITE_{}", name.to_ascii_uppercase())
+}
@@ -0,0 +1,211 @@
+use std::collections::HashMap;
+
+use aya_bitmap::{Bitmap, Color, Quantized, Tile};
+use aya_console::memory::TILE_MEMORY;
+
+use super::error::{Error, Result};
+use super::sheet::SpriteSource;
+
+/// Distance, between RGB components, a sprite color may be from a palette color and still be
+/// replaced by it when not packing strictly
+pub const COLOR_TOLERANCE: u32 = 32;
+
+/// Bytes taken by a packed 8x8 tile, two pixels per byte
+pub(super) const BYTES_PER_TILE: usize = 32;
+
+/// Packed sprite tiles and how many pixels had to be approximated to fit the palette.
+#[derive(Debug)]
+pub struct CompiledSprites {
+    pub data: Vec<u8>,
+    pub approximated: usize,
+    /// Index of the first tile of every sprite, sprites take consecutive tiles row-major unless
+    /// duplicated tiles were removed
+    pub first_tiles: Vec<usize>,
+    /// Tile every sprite tile ended up at, in the order they were cut from the sprites. Only
+    /// differs from the tile order when duplicated tiles were removed
+    pub remap: Vec<usize>,
+    /// How many tiles were saved by removing duplicates
+    pub saved_tiles: usize,
+}
+
+/// Packs sprites into tile memory. Pixels are replaced by the nearest `palette` color unless they
+/// are farther than `tolerance` from every one, so a tolerance of 0 only accepts palette colors.
+/// With `dedup` set, tiles identical to an earlier one are dropped and point to it instead.
+pub fn compile_sprites(
+    sprites: &[SpriteSource],
+    palette: &[Color],
+    tolerance: u32,
+    dedup: bool,
+) -> Result<CompiledSprites> {
+    let mut compiled = vec![];
+    let mut approximated = 0;
+    let mut first_tiles = Vec::with_capacity(sprites.len());
+
+    for SpriteSource { bitmap: sprite, .. } in sprites {
+        first_tiles.push(compiled.len() / BYTES_PER_TILE);
+        let tiles = sprite.tiles(8, 8).map_err(|err| invalid_size(sprite, err))?;
+        let quantized = quantize_sprite(sprite, palette, tolerance)?;
+        approximated += quantized.approximated;
+        compiled.extend(tiles.flat_map(|tile| pack_tile(&tile, &quantized)));
+    }
+
+    let (compiled, remap) = match dedup {
+        true => dedup_tiles(&compiled),
+        false => {
+            let tiles = compiled.len() / BYTES_PER_TILE;
+            (compiled, (0..tiles).collect())
+        }
+    };
+
+    let max_tiles = TILE_MEMORY / BYTES_PER_TILE;
+    if let Some(overflow) = remap.iter().position(|&tile| tile >= max_tiles) {
+        // the sprite owning the first tile past the limit is the one that didn't fit
+        let sprite = first_tiles.partition_point(|&first| first <= overflow) - 1;
+        return Err(Error::SpriteTooBig {
+            tiles: compiled.len() / BYTES_PER_TILE,
+            max_tiles,
+            file: sprite_name(&sprites[sprite].bitmap).into(),
+        });
+    }
+
+    let first_tiles = first_tiles
+        .into_iter()
+        .map(|tile| remap.get(tile).copied().unwrap_or(compiled.len() / BYTES_PER_TILE))
+        .collect();
+
+    let saved_tiles = remap.len() - compiled.len() / BYTES_PER_TILE;
+    Ok(CompiledSprites {
+        data: compiled,
+        approximated,
+        first_tiles,
+        saved_tiles,
+        remap,
+    })
+}
+
+/// Keeps the first occurrence of every packed tile, returning the remaining tiles and the index
+/// every original tile was moved to.
+fn dedup_tiles(data: &[u8]) -> (Vec<u8>, Vec<usize>) {
+    let (tiles, _) = data.as_chunks::<BYTES_PER_TILE>();
+    let mut unique = Vec::with_capacity(data.len());
+    let mut seen = HashMap::new();
+
+    let remap = tiles
+        .iter()
+        .map(|tile| {
+            *seen.entry(tile).or_insert_with(|| {
+                unique.extend_from_slice(tile);
+                unique.len() / BYTES_PER_TILE - 1
+            })
+        })
+        .collect();
+
+    (unique, remap)
+}
+
+/// Maps every pixel of `sprite` to `palette`.
+pub(super) fn quantize_sprite(sprite: &Bitmap, palette: &[Color], tolerance: u32) -> Result<Quantized> {
+    // indices of sprites sharing the palette already are palette indices
+    let shares_palette = palette.starts_with(sprite.palette());
+    match sprite.indices() {
+        Some(indices) if shares_palette => Ok(Quantized {
+            indices: indices.to_vec(),
+            approximated: 0,
+        }),
+        _ => match aya_bitmap::quantize(sprite, palette, tolerance) {
+            Ok(quantized) => Ok(quantized),
+            Err(aya_bitmap::Error::ColorOutOfTolerance { color, x, y }) => Err(unknown_color(sprite, color, x, y)),
+            Err(err) => panic!("palettes always have 16 colors: {err}"),
+        },
+    }
+}
+
+/// Packs a tile of a quantized sprite, two pixels per byte.
+pub(super) fn pack_tile(tile: &Tile, quantized: &Quantized) -> [u8; BYTES_PER_TILE] {
+    let indices = tile.indices().map(|idx| quantized.indices[idx]).collect::<Vec<_>>();
+    let (pairs, _) = indices.as_chunks::<2>();
+    let mut packed = [0; BYTES_PER_TILE];
+    for (byte, [left, right]) in packed.iter_mut().zip(pairs) {
+        *byte = left << 4 | right;
+    }
+    packed
+}
+
+pub(super) fn sprite_name(sprite: &Bitmap) -> &str {
+    sprite.file_name().unwrap_or("<memory>")
+}
+
+fn unknown_color(sprite: &Bitmap, color: Color, x: u32, y: u32) -> Error {
+    Error::UnknownColor {
+        file: sprite_name(sprite).into(),
+        tile: (x / 8, y / 8),
+        x,
+        y,
+        color,
+    }
+}
+
+fn invalid_size(sprite: &Bitmap, err: aya_bitmap::Error) -> Error {
+    Error::InvalidSpriteSize(format!("{err}, found on sprite image: {}", sprite_name(sprite)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rom::console_palette;
+
+    fn tiles16(name: &str) -> SpriteSource {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../aya-bitmap/fixtures/tiles16.bmp");
+        SpriteSource {
+            name: None,
+            bitmap: aya_bitmap::decode(path).unwrap().with_file_name(name),
+        }
+    }
+
+    #[test]
+    fn test_unknown_color_names_pixel() {
+        let err = compile_sprites(&[tiles16("tiles16.bmp")], &console_palette(), 0, false).unwrap_err();
+
+        assert!(matches!(
+            &err,
+            Error::UnknownColor { file, tile: (0, 0), x: 1, y: 0, color }
+                if file == "tiles16.bmp" && *color == Color::new(0x00, 0x20, 0x00)
+        ));
+        assert_eq!(
+            err.to_string(),
+            "tiles16.bmp: pixel (1, 0) on tile (0, 0) has color #002000 which is too far from every palette color"
+        );
+    }
+
+    #[test]
+    fn test_too_many_tiles_names_sprite() {
+        // every sprite takes 4 tiles, so the 65th is the first past the 256 tiles that fit
+        let sprites = (0..65).map(|i| tiles16(&format!("sprite{i}.bmp"))).collect::<Vec<_>>();
+
+        let err = compile_sprites(&sprites, &console_palette(), u32::MAX, false).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "sprites take 260 tiles but only 256 fit on tile memory, sprite64.bmp is the first image past the limit"
+        );
+
+        let compiled = compile_sprites(&sprites, &console_palette(), u32::MAX, true).unwrap();
+        assert_eq!(compiled.saved_tiles, 256);
+        assert_eq!(compiled.first_tiles, vec![0; 65]);
+    }
+
+    #[test]
+    fn test_dedup_tiles() {
+        let tiles = [
+            [1; BYTES_PER_TILE],
+            [2; BYTES_PER_TILE],
+            [1; BYTES_PER_TILE],
+            [3; BYTES_PER_TILE],
+            [2; BYTES_PER_TILE],
+        ];
+
+        let (data, remap) = dedup_tiles(tiles.as_flattened());
+
+        assert_eq!(data, [tiles[0], tiles[1], tiles[3]].as_flattened());
+        assert_eq!(remap, [0, 1, 0, 2, 1]);
+    }
+}
@@ -0,0 +1,115 @@
+use crate::{RomError, MAX_SPRITES_SIZE};
+
+// sprite compression, every block starts with a control byte: with the high bit set the next byte is
+// repeated `MIN_RUN` plus the low bits times, otherwise the control byte plus one bytes are copied
+const RUN_BIT: u8 = 0x80;
+const MIN_RUN: usize = 3;
+const MAX_RUN: usize = 0x7F + MIN_RUN;
+const MAX_LITERALS: usize = 0x80;
+
+/// Run length encodes a sprite section, sprites are mostly made of long runs of the same color so
+/// this is usually much smaller, while noisy data grows by at most a byte every 128.
+pub fn compress_sprites(sprites: &[u8]) -> Vec<u8> {
+    let mut compressed = vec![];
+    let mut literals = 0;
+    let mut idx = 0;
+
+    while idx < sprites.len() {
+        let byte = sprites[idx];
+        let run = sprites[idx..].iter().take(MAX_RUN).take_while(|b| **b == byte).count();
+        if run < MIN_RUN {
+            idx += 1;
+            continue;
+        }
+
+        push_literals(&mut compressed, &sprites[literals..idx]);
+        compressed.extend([RUN_BIT | (run - MIN_RUN) as u8, byte]);
+        idx += run;
+        literals = idx;
+    }
+    push_literals(&mut compressed, &sprites[literals..]);
+
+    compressed
+}
+
+fn push_literals(compressed: &mut Vec<u8>, literals: &[u8]) {
+    for chunk in literals.chunks(MAX_LITERALS) {
+        compressed.push((chunk.len() - 1) as u8);
+        compressed.extend(chunk);
+    }
+}
+
+/// Expands a sprite section written by [`compress_sprites`], which must fit in [`MAX_SPRITES_SIZE`] bytes.
+pub fn decompress_sprites(compressed: &[u8]) -> Result<Vec<u8>, RomError> {
+    let mut sprites = vec![];
+    // the whole section is walked even past the limit so the error tells how large it is
+    let mut size = 0;
+    let mut idx = 0;
+
+    while let Some(&control) = compressed.get(idx) {
+        let (is_run, len) = match control & RUN_BIT {
+            0 => (false, control as usize + 1),
+            _ => (true, (control & !RUN_BIT) as usize + MIN_RUN),
+        };
+        let stored = if is_run { 1 } else { len };
+        let bytes = compressed
+            .get(idx + 1..idx + 1 + stored)
+            .ok_or(RomError::InvalidCompression("sprite"))?;
+        idx += 1 + stored;
+
+        size += len;
+        if size > MAX_SPRITES_SIZE {
+            continue;
+        }
+        match is_run {
+            true => sprites.resize(size, bytes[0]),
+            false => sprites.extend_from_slice(bytes),
+        }
+    }
+
+    if size > MAX_SPRITES_SIZE {
+        return Err(RomError::DecompressedTooLarge {
+            section: "sprite",
+            size,
+            max: MAX_SPRITES_SIZE,
+        });
+    }
+
+    Ok(sprites)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sprite_compression_round_trip() {
+        let noise = (0..1000u32).map(|n| (n * 7919 % 251) as u8).collect::<Vec<_>>();
+        let runs = (0..1000u32).map(|n| (n / 200) as u8).collect::<Vec<_>>();
+        let mixed = [&noise[..300], &[0; 3], &[1; 2], &runs, &[9]].concat();
+
+        for sprites in [vec![], vec![0x42], noise, runs, mixed] {
+            let compressed = compress_sprites(&sprites);
+            assert_eq!(decompress_sprites(&compressed).unwrap(), sprites);
+            assert!(compressed.len() <= sprites.len() + sprites.len().div_ceil(MAX_LITERALS));
+        }
+
+        assert_eq!(compress_sprites(&[0; 32]), [0x80 | (32 - MIN_RUN as u8), 0]);
+    }
+
+    #[test]
+    fn test_decompressed_sprites_must_fit() {
+        let sprites = compress_sprites(&[0; MAX_SPRITES_SIZE]);
+        assert_eq!(decompress_sprites(&sprites).unwrap().len(), MAX_SPRITES_SIZE);
+
+        let sprites = compress_sprites(&[0; MAX_SPRITES_SIZE + 1]);
+        assert_eq!(
+            decompress_sprites(&sprites).unwrap_err(),
+            RomError::DecompressedTooLarge {
+                section: "sprite",
+                size: MAX_SPRITES_SIZE + 1,
+                max: MAX_SPRITES_SIZE,
+            }
+        );
+    }
+}
@@ -0,0 +1,94 @@
+use crate::{PALETTE_SIZE, VERSION};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RomError {
+    TooShort(usize),
+    BadMagic,
+    UnsupportedVersion(u8),
+    InvalidName,
+    /// A text field of the header, such as the author, is not a null terminated utf-8 string
+    InvalidText(&'static str),
+    /// A text field is longer than the `max` bytes the header holds for it
+    TextTooLong {
+        field: &'static str,
+        max: usize,
+    },
+    TooManySections,
+    MissingSection(&'static str),
+    SectionOutOfBounds {
+        section: &'static str,
+        end: usize,
+        file_size: usize,
+    },
+    ChecksumMismatch {
+        expected: u32,
+        actual: u32,
+    },
+    /// A palette that isn't made of exactly 16 RGBA colors was given to be written
+    PaletteSize(usize),
+    /// A compressed section ends in the middle of a block
+    InvalidCompression(&'static str),
+    /// A section is larger than the `max` bytes of the memory it is loaded into
+    SectionTooLarge {
+        section: &'static str,
+        size: usize,
+        max: usize,
+    },
+    /// A compressed section expands past the `max` bytes of the memory it is loaded into
+    DecompressedTooLarge {
+        section: &'static str,
+        size: usize,
+        max: usize,
+    },
+}
+
+impl std::fmt::Display for RomError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RomError::TooShort(size) => write!(f, "rom is {size} bytes long, which is too short to hold its header"),
+            RomError::BadMagic => write!(f, "file is not an aya rom, it doesn't start with the AYA magic bytes"),
+            RomError::UnsupportedVersion(version) => {
+                write!(
+                    f,
+                    "rom version {version} is not supported, expected version {VERSION} or older"
+                )
+            }
+            RomError::InvalidName => write!(f, "rom name is not a null terminated utf-8 string"),
+            RomError::InvalidText(field) => write!(f, "rom {field} is not a null terminated utf-8 string"),
+            RomError::TextTooLong { field, max } => write!(f, "rom {field} must be at most {max} bytes long"),
+            RomError::TooManySections => write!(f, "rom headers hold at most {} sections", u8::MAX),
+            RomError::MissingSection(section) => write!(f, "rom has no {section} section"),
+            RomError::SectionOutOfBounds {
+                section,
+                end,
+                file_size,
+            } => write!(
+                f,
+                "{section} section ends at byte {end}, but the rom is only {file_size} bytes long"
+            ),
+            RomError::ChecksumMismatch { expected, actual } => write!(
+                f,
+                "rom checksum mismatch, header declares 0x{expected:08X} but contents hash to 0x{actual:08X}"
+            ),
+            RomError::PaletteSize(size) => write!(
+                f,
+                "rom palettes hold 16 RGBA colors in {PALETTE_SIZE} bytes, but the palette is {size} bytes long"
+            ),
+            RomError::SectionTooLarge { section, size, max } => {
+                write!(
+                    f,
+                    "{section} section is {size} bytes long, but it must fit in {max} bytes"
+                )
+            }
+            RomError::InvalidCompression(section) => {
+                write!(f, "compressed {section} section is truncated in the middle of a block")
+            }
+            RomError::DecompressedTooLarge { section, size, max } => write!(
+                f,
+                "compressed {section} section expands to {size} bytes, but it must fit in {max} bytes"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RomError {}
@@ -0,0 +1,232 @@
+use crate::{RomError, HEADER_V2_SIZE, MAGIC, SECTION_ENTRY_SIZE, VERSION};
+
+// version 2 layout, every field is little endian
+const SECTION_COUNT_LOC: usize = 0x05;
+const ENTRY_POINT_LOC: (usize, usize) = (0x06, 0x08);
+const CYCLES_PER_FRAME_LOC: (usize, usize) = (0x08, 0x0C);
+const PALETTE_OFFSET_LOC: (usize, usize) = (0x0C, 0x10);
+const CHECKSUM_V2_LOC: (usize, usize) = (0x10, 0x14);
+const FLAGS_LOC: (usize, usize) = (0x14, 0x18);
+const NAME_V2_LOC: (usize, usize) = (0x20, 0x60);
+pub(crate) const AUTHOR_LOC: (usize, usize) = (0x60, 0xA0);
+const DESCRIPTION_LOC: (usize, usize) = (0xA0, 0x100);
+
+// bits of the flags word
+const COMPRESSED_SPRITES_FLAG: u32 = 1;
+
+/// Kinds of the sections listed on version 2 headers, unknown kinds are skipped so newer packers
+/// can add sections older consoles don't need, though the checksum still covers them.
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SectionKind {
+    Code = 1,
+    Sprites = 2,
+    /// A code bank, banks are numbered in the order they appear on the section table
+    Bank = 3,
+    /// Raw data, loaded at the end of code memory in the order they appear on the section table
+    Data = 4,
+    /// Initial contents of background memory, copied into it before the first frame
+    Background = 5,
+    /// Initial contents of interface memory, copied into it before the first frame
+    Interface = 6,
+}
+
+impl TryFrom<u32> for SectionKind {
+    type Error = u32;
+
+    fn try_from(kind: u32) -> Result<Self, u32> {
+        match kind {
+            1 => Ok(Self::Code),
+            2 => Ok(Self::Sprites),
+            3 => Ok(Self::Bank),
+            4 => Ok(Self::Data),
+            5 => Ok(Self::Background),
+            6 => Ok(Self::Interface),
+            _ => Err(kind),
+        }
+    }
+}
+
+/// Entry of the section table, `offset` is counted from the start of the rom.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Section {
+    pub kind: SectionKind,
+    pub offset: u32,
+    pub size: u32,
+}
+
+/// A version 2 header, [`crate::Rom`] fills it in when writing a rom and reads it back when
+/// loading one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Header<'rom> {
+    pub name: &'rom str,
+    pub author: Option<&'rom str>,
+    pub description: Option<&'rom str>,
+    /// Offset from the start of code memory where execution begins
+    pub entry_point: u16,
+    pub cycles_per_frame: u32,
+    /// Offset of the rom palette, 0 when the rom uses the console palette
+    pub palette_offset: u32,
+    /// CRC32 of every section on the table in table order, unknown kinds included
+    pub checksum: u32,
+    /// The sprite section is run length encoded, see [`compress_sprites`]
+    pub compressed_sprites: bool,
+    pub sections: Vec<Section>,
+}
+
+impl<'rom> Header<'rom> {
+    /// Bytes taken by the header, including its section table.
+    pub fn size(&self) -> usize {
+        HEADER_V2_SIZE + self.sections.len() * SECTION_ENTRY_SIZE
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, RomError> {
+        let mut header = vec![0; self.size()];
+
+        header[0x00..0x04].copy_from_slice(MAGIC);
+        header[0x04] = VERSION;
+        header[SECTION_COUNT_LOC] = u8::try_from(self.sections.len()).map_err(|_| RomError::TooManySections)?;
+        write_bytes(&mut header, ENTRY_POINT_LOC, &self.entry_point.to_le_bytes());
+        write_bytes(&mut header, CYCLES_PER_FRAME_LOC, &self.cycles_per_frame.to_le_bytes());
+        write_bytes(&mut header, PALETTE_OFFSET_LOC, &self.palette_offset.to_le_bytes());
+        write_bytes(&mut header, CHECKSUM_V2_LOC, &self.checksum.to_le_bytes());
+        let flags = if self.compressed_sprites { COMPRESSED_SPRITES_FLAG } else { 0 };
+        write_bytes(&mut header, FLAGS_LOC, &flags.to_le_bytes());
+
+        write_text(&mut header, NAME_V2_LOC, "name", self.name)?;
+        write_text(&mut header, AUTHOR_LOC, "author", self.author.unwrap_or_default())?;
+        write_text(
+            &mut header,
+            DESCRIPTION_LOC,
+            "description",
+            self.description.unwrap_or_default(),
+        )?;
+
+        for (idx, section) in self.sections.iter().enumerate() {
+            let entry = HEADER_V2_SIZE + idx * SECTION_ENTRY_SIZE;
+            header[entry..entry + 4].copy_from_slice(&(section.kind as u32).to_le_bytes());
+            header[entry + 4..entry + 8].copy_from_slice(&section.offset.to_le_bytes());
+            header[entry + 8..entry + 12].copy_from_slice(&section.size.to_le_bytes());
+        }
+
+        Ok(header)
+    }
+
+    /// Parses the header of a version 2 rom, sections of unknown kinds are left out.
+    pub fn from_bytes(rom: &'rom [u8]) -> Result<Self, RomError> {
+        let section_count = *rom.get(SECTION_COUNT_LOC).ok_or(RomError::TooShort(rom.len()))? as usize;
+        if rom.len() < HEADER_V2_SIZE + section_count * SECTION_ENTRY_SIZE {
+            return Err(RomError::TooShort(rom.len()));
+        }
+
+        let word = |start: usize| u32::from_le_bytes(rom[start..start + 4].try_into().unwrap());
+        let name = read_text(rom, NAME_V2_LOC).ok_or(RomError::InvalidName)?;
+        let author = read_text(rom, AUTHOR_LOC).ok_or(RomError::InvalidText("author"))?;
+        let description = read_text(rom, DESCRIPTION_LOC).ok_or(RomError::InvalidText("description"))?;
+
+        let sections = (0..section_count)
+            .map(|entry| HEADER_V2_SIZE + entry * SECTION_ENTRY_SIZE)
+            .filter_map(|entry| {
+                let kind = SectionKind::try_from(word(entry)).ok()?;
+                Some(Section {
+                    kind,
+                    offset: word(entry + 4),
+                    size: word(entry + 8),
+                })
+            })
+            .collect();
+
+        Ok(Header {
+            name,
+            author: (!author.is_empty()).then_some(author),
+            description: (!description.is_empty()).then_some(description),
+            entry_point: u16::from_le_bytes([rom[ENTRY_POINT_LOC.0], rom[ENTRY_POINT_LOC.0 + 1]]),
+            cycles_per_frame: word(CYCLES_PER_FRAME_LOC.0),
+            palette_offset: word(PALETTE_OFFSET_LOC.0),
+            checksum: word(CHECKSUM_V2_LOC.0),
+            compressed_sprites: word(FLAGS_LOC.0) & COMPRESSED_SPRITES_FLAG != 0,
+            sections,
+        })
+    }
+}
+
+/// Offset and size of every entry of the section table in table order, unknown kinds included. The
+/// table must fit on `rom`, which [`Header::from_bytes`] checks.
+pub(crate) fn table_entries(rom: &[u8]) -> Vec<(usize, usize)> {
+    let word = |start: usize| u32::from_le_bytes(rom[start..start + 4].try_into().unwrap()) as usize;
+    (0..rom[SECTION_COUNT_LOC] as usize)
+        .map(|entry| HEADER_V2_SIZE + entry * SECTION_ENTRY_SIZE)
+        .map(|entry| (word(entry + 4), word(entry + 8)))
+        .collect()
+}
+
+/// Reads a null terminated utf-8 string stored within `start..end`.
+pub(crate) fn read_text(rom: &[u8], (start, end): (usize, usize)) -> Option<&str> {
+    let text = &rom[start..end];
+    let len = text.iter().position(|ch| *ch == 0)?;
+    std::str::from_utf8(&text[..len]).ok()
+}
+
+/// Writes a null terminated string within `start..end`.
+fn write_text(
+    header: &mut [u8],
+    (start, end): (usize, usize),
+    field: &'static str,
+    text: &str,
+) -> Result<(), RomError> {
+    // the last byte is always left for the terminator
+    let max = end - start - 1;
+    if text.len() > max {
+        return Err(RomError::TextTooLong { field, max });
+    }
+    header[start..start + text.len()].copy_from_slice(text.as_bytes());
+    Ok(())
+}
+
+fn write_bytes(header: &mut [u8], (start, end): (usize, usize), bytes: &[u8]) {
+    header[start..end].copy_from_slice(bytes);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_header_round_trip() {
+        let header = Header {
+            name: "game",
+            author: Some("someone"),
+            description: None,
+            entry_point: 0x20,
+            cycles_per_frame: 1000,
+            palette_offset: 0,
+            checksum: 0xDEADBEEF,
+            compressed_sprites: true,
+            sections: vec![
+                Section {
+                    kind: SectionKind::Code,
+                    offset: 280,
+                    size: 4,
+                },
+                Section {
+                    kind: SectionKind::Sprites,
+                    offset: 284,
+                    size: 0,
+                },
+            ],
+        };
+
+        let bytes = header.to_bytes().unwrap();
+        assert_eq!(bytes.len(), header.size());
+        assert_eq!(Header::from_bytes(&bytes).unwrap(), header);
+
+        let long_name = "a".repeat(64);
+        let err = Header {
+            name: &long_name,
+            ..header
+        }
+        .to_bytes()
+        .unwrap_err();
+        assert_eq!(err, RomError::TextTooLong { field: "name", max: 63 });
+    }
+}
@@ -0,0 +1,634 @@
+mod compression;
+mod error;
+mod header;
+
+use std::borrow::Cow;
+
+pub use compression::{compress_sprites, decompress_sprites};
+pub use error::RomError;
+use header::read_text;
+pub use header::{Header, Section, SectionKind};
+
+/// Magic bytes every rom starts with
+pub const MAGIC: &[u8; 4] = b"AYA\0";
+/// Newest rom format version, older versions are still loadable
+pub const VERSION: u8 = 2;
+/// Size of the header of version 1 roms
+pub const HEADER_SIZE: usize = 128;
+/// Size of the fixed part of version 2 headers, the section table follows it
+pub const HEADER_V2_SIZE: usize = 256;
+/// Bytes taken by every entry of the section table: kind, offset and size as 32-bit words
+pub const SECTION_ENTRY_SIZE: usize = 12;
+/// Bytes taken by a palette, 16 RGBA colors
+pub const PALETTE_SIZE: usize = 64;
+/// Largest sprite section once expanded, the size of the console tile memory
+pub const MAX_SPRITES_SIZE: usize = 0x2000;
+/// Largest code bank, the size of the console bank window
+pub const MAX_BANK_SIZE: usize = 0x4000;
+/// Largest background section, the size of the console background memory
+pub const MAX_BACKGROUND_SIZE: usize = 420;
+/// Largest interface section, the size of the console interface memory
+pub const MAX_INTERFACE_SIZE: usize = 420;
+
+// version 1 layout
+const NAME_LOC: (usize, usize) = (0x05, 0x44);
+const CHECKSUM_LOC: (usize, usize) = (0x4C, 0x50);
+
+/// A rom as the console loads it, written by the packer with [`Rom::to_bytes`] and read back by
+/// the console with [`Rom::from_bytes`], so both sides go through the same layout.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rom<'rom> {
+    /// Format version the rom was packed with, roms are always written with the newest one
+    pub version: u8,
+    pub name: &'rom str,
+    /// Only version 2 roms carry an author and description
+    pub author: Option<&'rom str>,
+    pub description: Option<&'rom str>,
+    /// Offset from the start of code memory where execution begins
+    pub entry_point: u16,
+    /// Cycles the rom expects to run every frame, 0 when it doesn't ask for any
+    pub cycles_per_frame: u32,
+    /// Palette the rom was made for, as 16 RGBA colors, the console palette is used without one
+    pub palette: Option<&'rom [u8]>,
+    pub code: &'rom [u8],
+    /// Sprites as they are loaded into tile memory, already expanded when the rom stores them compressed
+    pub sprites: Cow<'rom, [u8]>,
+    /// The sprite section is stored run length encoded, see [`compress_sprites`]
+    pub compressed_sprites: bool,
+    /// Code banks, in the order they are selected by the bank select register
+    pub banks: Vec<&'rom [u8]>,
+    /// Raw data assets, such as level maps, in the order they are loaded into memory
+    pub data: Vec<&'rom [u8]>,
+    /// Tile of every background cell when the program starts, background memory starts zeroed
+    /// without it
+    pub background: Option<&'rom [u8]>,
+    /// Tile of every interface cell when the program starts, interface memory starts zeroed
+    /// without it
+    pub interface: Option<&'rom [u8]>,
+}
+
+impl<'rom> Rom<'rom> {
+    /// Reads a rom, when `verify_checksum` is set the sections must match the checksum declared on
+    /// the header.
+    pub fn from_bytes(rom: &'rom [u8], verify_checksum: bool) -> Result<Self, RomError> {
+        if rom.len() < MAGIC.len() + 1 {
+            return Err(RomError::TooShort(rom.len()));
+        }
+
+        if &rom[0..MAGIC.len()] != MAGIC {
+            return Err(RomError::BadMagic);
+        }
+
+        let (rom, actual, expected) = match rom[0x04] {
+            1 => load_v1(rom)?,
+            2 => load_v2(rom)?,
+            version => return Err(RomError::UnsupportedVersion(version)),
+        };
+
+        if verify_checksum && actual != expected {
+            return Err(RomError::ChecksumMismatch { expected, actual });
+        }
+
+        Ok(rom)
+    }
+
+    /// Writes the rom with the newest [`VERSION`] of the format, sections are laid out right after
+    /// the header in the order they are listed on it, followed by the palette.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, RomError> {
+        if let Some(palette) = self.palette.filter(|palette| palette.len() != PALETTE_SIZE) {
+            return Err(RomError::PaletteSize(palette.len()));
+        }
+
+        let sprites = self.stored_sprites();
+        let contents = self.sections(&sprites);
+
+        let mut offset = (HEADER_V2_SIZE + contents.len() * SECTION_ENTRY_SIZE) as u32;
+        let sections = contents
+            .iter()
+            .map(|(kind, bytes)| {
+                let section = Section {
+                    kind: *kind,
+                    offset,
+                    size: bytes.len() as u32,
+                };
+                offset += bytes.len() as u32;
+                section
+            })
+            .collect();
+
+        let header = Header {
+            name: self.name,
+            author: self.author,
+            description: self.description,
+            entry_point: self.entry_point,
+            cycles_per_frame: self.cycles_per_frame,
+            palette_offset: self.palette.map_or(0, |_| offset),
+            checksum: self.checksum(),
+            compressed_sprites: self.compressed_sprites,
+            sections,
+        };
+
+        let mut rom = header.to_bytes()?;
+        for (_, bytes) in contents {
+            rom.extend(bytes);
+        }
+        rom.extend(self.palette.unwrap_or_default());
+        Ok(rom)
+    }
+
+    /// CRC32 of every section as they are stored on the rom, it identifies the rom contents.
+    pub fn checksum(&self) -> u32 {
+        let sprites = self.stored_sprites();
+        let sections = self
+            .sections(&sprites)
+            .into_iter()
+            .map(|(_, bytes)| bytes)
+            .collect::<Vec<_>>();
+        sections_checksum(&sections)
+    }
+
+    /// Name of the rom along with its author, as shown on the window title.
+    pub fn title(&self) -> String {
+        match self.author {
+            Some(author) => format!("{} by {author}", self.name),
+            None => self.name.to_string(),
+        }
+    }
+
+    /// Colors of the rom palette, `None` when the rom is drawn with the console palette.
+    pub fn palette_colors(&self) -> Option<Vec<(u8, u8, u8, u8)>> {
+        let (colors, _) = self.palette?.as_chunks::<4>();
+        Some(colors.iter().map(|&[r, g, b, a]| (r, g, b, a)).collect())
+    }
+
+    /// Every section in the order it is listed on the section table, `sprites` being the sprite
+    /// section as it is stored.
+    fn sections<'a>(&'a self, sprites: &'a [u8]) -> Vec<(SectionKind, &'a [u8])> {
+        [(SectionKind::Code, self.code), (SectionKind::Sprites, sprites)]
+            .into_iter()
+            .chain(self.banks.iter().map(|bank| (SectionKind::Bank, *bank)))
+            .chain(self.data.iter().map(|data| (SectionKind::Data, *data)))
+            .chain(self.background.map(|background| (SectionKind::Background, background)))
+            .chain(self.interface.map(|interface| (SectionKind::Interface, interface)))
+            .collect()
+    }
+
+    /// The sprite section as it is written on the rom.
+    fn stored_sprites(&self) -> Cow<'_, [u8]> {
+        match self.compressed_sprites {
+            true => Cow::Owned(compress_sprites(&self.sprites)),
+            false => Cow::Borrowed(&self.sprites),
+        }
+    }
+}
+
+/// Loads a version 1 rom, returning it along with the checksum of its contents and the one
+/// declared on its header.
+fn load_v1(rom: &[u8]) -> Result<(Rom<'_>, u32, u32), RomError> {
+    if rom.len() < HEADER_SIZE {
+        return Err(RomError::TooShort(rom.len()));
+    }
+
+    let name = read_text(rom, NAME_LOC).ok_or(RomError::InvalidName)?;
+    let code = read_section(rom, "code", 0x44)?;
+    let sprites = read_section(rom, "sprite", 0x48)?;
+    let expected = u32::from_le_bytes(rom[CHECKSUM_LOC.0..CHECKSUM_LOC.1].try_into().unwrap());
+
+    let rom = Rom {
+        version: 1,
+        name,
+        author: None,
+        description: None,
+        entry_point: 0,
+        cycles_per_frame: 0,
+        palette: None,
+        code,
+        sprites: Cow::Borrowed(sprites),
+        compressed_sprites: false,
+        banks: vec![],
+        data: vec![],
+        background: None,
+        interface: None,
+    };
+    Ok((rom, checksum(code, sprites), expected))
+}
+
+/// Loads a version 2 rom, returning it along with the checksum of its contents and the one
+/// declared on its header.
+fn load_v2(rom: &[u8]) -> Result<(Rom<'_>, u32, u32), RomError> {
+    let header = Header::from_bytes(rom)?;
+
+    let mut code = None;
+    let mut sprites: &[u8] = &[];
+    let mut banks = vec![];
+    let mut data = vec![];
+    let mut background = None;
+    let mut interface = None;
+    for section in &header.sections {
+        let (offset, size) = (section.offset as usize, section.size as usize);
+        match section.kind {
+            SectionKind::Code => code = Some(slice(rom, "code", offset, size)?),
+            SectionKind::Sprites => sprites = slice(rom, "sprite", offset, size)?,
+            SectionKind::Bank if size > MAX_BANK_SIZE => {
+                return Err(RomError::SectionTooLarge {
+                    section: "bank",
+                    size,
+                    max: MAX_BANK_SIZE,
+                })
+            }
+            SectionKind::Bank => banks.push(slice(rom, "bank", offset, size)?),
+            SectionKind::Data => data.push(slice(rom, "data", offset, size)?),
+            SectionKind::Background => background = Some(region(rom, "background", offset, size, MAX_BACKGROUND_SIZE)?),
+            SectionKind::Interface => interface = Some(region(rom, "interface", offset, size, MAX_INTERFACE_SIZE)?),
+        }
+    }
+    let code = code.ok_or(RomError::MissingSection("code"))?;
+
+    let palette = match header.palette_offset as usize {
+        0 => None,
+        offset => Some(slice(rom, "palette", offset, PALETTE_SIZE)?),
+    };
+
+    // the checksum covers every section on the table as it is stored, unknown kinds included, so
+    // it is computed before expanding them
+    let stored = header::table_entries(rom)
+        .into_iter()
+        .map(|(offset, size)| slice(rom, "section", offset, size))
+        .collect::<Result<Vec<_>, _>>()?;
+    let checksum = sections_checksum(&stored);
+    let sprites = match header.compressed_sprites {
+        true => Cow::Owned(decompress_sprites(sprites)?),
+        false => Cow::Borrowed(sprites),
+    };
+
+    let loaded = Rom {
+        version: 2,
+        name: header.name,
+        author: header.author,
+        description: header.description,
+        entry_point: header.entry_point,
+        cycles_per_frame: header.cycles_per_frame,
+        palette,
+        code,
+        sprites,
+        compressed_sprites: header.compressed_sprites,
+        banks,
+        data,
+        background,
+        interface,
+    };
+    Ok((loaded, checksum, header.checksum))
+}
+
+/// Checksum of the code and sprite sections, in the order they are laid out on the rom
+pub fn checksum(code: &[u8], sprites: &[u8]) -> u32 {
+    sections_checksum(&[code, sprites])
+}
+
+/// Checksum of every section of a version 2 rom in the order they are listed on the section table,
+/// sections of kinds the console doesn't know included. Roms holding only a code and a sprite
+/// section hash the same as with [`checksum`].
+pub fn sections_checksum(sections: &[&[u8]]) -> u32 {
+    let mut hasher = crc32fast::Hasher::new();
+    for section in sections {
+        hasher.update(section);
+    }
+    hasher.finalize()
+}
+
+/// Reads a version 1 section described by the offset and size words at `header_loc`.
+fn read_section<'rom>(rom: &'rom [u8], section: &'static str, header_loc: usize) -> Result<&'rom [u8], RomError> {
+    let offset = u16::from_le_bytes([rom[header_loc], rom[header_loc + 1]]) as usize;
+    let size = u16::from_le_bytes([rom[header_loc + 2], rom[header_loc + 3]]) as usize;
+    slice(rom, section, offset, size)
+}
+
+/// Reads a section copied into a console memory region, which it must fit.
+fn region<'rom>(
+    rom: &'rom [u8],
+    section: &'static str,
+    offset: usize,
+    size: usize,
+    max: usize,
+) -> Result<&'rom [u8], RomError> {
+    if size > max {
+        return Err(RomError::SectionTooLarge { section, size, max });
+    }
+    slice(rom, section, offset, size)
+}
+
+fn slice<'rom>(rom: &'rom [u8], section: &'static str, offset: usize, size: usize) -> Result<&'rom [u8], RomError> {
+    // offsets and sizes come from the rom, so their sum may not fit on 32-bit targets
+    offset
+        .checked_add(size)
+        .and_then(|end| rom.get(offset..end))
+        .ok_or(RomError::SectionOutOfBounds {
+            section,
+            end: offset.saturating_add(size),
+            file_size: rom.len(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::header::AUTHOR_LOC;
+
+    #[test]
+    fn test_load_valid_rom() {
+        let rom = include_bytes!("../fixtures/valid.rom");
+        let rom = Rom::from_bytes(rom, true).unwrap();
+
+        assert_eq!(rom.name, "valid");
+        assert_eq!(rom.code, &[0xAA; 4]);
+        assert_eq!(*rom.sprites, [0xBB; 8]);
+        assert_eq!(rom.checksum(), checksum(&[0xAA; 4], &[0xBB; 8]));
+    }
+
+    #[test]
+    fn test_checksum_mismatch() {
+        let rom = include_bytes!("../fixtures/bad_checksum.rom");
+        let err = Rom::from_bytes(rom, true).unwrap_err();
+        assert_eq!(
+            err,
+            RomError::ChecksumMismatch {
+                expected: 0xDEADBEEF,
+                actual: checksum(&[0xAA; 4], &[0xBB; 8]),
+            }
+        );
+
+        assert!(Rom::from_bytes(rom, false).is_ok());
+    }
+
+    #[test]
+    fn test_reject_invalid_roms() {
+        let too_short = include_bytes!("../fixtures/too_short.rom");
+        assert_eq!(Rom::from_bytes(too_short, true).unwrap_err(), RomError::TooShort(64));
+
+        let bad_magic = include_bytes!("../fixtures/bad_magic.rom");
+        assert_eq!(Rom::from_bytes(bad_magic, true).unwrap_err(), RomError::BadMagic);
+
+        let unsupported_version = include_bytes!("../fixtures/unsupported_version.rom");
+        assert_eq!(
+            Rom::from_bytes(unsupported_version, true).unwrap_err(),
+            RomError::UnsupportedVersion(3)
+        );
+
+        let truncated_sprites = include_bytes!("../fixtures/truncated_sprites.rom");
+        assert_eq!(
+            Rom::from_bytes(truncated_sprites, true).unwrap_err(),
+            RomError::SectionOutOfBounds {
+                section: "sprite",
+                end: 140,
+                file_size: 136,
+            }
+        );
+    }
+
+    #[test]
+    fn test_load_v2_rom() {
+        let rom = include_bytes!("../fixtures/valid_v2.rom");
+        let rom = Rom::from_bytes(rom, true).unwrap();
+
+        assert_eq!(rom.version, 2);
+        assert_eq!(rom.name, "valid");
+        assert_eq!(rom.author, Some("someone"));
+        assert_eq!(rom.description, None);
+        assert_eq!(rom.entry_point, 0x10);
+        assert_eq!(rom.cycles_per_frame, 2000);
+        assert_eq!(rom.palette, Some(&[0xCC; PALETTE_SIZE][..]));
+        assert_eq!(rom.palette_colors(), Some(vec![(0xCC, 0xCC, 0xCC, 0xCC); 16]));
+        assert_eq!(rom.code, &[0xAA; 4]);
+        assert_eq!(*rom.sprites, [0xBB; 8]);
+        assert!(matches!(rom.sprites, Cow::Borrowed(_)));
+
+        let mut bad_author = include_bytes!("../fixtures/valid_v2.rom").to_vec();
+        bad_author[AUTHOR_LOC.0..AUTHOR_LOC.1].fill(b'a');
+        assert_eq!(
+            Rom::from_bytes(&bad_author, true).unwrap_err(),
+            RomError::InvalidText("author")
+        );
+    }
+
+    #[test]
+    fn test_v1_roms_have_no_metadata() {
+        let rom = Rom::from_bytes(include_bytes!("../fixtures/valid.rom"), true).unwrap();
+        assert_eq!(rom.version, 1);
+        assert_eq!((rom.author, rom.description, rom.palette), (None, None, None));
+        assert_eq!(rom.palette_colors(), None);
+        assert_eq!((rom.entry_point, rom.cycles_per_frame), (0, 0));
+    }
+
+    #[test]
+    fn test_load_compressed_rom() {
+        let rom = include_bytes!("../fixtures/valid_v2_compressed.rom");
+        let loaded = Rom::from_bytes(rom, true).unwrap();
+
+        let mut sprites = vec![0xBB; 8];
+        sprites.extend([0x01, 0x02, 0x03]);
+        assert_eq!(loaded.name, "compressed");
+        assert_eq!(loaded.code, &[0xAA; 4]);
+        assert_eq!(*loaded.sprites, sprites);
+        // the checksum is taken over the stored section, not the expanded one
+        assert_eq!(loaded.checksum(), checksum(&[0xAA; 4], &compress_sprites(&sprites)));
+
+        let mut truncated = rom.to_vec();
+        truncated[HEADER_V2_SIZE + SECTION_ENTRY_SIZE + 8] -= 1;
+        assert_eq!(
+            Rom::from_bytes(&truncated[..truncated.len() - 1], false).unwrap_err(),
+            RomError::InvalidCompression("sprite")
+        );
+    }
+
+    /// A rom using every section, which tests tweak to cover what they need.
+    fn full_rom<'rom>(code: &'rom [u8], sprites: &'rom [u8], banks: &[&'rom [u8]]) -> Rom<'rom> {
+        Rom {
+            version: VERSION,
+            name: "game",
+            author: Some("someone"),
+            description: Some("a game about things"),
+            entry_point: 4,
+            cycles_per_frame: 2000,
+            palette: Some(&[0xCC; PALETTE_SIZE]),
+            code,
+            sprites: Cow::Borrowed(sprites),
+            compressed_sprites: false,
+            banks: banks.to_vec(),
+            data: vec![],
+            background: None,
+            interface: None,
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let (code, sprites, bank) = ([0xAA; 6], [0xBB; 32], [0x11; 3]);
+        let rom = Rom {
+            data: vec![&[0x33; 5], &[]],
+            ..full_rom(&code, &sprites, &[&bank, &[0x22; 7]])
+        };
+        let bytes = rom.to_bytes().unwrap();
+        assert_eq!(Rom::from_bytes(&bytes, true).unwrap(), rom);
+
+        let rom = Rom {
+            author: None,
+            description: None,
+            palette: None,
+            banks: vec![],
+            data: vec![],
+            ..rom
+        };
+        let bytes = rom.to_bytes().unwrap();
+        assert_eq!(Rom::from_bytes(&bytes, true).unwrap(), rom);
+    }
+
+    #[test]
+    fn test_compressed_round_trip() {
+        let code = [0xAA; 6];
+        let sprites = [[0x11; 16], [0x12; 16], [0x00; 16]].concat();
+        let rom = Rom {
+            compressed_sprites: true,
+            ..full_rom(&code, &sprites, &[])
+        };
+
+        let bytes = rom.to_bytes().unwrap();
+        let uncompressed = Rom {
+            compressed_sprites: false,
+            ..rom.clone()
+        };
+        assert!(bytes.len() < uncompressed.to_bytes().unwrap().len());
+
+        let loaded = Rom::from_bytes(&bytes, true).unwrap();
+        assert_eq!(loaded, rom);
+        assert_eq!(loaded.checksum(), checksum(&code, &compress_sprites(&sprites)));
+    }
+
+    #[test]
+    fn test_palette_must_hold_16_colors() {
+        let rom = Rom {
+            palette: Some(&[0xCC; PALETTE_SIZE - 4]),
+            ..full_rom(&[0xAA; 6], &[], &[])
+        };
+        assert_eq!(rom.to_bytes().unwrap_err(), RomError::PaletteSize(PALETTE_SIZE - 4));
+    }
+
+    #[test]
+    fn test_reject_sections_past_address_space() {
+        let mut bytes = full_rom(&[0xAA; 4], &[], &[]).to_bytes().unwrap();
+        // the code section is the first entry of the table
+        let entry = HEADER_V2_SIZE;
+        bytes[entry + 4..entry + 8].copy_from_slice(&(u32::MAX - 1).to_le_bytes());
+        bytes[entry + 8..entry + 12].copy_from_slice(&u32::MAX.to_le_bytes());
+
+        assert_eq!(
+            Rom::from_bytes(&bytes, false).unwrap_err(),
+            RomError::SectionOutOfBounds {
+                section: "code",
+                end: (u32::MAX as usize - 1).saturating_add(u32::MAX as usize),
+                file_size: bytes.len(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_checksum_covers_unknown_sections() {
+        let (code, level) = ([0xAA; 4], [0x44; 6]);
+        let rom = Rom {
+            data: vec![&level],
+            ..full_rom(&code, &[], &[])
+        };
+        let mut bytes = rom.to_bytes().unwrap();
+        // the data section is the third entry of the table, after the code and sprite ones
+        let entry = HEADER_V2_SIZE + 2 * SECTION_ENTRY_SIZE;
+        bytes[entry..entry + 4].copy_from_slice(&0x99u32.to_le_bytes());
+
+        // the console skips the section but still hashes it where it sits on the table
+        let loaded = Rom::from_bytes(&bytes, true).unwrap();
+        assert!(loaded.data.is_empty());
+
+        let offset = u32::from_le_bytes(bytes[entry + 4..entry + 8].try_into().unwrap()) as usize;
+        bytes[offset] ^= 0xFF;
+        assert!(matches!(
+            Rom::from_bytes(&bytes, true).unwrap_err(),
+            RomError::ChecksumMismatch { .. }
+        ));
+    }
+
+    #[test]
+    fn test_load_banked_rom() {
+        let (code, first, second) = ([0xAA; 4], [0x11; 3], [0x22; 5]);
+        let rom = full_rom(&code, &[], &[&first, &second]);
+
+        let bytes = rom.to_bytes().unwrap();
+        let loaded = Rom::from_bytes(&bytes, true).unwrap();
+        assert_eq!(loaded.banks, [&first[..], &second[..]]);
+        assert!(loaded.sprites.is_empty());
+        assert_eq!(loaded.checksum(), sections_checksum(&[&code, &[], &first, &second]));
+
+        // every bank must fit in the bank window
+        let large = [0x33; MAX_BANK_SIZE + 1];
+        let bytes = full_rom(&code, &[], &[&first, &large]).to_bytes().unwrap();
+        assert_eq!(
+            Rom::from_bytes(&bytes, false).unwrap_err(),
+            RomError::SectionTooLarge {
+                section: "bank",
+                size: MAX_BANK_SIZE + 1,
+                max: MAX_BANK_SIZE,
+            }
+        );
+    }
+
+    #[test]
+    fn test_load_rom_with_data() {
+        let (code, bank, level, text) = ([0xAA; 4], [0x11; 3], [0x44; 6], [0x55; 2]);
+        let rom = Rom {
+            data: vec![&level, &text],
+            ..full_rom(&code, &[], &[&bank])
+        };
+
+        let bytes = rom.to_bytes().unwrap();
+        let loaded = Rom::from_bytes(&bytes, true).unwrap();
+        assert_eq!(loaded.banks, [&bank[..]]);
+        assert_eq!(loaded.data, [&level[..], &text[..]]);
+        // data is hashed right after the banks
+        assert_eq!(
+            loaded.checksum(),
+            sections_checksum(&[&code, &[], &bank, &level, &text])
+        );
+    }
+
+    #[test]
+    fn test_load_rom_with_initial_screen() {
+        let (code, level, background, interface) = ([0xAA; 4], [0x44; 6], [0x01; 40], [0x02; 20]);
+        let rom = Rom {
+            data: vec![&level],
+            background: Some(&background),
+            interface: Some(&interface),
+            ..full_rom(&code, &[], &[])
+        };
+
+        let bytes = rom.to_bytes().unwrap();
+        let loaded = Rom::from_bytes(&bytes, true).unwrap();
+        assert_eq!(loaded, rom);
+        // the screen is hashed after the data
+        assert_eq!(
+            loaded.checksum(),
+            sections_checksum(&[&code, &[], &level, &background, &interface])
+        );
+
+        // the screen must fit on the memory it is copied into
+        let large = [0x01; MAX_BACKGROUND_SIZE + 1];
+        let rom = Rom {
+            background: Some(&large),
+            ..full_rom(&code, &[], &[])
+        };
+        assert_eq!(
+            Rom::from_bytes(&rom.to_bytes().unwrap(), false).unwrap_err(),
+            RomError::SectionTooLarge {
+                section: "background",
+                size: MAX_BACKGROUND_SIZE + 1,
+                max: MAX_BACKGROUND_SIZE,
+            }
+        );
+    }
+}
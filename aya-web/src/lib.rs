@@ -0,0 +1,29 @@
+use std::cell::RefCell;
+
+use aya_console::web::WebConsole;
+use wasm_bindgen::prelude::*;
+
+thread_local! {
+    // the page calls in from a single thread, and wasm-bindgen can't hand a console that owns
+    // closures and canvas handles back to javascript, so it lives here between frames
+    static CONSOLE: RefCell<Option<WebConsole>> = const { RefCell::new(None) };
+}
+
+/// Loads a rom from its bytes and draws its first frame, replacing the rom that was running.
+#[wasm_bindgen]
+pub fn init(rom: &[u8]) -> Result<(), JsError> {
+    // the previous console is dropped first so its keyboard listeners are removed
+    CONSOLE.with(|console| console.borrow_mut().take());
+    let loaded = WebConsole::new(rom).map_err(|err| JsError::new(&err.to_string()))?;
+    CONSOLE.with(|console| *console.borrow_mut() = Some(loaded));
+    Ok(())
+}
+
+/// Runs and presents a frame, returns `false` once the program halted or when no rom was loaded.
+#[wasm_bindgen]
+pub fn frame() -> Result<bool, JsError> {
+    CONSOLE.with(|console| match console.borrow_mut().as_mut() {
+        Some(console) => console.frame().map_err(|err| JsError::new(&err.to_string())),
+        None => Ok(false),
+    })
+}
@@ -1,3 +1,4 @@
+import "../std/console.aya" Console &[$0000] {}
 import "./after_frame.s" AfterFrame &[$1000] {
   player: [!PLAYER],
   player_x: [!PLAYER_X],
@@ -10,24 +11,23 @@ const PLAYER = $2000
 const PLAYER_X = $2001
 const PLAYER_Y = $2002
 const PLAYER_FLAGS = $2003
+const SPRITE_ENABLE = $4
 const MOVE_SPEED = $2
 const GRAVITY = $5
 const JUMP_FORCE = $5
 
-const INPUT_ADDR = $677C
-const INTERRUPT_ADDR = $676C
-
 start:
 setup_sprites:
   mov8 &[!PLAYER], $04
   mov8 &[!PLAYER_X], $10
   mov8 &[!PLAYER_Y], $10
+  mov8 &[!PLAYER_FLAGS], !SPRITE_ENABLE
 
 setup_interrupts:
-  mov &[!INTERRUPT_ADDR + $2], $3280
+  mov &[!Console.AFTER_FRAME_INTERRUPT], [!Console.CODE_MEM + $1000]
 
 check_inputs:
-  mov8 r8, &[!INPUT_ADDR]
+  mov8 r8, &[!Console.INPUT_MEM]
 
 check_left_press:
   mov acc, r8
@@ -68,7 +68,7 @@ check_right_press:
   call &[!look_right]
 
 clear_input:
-  mov8 &[!INPUT_ADDR], $0
+  mov8 &[!Console.INPUT_MEM], $0
 
 game_loop:
   jmp &[!check_inputs]